@@ -0,0 +1,56 @@
+//! Types shared by environments and wrappers.
+
+use std::collections::HashMap;
+
+use candle_core::Tensor;
+
+/// Auxiliary diagnostic information returned by [`Env::step`](crate::core::Env::step) and
+/// [`Env::reset`](crate::core::Env::reset).
+pub type InfoMap = HashMap<String, InfoValue>;
+
+/// A value stored in an [`InfoMap`].
+#[derive(Debug, Clone)]
+pub enum InfoValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Tensor(Tensor),
+    Map(InfoMap),
+}
+
+impl From<bool> for InfoValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i64> for InfoValue {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<f64> for InfoValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<String> for InfoValue {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
+
+impl From<Tensor> for InfoValue {
+    fn from(value: Tensor) -> Self {
+        Self::Tensor(value)
+    }
+}
+
+impl From<InfoMap> for InfoValue {
+    fn from(value: InfoMap) -> Self {
+        Self::Map(value)
+    }
+}
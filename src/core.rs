@@ -0,0 +1,1104 @@
+//! Core API for environments.
+
+use candle_core::Tensor;
+
+use crate::common::InfoMap;
+use crate::envs::registration::EnvSpec;
+use crate::spaces::SpaceEnum;
+use crate::utils::seeding::Generator;
+
+/// A frame produced by [`Env::render`].
+#[derive(Debug, Clone)]
+pub enum RenderFrame {
+    /// An RGB image of shape `(height, width, 3)` with dtype `U8`.
+    RgbArray(Tensor),
+    /// A text representation of the environment, possibly spanning several lines.
+    Ansi(String),
+    /// The frames rendered since the last call to render, as collected by the
+    /// [`RenderCollection`](crate::wrappers::RenderCollection) wrapper.
+    List(Vec<RenderFrame>),
+}
+
+/// The result of [`Env::step`].
+///
+/// Converts from and into the `(observation, reward, terminated, truncated, info)` tuple for code
+/// written against the tuple form.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepResult<ObsType, Info = InfoMap> {
+    /// An element of the environment's observation space as the next observation due to the agent
+    /// actions.
+    pub observation: ObsType,
+    /// The reward as a result of taking the action.
+    pub reward: f32,
+    /// Whether the agent reaches the terminal state (as defined under the MDP of the task) which
+    /// can be positive or negative.
+    pub terminated: bool,
+    /// Whether the truncation condition outside the scope of the MDP is satisfied, typically a
+    /// timelimit.
+    pub truncated: bool,
+    /// Contains auxiliary diagnostic information (helpful for debugging, learning, and logging).
+    pub info: Info,
+}
+
+impl<ObsType, Info> From<(ObsType, f32, bool, bool, Info)> for StepResult<ObsType, Info> {
+    fn from(
+        (observation, reward, terminated, truncated, info): (ObsType, f32, bool, bool, Info),
+    ) -> Self {
+        Self {
+            observation,
+            reward,
+            terminated,
+            truncated,
+            info,
+        }
+    }
+}
+
+impl<ObsType, Info> From<StepResult<ObsType, Info>> for (ObsType, f32, bool, bool, Info) {
+    fn from(result: StepResult<ObsType, Info>) -> Self {
+        (
+            result.observation,
+            result.reward,
+            result.terminated,
+            result.truncated,
+            result.info,
+        )
+    }
+}
+
+/// The metadata of an environment, describing how it can be rendered.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+    /// The render modes supported by the environment, e.g. "human", "rgb_array" or "ansi".
+    render_modes: Vec<String>,
+    /// The number of frames per second at which the environment is rendered.
+    render_fps: Option<u32>,
+}
+
+impl Metadata {
+    /// Creates the metadata of an environment supporting `render_modes` at `render_fps`.
+    pub fn new(render_modes: Vec<String>, render_fps: Option<u32>) -> Self {
+        Self {
+            render_modes,
+            render_fps,
+        }
+    }
+
+    /// The render modes supported by the environment.
+    pub fn render_modes(&self) -> &[String] {
+        &self.render_modes
+    }
+
+    /// The number of frames per second at which the environment is rendered.
+    pub fn render_fps(&self) -> Option<u32> {
+        self.render_fps
+    }
+
+    /// Returns `true` if the environment supports the render `mode`.
+    pub fn supports_mode(&self, mode: &str) -> bool {
+        self.render_modes.iter().any(|supported| supported == mode)
+    }
+}
+
+/// The metadata of environments that do not declare any.
+static EMPTY_METADATA: Metadata = Metadata {
+    render_modes: Vec::new(),
+    render_fps: None,
+};
+
+/// The main API for implementing reinforcement learning agents' environments, inspired by Gymnasium.
+///
+/// The main API methods that users of this trait need to know are:
+/// - [`step`](Env::step) - Updates an environment with actions returning the next agent observation,
+///   the reward for taking that action, if the environment has terminated or truncated due to the
+///   latest action and information from the environment about the step, i.e. metrics, debug info.
+/// - [`reset`](Env::reset) - Resets the environment to an initial state, required before calling step.
+///   Returns the first agent observation for an episode and information, i.e. metrics, debug info.
+/// - [`render`](Env::render) - Renders the environment to help visualise what the agent sees, example
+///   modes are "human", "rgb_array", "ansi" for text.
+/// - [`close`](Env::close) - Closes the environment, important when external software is used, i.e.
+///   pygame for rendering, databases.
+pub trait Env<ObsType, ActType>: AsEnv<ObsType, ActType> {
+    /// Run one timestep of the environment's dynamics using the agent actions.
+    ///
+    /// When the end of an episode is reached (`terminated` or `truncated`), it is necessary to call
+    /// [`reset`](Env::reset) to reset this environment's state for the next episode.
+    ///
+    /// Returns the next observation, the reward, whether the episode terminated or was truncated
+    /// and the info of the step as a [`StepResult`].
+    fn step(&mut self, action: ActType) -> StepResult<ObsType>;
+
+    /// Resets the environment to an initial internal state, returning an initial observation and info.
+    ///
+    /// If `seed` is given, the environment's random number generator is reseeded with it; otherwise
+    /// the generator keeps its current state. `options` carries additional information to specify how
+    /// the environment is reset, depending on the specific environment.
+    fn reset(&mut self, seed: Option<u64>, options: Option<&InfoMap>) -> (ObsType, InfoMap);
+
+    /// Compute the render frames as specified by the `render_mode` given during construction.
+    ///
+    /// The render modes supported by the environment are listed in its [`Metadata`]. The
+    /// "rgb_array_list" and "ansi_list" modes, returning every frame rendered since the last call,
+    /// are applied with the [`RenderCollection`](crate::wrappers::RenderCollection) wrapper.
+    ///
+    /// Returns `None` if the environment does not render or renders to a window by itself.
+    fn render(&mut self) -> Option<RenderFrame> {
+        None
+    }
+
+    /// After the user has finished using the environment, close contains the code necessary to
+    /// "clean up" the environment.
+    fn close(&mut self) {}
+
+    /// The space of the valid actions of the environment.
+    fn action_space(&self) -> &SpaceEnum;
+
+    /// The space of the observations returned by [`step`](Env::step) and [`reset`](Env::reset).
+    fn observation_space(&self) -> &SpaceEnum;
+
+    /// The metadata of the environment, such as its render modes. Empty by default.
+    fn metadata(&self) -> &Metadata {
+        &EMPTY_METADATA
+    }
+
+    /// The specification the environment was created from by
+    /// [`make`](crate::envs::registration::make), or `None` if it was constructed directly.
+    fn spec(&self) -> Option<&EnvSpec> {
+        None
+    }
+
+    /// Returns the random number generator of the environment, or `None` if the environment has no
+    /// randomness.
+    ///
+    /// If the generator was not seeded by [`reset`](Env::reset), it is seeded from OS entropy on
+    /// first use.
+    fn rs_random(&mut self) -> Option<&mut Generator> {
+        None
+    }
+
+    /// The seed of the generator returned by [`rs_random`](Env::rs_random), or `None` if it was not
+    /// seeded yet.
+    fn rs_random_seed(&self) -> Option<u64> {
+        None
+    }
+
+    /// Returns the base non-wrapped environment.
+    fn unwrapped(&self) -> &dyn Env<ObsType, ActType> {
+        self.as_env()
+    }
+}
+
+/// Converts an environment into a trait object, used by the default [`Env::unwrapped`].
+///
+/// This is implemented for every [`Env`] and does not need to be implemented by hand.
+pub trait AsEnv<ObsType, ActType> {
+    /// Returns the environment as a trait object.
+    fn as_env(&self) -> &dyn Env<ObsType, ActType>;
+}
+
+impl<ObsType, ActType, E: Env<ObsType, ActType>> AsEnv<ObsType, ActType> for E {
+    fn as_env(&self) -> &dyn Env<ObsType, ActType> {
+        self
+    }
+}
+
+/// Wraps an [`Env`] to allow a modular transformation of the [`step`](Env::step) and
+/// [`reset`](Env::reset) methods.
+///
+/// Every wrapper is an [`Env`]: each method delegates to the wrapped environment by default, so that
+/// a wrapper only overrides the methods it changes. [`unwrapped`](Env::unwrapped) returns the base
+/// environment, however many wrappers are stacked on top of it.
+///
+/// The observation and action types are associated types rather than type parameters, so that
+/// wrappers changing them, such as an [`ObservationWrapper`], can implement [`Env`] themselves.
+pub trait Wrapper {
+    /// The observation type of the wrapped environment.
+    type ObsType;
+    /// The action type of the wrapped environment.
+    type ActType;
+
+    /// The wrapped environment.
+    fn env(&self) -> &dyn Env<Self::ObsType, Self::ActType>;
+
+    /// The wrapped environment, mutably.
+    fn env_mut(&mut self) -> &mut dyn Env<Self::ObsType, Self::ActType>;
+
+    /// Uses the [`step`](Env::step) of the wrapped environment, can be overridden to change the
+    /// returned data.
+    fn step(&mut self, action: Self::ActType) -> StepResult<Self::ObsType> {
+        self.env_mut().step(action)
+    }
+
+    /// Uses the [`reset`](Env::reset) of the wrapped environment, can be overridden to change the
+    /// returned data.
+    fn reset(&mut self, seed: Option<u64>, options: Option<&InfoMap>) -> (Self::ObsType, InfoMap) {
+        self.env_mut().reset(seed, options)
+    }
+
+    /// Uses the [`render`](Env::render) of the wrapped environment.
+    fn render(&mut self) -> Option<RenderFrame> {
+        self.env_mut().render()
+    }
+
+    /// Closes the wrapped environment.
+    fn close(&mut self) {
+        self.env_mut().close()
+    }
+
+    /// Uses the action space of the wrapped environment, can be overridden to change it.
+    fn action_space(&self) -> &SpaceEnum {
+        self.env().action_space()
+    }
+
+    /// Uses the observation space of the wrapped environment, can be overridden to change it.
+    fn observation_space(&self) -> &SpaceEnum {
+        self.env().observation_space()
+    }
+
+    /// Uses the metadata of the wrapped environment, can be overridden to change it.
+    fn metadata(&self) -> &Metadata {
+        self.env().metadata()
+    }
+
+    /// Uses the spec of the wrapped environment, can be overridden to change it.
+    fn spec(&self) -> Option<&EnvSpec> {
+        self.env().spec()
+    }
+
+    /// Uses the generator of the wrapped environment.
+    fn rs_random(&mut self) -> Option<&mut Generator> {
+        self.env_mut().rs_random()
+    }
+
+    /// Uses the seed of the generator of the wrapped environment.
+    fn rs_random_seed(&self) -> Option<u64> {
+        self.env().rs_random_seed()
+    }
+
+    /// Uses the base environment of the wrapped environment.
+    fn unwrapped(&self) -> &dyn Env<Self::ObsType, Self::ActType> {
+        self.env().unwrapped()
+    }
+}
+
+/// Selects the wrapper trait through which a wrapper implements [`Env`].
+///
+/// Every wrapper implementing [`WrapperKind`] is an [`Env`], delegating to the trait selected by
+/// [`Kind`](WrapperKind::Kind). It is implemented for every [`Wrapper`], and so for every
+/// [`RewardWrapper`], while an [`ObservationWrapper`] or an [`ActionWrapper`] implements it with
+/// [`ObservationWrapperKind`] or [`ActionWrapperKind`]: Rust does not allow blanket [`Env`]
+/// implementations for several traits.
+pub trait WrapperKind: Sized {
+    /// The marker of the wrapper trait implementing [`Env`].
+    type Kind: DelegateEnv<Self>;
+}
+
+/// Marks wrappers implementing [`Env`] through [`Wrapper`].
+pub enum PlainWrapperKind {}
+
+/// Marks wrappers implementing [`Env`] through [`ObservationWrapper`].
+pub enum ObservationWrapperKind {}
+
+/// Marks wrappers implementing [`Env`] through [`ActionWrapper`].
+pub enum ActionWrapperKind {}
+
+/// Implements [`Env`] for the wrappers `W` of a [`WrapperKind`], each method taking the wrapper.
+///
+/// This is implemented for the markers of the wrapper traits and does not need to be implemented by
+/// hand.
+pub trait DelegateEnv<W> {
+    /// The observation type of the wrapper.
+    type ObsType;
+    /// The action type of the wrapper.
+    type ActType;
+
+    /// The [`step`](Env::step) of the wrapper.
+    fn step(wrapper: &mut W, action: Self::ActType) -> StepResult<Self::ObsType>;
+
+    /// The [`reset`](Env::reset) of the wrapper.
+    fn reset(
+        wrapper: &mut W,
+        seed: Option<u64>,
+        options: Option<&InfoMap>,
+    ) -> (Self::ObsType, InfoMap);
+
+    /// The [`render`](Env::render) of the wrapper.
+    fn render(wrapper: &mut W) -> Option<RenderFrame>;
+
+    /// The [`close`](Env::close) of the wrapper.
+    fn close(wrapper: &mut W);
+
+    /// The [`action_space`](Env::action_space) of the wrapper.
+    fn action_space(wrapper: &W) -> &SpaceEnum;
+
+    /// The [`observation_space`](Env::observation_space) of the wrapper.
+    fn observation_space(wrapper: &W) -> &SpaceEnum;
+
+    /// The [`metadata`](Env::metadata) of the wrapper.
+    fn metadata(wrapper: &W) -> &Metadata;
+
+    /// The [`spec`](Env::spec) of the wrapper.
+    fn spec(wrapper: &W) -> Option<&EnvSpec>;
+
+    /// The [`rs_random`](Env::rs_random) of the wrapper.
+    fn rs_random(wrapper: &mut W) -> Option<&mut Generator>;
+
+    /// The [`rs_random_seed`](Env::rs_random_seed) of the wrapper.
+    fn rs_random_seed(wrapper: &W) -> Option<u64>;
+
+    /// The [`unwrapped`](Env::unwrapped) of the wrapper, or `None` if the base environment has
+    /// other observation or action types, in which case the wrapper itself is returned.
+    fn unwrapped(wrapper: &W) -> Option<&dyn Env<Self::ObsType, Self::ActType>>;
+}
+
+impl<W: Wrapper> WrapperKind for W {
+    type Kind = PlainWrapperKind;
+}
+
+impl<W> Env<<W::Kind as DelegateEnv<W>>::ObsType, <W::Kind as DelegateEnv<W>>::ActType> for W
+where
+    W: WrapperKind,
+{
+    fn step(
+        &mut self,
+        action: <W::Kind as DelegateEnv<W>>::ActType,
+    ) -> StepResult<<W::Kind as DelegateEnv<W>>::ObsType> {
+        W::Kind::step(self, action)
+    }
+
+    fn reset(
+        &mut self,
+        seed: Option<u64>,
+        options: Option<&InfoMap>,
+    ) -> (<W::Kind as DelegateEnv<W>>::ObsType, InfoMap) {
+        W::Kind::reset(self, seed, options)
+    }
+
+    fn render(&mut self) -> Option<RenderFrame> {
+        W::Kind::render(self)
+    }
+
+    fn close(&mut self) {
+        W::Kind::close(self)
+    }
+
+    fn action_space(&self) -> &SpaceEnum {
+        W::Kind::action_space(self)
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        W::Kind::observation_space(self)
+    }
+
+    fn metadata(&self) -> &Metadata {
+        W::Kind::metadata(self)
+    }
+
+    fn spec(&self) -> Option<&EnvSpec> {
+        W::Kind::spec(self)
+    }
+
+    fn rs_random(&mut self) -> Option<&mut Generator> {
+        W::Kind::rs_random(self)
+    }
+
+    fn rs_random_seed(&self) -> Option<u64> {
+        W::Kind::rs_random_seed(self)
+    }
+
+    fn unwrapped(
+        &self,
+    ) -> &dyn Env<<W::Kind as DelegateEnv<W>>::ObsType, <W::Kind as DelegateEnv<W>>::ActType> {
+        match W::Kind::unwrapped(self) {
+            Some(env) => env,
+            None => self,
+        }
+    }
+}
+
+impl<W: Wrapper> DelegateEnv<W> for PlainWrapperKind {
+    type ObsType = W::ObsType;
+    type ActType = W::ActType;
+
+    fn step(wrapper: &mut W, action: W::ActType) -> StepResult<W::ObsType> {
+        Wrapper::step(wrapper, action)
+    }
+
+    fn reset(
+        wrapper: &mut W,
+        seed: Option<u64>,
+        options: Option<&InfoMap>,
+    ) -> (W::ObsType, InfoMap) {
+        Wrapper::reset(wrapper, seed, options)
+    }
+
+    fn render(wrapper: &mut W) -> Option<RenderFrame> {
+        Wrapper::render(wrapper)
+    }
+
+    fn close(wrapper: &mut W) {
+        Wrapper::close(wrapper)
+    }
+
+    fn action_space(wrapper: &W) -> &SpaceEnum {
+        Wrapper::action_space(wrapper)
+    }
+
+    fn observation_space(wrapper: &W) -> &SpaceEnum {
+        Wrapper::observation_space(wrapper)
+    }
+
+    fn metadata(wrapper: &W) -> &Metadata {
+        Wrapper::metadata(wrapper)
+    }
+
+    fn spec(wrapper: &W) -> Option<&EnvSpec> {
+        Wrapper::spec(wrapper)
+    }
+
+    fn rs_random(wrapper: &mut W) -> Option<&mut Generator> {
+        Wrapper::rs_random(wrapper)
+    }
+
+    fn rs_random_seed(wrapper: &W) -> Option<u64> {
+        Wrapper::rs_random_seed(wrapper)
+    }
+
+    fn unwrapped(wrapper: &W) -> Option<&dyn Env<W::ObsType, W::ActType>> {
+        Some(Wrapper::unwrapped(wrapper))
+    }
+}
+
+/// Superclass of wrappers that can modify observations using
+/// [`observation`](ObservationWrapper::observation) for [`reset`](Env::reset) and
+/// [`step`](Env::step), while actions are passed through untouched.
+///
+/// Implementers also implement [`WrapperKind`] with [`ObservationWrapperKind`], making the wrapper
+/// an [`Env`] that delegates every other method to the wrapped environment. If the observation
+/// space is changed, implementers should override
+/// [`observation_space`](ObservationWrapper::observation_space).
+///
+/// As the observation type of the wrapped environment differs from that of the wrapper in general,
+/// [`unwrapped`](Env::unwrapped) returns the wrapper itself; the base environment is reached with
+/// `env().unwrapped()`.
+pub trait ObservationWrapper: WrapperKind<Kind = ObservationWrapperKind> {
+    /// The observation type of the wrapped environment.
+    type ObsType;
+    /// The action type of both the wrapper and the wrapped environment.
+    type ActType;
+    /// The observation type returned by the wrapper.
+    type WrapperObsType;
+
+    /// The wrapped environment.
+    fn env(&self) -> &dyn Env<Self::ObsType, Self::ActType>;
+
+    /// The wrapped environment, mutably.
+    fn env_mut(&mut self) -> &mut dyn Env<Self::ObsType, Self::ActType>;
+
+    /// Returns a modified observation.
+    fn observation(&mut self, observation: Self::ObsType) -> Self::WrapperObsType;
+
+    /// The observation space of the wrapper, that of the wrapped environment by default.
+    fn observation_space(&self) -> &SpaceEnum {
+        self.env().observation_space()
+    }
+
+    /// Modifies the observation of [`Env::step`] using
+    /// [`observation`](ObservationWrapper::observation).
+    fn step(&mut self, action: Self::ActType) -> StepResult<Self::WrapperObsType> {
+        let StepResult {
+            observation,
+            reward,
+            terminated,
+            truncated,
+            info,
+        } = self.env_mut().step(action);
+        StepResult {
+            observation: self.observation(observation),
+            reward,
+            terminated,
+            truncated,
+            info,
+        }
+    }
+
+    /// Modifies the observation of [`Env::reset`] using
+    /// [`observation`](ObservationWrapper::observation).
+    fn reset(
+        &mut self,
+        seed: Option<u64>,
+        options: Option<&InfoMap>,
+    ) -> (Self::WrapperObsType, InfoMap) {
+        let (observation, info) = self.env_mut().reset(seed, options);
+        (self.observation(observation), info)
+    }
+}
+
+impl<W: ObservationWrapper> DelegateEnv<W> for ObservationWrapperKind {
+    type ObsType = W::WrapperObsType;
+    type ActType = W::ActType;
+
+    fn step(wrapper: &mut W, action: W::ActType) -> StepResult<W::WrapperObsType> {
+        ObservationWrapper::step(wrapper, action)
+    }
+
+    fn reset(
+        wrapper: &mut W,
+        seed: Option<u64>,
+        options: Option<&InfoMap>,
+    ) -> (W::WrapperObsType, InfoMap) {
+        ObservationWrapper::reset(wrapper, seed, options)
+    }
+
+    fn render(wrapper: &mut W) -> Option<RenderFrame> {
+        wrapper.env_mut().render()
+    }
+
+    fn close(wrapper: &mut W) {
+        wrapper.env_mut().close()
+    }
+
+    fn action_space(wrapper: &W) -> &SpaceEnum {
+        wrapper.env().action_space()
+    }
+
+    fn observation_space(wrapper: &W) -> &SpaceEnum {
+        ObservationWrapper::observation_space(wrapper)
+    }
+
+    fn metadata(wrapper: &W) -> &Metadata {
+        wrapper.env().metadata()
+    }
+
+    fn spec(wrapper: &W) -> Option<&EnvSpec> {
+        wrapper.env().spec()
+    }
+
+    fn rs_random(wrapper: &mut W) -> Option<&mut Generator> {
+        wrapper.env_mut().rs_random()
+    }
+
+    fn rs_random_seed(wrapper: &W) -> Option<u64> {
+        wrapper.env().rs_random_seed()
+    }
+
+    fn unwrapped(_wrapper: &W) -> Option<&dyn Env<W::WrapperObsType, W::ActType>> {
+        None
+    }
+}
+
+/// Superclass of wrappers that can modify the action before [`step`](Env::step) using
+/// [`action`](ActionWrapper::action), while observations are passed through untouched.
+///
+/// Implementers also implement [`WrapperKind`] with [`ActionWrapperKind`], making the wrapper an
+/// [`Env`] that delegates every other method to the wrapped environment. If the action space is
+/// changed, implementers should override [`action_space`](ActionWrapper::action_space).
+///
+/// As the action type of the wrapped environment differs from that of the wrapper in general,
+/// [`unwrapped`](Env::unwrapped) returns the wrapper itself; the base environment is reached with
+/// `env().unwrapped()`.
+pub trait ActionWrapper: WrapperKind<Kind = ActionWrapperKind> {
+    /// The observation type of both the wrapper and the wrapped environment.
+    type ObsType;
+    /// The action type of the wrapped environment.
+    type ActType;
+    /// The action type taken by the wrapper.
+    type WrapperActType;
+
+    /// The wrapped environment.
+    fn env(&self) -> &dyn Env<Self::ObsType, Self::ActType>;
+
+    /// The wrapped environment, mutably.
+    fn env_mut(&mut self) -> &mut dyn Env<Self::ObsType, Self::ActType>;
+
+    /// Returns a modified action before [`Env::step`] is called on the wrapped environment.
+    fn action(&mut self, action: Self::WrapperActType) -> Self::ActType;
+
+    /// The action space of the wrapper, that of the wrapped environment by default.
+    fn action_space(&self) -> &SpaceEnum {
+        self.env().action_space()
+    }
+
+    /// Runs the [`Env::step`] of the wrapped environment with the modified action.
+    fn step(&mut self, action: Self::WrapperActType) -> StepResult<Self::ObsType> {
+        let action = self.action(action);
+        self.env_mut().step(action)
+    }
+
+    /// Uses the [`reset`](Env::reset) of the wrapped environment, can be overridden to reset the
+    /// state of the wrapper.
+    fn reset(&mut self, seed: Option<u64>, options: Option<&InfoMap>) -> (Self::ObsType, InfoMap) {
+        self.env_mut().reset(seed, options)
+    }
+}
+
+impl<W: ActionWrapper> DelegateEnv<W> for ActionWrapperKind {
+    type ObsType = W::ObsType;
+    type ActType = W::WrapperActType;
+
+    fn step(wrapper: &mut W, action: W::WrapperActType) -> StepResult<W::ObsType> {
+        ActionWrapper::step(wrapper, action)
+    }
+
+    fn reset(
+        wrapper: &mut W,
+        seed: Option<u64>,
+        options: Option<&InfoMap>,
+    ) -> (W::ObsType, InfoMap) {
+        ActionWrapper::reset(wrapper, seed, options)
+    }
+
+    fn render(wrapper: &mut W) -> Option<RenderFrame> {
+        wrapper.env_mut().render()
+    }
+
+    fn close(wrapper: &mut W) {
+        wrapper.env_mut().close()
+    }
+
+    fn action_space(wrapper: &W) -> &SpaceEnum {
+        ActionWrapper::action_space(wrapper)
+    }
+
+    fn observation_space(wrapper: &W) -> &SpaceEnum {
+        wrapper.env().observation_space()
+    }
+
+    fn metadata(wrapper: &W) -> &Metadata {
+        wrapper.env().metadata()
+    }
+
+    fn spec(wrapper: &W) -> Option<&EnvSpec> {
+        wrapper.env().spec()
+    }
+
+    fn rs_random(wrapper: &mut W) -> Option<&mut Generator> {
+        wrapper.env_mut().rs_random()
+    }
+
+    fn rs_random_seed(wrapper: &W) -> Option<u64> {
+        wrapper.env().rs_random_seed()
+    }
+
+    fn unwrapped(_wrapper: &W) -> Option<&dyn Env<W::ObsType, W::WrapperActType>> {
+        None
+    }
+}
+
+/// Superclass of wrappers that can modify the returning reward from a [`step`](Env::step) using
+/// [`reward`](RewardWrapper::reward), while the observation, termination, truncation and info are
+/// passed through untouched.
+///
+/// Every reward wrapper is a [`Wrapper`], and so an [`Env`] delegating every other method to the
+/// wrapped environment.
+pub trait RewardWrapper {
+    /// The observation type of both the wrapper and the wrapped environment.
+    type ObsType;
+    /// The action type of both the wrapper and the wrapped environment.
+    type ActType;
+
+    /// The wrapped environment.
+    fn env(&self) -> &dyn Env<Self::ObsType, Self::ActType>;
+
+    /// The wrapped environment, mutably.
+    fn env_mut(&mut self) -> &mut dyn Env<Self::ObsType, Self::ActType>;
+
+    /// Returns a modified reward.
+    fn reward(&mut self, reward: f32) -> f32;
+
+    /// Modifies the reward of [`Env::step`] using [`reward`](RewardWrapper::reward).
+    fn step(&mut self, action: Self::ActType) -> StepResult<Self::ObsType> {
+        let mut result = self.env_mut().step(action);
+        result.reward = self.reward(result.reward);
+        result
+    }
+}
+
+impl<W: RewardWrapper> Wrapper for W {
+    type ObsType = <W as RewardWrapper>::ObsType;
+    type ActType = <W as RewardWrapper>::ActType;
+
+    fn env(&self) -> &dyn Env<Self::ObsType, Self::ActType> {
+        RewardWrapper::env(self)
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<Self::ObsType, Self::ActType> {
+        RewardWrapper::env_mut(self)
+    }
+
+    fn step(&mut self, action: Self::ActType) -> StepResult<Self::ObsType> {
+        RewardWrapper::step(self, action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use candle_core::{DType, Device};
+
+    use super::*;
+    use crate::common::InfoValue;
+    use crate::spaces::{Bound, Box as BoxSpace, Discrete};
+
+    /// Returns the sum of the actions taken since the last reset, also reported in the info.
+    ///
+    /// Its observations and actions are elements of the same `Discrete` space.
+    struct SumEnv {
+        sum: i64,
+        closed: Rc<Cell<bool>>,
+        space: SpaceEnum,
+    }
+
+    impl SumEnv {
+        fn new(sum: i64, closed: Rc<Cell<bool>>) -> Self {
+            Self {
+                sum,
+                closed,
+                space: Discrete::new(21, -10, None).into(),
+            }
+        }
+    }
+
+    impl Env<i64, i64> for SumEnv {
+        fn step(&mut self, action: i64) -> StepResult<i64> {
+            self.sum += action;
+            let info = InfoMap::from([("sum".to_string(), InfoValue::Int(self.sum))]);
+            (self.sum, action as f32, false, false, info).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (i64, InfoMap) {
+            self.sum = 0;
+            (self.sum, InfoMap::new())
+        }
+
+        fn render(&mut self) -> Option<RenderFrame> {
+            Some(RenderFrame::Ansi(self.sum.to_string()))
+        }
+
+        fn close(&mut self) {
+            self.closed.set(true);
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    /// Overrides nothing, so every method is delegated to the wrapped environment.
+    struct PassThrough {
+        env: std::boxed::Box<dyn Env<i64, i64>>,
+    }
+
+    impl Wrapper for PassThrough {
+        type ObsType = i64;
+        type ActType = i64;
+
+        fn env(&self) -> &dyn Env<i64, i64> {
+            self.env.as_ref()
+        }
+
+        fn env_mut(&mut self) -> &mut dyn Env<i64, i64> {
+            self.env.as_mut()
+        }
+    }
+
+    #[test]
+    fn test_env_info() {
+        let mut env = SumEnv::new(0, Rc::default());
+        let (_, info) = env.reset(None, None);
+        assert!(info.is_empty());
+
+        env.step(4);
+        let info = env.step(-1).info;
+        assert_eq!(info.len(), 1);
+        assert!(matches!(info["sum"], InfoValue::Int(3)));
+    }
+
+    #[test]
+    fn test_wrapper_delegates() {
+        let closed = Rc::new(Cell::new(false));
+        let mut env = PassThrough {
+            env: Box::new(SumEnv::new(5, closed.clone())),
+        };
+
+        assert_eq!(Env::reset(&mut env, None, None).0, 0);
+        let StepResult {
+            observation,
+            reward,
+            terminated,
+            truncated,
+            info,
+        } = Env::step(&mut env, 3);
+        assert_eq!(
+            (observation, reward, terminated, truncated),
+            (3, 3.0, false, false)
+        );
+        assert!(matches!(info["sum"], InfoValue::Int(3)));
+        assert_eq!(Env::step(&mut env, 2).observation, 5);
+        assert!(matches!(Env::render(&mut env), Some(RenderFrame::Ansi(frame)) if frame == "5"));
+        assert_eq!(
+            Env::action_space(&env),
+            &Discrete::new(21, -10, None).into()
+        );
+        assert_eq!(Env::observation_space(&env), env.env().observation_space());
+        Env::close(&mut env);
+        assert!(closed.get());
+    }
+
+    #[test]
+    fn test_wrapper_unwrapped() {
+        let base: Box<dyn Env<i64, i64>> = Box::new(SumEnv::new(0, Rc::default()));
+        let base_ptr = base.as_ref() as *const dyn Env<i64, i64>;
+        let env = PassThrough {
+            env: Box::new(PassThrough { env: base }),
+        };
+
+        assert!(std::ptr::addr_eq(Env::unwrapped(&env), base_ptr));
+        assert!(std::ptr::addr_eq(env.env().unwrapped(), base_ptr));
+    }
+
+    /// Scales the observations of the wrapped environment, converting them to `f64`.
+    struct ScaleObservation<ActType> {
+        env: Box<dyn Env<i64, ActType>>,
+        scale: f64,
+        observation_space: SpaceEnum,
+    }
+
+    impl<ActType> ObservationWrapper for ScaleObservation<ActType> {
+        type ObsType = i64;
+        type ActType = ActType;
+        type WrapperObsType = f64;
+
+        fn env(&self) -> &dyn Env<i64, ActType> {
+            self.env.as_ref()
+        }
+
+        fn env_mut(&mut self) -> &mut dyn Env<i64, ActType> {
+            self.env.as_mut()
+        }
+
+        fn observation(&mut self, observation: i64) -> f64 {
+            observation as f64 * self.scale
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.observation_space
+        }
+    }
+
+    impl<ActType> WrapperKind for ScaleObservation<ActType> {
+        type Kind = ObservationWrapperKind;
+    }
+
+    #[test]
+    fn test_observation_wrapper() {
+        let observation_space = SpaceEnum::from(
+            BoxSpace::new(
+                Bound::F64(f64::NEG_INFINITY),
+                Bound::F64(f64::INFINITY),
+                Some(vec![]),
+                DType::F64,
+                &Device::Cpu,
+                None,
+            )
+            .unwrap(),
+        );
+        let closed = Rc::new(Cell::new(false));
+        let mut env = ScaleObservation {
+            env: Box::new(SumEnv::new(0, closed.clone())),
+            scale: 0.5,
+            observation_space: observation_space.clone(),
+        };
+
+        assert_eq!(Env::reset(&mut env, None, None).0, 0.0);
+        let result = Env::step(&mut env, 3);
+        assert_eq!(
+            (
+                result.observation,
+                result.reward,
+                result.terminated,
+                result.truncated
+            ),
+            (1.5, 3.0, false, false)
+        );
+        assert_eq!(Env::step(&mut env, 2).observation, 2.5);
+        assert_eq!(Env::observation_space(&env), &observation_space);
+        assert_eq!(Env::action_space(&env), env.env().action_space());
+        assert!(matches!(Env::render(&mut env), Some(RenderFrame::Ansi(frame)) if frame == "5"));
+        assert!(std::ptr::addr_eq(Env::unwrapped(&env), &env));
+        Env::close(&mut env);
+        assert!(closed.get());
+    }
+
+    /// Negates the actions before passing them to the wrapped environment.
+    struct NegateAction<ObsType> {
+        env: Box<dyn Env<ObsType, i64>>,
+    }
+
+    impl<ObsType> ActionWrapper for NegateAction<ObsType> {
+        type ObsType = ObsType;
+        type ActType = i64;
+        type WrapperActType = i64;
+
+        fn env(&self) -> &dyn Env<ObsType, i64> {
+            self.env.as_ref()
+        }
+
+        fn env_mut(&mut self) -> &mut dyn Env<ObsType, i64> {
+            self.env.as_mut()
+        }
+
+        fn action(&mut self, action: i64) -> i64 {
+            -action
+        }
+    }
+
+    impl<ObsType> WrapperKind for NegateAction<ObsType> {
+        type Kind = ActionWrapperKind;
+    }
+
+    #[test]
+    fn test_action_wrapper() {
+        let closed = Rc::new(Cell::new(false));
+        let mut env = NegateAction {
+            env: Box::new(SumEnv::new(0, closed.clone())),
+        };
+
+        assert_eq!(Env::reset(&mut env, None, None).0, 0);
+        let StepResult {
+            observation,
+            reward,
+            ..
+        } = Env::step(&mut env, 3);
+        assert_eq!((observation, reward), (-3, -3.0));
+        assert_eq!(Env::step(&mut env, -1).observation, -2);
+        assert_eq!(Env::action_space(&env), env.env().action_space());
+        assert!(matches!(Env::render(&mut env), Some(RenderFrame::Ansi(frame)) if frame == "-2"));
+        Env::close(&mut env);
+        assert!(closed.get());
+    }
+
+    /// Doubles the rewards of the wrapped environment.
+    struct DoubleReward {
+        env: Box<dyn Env<i64, i64>>,
+    }
+
+    impl RewardWrapper for DoubleReward {
+        type ObsType = i64;
+        type ActType = i64;
+
+        fn env(&self) -> &dyn Env<i64, i64> {
+            self.env.as_ref()
+        }
+
+        fn env_mut(&mut self) -> &mut dyn Env<i64, i64> {
+            self.env.as_mut()
+        }
+
+        fn reward(&mut self, reward: f32) -> f32 {
+            reward * 2.0
+        }
+    }
+
+    #[test]
+    fn test_reward_wrapper() {
+        let new_env = || SumEnv::new(0, Rc::default());
+        let mut base = new_env();
+        let mut env = DoubleReward {
+            env: Box::new(new_env()),
+        };
+        base.reset(None, None);
+        Env::reset(&mut env, None, None);
+
+        for action in [3, -1, 4] {
+            let result = base.step(action);
+            let wrapped = Env::step(&mut env, action);
+
+            assert_eq!(wrapped.reward, result.reward * 2.0);
+            assert_eq!(
+                (wrapped.observation, wrapped.terminated, wrapped.truncated),
+                (result.observation, result.terminated, result.truncated)
+            );
+            assert_eq!(wrapped.info.len(), result.info.len());
+        }
+        assert!(matches!(Env::render(&mut env), Some(RenderFrame::Ansi(frame)) if frame == "6"));
+        let base_ptr = RewardWrapper::env(&env) as *const dyn Env<i64, i64>;
+        assert!(std::ptr::addr_eq(Env::unwrapped(&env), base_ptr));
+    }
+
+    #[test]
+    fn test_step_result() {
+        let result = StepResult {
+            observation: 1,
+            reward: 0.5,
+            terminated: true,
+            truncated: false,
+            info: InfoMap::from([("sum".to_string(), InfoValue::Int(1))]),
+        };
+        let StepResult {
+            observation,
+            reward,
+            terminated,
+            truncated,
+            info,
+        } = result.clone();
+        assert_eq!(
+            (observation, reward, terminated, truncated),
+            (1, 0.5, true, false)
+        );
+        assert!(matches!(info["sum"], InfoValue::Int(1)));
+
+        let (observation, reward, terminated, truncated, info) = result.into();
+        assert_eq!(
+            (observation, reward, terminated, truncated),
+            (1, 0.5, true, false)
+        );
+        assert_eq!(info.len(), 1);
+    }
+
+    #[test]
+    fn test_step_result_from_tuple() {
+        let result = StepResult::from((3, -1.0, false, true, ()));
+
+        assert_eq!(
+            result,
+            StepResult {
+                observation: 3,
+                reward: -1.0,
+                terminated: false,
+                truncated: true,
+                info: (),
+            }
+        );
+        assert_eq!(
+            <(i32, f32, bool, bool, ())>::from(result),
+            (3, -1.0, false, true, ())
+        );
+    }
+
+    #[test]
+    fn test_metadata() {
+        let metadata = Metadata::new(vec!["human".to_string(), "rgb_array".to_string()], Some(30));
+
+        assert_eq!(metadata.render_modes(), ["human", "rgb_array"]);
+        assert_eq!(metadata.render_fps(), Some(30));
+        assert!(metadata.supports_mode("human"));
+        assert!(metadata.supports_mode("rgb_array"));
+        assert!(!metadata.supports_mode("ansi"));
+    }
+
+    #[test]
+    fn test_metadata_default() {
+        let metadata = Metadata::default();
+
+        assert!(metadata.render_modes().is_empty());
+        assert_eq!(metadata.render_fps(), None);
+        assert!(!metadata.supports_mode("human"));
+    }
+}
@@ -0,0 +1,340 @@
+//! Acrobot system from Sutton's "Generalization in Reinforcement Learning", using the dynamics of
+//! "Reinforcement Learning: An Introduction" by Sutton and Barto.
+
+use std::f64::consts::PI;
+
+use candle_core::{DType, Device, Tensor};
+use rand::Rng;
+
+use crate::common::InfoMap;
+use crate::core::{Env, Metadata, RenderFrame, StepResult};
+use crate::envs::classic_control::utils::maybe_parse_reset_bounds;
+use crate::spaces::{Bound, Box, Discrete, Space, SpaceEnum};
+use crate::utils::seeding::{Generator, LazyGenerator};
+
+const DT: f64 = 0.2;
+/// Length of link 1 in meters.
+const LINK_LENGTH_1: f64 = 1.0;
+/// Mass of link 1 in kilograms.
+const LINK_MASS_1: f64 = 1.0;
+/// Mass of link 2 in kilograms.
+const LINK_MASS_2: f64 = 1.0;
+/// Position of the center of mass of link 1 in meters.
+const LINK_COM_POS_1: f64 = 0.5;
+/// Position of the center of mass of link 2 in meters.
+const LINK_COM_POS_2: f64 = 0.5;
+/// Moments of inertia for both links.
+const LINK_MOI: f64 = 1.0;
+const MAX_VEL_1: f64 = 4.0 * PI;
+const MAX_VEL_2: f64 = 9.0 * PI;
+const AVAIL_TORQUE: [f64; 3] = [-1.0, 0.0, 1.0];
+
+/// A chain of two links connected linearly, with one end of the chain fixed.
+///
+/// The joint between the two links is actuated. The goal is to apply torques on the actuated
+/// joint to swing the free end of the linear chain above a given height while starting from the
+/// initial state of hanging downwards. The dynamics are integrated with a fourth-order Runge-Kutta
+/// step, following the equations of the book "Reinforcement Learning: An Introduction".
+///
+/// ## Action Space
+///
+/// The action is an `i64` in `{0, 1, 2}`, applying a torque of `-1`, `0` or `1` to the actuated
+/// joint.
+///
+/// ## Observation Space
+///
+/// The observation is a `F32` tensor of shape `(6,)` with the values:
+///
+/// | Num | Observation                  | Min                 | Max               |
+/// |-----|------------------------------|---------------------|-------------------|
+/// | 0   | Cosine of `theta1`           | -1                  | 1                 |
+/// | 1   | Sine of `theta1`             | -1                  | 1                 |
+/// | 2   | Cosine of `theta2`           | -1                  | 1                 |
+/// | 3   | Sine of `theta2`             | -1                  | 1                 |
+/// | 4   | Angular velocity of `theta1` | ~ -12.567 (-4 * pi) | ~ 12.567 (4 * pi) |
+/// | 5   | Angular velocity of `theta2` | ~ -28.274 (-9 * pi) | ~ 28.274 (9 * pi) |
+///
+/// `theta1` is the angle of the first joint, where an angle of `0` indicates the first link is
+/// pointing directly downwards, and `theta2` is relative to the angle of the first link.
+///
+/// ## Rewards
+///
+/// A reward of `-1` is given for every step that does not reach the goal, and `0` when the goal
+/// is reached.
+///
+/// ## Starting State
+///
+/// Each of `theta1`, `theta2` and their angular velocities is assigned a uniform random value in
+/// `[-0.1, 0.1]`, which can be changed with the `"low"` and `"high"` reset options.
+///
+/// ## Episode End
+///
+/// The episode terminates if the free end reaches the target height, i.e.
+/// `-cos(theta1) - cos(theta2 + theta1) > 1.0`.
+pub struct Acrobot {
+    action_space: SpaceEnum,
+    observation_space: SpaceEnum,
+    metadata: Metadata,
+    render_mode: Option<String>,
+    state: Option<[f64; 4]>,
+    rs_random: LazyGenerator,
+}
+
+impl Acrobot {
+    /// Creates the environment, rendering frames in `render_mode` if given.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `render_mode` is not one of the render modes of the metadata.
+    pub fn new(render_mode: Option<&str>) -> Self {
+        let metadata = Metadata::new(vec!["ansi".to_string()], Some(15));
+        if let Some(render_mode) = render_mode {
+            assert!(
+                metadata.supports_mode(render_mode),
+                "Unsupported render mode {render_mode:?}"
+            );
+        }
+
+        let high = Tensor::new(&[1.0, 1.0, 1.0, 1.0, MAX_VEL_1, MAX_VEL_2], &Device::Cpu)
+            .expect("Bounds are a valid tensor");
+        let low = high.neg().expect("Bounds can be negated");
+        let observation_space = Box::new(
+            Bound::Tensor(low),
+            Bound::Tensor(high),
+            None,
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .expect("The bounds are tensors");
+
+        Self {
+            action_space: Discrete::new(3, 0, None).into(),
+            observation_space: observation_space.into(),
+            metadata,
+            render_mode: render_mode.map(str::to_string),
+            state: None,
+            rs_random: LazyGenerator::default(),
+        }
+    }
+
+    /// The render mode given at construction.
+    pub fn render_mode(&self) -> Option<&str> {
+        self.render_mode.as_deref()
+    }
+
+    fn observation(state: &[f64; 4]) -> Tensor {
+        let [theta1, theta2, dtheta1, dtheta2] = *state;
+        let observation = [
+            theta1.cos(),
+            theta1.sin(),
+            theta2.cos(),
+            theta2.sin(),
+            dtheta1,
+            dtheta2,
+        ];
+        Tensor::new(&observation.map(|value| value as f32), &Device::Cpu)
+            .expect("The observation is a valid tensor")
+    }
+
+    fn terminal(state: &[f64; 4]) -> bool {
+        -state[0].cos() - (state[1] + state[0]).cos() > 1.0
+    }
+}
+
+impl Default for Acrobot {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl Env<Tensor, i64> for Acrobot {
+    fn step(&mut self, action: i64) -> StepResult<Tensor> {
+        assert!(
+            self.action_space.contains(&action.into()),
+            "{action} invalid action"
+        );
+        let state = self.state.expect("Call reset before using step method.");
+        let torque = AVAIL_TORQUE[action as usize];
+
+        let [theta1, theta2, dtheta1, dtheta2] = rk4(state, torque, DT);
+        let state = [
+            wrap(theta1, -PI, PI),
+            wrap(theta2, -PI, PI),
+            dtheta1.clamp(-MAX_VEL_1, MAX_VEL_1),
+            dtheta2.clamp(-MAX_VEL_2, MAX_VEL_2),
+        ];
+        self.state = Some(state);
+
+        let terminated = Self::terminal(&state);
+        let reward = if terminated { 0.0 } else { -1.0 };
+        StepResult {
+            observation: Self::observation(&state),
+            reward,
+            terminated,
+            truncated: false,
+            info: InfoMap::new(),
+        }
+    }
+
+    fn reset(&mut self, seed: Option<u64>, options: Option<&InfoMap>) -> (Tensor, InfoMap) {
+        if let Some(seed) = seed {
+            self.rs_random.reseed(seed);
+        }
+        // Note that if you use custom reset bounds, it may lead to out-of-bound state/observations.
+        let (low, high) = maybe_parse_reset_bounds(options, -0.1, 0.1);
+        let state = [(); 4].map(|_| self.rs_random.get().gen_range(low..=high));
+        self.state = Some(state);
+
+        (Self::observation(&state), InfoMap::new())
+    }
+
+    fn render(&mut self) -> Option<RenderFrame> {
+        let [theta1, theta2, dtheta1, dtheta2] = self.state?;
+        match self.render_mode.as_deref()? {
+            "ansi" => Some(RenderFrame::Ansi(format!(
+                "theta1: {theta1:.4}, theta2: {theta2:.4}, \
+                 dtheta1: {dtheta1:.4}, dtheta2: {dtheta2:.4}"
+            ))),
+            _ => None,
+        }
+    }
+
+    fn action_space(&self) -> &SpaceEnum {
+        &self.action_space
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+
+    fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn rs_random(&mut self) -> Option<&mut Generator> {
+        Some(self.rs_random.get())
+    }
+
+    fn rs_random_seed(&self) -> Option<u64> {
+        self.rs_random.seed()
+    }
+}
+
+/// Time derivative of the state under the applied `torque`.
+fn dsdt(state: [f64; 4], torque: f64) -> [f64; 4] {
+    let (m1, m2) = (LINK_MASS_1, LINK_MASS_2);
+    let l1 = LINK_LENGTH_1;
+    let (lc1, lc2) = (LINK_COM_POS_1, LINK_COM_POS_2);
+    let (i1, i2) = (LINK_MOI, LINK_MOI);
+    let g = 9.8;
+    let [theta1, theta2, dtheta1, dtheta2] = state;
+
+    let d1 = m1 * lc1.powi(2)
+        + m2 * (l1.powi(2) + lc2.powi(2) + 2.0 * l1 * lc2 * theta2.cos())
+        + i1
+        + i2;
+    let d2 = m2 * (lc2.powi(2) + l1 * lc2 * theta2.cos()) + i2;
+    let phi2 = m2 * lc2 * g * (theta1 + theta2 - PI / 2.0).cos();
+    let phi1 = -m2 * l1 * lc2 * dtheta2.powi(2) * theta2.sin()
+        - 2.0 * m2 * l1 * lc2 * dtheta2 * dtheta1 * theta2.sin()
+        + (m1 * lc1 + m2 * l1) * g * (theta1 - PI / 2.0).cos()
+        + phi2;
+    let ddtheta2 =
+        (torque + d2 / d1 * phi1 - m2 * l1 * lc2 * dtheta1.powi(2) * theta2.sin() - phi2)
+            / (m2 * lc2.powi(2) + i2 - d2.powi(2) / d1);
+    let ddtheta1 = -(d2 * ddtheta2 + phi1) / d1;
+
+    [dtheta1, dtheta2, ddtheta1, ddtheta2]
+}
+
+/// Integrates the dynamics over `dt` with a single fourth-order Runge-Kutta step.
+fn rk4(state: [f64; 4], torque: f64, dt: f64) -> [f64; 4] {
+    let add = |state: [f64; 4], derivative: [f64; 4], scale: f64| {
+        std::array::from_fn(|i| state[i] + scale * derivative[i])
+    };
+
+    let k1 = dsdt(state, torque);
+    let k2 = dsdt(add(state, k1, dt / 2.0), torque);
+    let k3 = dsdt(add(state, k2, dt / 2.0), torque);
+    let k4 = dsdt(add(state, k3, dt), torque);
+    std::array::from_fn(|i| state[i] + dt / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]))
+}
+
+/// Wraps `x` so that it lies within `[m, big_m]`, e.g. to keep angles within `[-pi, pi]`.
+///
+/// Unlike the modulus, this keeps `x` unchanged if it already lies within the range.
+fn wrap(mut x: f64, m: f64, big_m: f64) -> f64 {
+    let diff = big_m - m;
+    while x > big_m {
+        x -= diff;
+    }
+    while x < m {
+        x += diff;
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{wrap, Acrobot};
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Discrete, Space};
+
+    fn rollout(seed: u64) -> Vec<(Vec<f32>, f32, bool)> {
+        let mut env = Acrobot::default();
+        let mut actions = Discrete::new(3, 0, Some(seed));
+        let (observation, _) = env.reset(Some(seed), None);
+        let mut transitions = vec![(observation.to_vec1::<f32>().unwrap(), 0.0, false)];
+        for _ in 0..100 {
+            let StepResult {
+                observation,
+                reward,
+                terminated,
+                ..
+            } = env.step(actions.sample(None));
+            transitions.push((observation.to_vec1::<f32>().unwrap(), reward, terminated));
+        }
+        transitions
+    }
+
+    #[test]
+    fn test_acrobot_observation() {
+        let mut env = Acrobot::default();
+        let (observation, _) = env.reset(Some(0), None);
+        assert_eq!(observation.dims(), [6]);
+        assert!(env
+            .observation_space()
+            .contains(&observation.clone().into()));
+
+        for action in [0, 1, 2, 2, 0] {
+            let StepResult {
+                observation,
+                reward,
+                terminated,
+                truncated,
+                ..
+            } = env.step(action);
+            assert_eq!(observation.dims(), [6]);
+            assert!(env
+                .observation_space()
+                .contains(&observation.clone().into()));
+            assert_eq!(reward, -1.0);
+            assert!(!terminated);
+            assert!(!truncated);
+        }
+    }
+
+    #[test]
+    fn test_acrobot_reproducible() {
+        assert_eq!(rollout(7), rollout(7));
+        assert_ne!(rollout(7), rollout(8));
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap(0.5, -1.0, 1.0), 0.5);
+        assert_eq!(wrap(1.5, -1.0, 1.0), -0.5);
+        assert_eq!(wrap(-2.5, -1.0, 1.0), -0.5);
+    }
+}
@@ -0,0 +1,493 @@
+//! Classic cart-pole system implemented by Rich Sutton et al.
+
+use candle_core::{DType, Device, Tensor};
+use rand::Rng;
+
+use crate::common::{InfoMap, InfoValue};
+use crate::core::{Env, Metadata, RenderFrame, StepResult};
+use crate::envs::classic_control::utils::maybe_parse_reset_bounds;
+use crate::spaces::{Bound, Box, Discrete, Space, SpaceEnum};
+use crate::utils::seeding::{Generator, LazyGenerator};
+
+const SCREEN_WIDTH: usize = 600;
+const SCREEN_HEIGHT: usize = 400;
+
+/// A pole is attached by an un-actuated joint to a cart, which moves along a frictionless track.
+///
+/// This environment corresponds to the version of the cart-pole problem described by Barto,
+/// Sutton, and Anderson in "Neuronlike Adaptive Elements That Can Solve Difficult Learning Control
+/// Problem". The pendulum is placed upright on the cart and the goal is to balance the pole by
+/// applying forces in the left and right direction on the cart.
+///
+/// ## Action Space
+///
+/// The action is an `i64` in `{0, 1}` indicating the direction of the fixed force the cart is
+/// pushed with: `0` pushes the cart to the left and `1` to the right.
+///
+/// ## Observation Space
+///
+/// The observation is a `F32` tensor of shape `(4,)` with the values:
+///
+/// | Num | Observation           | Min                 | Max               |
+/// |-----|-----------------------|---------------------|-------------------|
+/// | 0   | Cart Position         | -4.8                | 4.8               |
+/// | 1   | Cart Velocity         | -Inf                | Inf               |
+/// | 2   | Pole Angle            | ~ -0.418 rad (-24°) | ~ 0.418 rad (24°) |
+/// | 3   | Pole Angular Velocity | -Inf                | Inf               |
+///
+/// ## Rewards
+///
+/// A reward of `+1` is given for every step taken, including the termination step.
+///
+/// ## Starting State
+///
+/// All observations are assigned a uniformly random value in `(-0.05, 0.05)`. The bounds can be
+/// changed with the `"low"` and `"high"` reset options.
+///
+/// ## Episode End
+///
+/// The episode terminates if the pole angle is greater than ±12°, or if the cart position is
+/// greater than ±2.4, i.e. the center of the cart reaches the edge of the display.
+///
+/// The info of the reset is empty. Once the episode has terminated, the info of each step holds
+/// the number of steps taken since the termination under `"steps_beyond_terminated"`, starting
+/// from `0` at the termination step. Stepping once more after the termination logs a warning
+/// through the [`log`] facade.
+pub struct CartPole {
+    gravity: f64,
+    masspole: f64,
+    total_mass: f64,
+    length: f64,
+    polemass_length: f64,
+    force_mag: f64,
+    tau: f64,
+    theta_threshold_radians: f64,
+    x_threshold: f64,
+    action_space: SpaceEnum,
+    observation_space: SpaceEnum,
+    metadata: Metadata,
+    render_mode: Option<String>,
+    state: Option<[f64; 4]>,
+    steps_beyond_terminated: Option<usize>,
+    rs_random: LazyGenerator,
+}
+
+impl CartPole {
+    /// Creates the environment, rendering frames in `render_mode` if given.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `render_mode` is not one of the render modes of the metadata.
+    pub fn new(render_mode: Option<&str>) -> Self {
+        let metadata = Metadata::new(vec!["rgb_array".to_string(), "ansi".to_string()], Some(50));
+        if let Some(render_mode) = render_mode {
+            assert!(
+                metadata.supports_mode(render_mode),
+                "Unsupported render mode {render_mode:?}"
+            );
+        }
+
+        let masscart = 1.0;
+        let masspole = 0.1;
+        let length = 0.5;
+        // Angle at which to fail the episode.
+        let theta_threshold_radians = 12.0 * 2.0 * std::f64::consts::PI / 360.0;
+        let x_threshold = 2.4;
+
+        // Angle limit set to 2 * theta_threshold_radians so failing observation is still within
+        // bounds.
+        let high = [
+            x_threshold * 2.0,
+            f32::MAX as f64,
+            theta_threshold_radians * 2.0,
+            f32::MAX as f64,
+        ];
+        let high = Tensor::new(&high, &Device::Cpu).expect("Bounds are a valid tensor");
+        let low = high.neg().expect("Bounds can be negated");
+        let observation_space = Box::new(
+            Bound::Tensor(low),
+            Bound::Tensor(high),
+            None,
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .expect("The bounds are tensors");
+
+        Self {
+            gravity: 9.8,
+            masspole,
+            total_mass: masspole + masscart,
+            length,
+            polemass_length: masspole * length,
+            force_mag: 10.0,
+            tau: 0.02,
+            theta_threshold_radians,
+            x_threshold,
+            action_space: Discrete::new(2, 0, None).into(),
+            observation_space: observation_space.into(),
+            metadata,
+            render_mode: render_mode.map(str::to_string),
+            state: None,
+            steps_beyond_terminated: None,
+            rs_random: LazyGenerator::default(),
+        }
+    }
+
+    /// The render mode given at construction.
+    pub fn render_mode(&self) -> Option<&str> {
+        self.render_mode.as_deref()
+    }
+
+    fn observation(state: &[f64; 4]) -> Tensor {
+        Tensor::new(&state.map(|value| value as f32), &Device::Cpu)
+            .expect("The state is a valid tensor")
+    }
+
+    /// Draws the cart, the pole and the track as a `(400, 600, 3)` image.
+    fn render_rgb_array(&self, state: &[f64; 4]) -> Tensor {
+        let [x, _, theta, _] = *state;
+        let scale = SCREEN_WIDTH as f64 / (self.x_threshold * 2.0);
+        let pole_width = 10.0;
+        let pole_length = scale * (2.0 * self.length);
+        let (cart_width, cart_height) = (50.0, 30.0);
+
+        let cart_x = x * scale + SCREEN_WIDTH as f64 / 2.0;
+        let cart_y = 100.0;
+        let axle = (cart_x, cart_y + cart_height / 4.0);
+        let tip = (
+            axle.0 + (pole_length - pole_width / 2.0) * theta.sin(),
+            axle.1 + (pole_length - pole_width / 2.0) * theta.cos(),
+        );
+
+        let mut pixels: Vec<u8> = Vec::with_capacity(SCREEN_HEIGHT * SCREEN_WIDTH * 3);
+        for row in 0..SCREEN_HEIGHT {
+            // Rows are drawn top to bottom, while the scene is laid out bottom to top.
+            let y = (SCREEN_HEIGHT - 1 - row) as f64;
+            for column in 0..SCREEN_WIDTH {
+                let point = (column as f64, y);
+                let color = if distance(point, axle) <= pole_width / 2.0 {
+                    [129, 132, 203]
+                } else if distance_to_segment(point, axle, tip) <= pole_width / 2.0 {
+                    [202, 152, 101]
+                } else if (point.0 - cart_x).abs() <= cart_width / 2.0
+                    && (point.1 - cart_y).abs() <= cart_height / 2.0
+                    || (point.1 - cart_y).abs() < 0.5
+                {
+                    // The cart and the track.
+                    [0, 0, 0]
+                } else {
+                    [255, 255, 255]
+                };
+                pixels.extend(color);
+            }
+        }
+        Tensor::from_vec(pixels, (SCREEN_HEIGHT, SCREEN_WIDTH, 3), &Device::Cpu)
+            .expect("The frame has the shape of the screen")
+    }
+}
+
+impl Default for CartPole {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl Env<Tensor, i64> for CartPole {
+    fn step(&mut self, action: i64) -> StepResult<Tensor> {
+        assert!(
+            self.action_space.contains(&action.into()),
+            "{action} invalid action"
+        );
+        let [x, x_dot, theta, theta_dot] =
+            self.state.expect("Call reset before using step method.");
+
+        let force = if action == 1 {
+            self.force_mag
+        } else {
+            -self.force_mag
+        };
+        let (sintheta, costheta) = theta.sin_cos();
+
+        // For the interested reader:
+        // https://coneural.org/florian/papers/05_cart_pole.pdf
+        let temp = (force + self.polemass_length * theta_dot.powi(2) * sintheta) / self.total_mass;
+        let thetaacc = (self.gravity * sintheta - costheta * temp)
+            / (self.length * (4.0 / 3.0 - self.masspole * costheta.powi(2) / self.total_mass));
+        let xacc = temp - self.polemass_length * thetaacc * costheta / self.total_mass;
+
+        let state = [
+            x + self.tau * x_dot,
+            x_dot + self.tau * xacc,
+            theta + self.tau * theta_dot,
+            theta_dot + self.tau * thetaacc,
+        ];
+        self.state = Some(state);
+
+        let terminated =
+            state[0].abs() > self.x_threshold || state[2].abs() > self.theta_threshold_radians;
+        let reward = match (terminated, self.steps_beyond_terminated) {
+            (false, _) => 1.0,
+            // Pole just fell!
+            (true, None) => {
+                self.steps_beyond_terminated = Some(0);
+                1.0
+            }
+            (true, Some(steps)) => {
+                if steps == 0 {
+                    log::warn!(
+                        "You are calling 'step()' even though this environment has already \
+                         returned terminated = true. You should always call 'reset()' once you \
+                         receive 'terminated = true' -- any further steps are undefined behavior."
+                    );
+                }
+                self.steps_beyond_terminated = Some(steps + 1);
+                0.0
+            }
+        };
+
+        let mut info = InfoMap::new();
+        if let Some(steps) = self.steps_beyond_terminated {
+            info.insert(
+                "steps_beyond_terminated".to_string(),
+                InfoValue::Int(steps as i64),
+            );
+        }
+
+        StepResult {
+            observation: Self::observation(&state),
+            reward,
+            terminated,
+            truncated: false,
+            info,
+        }
+    }
+
+    fn reset(&mut self, seed: Option<u64>, options: Option<&InfoMap>) -> (Tensor, InfoMap) {
+        if let Some(seed) = seed {
+            self.rs_random.reseed(seed);
+        }
+        // Note that if you use custom reset bounds, it may lead to out-of-bound state/observations.
+        let (low, high) = maybe_parse_reset_bounds(options, -0.05, 0.05);
+        let state = [(); 4].map(|_| self.rs_random.get().gen_range(low..=high));
+        self.state = Some(state);
+        self.steps_beyond_terminated = None;
+
+        (Self::observation(&state), InfoMap::new())
+    }
+
+    fn render(&mut self) -> Option<RenderFrame> {
+        let state = self.state.as_ref()?;
+        match self.render_mode.as_deref()? {
+            "rgb_array" => Some(RenderFrame::RgbArray(self.render_rgb_array(state))),
+            "ansi" => {
+                let [x, x_dot, theta, theta_dot] = state;
+                Some(RenderFrame::Ansi(format!(
+                    "cart position: {x:.4}, cart velocity: {x_dot:.4}, \
+                     pole angle: {theta:.4}, pole angular velocity: {theta_dot:.4}"
+                )))
+            }
+            _ => None,
+        }
+    }
+
+    fn action_space(&self) -> &SpaceEnum {
+        &self.action_space
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+
+    fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn rs_random(&mut self) -> Option<&mut Generator> {
+        Some(self.rs_random.get())
+    }
+
+    fn rs_random_seed(&self) -> Option<u64> {
+        self.rs_random.seed()
+    }
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+/// Distance between `point` and the segment from `start` to `end`.
+fn distance_to_segment(point: (f64, f64), start: (f64, f64), end: (f64, f64)) -> f64 {
+    let segment = (end.0 - start.0, end.1 - start.1);
+    let length_squared = segment.0.powi(2) + segment.1.powi(2);
+    let t = ((point.0 - start.0) * segment.0 + (point.1 - start.1) * segment.1) / length_squared;
+    let t = t.clamp(0.0, 1.0);
+    distance(point, (start.0 + t * segment.0, start.1 + t * segment.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::DType;
+    use rand::Rng;
+
+    use super::CartPole;
+    use crate::common::{InfoMap, InfoValue};
+    use crate::core::{Env, RenderFrame, StepResult};
+    use crate::spaces::{Discrete, Space, SpaceEnum};
+    use crate::utils::seeding::rs_random;
+    use crate::wrappers::TimeLimit;
+
+    #[test]
+    fn test_cartpole_observation_bounds() {
+        let mut env = CartPole::default();
+        let mut actions = Discrete::new(2, 0, Some(0));
+
+        let (mut observation, _) = env.reset(Some(0), None);
+        for _ in 0..500 {
+            assert!(env
+                .observation_space()
+                .contains(&observation.clone().into()));
+            let StepResult {
+                observation: next,
+                reward,
+                terminated,
+                truncated,
+                ..
+            } = env.step(actions.sample(None));
+            assert_eq!(reward, 1.0);
+            assert!(!truncated);
+            observation = if terminated {
+                env.reset(None, None).0
+            } else {
+                next
+            };
+        }
+    }
+
+    #[test]
+    fn test_cartpole_spaces() {
+        let env = CartPole::default();
+        let env: &dyn Env<_, _> = &env;
+
+        assert_eq!(env.action_space(), &Discrete::new(2, 0, None).into());
+        let SpaceEnum::Box(space) = env.observation_space() else {
+            panic!("The observation space must be a Box");
+        };
+        assert_eq!(space.shape(), [4]);
+        assert_eq!(space.dtype(), DType::F32);
+    }
+
+    #[test]
+    fn test_cartpole_rs_random() {
+        let mut env = CartPole::default();
+        assert_eq!(env.rs_random_seed(), None);
+
+        let first = env.rs_random().unwrap().gen::<u64>();
+        let seed = env.rs_random_seed().unwrap();
+        assert_eq!(first, rs_random(Some(seed)).0.gen::<u64>());
+
+        env.reset(Some(3), None);
+        assert_eq!(env.rs_random_seed(), Some(3));
+        let mut env = TimeLimit::new(Box::new(env), 10);
+        assert_eq!(env.rs_random_seed(), Some(3));
+        assert!(env.rs_random().is_some());
+    }
+
+    #[test]
+    fn test_cartpole_deterministic() {
+        let rollout = || {
+            let mut env = CartPole::default();
+            let (observation, _) = env.reset(Some(42), None);
+            let mut observations = vec![observation.to_vec1::<f32>().unwrap()];
+            for step in 0..20 {
+                let StepResult { observation, .. } = env.step(step % 2);
+                observations.push(observation.to_vec1::<f32>().unwrap());
+            }
+            observations
+        };
+
+        let observations = rollout();
+        assert_eq!(observations, rollout());
+        assert!(observations[0].iter().all(|value| value.abs() <= 0.05));
+    }
+
+    #[test]
+    fn test_cartpole_terminates() {
+        let mut env = CartPole::default();
+        env.reset(Some(0), None);
+
+        // Always pushing the cart to the right makes the pole fall.
+        let steps = (1..=200).find(|_| env.step(1).terminated).unwrap();
+        assert!(steps < 200);
+
+        // Further steps are not rewarded.
+        assert_eq!(env.step(1).reward, 0.0);
+    }
+
+    #[test]
+    fn test_cartpole_info() {
+        let mut env = CartPole::default();
+        let (_, info) = env.reset(Some(0), None);
+        assert!(info.is_empty());
+
+        let steps_beyond_terminated = |info: &InfoMap| match info.get("steps_beyond_terminated") {
+            Some(InfoValue::Int(steps)) => Some(*steps),
+            None => None,
+            Some(value) => panic!("Expected an integer, got {value:?}"),
+        };
+        loop {
+            let StepResult {
+                terminated, info, ..
+            } = env.step(1);
+            if terminated {
+                assert_eq!(steps_beyond_terminated(&info), Some(0));
+                break;
+            }
+            assert_eq!(steps_beyond_terminated(&info), None);
+        }
+        assert_eq!(steps_beyond_terminated(&env.step(1).info), Some(1));
+
+        env.reset(None, None);
+        assert_eq!(steps_beyond_terminated(&env.step(1).info), None);
+    }
+
+    #[test]
+    fn test_cartpole_reset_bounds() {
+        let mut env = CartPole::default();
+        let options = InfoMap::from([
+            ("low".to_string(), InfoValue::Float(0.1)),
+            ("high".to_string(), InfoValue::Float(0.2)),
+        ]);
+        let (observation, _) = env.reset(Some(0), Some(&options));
+        let observation = observation.to_vec1::<f32>().unwrap();
+        assert!(observation.iter().all(|value| (0.1..=0.2).contains(value)));
+    }
+
+    #[test]
+    fn test_cartpole_render() {
+        let mut env = CartPole::new(Some("rgb_array"));
+        assert!(env.render().is_none());
+        env.reset(Some(0), None);
+
+        let Some(RenderFrame::RgbArray(frame)) = env.render() else {
+            panic!("Expected an RGB frame");
+        };
+        assert_eq!(frame.dims(), [400, 600, 3]);
+        assert_eq!(frame.dtype(), DType::U8);
+        // The cart is drawn in black at the center of the screen.
+        let pixel = frame.get(300).unwrap().get(300).unwrap();
+        assert_eq!(pixel.to_vec1::<u8>().unwrap(), [0, 0, 0]);
+
+        let mut env = CartPole::new(Some("ansi"));
+        env.reset(Some(0), None);
+        assert!(
+            matches!(env.render(), Some(RenderFrame::Ansi(text)) if text.contains("pole angle"))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Unsupported render mode")]
+    fn test_cartpole_unsupported_render_mode() {
+        CartPole::new(Some("human"));
+    }
+}
@@ -0,0 +1,12 @@
+//! Classic control environments from the reinforcement learning literature.
+
+pub mod acrobot;
+pub mod cartpole;
+pub mod mountain_car;
+pub mod pendulum;
+pub mod utils;
+
+pub use acrobot::Acrobot;
+pub use cartpole::CartPole;
+pub use mountain_car::MountainCar;
+pub use pendulum::Pendulum;
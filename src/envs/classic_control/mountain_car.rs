@@ -0,0 +1,280 @@
+//! Mountain car problem, first described by Andrew Moore in his PhD thesis.
+
+use candle_core::{DType, Device, Tensor};
+use rand::Rng;
+
+use crate::common::InfoMap;
+use crate::core::{Env, Metadata, RenderFrame, StepResult};
+use crate::envs::classic_control::utils::maybe_parse_reset_bounds;
+use crate::spaces::{Bound, Box, Discrete, Space, SpaceEnum};
+use crate::utils::seeding::{Generator, LazyGenerator};
+
+/// A car is placed stochastically at the bottom of a sinusoidal valley, with the only possible
+/// actions being the accelerations that can be applied to the car in either direction.
+///
+/// The goal of the MDP is to strategically accelerate the car to reach the goal state on top of
+/// the right hill. The engine is not strong enough to drive up the hill directly, so the car has
+/// to build momentum by driving back and forth.
+///
+/// ## Action Space
+///
+/// The action is an `i64` in `{0, 1, 2}`: `0` accelerates to the left, `1` does not accelerate
+/// and `2` accelerates to the right.
+///
+/// ## Observation Space
+///
+/// The observation is a `F32` tensor of shape `(2,)` with the values:
+///
+/// | Num | Observation                          | Min   | Max  |
+/// |-----|--------------------------------------|-------|------|
+/// | 0   | position of the car along the x-axis | -1.2  | 0.6  |
+/// | 1   | velocity of the car                  | -0.07 | 0.07 |
+///
+/// ## Rewards
+///
+/// A reward of `-1` is given at every step, penalising the time taken to reach the goal.
+///
+/// ## Starting State
+///
+/// The position of the car is assigned a uniform random value in `[-0.6, -0.4]`, which can be
+/// changed with the `"low"` and `"high"` reset options. The starting velocity is always `0`.
+///
+/// ## Episode End
+///
+/// The episode terminates if the position of the car is greater than or equal to `0.5`, the
+/// position of the goal on top of the right hill.
+pub struct MountainCar {
+    min_position: f64,
+    max_position: f64,
+    max_speed: f64,
+    goal_position: f64,
+    goal_velocity: f64,
+    force: f64,
+    gravity: f64,
+    action_space: SpaceEnum,
+    observation_space: SpaceEnum,
+    metadata: Metadata,
+    render_mode: Option<String>,
+    state: Option<[f64; 2]>,
+    rs_random: LazyGenerator,
+}
+
+impl MountainCar {
+    /// Creates the environment with a target velocity of `goal_velocity` at the goal, rendering
+    /// frames in `render_mode` if given.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `render_mode` is not one of the render modes of the metadata.
+    pub fn new(render_mode: Option<&str>, goal_velocity: f64) -> Self {
+        let metadata = Metadata::new(vec!["ansi".to_string()], Some(30));
+        if let Some(render_mode) = render_mode {
+            assert!(
+                metadata.supports_mode(render_mode),
+                "Unsupported render mode {render_mode:?}"
+            );
+        }
+
+        let (min_position, max_position, max_speed) = (-1.2, 0.6, 0.07);
+        let bound = |position: f64, speed: f64| {
+            let bound = Tensor::new(&[position, speed], &Device::Cpu);
+            Bound::Tensor(bound.expect("Bounds are a valid tensor"))
+        };
+        let observation_space = Box::new(
+            bound(min_position, -max_speed),
+            bound(max_position, max_speed),
+            None,
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .expect("The bounds are tensors");
+
+        Self {
+            min_position,
+            max_position,
+            max_speed,
+            goal_position: 0.5,
+            goal_velocity,
+            force: 0.001,
+            gravity: 0.0025,
+            action_space: Discrete::new(3, 0, None).into(),
+            observation_space: observation_space.into(),
+            metadata,
+            render_mode: render_mode.map(str::to_string),
+            state: None,
+            rs_random: LazyGenerator::default(),
+        }
+    }
+
+    /// The render mode given at construction.
+    pub fn render_mode(&self) -> Option<&str> {
+        self.render_mode.as_deref()
+    }
+
+    /// The height of the hills at `position`.
+    pub fn height(position: f64) -> f64 {
+        (3.0 * position).sin() * 0.45 + 0.55
+    }
+
+    fn observation(state: &[f64; 2]) -> Tensor {
+        Tensor::new(&state.map(|value| value as f32), &Device::Cpu)
+            .expect("The state is a valid tensor")
+    }
+}
+
+impl Default for MountainCar {
+    fn default() -> Self {
+        Self::new(None, 0.0)
+    }
+}
+
+impl Env<Tensor, i64> for MountainCar {
+    fn step(&mut self, action: i64) -> StepResult<Tensor> {
+        assert!(
+            self.action_space.contains(&action.into()),
+            "{action} invalid action"
+        );
+        let [mut position, mut velocity] =
+            self.state.expect("Call reset before using step method.");
+
+        velocity += (action - 1) as f64 * self.force + (3.0 * position).cos() * -self.gravity;
+        velocity = velocity.clamp(-self.max_speed, self.max_speed);
+        position += velocity;
+        position = position.clamp(self.min_position, self.max_position);
+        if position == self.min_position && velocity < 0.0 {
+            velocity = 0.0;
+        }
+
+        let terminated = position >= self.goal_position && velocity >= self.goal_velocity;
+        let state = [position, velocity];
+        self.state = Some(state);
+
+        StepResult {
+            observation: Self::observation(&state),
+            reward: -1.0,
+            terminated,
+            truncated: false,
+            info: InfoMap::new(),
+        }
+    }
+
+    fn reset(&mut self, seed: Option<u64>, options: Option<&InfoMap>) -> (Tensor, InfoMap) {
+        if let Some(seed) = seed {
+            self.rs_random.reseed(seed);
+        }
+        // Note that if you use custom reset bounds, it may lead to out-of-bound state/observations.
+        let (low, high) = maybe_parse_reset_bounds(options, -0.6, -0.4);
+        let state = [self.rs_random.get().gen_range(low..=high), 0.0];
+        self.state = Some(state);
+
+        (Self::observation(&state), InfoMap::new())
+    }
+
+    fn render(&mut self) -> Option<RenderFrame> {
+        let [position, velocity] = self.state?;
+        match self.render_mode.as_deref()? {
+            "ansi" => Some(RenderFrame::Ansi(format!(
+                "position: {position:.4}, velocity: {velocity:.4}, height: {:.4}",
+                Self::height(position)
+            ))),
+            _ => None,
+        }
+    }
+
+    fn action_space(&self) -> &SpaceEnum {
+        &self.action_space
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+
+    fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn rs_random(&mut self) -> Option<&mut Generator> {
+        Some(self.rs_random.get())
+    }
+
+    fn rs_random_seed(&self) -> Option<u64> {
+        self.rs_random.seed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MountainCar;
+    use crate::core::{Env, RenderFrame, StepResult};
+    use crate::spaces::{Space, SpaceEnum};
+    use crate::wrappers::TimeLimit;
+
+    #[test]
+    fn test_mountain_car_metadata() {
+        let env = MountainCar::default();
+        assert_eq!(env.metadata().render_fps(), Some(30));
+        assert!(env.spec().is_none());
+        assert!(matches!(env.action_space(), SpaceEnum::Discrete(space) if space.n() == 3));
+
+        let wrapped = TimeLimit::new(Box::new(MountainCar::default()), 200);
+        assert_eq!(wrapped.metadata(), env.metadata());
+    }
+
+    #[test]
+    fn test_mountain_car_accelerate_right_stays_in_bounds() {
+        let mut env = MountainCar::default();
+        let (observation, _) = env.reset(Some(0), None);
+        assert!(env
+            .observation_space()
+            .contains(&observation.clone().into()));
+
+        // The engine is too weak to drive up the right hill directly.
+        for _ in 0..1000 {
+            let StepResult {
+                observation,
+                reward,
+                terminated,
+                truncated,
+                ..
+            } = env.step(2);
+            assert!(env
+                .observation_space()
+                .contains(&observation.clone().into()));
+            assert_eq!(reward, -1.0);
+            assert!(!terminated);
+            assert!(!truncated);
+        }
+    }
+
+    #[test]
+    fn test_mountain_car_momentum_terminates() {
+        let mut env = MountainCar::default();
+        env.reset(Some(0), None);
+
+        // Accelerating in the direction of the velocity builds momentum to reach the goal.
+        let mut velocity = 0.0;
+        let steps = (1..=1000).find(|_| {
+            let action = if velocity < 0.0 { 0 } else { 2 };
+            let StepResult {
+                observation,
+                terminated,
+                ..
+            } = env.step(action);
+            assert!(env
+                .observation_space()
+                .contains(&observation.clone().into()));
+            velocity = observation.to_vec1::<f32>().unwrap()[1];
+            terminated
+        });
+        assert!(steps.is_some());
+    }
+
+    #[test]
+    fn test_mountain_car_render() {
+        let mut env = MountainCar::new(Some("ansi"), 0.0);
+        assert!(env.render().is_none());
+        env.reset(Some(0), None);
+        assert!(matches!(env.render(), Some(RenderFrame::Ansi(text)) if text.contains("position")));
+    }
+}
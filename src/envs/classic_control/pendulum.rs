@@ -0,0 +1,287 @@
+//! Inverted pendulum swingup problem, based on the classic problem in control theory.
+
+use std::f64::consts::PI;
+
+use candle_core::{DType, Device, Tensor};
+use rand::Rng;
+
+use crate::common::InfoMap;
+use crate::core::{Env, Metadata, RenderFrame, StepResult};
+use crate::envs::classic_control::utils::verify_number_and_cast;
+use crate::spaces::{Bound, Box, SpaceEnum};
+use crate::utils::seeding::{Generator, LazyGenerator};
+
+/// A pendulum is attached at one end to a fixed point, with the other end being free.
+///
+/// The pendulum starts in a random position and the goal is to apply torque on the free end to
+/// swing it into an upright position, with its center of gravity right above the fixed point.
+///
+/// ## Action Space
+///
+/// The action is a `F32` tensor of shape `(1,)` representing the torque applied to the free end
+/// of the pendulum, in `[-2, 2]`. Actions outside this range are clipped.
+///
+/// ## Observation Space
+///
+/// The observation is a `F32` tensor of shape `(3,)` with the values:
+///
+/// | Num | Observation      | Min  | Max |
+/// |-----|------------------|------|-----|
+/// | 0   | x = cos(theta)   | -1.0 | 1.0 |
+/// | 1   | y = sin(theta)   | -1.0 | 1.0 |
+/// | 2   | Angular Velocity | -8.0 | 8.0 |
+///
+/// ## Rewards
+///
+/// The reward is `-(theta^2 + 0.1 * theta_dot^2 + 0.001 * torque^2)`, where `theta` is the angle
+/// of the pendulum normalized to `[-pi, pi)`, `0` being upright. The reward is therefore always
+/// non-positive, with a maximum of `0` in the upright position at rest.
+///
+/// ## Starting State
+///
+/// The starting angle is a uniform random value in `[-pi, pi]` and the starting angular velocity
+/// in `[-1, 1]`. The bounds can be changed with the `"x_init"` and `"y_init"` reset options.
+///
+/// ## Episode End
+///
+/// The episode never terminates and should be truncated, e.g. after 200 steps.
+pub struct Pendulum {
+    max_speed: f64,
+    max_torque: f64,
+    dt: f64,
+    g: f64,
+    m: f64,
+    l: f64,
+    action_space: SpaceEnum,
+    observation_space: SpaceEnum,
+    metadata: Metadata,
+    render_mode: Option<String>,
+    state: Option<[f64; 2]>,
+    last_u: Option<f64>,
+    rs_random: LazyGenerator,
+}
+
+impl Pendulum {
+    /// Creates the environment with the acceleration of gravity `g`, rendering frames in
+    /// `render_mode` if given.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `render_mode` is not one of the render modes of the metadata.
+    pub fn new(render_mode: Option<&str>, g: f64) -> Self {
+        let metadata = Metadata::new(vec!["ansi".to_string()], Some(30));
+        if let Some(render_mode) = render_mode {
+            assert!(
+                metadata.supports_mode(render_mode),
+                "Unsupported render mode {render_mode:?}"
+            );
+        }
+
+        let (max_speed, max_torque) = (8.0, 2.0);
+        let high =
+            Tensor::new(&[1.0, 1.0, max_speed], &Device::Cpu).expect("Bounds are a valid tensor");
+        let low = high.neg().expect("Bounds can be negated");
+        let observation_space = Box::new(
+            Bound::Tensor(low),
+            Bound::Tensor(high),
+            None,
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .expect("The bounds are tensors");
+        let action_space = Box::new(
+            Bound::F64(-max_torque),
+            Bound::F64(max_torque),
+            Some(vec![1]),
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .expect("A shape is given");
+
+        Self {
+            max_speed,
+            max_torque,
+            dt: 0.05,
+            g,
+            m: 1.0,
+            l: 1.0,
+            action_space: action_space.into(),
+            observation_space: observation_space.into(),
+            metadata,
+            render_mode: render_mode.map(str::to_string),
+            state: None,
+            last_u: None,
+            rs_random: LazyGenerator::default(),
+        }
+    }
+
+    /// The render mode given at construction.
+    pub fn render_mode(&self) -> Option<&str> {
+        self.render_mode.as_deref()
+    }
+
+    fn observation(state: &[f64; 2]) -> Tensor {
+        let [theta, theta_dot] = *state;
+        let observation = [theta.cos(), theta.sin(), theta_dot].map(|value| value as f32);
+        Tensor::new(&observation, &Device::Cpu).expect("The observation is a valid tensor")
+    }
+}
+
+impl Default for Pendulum {
+    fn default() -> Self {
+        Self::new(None, 10.0)
+    }
+}
+
+impl Env<Tensor, Tensor> for Pendulum {
+    fn step(&mut self, action: Tensor) -> StepResult<Tensor> {
+        assert_eq!(action.dims(), [1], "The action must have shape (1,)");
+        let [theta, theta_dot] = self.state.expect("Call reset before using step method.");
+        let (g, m, l, dt) = (self.g, self.m, self.l, self.dt);
+
+        let u = action
+            .to_dtype(DType::F64)
+            .and_then(|action| action.to_vec1::<f64>())
+            .expect("The action is a 1-D tensor")[0]
+            .clamp(-self.max_torque, self.max_torque);
+        self.last_u = Some(u);
+        let costs = angle_normalize(theta).powi(2) + 0.1 * theta_dot.powi(2) + 0.001 * u.powi(2);
+
+        let new_theta_dot =
+            theta_dot + (3.0 * g / (2.0 * l) * theta.sin() + 3.0 / (m * l.powi(2)) * u) * dt;
+        let new_theta_dot = new_theta_dot.clamp(-self.max_speed, self.max_speed);
+        let new_theta = theta + new_theta_dot * dt;
+
+        let state = [new_theta, new_theta_dot];
+        self.state = Some(state);
+        StepResult {
+            observation: Self::observation(&state),
+            reward: -costs as f32,
+            terminated: false,
+            truncated: false,
+            info: InfoMap::new(),
+        }
+    }
+
+    fn reset(&mut self, seed: Option<u64>, options: Option<&InfoMap>) -> (Tensor, InfoMap) {
+        if let Some(seed) = seed {
+            self.rs_random.reseed(seed);
+        }
+        let option = |key: &str, default: f64| {
+            options
+                .and_then(|options| options.get(key))
+                .map_or(default, verify_number_and_cast)
+        };
+        let (x, y) = (option("x_init", PI), option("y_init", 1.0));
+        let state = [
+            self.rs_random.get().gen_range(-x..=x),
+            self.rs_random.get().gen_range(-y..=y),
+        ];
+        self.state = Some(state);
+        self.last_u = None;
+
+        (Self::observation(&state), InfoMap::new())
+    }
+
+    fn render(&mut self) -> Option<RenderFrame> {
+        let [theta, theta_dot] = self.state?;
+        match self.render_mode.as_deref()? {
+            "ansi" => Some(RenderFrame::Ansi(format!(
+                "angle: {:.4}, angular velocity: {theta_dot:.4}, torque: {:.4}",
+                angle_normalize(theta),
+                self.last_u.unwrap_or(0.0)
+            ))),
+            _ => None,
+        }
+    }
+
+    fn action_space(&self) -> &SpaceEnum {
+        &self.action_space
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+
+    fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn rs_random(&mut self) -> Option<&mut Generator> {
+        Some(self.rs_random.get())
+    }
+
+    fn rs_random_seed(&self) -> Option<u64> {
+        self.rs_random.seed()
+    }
+}
+
+/// Normalizes `x` to `[-pi, pi)`.
+fn angle_normalize(x: f64) -> f64 {
+    (x + PI).rem_euclid(2.0 * PI) - PI
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use candle_core::{Device, Tensor};
+
+    use super::{angle_normalize, Pendulum};
+    use crate::core::{Env, StepResult};
+    use crate::spaces::Space;
+
+    #[test]
+    fn test_pendulum_observation_and_reward() {
+        let mut env = Pendulum::default();
+        let mut actions = env.action_space().clone();
+        actions.seed(Some(0));
+
+        let (observation, _) = env.reset(Some(0), None);
+        assert!(env
+            .observation_space()
+            .contains(&observation.clone().into()));
+        for _ in 0..500 {
+            let StepResult {
+                observation,
+                reward,
+                terminated,
+                truncated,
+                ..
+            } = env.step(Tensor::try_from(actions.sample(None)).unwrap());
+            let observation = observation.to_vec1::<f32>().unwrap();
+            assert!(observation[..2].iter().all(|value| value.abs() <= 1.0));
+            assert!(observation[2].abs() <= 8.0);
+            assert!(reward <= 0.0);
+            assert!(!terminated);
+            assert!(!truncated);
+        }
+    }
+
+    #[test]
+    fn test_pendulum_clips_torque() {
+        let rollout = |torque: f32| {
+            let mut env = Pendulum::default();
+            env.reset(Some(3), None);
+            let action = Tensor::new(&[torque], &Device::Cpu).unwrap();
+            let StepResult {
+                observation,
+                reward,
+                ..
+            } = env.step(action);
+            (observation.to_vec1::<f32>().unwrap(), reward)
+        };
+
+        assert_eq!(rollout(5.0), rollout(2.0));
+    }
+
+    #[test]
+    fn test_angle_normalize() {
+        assert!((angle_normalize(0.5) - 0.5).abs() < 1e-12);
+        assert!((angle_normalize(2.0 * PI + 0.5) - 0.5).abs() < 1e-12);
+        assert!((angle_normalize(-PI - 0.5) - (PI - 0.5)).abs() < 1e-12);
+        assert_eq!(angle_normalize(PI), -PI);
+    }
+}
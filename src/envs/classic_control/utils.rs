@@ -0,0 +1,66 @@
+//! Utility functions shared by the classic control environments.
+
+use crate::common::{InfoMap, InfoValue};
+
+/// Returns the number stored in `value`, cast to `f64`.
+///
+/// # Panics
+///
+/// Panics if `value` is not a number.
+pub fn verify_number_and_cast(value: &InfoValue) -> f64 {
+    match value {
+        InfoValue::Float(value) => *value,
+        InfoValue::Int(value) => *value as f64,
+        value => panic!("An option is not a number: {value:?}"),
+    }
+}
+
+/// Returns the `low` and `high` bounds of the initial state given in the `options` of
+/// [`reset`](crate::core::Env::reset), falling back to `default_low` and `default_high`.
+///
+/// # Panics
+///
+/// Panics if a bound is not a number, or if `low` is greater than `high`.
+pub fn maybe_parse_reset_bounds(
+    options: Option<&InfoMap>,
+    default_low: f64,
+    default_high: f64,
+) -> (f64, f64) {
+    let bound = |key: &str, default: f64| {
+        options
+            .and_then(|options| options.get(key))
+            .map_or(default, verify_number_and_cast)
+    };
+    let (low, high) = (bound("low", default_low), bound("high", default_high));
+    assert!(
+        low <= high,
+        "Lower bound {low} must be lower than the higher bound {high}"
+    );
+    (low, high)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maybe_parse_reset_bounds() {
+        assert_eq!(maybe_parse_reset_bounds(None, -0.05, 0.05), (-0.05, 0.05));
+
+        let options = InfoMap::from([
+            ("low".to_string(), InfoValue::Int(-1)),
+            ("high".to_string(), InfoValue::Float(0.5)),
+        ]);
+        assert_eq!(
+            maybe_parse_reset_bounds(Some(&options), -0.05, 0.05),
+            (-1.0, 0.5)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "must be lower than")]
+    fn test_maybe_parse_reset_bounds_invalid() {
+        let options = InfoMap::from([("low".to_string(), InfoValue::Float(1.0))]);
+        maybe_parse_reset_bounds(Some(&options), -0.05, 0.05);
+    }
+}
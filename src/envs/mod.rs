@@ -0,0 +1,5 @@
+//! Environment registration and the built-in environments.
+
+pub mod classic_control;
+pub mod registration;
+pub mod toy_text;
@@ -0,0 +1,1422 @@
+//! Functions for registering environments within gymnust using public functions `make` and `register`.
+
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::{Arc, LazyLock, Mutex};
+
+use thiserror::Error;
+
+use crate::core::{Env, Metadata, Wrapper};
+use crate::spaces::StructuredSample;
+use crate::wrappers::{OrderEnforcing, PassiveEnvChecker, RenderCollection, TimeLimit};
+
+/// A keyword argument passed to environment and wrapper constructors.
+///
+/// With the `serde` feature, keyword arguments are serialized as plain values, e.g. `500` rather
+/// than `{"Int": 500}`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(untagged)
+)]
+pub enum KwargValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    StrList(Vec<String>),
+}
+
+/// Keyword arguments passed to environment and wrapper constructors, keyed by argument name.
+pub type Kwargs = BTreeMap<String, KwargValue>;
+
+impl From<bool> for KwargValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i64> for KwargValue {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<f64> for KwargValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<&str> for KwargValue {
+    fn from(value: &str) -> Self {
+        Self::Str(value.to_string())
+    }
+}
+
+impl From<String> for KwargValue {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
+
+impl From<Vec<String>> for KwargValue {
+    fn from(value: Vec<String>) -> Self {
+        Self::StrList(value)
+    }
+}
+
+/// A specification for recording wrapper configs.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "WrapperSpecArgs: serde::Serialize",
+        deserialize = "WrapperSpecArgs: serde::de::DeserializeOwned"
+    ))
+)]
+pub struct WrapperSpec<WrapperSpecArgs = Kwargs> {
+    /// The name of the wrapper.
+    name: String,
+    /// The location of the wrapper to create from.
+    entry_point: String,
+    /// Additional keyword arguments passed to the wrapper.
+    kwargs: WrapperSpecArgs,
+}
+
+impl<WrapperSpecArgs> WrapperSpec<WrapperSpecArgs> {
+    /// Creates the spec of the wrapper `name`, created from `entry_point` with `kwargs`.
+    pub fn new(
+        name: impl Into<String>,
+        entry_point: impl Into<String>,
+        kwargs: WrapperSpecArgs,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            entry_point: entry_point.into(),
+            kwargs,
+        }
+    }
+
+    /// The name of the wrapper.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The location of the wrapper to create from.
+    pub fn entry_point(&self) -> &str {
+        &self.entry_point
+    }
+
+    /// Additional keyword arguments passed to the wrapper.
+    pub fn kwargs(&self) -> &WrapperSpecArgs {
+        &self.kwargs
+    }
+}
+
+impl<WrapperSpecArgs: fmt::Debug> fmt::Display for WrapperSpec<WrapperSpecArgs> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "WrapperSpec(name={}, entry_point={}, kwargs={:?})",
+            self.name, self.entry_point, self.kwargs
+        )
+    }
+}
+
+/// A specification for creating environments with [`make`].
+///
+/// - `id`: The string used to create the environment with [`make`].
+/// - `entry_point`: The location of the environment to create from, registered with
+///   [`register_entry_point`].
+/// - `reward_threshold`: The reward threshold for completing the environment.
+/// - `nondeterministic`: If the observation of an environment cannot be repeated with the same initial
+///   state, random number generator state and actions.
+/// - `max_episode_steps`: The max number of steps that the environment can take before truncation.
+/// - `order_enforce`: If to enforce the order of `reset` before `step` and `render` functions.
+/// - `disable_env_checker`: If to disable the environment checker wrapper in [`make`].
+/// - `kwargs`: Additional keyword arguments passed to the environment during initialisation.
+/// - `namespace`, `name` and `version`: The components of `id`.
+/// - `applied_wrappers`: The wrappers applied to the environment by [`make`].
+/// - `vector_entry_point`: The location of the vectorized environment to create from.
+///
+/// With the `serde` feature, specs can be serialized, e.g. to save and share environment
+/// configurations.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "EnvSpecArgs: serde::Serialize, WrapperSpecArgs: serde::Serialize",
+        deserialize = "EnvSpecArgs: serde::de::DeserializeOwned, \
+                       WrapperSpecArgs: serde::de::DeserializeOwned"
+    ))
+)]
+pub struct EnvSpec<EnvSpecArgs = Kwargs, WrapperSpecArgs = Kwargs> {
+    pub id: String,
+    pub entry_point: String,
+
+    // Environment attributes
+    pub reward_threshold: Option<f32>,
+    pub nondeterministic: bool,
+
+    // Wrappers
+    pub max_episode_steps: Option<usize>,
+    pub order_enforce: bool,
+    pub disable_env_checker: bool,
+
+    // Environment arguments
+    pub kwargs: EnvSpecArgs,
+
+    // post-init attributes
+    pub namespace: Option<String>,
+    pub name: String,
+    pub version: Option<usize>,
+
+    // applied wrappers
+    pub applied_wrappers: Vec<WrapperSpec<WrapperSpecArgs>>,
+
+    // Vectorized environment entry point
+    pub vector_entry_point: Option<String>,
+}
+
+impl EnvSpec {
+    /// Returns a builder for a spec with the given `id` and `entry_point`.
+    ///
+    /// The order of `reset` before `step` is enforced and the environment checker is enabled by
+    /// default, while every other attribute is unset.
+    pub fn builder(id: impl Into<String>, entry_point: impl Into<String>) -> EnvSpecBuilder {
+        EnvSpecBuilder {
+            id: id.into(),
+            entry_point: entry_point.into(),
+            reward_threshold: None,
+            nondeterministic: false,
+            max_episode_steps: None,
+            order_enforce: true,
+            disable_env_checker: false,
+            kwargs: Kwargs::new(),
+            vector_entry_point: None,
+        }
+    }
+}
+
+/// Builder for an [`EnvSpec`], created with [`EnvSpec::builder`].
+#[derive(Debug, Clone)]
+pub struct EnvSpecBuilder {
+    id: String,
+    entry_point: String,
+    reward_threshold: Option<f32>,
+    nondeterministic: bool,
+    max_episode_steps: Option<usize>,
+    order_enforce: bool,
+    disable_env_checker: bool,
+    kwargs: Kwargs,
+    vector_entry_point: Option<String>,
+}
+
+impl EnvSpecBuilder {
+    /// Sets the reward threshold for completing the environment.
+    pub fn reward_threshold(mut self, reward_threshold: f32) -> Self {
+        self.reward_threshold = Some(reward_threshold);
+        self
+    }
+
+    /// Sets if the environment is nondeterministic.
+    pub fn nondeterministic(mut self, nondeterministic: bool) -> Self {
+        self.nondeterministic = nondeterministic;
+        self
+    }
+
+    /// Sets the max number of steps that the environment can take before truncation.
+    pub fn max_episode_steps(mut self, max_episode_steps: usize) -> Self {
+        self.max_episode_steps = Some(max_episode_steps);
+        self
+    }
+
+    /// Sets if to enforce the order of `reset` before `step` and `render` functions.
+    pub fn order_enforce(mut self, order_enforce: bool) -> Self {
+        self.order_enforce = order_enforce;
+        self
+    }
+
+    /// Sets if to disable the environment checker wrapper in [`make`].
+    pub fn disable_env_checker(mut self, disable_env_checker: bool) -> Self {
+        self.disable_env_checker = disable_env_checker;
+        self
+    }
+
+    /// Adds a keyword argument passed to the environment during initialisation.
+    pub fn kwarg(mut self, key: impl Into<String>, value: impl Into<KwargValue>) -> Self {
+        self.kwargs.insert(key.into(), value.into());
+        self
+    }
+
+    /// Sets the keyword arguments passed to the environment during initialisation.
+    pub fn kwargs(mut self, kwargs: Kwargs) -> Self {
+        self.kwargs = kwargs;
+        self
+    }
+
+    /// Sets the location of the vectorized environment to create from.
+    pub fn vector_entry_point(mut self, vector_entry_point: impl Into<String>) -> Self {
+        self.vector_entry_point = Some(vector_entry_point.into());
+        self
+    }
+
+    /// Builds the spec, parsing its id into the namespace, name and version.
+    pub fn build(self) -> Result<EnvSpec, ParseError> {
+        let (namespace, name, version) = parse_env_id(&self.id)?;
+        Ok(EnvSpec {
+            id: self.id,
+            entry_point: self.entry_point,
+            reward_threshold: self.reward_threshold,
+            nondeterministic: self.nondeterministic,
+            max_episode_steps: self.max_episode_steps,
+            order_enforce: self.order_enforce,
+            disable_env_checker: self.disable_env_checker,
+            kwargs: self.kwargs,
+            namespace,
+            name,
+            version,
+            applied_wrappers: Vec::new(),
+            vector_entry_point: self.vector_entry_point,
+        })
+    }
+}
+
+impl<EnvSpecArgs: fmt::Debug, WrapperSpecArgs> fmt::Display
+    for EnvSpec<EnvSpecArgs, WrapperSpecArgs>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "EnvSpec(id={}, entry_point={}, reward_threshold={:?}, nondeterministic={}, \
+             max_episode_steps={:?}, order_enforce={}, disable_env_checker={}, kwargs={:?})",
+            self.id,
+            self.entry_point,
+            self.reward_threshold,
+            self.nondeterministic,
+            self.max_episode_steps,
+            self.order_enforce,
+            self.disable_env_checker,
+            self.kwargs
+        )
+    }
+}
+
+/// Additional arguments for [`make`].
+#[derive(Debug, Clone, Default)]
+pub struct MakeOptions {
+    /// Keyword arguments passed to the environment, overriding those of the spec.
+    pub kwargs: Kwargs,
+    /// Overrides the `max_episode_steps` of the spec if set: `Some(None)` disables the time limit,
+    /// and `Some(Some(steps))` replaces it.
+    pub max_episode_steps: Option<Option<usize>>,
+    /// The render mode passed to the environment as the `render_mode` keyword argument.
+    pub render_mode: Option<String>,
+}
+
+/// Error returned by [`make`].
+#[derive(Debug, Error)]
+pub enum MakeError {
+    #[error("No registered env with id: {0}")]
+    UnknownId(String),
+    #[error("No entry point registered for `{0}`, register it with `register_entry_point`")]
+    UnknownEntryPoint(String),
+    #[error("The environment `{id}` does not have the requested observation and action types")]
+    TypeMismatch { id: String },
+    #[error(
+        "No wrapper entry point registered for `{0}`, register it with \
+         `register_wrapper_entry_point`"
+    )]
+    UnknownWrapperEntryPoint(String),
+    #[error("The wrapper `{name}` does not wrap environments of the requested types")]
+    WrapperTypeMismatch { name: String },
+    #[error(
+        "The environment `{id}` does not support the render mode `{mode}`, expected one of \
+         {supported:?}"
+    )]
+    UnsupportedRenderMode {
+        id: String,
+        mode: String,
+        supported: Vec<String>,
+    },
+    #[error("Failed to create the environment `{id}`: {source}")]
+    Creation { id: String, source: CreationError },
+}
+
+/// Error returned by the constructor of an environment registered with [`register_entry_point`].
+pub type CreationError = Box<dyn StdError + Send + Sync>;
+
+/// Error returned by [`register`].
+#[derive(Debug, Error)]
+pub enum RegisterError {
+    #[error(
+        "An environment with id `{0}` is already registered, set `allow_override` to replace it"
+    )]
+    AlreadyRegistered(String),
+    #[error(transparent)]
+    InvalidId(#[from] ParseError),
+}
+
+/// Error returned by [`parse_env_id`].
+#[derive(Debug, Error, PartialEq)]
+pub enum ParseError {
+    #[error(
+        "Malformed environment ID: {0}. (Currently all IDs must be of the form \
+         [namespace/](env-name)-v(version). (namespace is optional))"
+    )]
+    MalformedId(String),
+}
+
+/// Type-erased constructor of an environment, returning a `Box<dyn Env<ObsType, ActType>>`.
+type EnvCreator = Arc<dyn Fn(&Kwargs) -> Result<Box<dyn Any>, CreationError> + Send + Sync>;
+
+/// Type-erased constructor of a wrapper, wrapping a `Box<dyn Env<ObsType, ActType>>` into another.
+///
+/// Returns `None` if the environment does not have the types of the wrapper.
+type WrapperCreator = Arc<dyn Fn(Box<dyn Any>, &Kwargs) -> Option<Box<dyn Any>> + Send + Sync>;
+
+/// Global registry of environment specifications, keyed by id.
+static REGISTRY: LazyLock<Mutex<HashMap<String, EnvSpec>>> = LazyLock::new(Default::default);
+
+/// Global table of environment constructors and the metadata of their environments, keyed by entry
+/// point.
+static ENTRY_POINTS: LazyLock<Mutex<HashMap<String, (EnvCreator, Metadata)>>> =
+    LazyLock::new(Default::default);
+
+/// Global table of wrapper constructors, keyed by entry point.
+static WRAPPER_ENTRY_POINTS: LazyLock<Mutex<HashMap<String, WrapperCreator>>> =
+    LazyLock::new(Default::default);
+
+/// Registers the constructor of an environment under `entry_point`, so that specs can refer to it.
+///
+/// `metadata` is that of the environments created by `creator`, against which [`make`] checks
+/// render modes before creating anything. `creator` returns an error for invalid keyword
+/// arguments, which [`make`] surfaces as [`MakeError::Creation`].
+pub fn register_entry_point<ObsType, ActType, F>(entry_point: &str, metadata: Metadata, creator: F)
+where
+    ObsType: 'static,
+    ActType: 'static,
+    F: Fn(&Kwargs) -> Result<Box<dyn Env<ObsType, ActType>>, CreationError> + Send + Sync + 'static,
+{
+    let creator: EnvCreator =
+        Arc::new(move |kwargs| Ok(Box::new(creator(kwargs)?) as Box<dyn Any>));
+    ENTRY_POINTS
+        .lock()
+        .unwrap()
+        .insert(entry_point.to_string(), (creator, metadata));
+}
+
+/// Registers the constructor of a wrapper under `entry_point`, so that the
+/// [`applied_wrappers`](EnvSpec::applied_wrappers) of specs can refer to it.
+///
+/// `creator` wraps the environment it is given, configured by the keyword arguments of the
+/// [`WrapperSpec`].
+pub fn register_wrapper_entry_point<ObsType, ActType, F>(entry_point: &str, creator: F)
+where
+    ObsType: 'static,
+    ActType: 'static,
+    F: Fn(Box<dyn Env<ObsType, ActType>>, &Kwargs) -> Box<dyn Env<ObsType, ActType>>
+        + Send
+        + Sync
+        + 'static,
+{
+    let creator: WrapperCreator = Arc::new(move |env, kwargs| {
+        let env = *env.downcast::<Box<dyn Env<ObsType, ActType>>>().ok()?;
+        Some(Box::new(creator(env, kwargs)))
+    });
+    WRAPPER_ENTRY_POINTS
+        .lock()
+        .unwrap()
+        .insert(entry_point.to_string(), creator);
+}
+
+/// Parses an environment id of the form `[namespace/](env-name)[-v(version)]`, where the namespace
+/// and version are optional.
+///
+/// Returns a tuple of the environment namespace, name and version.
+pub fn parse_env_id(id: &str) -> Result<(Option<String>, String, Option<usize>), ParseError> {
+    let malformed = || ParseError::MalformedId(id.to_string());
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+    let (namespace, rest) = match id.split_once('/') {
+        Some((namespace, rest)) => (Some(namespace), rest),
+        None => (None, id),
+    };
+    if let Some(namespace) = namespace {
+        if namespace.is_empty()
+            || !namespace
+                .chars()
+                .all(|c| is_word(c) || c == ':' || c == '-')
+        {
+            return Err(malformed());
+        }
+    }
+
+    let (name, version) = match rest.rsplit_once("-v") {
+        Some((name, version)) if version.chars().all(|c| c.is_ascii_digit()) => {
+            (name, Some(version.parse().map_err(|_| malformed())?))
+        }
+        _ => (rest, None),
+    };
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| is_word(c) || c == ':' || c == '.' || c == '-')
+    {
+        return Err(malformed());
+    }
+
+    Ok((namespace.map(str::to_string), name.to_string(), version))
+}
+
+/// Registers an environment in gymnust with an `id` to use with [`make`].
+///
+/// The `namespace`, `name` and `version` of the spec are filled in from its id. Returns an error if
+/// the id is malformed, or if an environment with the same id is already registered, unless
+/// `allow_override` is `true`, in which case the previous spec is replaced.
+pub fn register(mut spec: EnvSpec, allow_override: bool) -> Result<(), RegisterError> {
+    (spec.namespace, spec.name, spec.version) = parse_env_id(&spec.id)?;
+
+    let mut registry = REGISTRY.lock().unwrap();
+    if !allow_override && registry.contains_key(&spec.id) {
+        return Err(RegisterError::AlreadyRegistered(spec.id));
+    }
+    registry.insert(spec.id.clone(), spec);
+    Ok(())
+}
+
+/// Returns the ids of all registered environments, sorted alphabetically.
+pub fn registry() -> Vec<String> {
+    let mut ids: Vec<String> = REGISTRY.lock().unwrap().keys().cloned().collect();
+    ids.sort();
+    ids
+}
+
+/// Returns a listing of the registered environments, grouped by namespace, e.g. to discover the
+/// environments available to [`make`].
+///
+/// Each group starts with a `===== namespace =====` header, followed by the ids of the group, one
+/// per line. Environments without a namespace are grouped by the module of their entry point, i.e.
+/// the part before the `:`. Groups and ids are sorted alphabetically.
+pub fn pprint_registry() -> String {
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for spec in REGISTRY.lock().unwrap().values() {
+        let namespace = match &spec.namespace {
+            Some(namespace) => namespace.clone(),
+            None => match spec.entry_point.split_once(':') {
+                Some((module, _)) => module.to_string(),
+                None => spec.entry_point.clone(),
+            },
+        };
+        groups.entry(namespace).or_default().push(spec.id.clone());
+    }
+
+    let groups: Vec<String> = groups
+        .into_iter()
+        .map(|(namespace, mut ids)| {
+            ids.sort();
+            format!("===== {namespace} =====\n{}\n", ids.join("\n"))
+        })
+        .collect();
+    groups.join("\n")
+}
+
+/// Entry points recorded in the [`applied_wrappers`](EnvSpec::applied_wrappers) of made
+/// environments for the wrappers applied by [`make`] itself.
+const MAKE_WRAPPER_ENTRY_POINTS: [&str; 4] = [
+    "gymnust.wrappers:PassiveEnvChecker",
+    "gymnust.wrappers:OrderEnforcing",
+    "gymnust.wrappers:TimeLimit",
+    "gymnust.wrappers:RenderCollection",
+];
+
+/// The spec of the wrapper `name` applied by [`make`] itself, called with `kwargs`.
+fn _make_wrapper_spec<const N: usize>(name: &str, kwargs: [(&str, KwargValue); N]) -> WrapperSpec {
+    let kwargs = kwargs
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect();
+    WrapperSpec::new(name, format!("gymnust.wrappers:{name}"), kwargs)
+}
+
+/// Reports the spec an environment was made from through [`Env::spec`].
+///
+/// As it is the innermost wrapper, it is returned by [`unwrapped`](Env::unwrapped) in place of the
+/// environment it wraps, so that the spec is also reported by the base environment.
+struct SpecifiedEnv<ObsType, ActType> {
+    env: Box<dyn Env<ObsType, ActType>>,
+    spec: EnvSpec,
+}
+
+impl<ObsType, ActType> Wrapper for SpecifiedEnv<ObsType, ActType> {
+    type ObsType = ObsType;
+    type ActType = ActType;
+
+    fn env(&self) -> &dyn Env<ObsType, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<ObsType, ActType> {
+        self.env.as_mut()
+    }
+
+    fn spec(&self) -> Option<&EnvSpec> {
+        Some(&self.spec)
+    }
+
+    fn unwrapped(&self) -> &dyn Env<ObsType, ActType> {
+        self
+    }
+}
+
+/// Creates an environment previously registered with [`register`].
+///
+/// The keyword arguments of `options` are merged over those of the spec before being passed to the
+/// constructor. The environment is wrapped in [`PassiveEnvChecker`] unless the spec sets
+/// `disable_env_checker`, in [`OrderEnforcing`] if the spec requires it, and in
+/// [`TimeLimit`] if `max_episode_steps` is set, by `options` or else by the spec. Finally, the
+/// [`applied_wrappers`](EnvSpec::applied_wrappers) of the spec are applied in order, from the
+/// constructors registered with [`register_wrapper_entry_point`].
+///
+/// The [`spec`](Env::spec) of the environment, also reported by its
+/// [`unwrapped`](Env::unwrapped) environment, is the registered spec with the merged keyword
+/// arguments, the resolved `max_episode_steps`, and every applied wrapper in
+/// [`applied_wrappers`](EnvSpec::applied_wrappers), starting with those applied by `make` itself
+/// under `gymnust.wrappers` entry points. These are skipped in the `applied_wrappers` of a
+/// registered spec, so that the spec of a made environment can be registered again.
+///
+/// The `render_mode` of `options` is checked against the metadata registered with the entry point,
+/// before the environment is created. The "rgb_array_list" and "ansi_list" modes, if not supported
+/// by the environment itself, create it in the "rgb_array" or "ansi" mode and wrap it in
+/// [`RenderCollection`].
+///
+/// Returns an error if the id is unknown, its entry point or that of one of its wrappers was never
+/// registered, the environment or one of its wrappers does not have the requested observation and
+/// action types, the environment does not support the render mode, or its constructor fails.
+pub fn make<ObsType, ActType>(
+    id: &str,
+    options: MakeOptions,
+) -> Result<Box<dyn Env<ObsType, ActType>>, MakeError>
+where
+    ObsType: Clone + Into<StructuredSample> + 'static,
+    ActType: 'static,
+{
+    let spec = REGISTRY
+        .lock()
+        .unwrap()
+        .get(id)
+        .cloned()
+        .ok_or_else(|| MakeError::UnknownId(id.to_string()))?;
+    let (creator, metadata) = ENTRY_POINTS
+        .lock()
+        .unwrap()
+        .get(&spec.entry_point)
+        .cloned()
+        .ok_or_else(|| MakeError::UnknownEntryPoint(spec.entry_point.clone()))?;
+
+    let mut kwargs = spec.kwargs.clone();
+    kwargs.extend(options.kwargs);
+    let mut collect_frames = false;
+    if let Some(mode) = options.render_mode {
+        let mode = match mode.strip_suffix("_list") {
+            _ if metadata.supports_mode(&mode) => mode,
+            Some(base @ ("rgb_array" | "ansi")) if metadata.supports_mode(base) => {
+                collect_frames = true;
+                base.to_string()
+            }
+            _ => {
+                return Err(MakeError::UnsupportedRenderMode {
+                    id: id.to_string(),
+                    mode,
+                    supported: metadata.render_modes().to_vec(),
+                })
+            }
+        };
+        kwargs.insert("render_mode".to_string(), KwargValue::Str(mode));
+    }
+    let env = creator(&kwargs)
+        .map_err(|source| MakeError::Creation {
+            id: id.to_string(),
+            source,
+        })?
+        .downcast::<Box<dyn Env<ObsType, ActType>>>()
+        .map(|env| *env)
+        .map_err(|_| MakeError::TypeMismatch { id: id.to_string() })?;
+    let env_checker = !spec.disable_env_checker;
+    let order_enforce = spec.order_enforce;
+    let max_episode_steps = options.max_episode_steps.unwrap_or(spec.max_episode_steps);
+    let wrapper_specs: Vec<WrapperSpec> = spec
+        .applied_wrappers
+        .iter()
+        .filter(|wrapper_spec| !MAKE_WRAPPER_ENTRY_POINTS.contains(&wrapper_spec.entry_point()))
+        .cloned()
+        .collect();
+
+    let mut applied_wrappers = Vec::new();
+    if env_checker {
+        applied_wrappers.push(_make_wrapper_spec("PassiveEnvChecker", []));
+    }
+    if order_enforce {
+        applied_wrappers.push(_make_wrapper_spec(
+            "OrderEnforcing",
+            [("disable_render_order_enforcing", KwargValue::Bool(false))],
+        ));
+    }
+    if let Some(max_episode_steps) = max_episode_steps {
+        applied_wrappers.push(_make_wrapper_spec(
+            "TimeLimit",
+            [(
+                "max_episode_steps",
+                KwargValue::Int(max_episode_steps as i64),
+            )],
+        ));
+    }
+    if collect_frames {
+        applied_wrappers.push(_make_wrapper_spec(
+            "RenderCollection",
+            [
+                ("pop_frames", KwargValue::Bool(true)),
+                ("reset_clean", KwargValue::Bool(true)),
+            ],
+        ));
+    }
+    applied_wrappers.extend(wrapper_specs.iter().cloned());
+    let spec = EnvSpec {
+        kwargs,
+        max_episode_steps,
+        applied_wrappers,
+        ..spec
+    };
+    let mut env: Box<dyn Env<ObsType, ActType>> = Box::new(SpecifiedEnv { env, spec });
+
+    if env_checker {
+        env = Box::new(PassiveEnvChecker::new(env));
+    }
+    if order_enforce {
+        env = Box::new(OrderEnforcing::new(env, false));
+    }
+    if let Some(max_episode_steps) = max_episode_steps {
+        env = Box::new(TimeLimit::new(env, max_episode_steps));
+    }
+    if collect_frames {
+        let wrapped =
+            RenderCollection::new(env, true, true).map_err(|err| MakeError::Creation {
+                id: id.to_string(),
+                source: Box::new(err),
+            })?;
+        env = Box::new(wrapped);
+    }
+    for wrapper_spec in &wrapper_specs {
+        let creator = WRAPPER_ENTRY_POINTS
+            .lock()
+            .unwrap()
+            .get(wrapper_spec.entry_point())
+            .cloned()
+            .ok_or_else(|| {
+                MakeError::UnknownWrapperEntryPoint(wrapper_spec.entry_point().to_string())
+            })?;
+        env = creator(Box::new(env), wrapper_spec.kwargs())
+            .and_then(|env| env.downcast::<Box<dyn Env<ObsType, ActType>>>().ok())
+            .map(|env| *env)
+            .ok_or_else(|| MakeError::WrapperTypeMismatch {
+                name: wrapper_spec.name().to_string(),
+            })?;
+    }
+
+    Ok(env)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::common::InfoMap;
+    use crate::core::{Metadata, RenderFrame, StepResult};
+    use crate::envs::toy_text::{FrozenLake, FrozenLakeError};
+    use crate::spaces::{Discrete, SpaceEnum};
+
+    /// Counts the steps taken since the last reset, starting from `start`, and renders the count in
+    /// the "ansi" mode.
+    struct CountingEnv {
+        start: i64,
+        count: i64,
+        space: SpaceEnum,
+        metadata: Metadata,
+        render_mode: Option<String>,
+    }
+
+    impl Env<i64, i64> for CountingEnv {
+        fn step(&mut self, action: i64) -> StepResult<i64> {
+            self.count += action;
+            (self.count, 1.0, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (i64, InfoMap) {
+            self.count = self.start;
+            (self.count, InfoMap::new())
+        }
+
+        fn render(&mut self) -> Option<RenderFrame> {
+            match self.render_mode.as_deref()? {
+                "ansi" => Some(RenderFrame::Ansi(self.count.to_string())),
+                mode => panic!("Unsupported render mode {mode:?}"),
+            }
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn metadata(&self) -> &Metadata {
+            &self.metadata
+        }
+    }
+
+    fn counting_spec(id: &str) -> EnvSpec {
+        // Built by hand so that malformed ids can reach `register`.
+        EnvSpec {
+            id: id.to_string(),
+            entry_point: "tests:CountingEnv".to_string(),
+            reward_threshold: None,
+            nondeterministic: false,
+            max_episode_steps: None,
+            order_enforce: true,
+            disable_env_checker: false,
+            kwargs: Kwargs::from([("start".to_string(), KwargValue::Int(10))]),
+            namespace: None,
+            name: id.to_string(),
+            version: None,
+            applied_wrappers: Vec::new(),
+            vector_entry_point: None,
+        }
+    }
+
+    #[test]
+    fn test_wrapper_spec_accessors() {
+        let spec = WrapperSpec::new(
+            "TimeLimit",
+            "gymnust.wrappers:TimeLimit",
+            Kwargs::from([("max_episode_steps".to_string(), KwargValue::Int(100))]),
+        );
+
+        assert_eq!(spec.name(), "TimeLimit");
+        assert_eq!(spec.entry_point(), "gymnust.wrappers:TimeLimit");
+        assert_eq!(spec.kwargs()["max_episode_steps"], KwargValue::Int(100));
+        assert_eq!(
+            spec.to_string(),
+            "WrapperSpec(name=TimeLimit, entry_point=gymnust.wrappers:TimeLimit, \
+             kwargs={\"max_episode_steps\": Int(100)})"
+        );
+    }
+
+    #[test]
+    fn test_env_spec_builder_defaults() {
+        let spec = EnvSpec::builder("BuilderMinimal", "tests:CountingEnv")
+            .build()
+            .unwrap();
+
+        assert_eq!(spec.id, "BuilderMinimal");
+        assert_eq!(spec.entry_point, "tests:CountingEnv");
+        assert_eq!(spec.reward_threshold, None);
+        assert!(!spec.nondeterministic);
+        assert_eq!(spec.max_episode_steps, None);
+        assert!(spec.order_enforce);
+        assert!(!spec.disable_env_checker);
+        assert!(spec.kwargs.is_empty());
+        assert_eq!(spec.namespace, None);
+        assert_eq!(spec.name, "BuilderMinimal");
+        assert_eq!(spec.version, None);
+        assert!(spec.applied_wrappers.is_empty());
+        assert_eq!(spec.vector_entry_point, None);
+    }
+
+    #[test]
+    fn test_env_spec_builder_all_fields() {
+        let spec = EnvSpec::builder("test/BuilderFull-v1", "tests:CountingEnv")
+            .reward_threshold(195.0)
+            .nondeterministic(true)
+            .max_episode_steps(200)
+            .order_enforce(false)
+            .disable_env_checker(true)
+            .kwarg("start", 3_i64)
+            .vector_entry_point("tests:CountingVectorEnv")
+            .build()
+            .unwrap();
+
+        assert_eq!(spec.reward_threshold, Some(195.0));
+        assert!(spec.nondeterministic);
+        assert_eq!(spec.max_episode_steps, Some(200));
+        assert!(!spec.order_enforce);
+        assert!(spec.disable_env_checker);
+        assert_eq!(spec.kwargs["start"], KwargValue::Int(3));
+        assert_eq!(spec.namespace.as_deref(), Some("test"));
+        assert_eq!(spec.name, "BuilderFull");
+        assert_eq!(spec.version, Some(1));
+        assert_eq!(
+            spec.vector_entry_point.as_deref(),
+            Some("tests:CountingVectorEnv")
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_env_spec_serde_round_trip() {
+        let mut spec = EnvSpec::builder("test/Serde-v2", "tests:CountingEnv")
+            .reward_threshold(0.5)
+            .max_episode_steps(100)
+            .kwarg("start", 3_i64)
+            .kwarg("scale", 1.5)
+            .kwarg("names", vec!["a".to_string(), "b".to_string()])
+            .build()
+            .unwrap();
+        spec.applied_wrappers.push(WrapperSpec::new(
+            "TimeLimit",
+            "gymnust.wrappers:TimeLimit",
+            Kwargs::from([("max_episode_steps".to_string(), KwargValue::Int(100))]),
+        ));
+
+        let json = serde_json::to_string(&spec).unwrap();
+        assert!(json.contains("\"start\":3"));
+        let deserialized: EnvSpec = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.id, spec.id);
+        assert_eq!(deserialized.entry_point, spec.entry_point);
+        assert_eq!(deserialized.reward_threshold, spec.reward_threshold);
+        assert_eq!(deserialized.nondeterministic, spec.nondeterministic);
+        assert_eq!(deserialized.max_episode_steps, spec.max_episode_steps);
+        assert_eq!(deserialized.order_enforce, spec.order_enforce);
+        assert_eq!(deserialized.disable_env_checker, spec.disable_env_checker);
+        assert_eq!(deserialized.kwargs, spec.kwargs);
+        assert_eq!(deserialized.namespace, spec.namespace);
+        assert_eq!(deserialized.name, spec.name);
+        assert_eq!(deserialized.version, spec.version);
+        assert_eq!(deserialized.applied_wrappers, spec.applied_wrappers);
+        assert_eq!(deserialized.vector_entry_point, spec.vector_entry_point);
+        assert_eq!(deserialized, spec);
+    }
+
+    #[test]
+    fn test_env_spec_builder_rejects_malformed_id() {
+        let err = EnvSpec::builder("Builder-v", "tests:CountingEnv")
+            .build()
+            .unwrap_err();
+        assert_eq!(err, ParseError::MalformedId("Builder-v".to_string()));
+    }
+
+    fn counting_metadata() -> Metadata {
+        Metadata::new(vec!["ansi".to_string()], None)
+    }
+
+    fn counting_env(kwargs: &Kwargs) -> Result<Box<dyn Env<i64, i64>>, CreationError> {
+        let start = match kwargs.get("start") {
+            Some(KwargValue::Int(start)) => *start,
+            _ => 0,
+        };
+        let render_mode = match kwargs.get("render_mode") {
+            Some(KwargValue::Str(mode)) => Some(mode.clone()),
+            _ => None,
+        };
+        Ok(Box::new(CountingEnv {
+            start,
+            count: 0,
+            space: Discrete::new(100, 0, None).into(),
+            metadata: counting_metadata(),
+            render_mode,
+        }))
+    }
+
+    fn register_counting_env() {
+        register_entry_point("tests:CountingEnv", counting_metadata(), counting_env);
+    }
+
+    #[test]
+    fn test_make_registered_env() {
+        register_counting_env();
+        register(counting_spec("MakeCounting-v0"), false).unwrap();
+
+        let mut env = make::<i64, i64>("MakeCounting-v0", MakeOptions::default()).unwrap();
+        assert_eq!(env.reset(None, None).0, 10);
+        assert_eq!(env.step(2).observation, 12);
+
+        let options = MakeOptions {
+            kwargs: Kwargs::from([("start".to_string(), KwargValue::Int(-5))]),
+            ..Default::default()
+        };
+        let mut env = make::<i64, i64>("MakeCounting-v0", options).unwrap();
+        assert_eq!(env.reset(None, None).0, -5);
+    }
+
+    #[test]
+    fn test_make_reports_spec() {
+        register_counting_env();
+        let mut spec = counting_spec("MakeSpec-v0");
+        spec.max_episode_steps = Some(5);
+        register(spec, false).unwrap();
+
+        let options = MakeOptions {
+            kwargs: Kwargs::from([("start".to_string(), KwargValue::Int(3))]),
+            ..Default::default()
+        };
+        let env = make::<i64, i64>("MakeSpec-v0", options).unwrap();
+        let spec = env.spec().unwrap();
+        assert_eq!(spec.id, "MakeSpec-v0");
+        assert_eq!(spec.max_episode_steps, Some(5));
+        assert_eq!(spec.kwargs["start"], KwargValue::Int(3));
+        assert_eq!(env.unwrapped().spec(), Some(spec));
+        assert_eq!(
+            spec.applied_wrappers,
+            [
+                WrapperSpec::new(
+                    "PassiveEnvChecker",
+                    "gymnust.wrappers:PassiveEnvChecker",
+                    Kwargs::new(),
+                ),
+                WrapperSpec::new(
+                    "OrderEnforcing",
+                    "gymnust.wrappers:OrderEnforcing",
+                    Kwargs::from([(
+                        "disable_render_order_enforcing".to_string(),
+                        KwargValue::Bool(false)
+                    )]),
+                ),
+                WrapperSpec::new(
+                    "TimeLimit",
+                    "gymnust.wrappers:TimeLimit",
+                    Kwargs::from([("max_episode_steps".to_string(), KwargValue::Int(5))]),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_make_registers_spec_of_made_env() {
+        register_counting_env();
+        let mut spec = counting_spec("MakeSpecAgain-v0");
+        spec.max_episode_steps = Some(5);
+        register(spec, false).unwrap();
+
+        let env = make::<i64, i64>("MakeSpecAgain-v0", MakeOptions::default()).unwrap();
+        let mut spec = env.spec().unwrap().clone();
+        spec.id = "MakeSpecAgain-v1".to_string();
+        register(spec, false).unwrap();
+
+        // The wrappers applied by `make` itself are not applied twice.
+        let mut env = make::<i64, i64>("MakeSpecAgain-v1", MakeOptions::default()).unwrap();
+        let names: Vec<&str> = env
+            .spec()
+            .unwrap()
+            .applied_wrappers
+            .iter()
+            .map(WrapperSpec::name)
+            .collect();
+        assert_eq!(names, ["PassiveEnvChecker", "OrderEnforcing", "TimeLimit"]);
+        env.reset(None, None);
+        assert_eq!((1..=10).find(|_| env.step(1).truncated), Some(5));
+    }
+
+    #[test]
+    fn test_make_applies_env_checker() {
+        register_counting_env();
+        register(counting_spec("MakeEnvChecker-v0"), false).unwrap();
+        let mut spec = counting_spec("MakeNoEnvChecker-v0");
+        spec.disable_env_checker = true;
+        register(spec, false).unwrap();
+        let first_wrapper = |id: &str| {
+            let env = make::<i64, i64>(id, MakeOptions::default()).unwrap();
+            let name = env.spec().unwrap().applied_wrappers[0].name().to_string();
+            name
+        };
+
+        assert_eq!(first_wrapper("MakeEnvChecker-v0"), "PassiveEnvChecker");
+        assert_eq!(first_wrapper("MakeNoEnvChecker-v0"), "OrderEnforcing");
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot call env.step() before calling env.reset()")]
+    fn test_make_enforces_order() {
+        register_counting_env();
+        register(counting_spec("MakeOrderEnforced-v0"), false).unwrap();
+
+        let mut env = make::<i64, i64>("MakeOrderEnforced-v0", MakeOptions::default()).unwrap();
+        env.step(1);
+    }
+
+    #[test]
+    fn test_make_applies_time_limit() {
+        register_counting_env();
+        let mut spec = counting_spec("MakeTimeLimit-v0");
+        spec.max_episode_steps = Some(2);
+        register(spec, false).unwrap();
+
+        let mut env = make::<i64, i64>("MakeTimeLimit-v0", MakeOptions::default()).unwrap();
+        env.reset(None, None);
+        assert!(!env.step(1).truncated);
+        assert!(env.step(1).truncated);
+    }
+
+    #[test]
+    fn test_make_overrides_max_episode_steps() {
+        register_counting_env();
+        let mut spec = counting_spec("MakeMaxEpisodeSteps-v0");
+        spec.max_episode_steps = Some(2);
+        register(spec, false).unwrap();
+        let steps_until_truncated = |env: &mut Box<dyn Env<i64, i64>>| {
+            env.reset(None, None);
+            (1..=10).find(|_| env.step(1).truncated)
+        };
+
+        let mut env = make::<i64, i64>("MakeMaxEpisodeSteps-v0", MakeOptions::default()).unwrap();
+        assert_eq!(env.spec().unwrap().max_episode_steps, Some(2));
+        assert_eq!(steps_until_truncated(&mut env), Some(2));
+
+        let options = MakeOptions {
+            max_episode_steps: Some(Some(5)),
+            ..Default::default()
+        };
+        let mut env = make::<i64, i64>("MakeMaxEpisodeSteps-v0", options).unwrap();
+        assert_eq!(env.spec().unwrap().max_episode_steps, Some(5));
+        assert_eq!(steps_until_truncated(&mut env), Some(5));
+
+        let options = MakeOptions {
+            max_episode_steps: Some(None),
+            ..Default::default()
+        };
+        let mut env = make::<i64, i64>("MakeMaxEpisodeSteps-v0", options).unwrap();
+        assert_eq!(env.spec().unwrap().max_episode_steps, None);
+        assert_eq!(steps_until_truncated(&mut env), None);
+    }
+
+    #[test]
+    fn test_make_applies_wrappers() {
+        register_counting_env();
+        register_wrapper_entry_point("tests:TimeLimit", |env, kwargs: &Kwargs| {
+            let max_episode_steps = match kwargs.get("max_episode_steps") {
+                Some(KwargValue::Int(steps)) => *steps as usize,
+                _ => panic!("Expected an integer max_episode_steps"),
+            };
+            Box::new(TimeLimit::<i64, i64>::new(env, max_episode_steps)) as Box<dyn Env<i64, i64>>
+        });
+        let mut spec = counting_spec("MakeAppliedWrappers-v0");
+        spec.applied_wrappers = vec![WrapperSpec::new(
+            "TimeLimit",
+            "tests:TimeLimit",
+            Kwargs::from([("max_episode_steps".to_string(), KwargValue::Int(3))]),
+        )];
+        register(spec, false).unwrap();
+
+        let mut env = make::<i64, i64>("MakeAppliedWrappers-v0", MakeOptions::default()).unwrap();
+        env.reset(None, None);
+        assert!(!env.step(1).truncated);
+        assert!(!env.step(1).truncated);
+        assert!(env.step(1).truncated);
+        let names: Vec<&str> = env
+            .spec()
+            .unwrap()
+            .applied_wrappers
+            .iter()
+            .map(WrapperSpec::name)
+            .collect();
+        assert_eq!(names, ["PassiveEnvChecker", "OrderEnforcing", "TimeLimit"]);
+        assert_eq!(
+            env.spec().unwrap().applied_wrappers[2].entry_point(),
+            "tests:TimeLimit"
+        );
+    }
+
+    #[test]
+    fn test_make_wrapper_errors() {
+        register_counting_env();
+        register_wrapper_entry_point("tests:FloatTimeLimit", |env, _kwargs: &Kwargs| {
+            Box::new(TimeLimit::<f32, i64>::new(env, 1)) as Box<dyn Env<f32, i64>>
+        });
+        let mut spec = counting_spec("MakeUnknownWrapper-v0");
+        spec.applied_wrappers = vec![WrapperSpec::new("Unknown", "tests:Unknown", Kwargs::new())];
+        register(spec, false).unwrap();
+        let mut spec = counting_spec("MakeWrongWrapperTypes-v0");
+        spec.applied_wrappers = vec![WrapperSpec::new(
+            "FloatTimeLimit",
+            "tests:FloatTimeLimit",
+            Kwargs::new(),
+        )];
+        register(spec, false).unwrap();
+
+        let err = make::<i64, i64>("MakeUnknownWrapper-v0", MakeOptions::default())
+            .err()
+            .unwrap();
+        assert!(
+            matches!(err, MakeError::UnknownWrapperEntryPoint(entry) if entry == "tests:Unknown")
+        );
+        let err = make::<i64, i64>("MakeWrongWrapperTypes-v0", MakeOptions::default())
+            .err()
+            .unwrap();
+        assert!(matches!(err, MakeError::WrapperTypeMismatch { name } if name == "FloatTimeLimit"));
+    }
+
+    #[test]
+    fn test_make_render_mode() {
+        register_counting_env();
+        register(counting_spec("MakeRenderMode-v0"), false).unwrap();
+        let options = |mode: &str| MakeOptions {
+            render_mode: Some(mode.to_string()),
+            ..Default::default()
+        };
+
+        let mut env = make::<i64, i64>("MakeRenderMode-v0", options("ansi")).unwrap();
+        assert_eq!(
+            env.spec().unwrap().kwargs["render_mode"],
+            KwargValue::Str("ansi".to_string())
+        );
+        env.reset(None, None);
+        env.step(2);
+        assert!(matches!(env.render(), Some(RenderFrame::Ansi(frame)) if frame == "12"));
+
+        let err = make::<i64, i64>("MakeRenderMode-v0", options("rgb_array"))
+            .err()
+            .unwrap();
+        assert!(matches!(
+            &err,
+            MakeError::UnsupportedRenderMode { mode, supported, .. }
+                if mode == "rgb_array" && supported == &["ansi"]
+        ));
+        assert_eq!(
+            err.to_string(),
+            "The environment `MakeRenderMode-v0` does not support the render mode `rgb_array`, \
+             expected one of [\"ansi\"]"
+        );
+    }
+
+    #[test]
+    fn test_make_surfaces_creation_errors() {
+        register_entry_point("tests:FrozenLake", counting_metadata(), |kwargs| {
+            let map_name = match kwargs.get("map_name") {
+                Some(KwargValue::Str(map_name)) => map_name.as_str(),
+                _ => "4x4",
+            };
+            let env = FrozenLake::new(None, None, map_name, false)?;
+            Ok(Box::new(env) as Box<dyn Env<i64, i64>>)
+        });
+        let spec = EnvSpec::builder("MakeCreationError-v0", "tests:FrozenLake")
+            .build()
+            .unwrap();
+        register(spec, false).unwrap();
+
+        assert!(make::<i64, i64>("MakeCreationError-v0", MakeOptions::default()).is_ok());
+        let options = MakeOptions {
+            kwargs: Kwargs::from([("map_name".to_string(), KwargValue::from("5x5"))]),
+            ..Default::default()
+        };
+        let err = make::<i64, i64>("MakeCreationError-v0", options)
+            .err()
+            .unwrap();
+        let MakeError::Creation { id, source } = &err else {
+            panic!("expected a creation error, got {err:?}");
+        };
+        assert_eq!(id, "MakeCreationError-v0");
+        assert_eq!(
+            source.downcast_ref::<FrozenLakeError>(),
+            Some(&FrozenLakeError::UnknownMapName("5x5".to_string()))
+        );
+        assert_eq!(
+            err.to_string(),
+            "Failed to create the environment `MakeCreationError-v0`: Unknown map name \"5x5\", \
+             expected \"4x4\" or \"8x8\""
+        );
+    }
+
+    #[test]
+    fn test_make_render_mode_creates_env_once() {
+        static CREATED: AtomicUsize = AtomicUsize::new(0);
+        register_entry_point("tests:CreationCounted", counting_metadata(), |kwargs| {
+            CREATED.fetch_add(1, Ordering::SeqCst);
+            counting_env(kwargs)
+        });
+        let mut spec = counting_spec("MakeRenderModeCreations-v0");
+        spec.entry_point = "tests:CreationCounted".to_string();
+        register(spec, false).unwrap();
+        let options = |mode: &str| MakeOptions {
+            render_mode: Some(mode.to_string()),
+            ..Default::default()
+        };
+
+        make::<i64, i64>("MakeRenderModeCreations-v0", options("ansi_list")).unwrap();
+        assert_eq!(CREATED.load(Ordering::SeqCst), 1);
+        // Unsupported modes are rejected before creating the environment.
+        assert!(make::<i64, i64>("MakeRenderModeCreations-v0", options("human")).is_err());
+        assert_eq!(CREATED.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_make_render_mode_list() {
+        register_counting_env();
+        register(counting_spec("MakeRenderModeList-v0"), false).unwrap();
+        let options = MakeOptions {
+            render_mode: Some("ansi_list".to_string()),
+            ..Default::default()
+        };
+
+        let mut env = make::<i64, i64>("MakeRenderModeList-v0", options).unwrap();
+        env.reset(None, None);
+        env.step(1);
+        env.step(1);
+        let Some(RenderFrame::List(frames)) = env.render() else {
+            panic!("Expected the frames collected since the reset");
+        };
+        let frames: Vec<String> = frames
+            .into_iter()
+            .map(|frame| match frame {
+                RenderFrame::Ansi(frame) => frame,
+                frame => panic!("Expected an ansi frame, got {frame:?}"),
+            })
+            .collect();
+        assert_eq!(frames, ["10", "11", "12"]);
+        assert!(matches!(env.render(), Some(RenderFrame::List(frames)) if frames.is_empty()));
+        let applied_wrappers = &env.spec().unwrap().applied_wrappers;
+        assert_eq!(applied_wrappers.last().unwrap().name(), "RenderCollection");
+    }
+
+    #[test]
+    fn test_make_unknown_id() {
+        let err = make::<i64, i64>("Unknown-v0", MakeOptions::default())
+            .err()
+            .unwrap();
+
+        assert!(matches!(err, MakeError::UnknownId(_)));
+        assert_eq!(err.to_string(), "No registered env with id: Unknown-v0");
+    }
+
+    #[test]
+    fn test_make_wrong_types() {
+        register_counting_env();
+        register(counting_spec("MakeWrongTypes-v0"), false).unwrap();
+
+        let err = make::<StructuredSample, i64>("MakeWrongTypes-v0", MakeOptions::default())
+            .err()
+            .unwrap();
+        assert!(matches!(err, MakeError::TypeMismatch { .. }));
+    }
+
+    #[test]
+    fn test_register_lists_ids() {
+        register(counting_spec("RegisterListed-v0"), false).unwrap();
+        register(counting_spec("RegisterListed-v1"), false).unwrap();
+
+        let ids = registry();
+        assert!(ids.contains(&"RegisterListed-v0".to_string()));
+        assert!(ids.contains(&"RegisterListed-v1".to_string()));
+        assert!(ids.windows(2).all(|ids| ids[0] <= ids[1]));
+    }
+
+    #[test]
+    fn test_pprint_registry_groups_by_namespace() {
+        for id in [
+            "pprint_b/Gamma-v0",
+            "pprint_a/Beta-v0",
+            "pprint_a/Alpha-v1",
+            "pprint_a/Alpha-v0",
+            "PprintDefault-v0",
+        ] {
+            register(counting_spec(id), false).unwrap();
+        }
+
+        let listing = pprint_registry();
+        let first = listing
+            .find(
+                "===== pprint_a =====\npprint_a/Alpha-v0\npprint_a/Alpha-v1\npprint_a/Beta-v0\n\n",
+            )
+            .unwrap();
+        let second = listing
+            .find("===== pprint_b =====\npprint_b/Gamma-v0\n")
+            .unwrap();
+        assert!(first < second);
+        // Without a namespace, the environment is listed under the module of its entry point.
+        let default = listing.find("===== tests =====\n").unwrap();
+        assert!(listing[default..].contains("\nPprintDefault-v0\n"));
+    }
+
+    #[test]
+    fn test_register_rejects_duplicates() {
+        register(counting_spec("RegisterDuplicate-v0"), false).unwrap();
+        let err = register(counting_spec("RegisterDuplicate-v0"), false).unwrap_err();
+
+        assert!(
+            matches!(err, RegisterError::AlreadyRegistered(id) if id == "RegisterDuplicate-v0")
+        );
+    }
+
+    #[test]
+    fn test_register_with_override() {
+        register_counting_env();
+        register(counting_spec("RegisterOverride-v0"), false).unwrap();
+        let mut spec = counting_spec("RegisterOverride-v0");
+        spec.kwargs.insert("start".to_string(), KwargValue::Int(3));
+        register(spec, true).unwrap();
+
+        let mut env = make::<i64, i64>("RegisterOverride-v0", MakeOptions::default()).unwrap();
+        assert_eq!(env.reset(None, None).0, 3);
+    }
+
+    #[test]
+    fn test_parse_env_id_valid_forms() {
+        assert_eq!(
+            parse_env_id("ns/Name-v3"),
+            Ok((Some("ns".to_string()), "Name".to_string(), Some(3)))
+        );
+        assert_eq!(
+            parse_env_id("Name-v0"),
+            Ok((None, "Name".to_string(), Some(0)))
+        );
+        assert_eq!(parse_env_id("Name"), Ok((None, "Name".to_string(), None)));
+        assert_eq!(
+            parse_env_id("my-ns/Cart.Pole-Hard-v12"),
+            Ok((
+                Some("my-ns".to_string()),
+                "Cart.Pole-Hard".to_string(),
+                Some(12)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_env_id_invalid_forms() {
+        for id in [
+            "Name-v",
+            "ns//Name-v0",
+            "ns/sub/Name-v0",
+            "/Name-v0",
+            "ns/",
+            "",
+            "-v1",
+            "Na me",
+        ] {
+            assert_eq!(
+                parse_env_id(id),
+                Err(ParseError::MalformedId(id.to_string())),
+                "{id} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_register_fills_id_components() {
+        register(counting_spec("test/RegisterParsed-v2"), false).unwrap();
+        let spec = REGISTRY.lock().unwrap()["test/RegisterParsed-v2"].clone();
+
+        assert_eq!(spec.namespace.as_deref(), Some("test"));
+        assert_eq!(spec.name, "RegisterParsed");
+        assert_eq!(spec.version, Some(2));
+        assert!(matches!(
+            register(counting_spec("RegisterParsed-v"), false),
+            Err(RegisterError::InvalidId(_))
+        ));
+    }
+}
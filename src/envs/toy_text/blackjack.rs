@@ -0,0 +1,363 @@
+//! Blackjack card game, as described in Example 5.1 of Sutton and Barto's "Reinforcement
+//! Learning: An Introduction".
+
+use std::cmp::Ordering;
+
+use rand::Rng;
+
+use crate::common::InfoMap;
+use crate::core::{Env, Metadata, RenderFrame, StepResult};
+use crate::spaces::{Discrete, Space, SpaceEnum, StructuredSample, Tuple};
+use crate::utils::seeding::{Generator, LazyGenerator};
+
+/// 1 = Ace, 2-10 = Number cards, Jack/Queen/King = 10.
+const DECK: [i64; 13] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 10, 10, 10];
+
+const STICK: i64 = 0;
+const HIT: i64 = 1;
+
+/// Does this hand have a usable ace?
+fn usable_ace(hand: &[i64]) -> bool {
+    hand.contains(&1) && hand.iter().sum::<i64>() + 10 <= 21
+}
+
+/// Returns the total of the current hand.
+fn sum_hand(hand: &[i64]) -> i64 {
+    hand.iter().sum::<i64>() + if usable_ace(hand) { 10 } else { 0 }
+}
+
+/// Is this hand a bust?
+fn is_bust(hand: &[i64]) -> bool {
+    sum_hand(hand) > 21
+}
+
+/// What is the score of this hand (0 if bust)?
+fn score(hand: &[i64]) -> i64 {
+    if is_bust(hand) {
+        0
+    } else {
+        sum_hand(hand)
+    }
+}
+
+/// Is this hand a natural blackjack?
+fn is_natural(hand: &[i64]) -> bool {
+    hand.len() == 2 && hand.contains(&1) && hand.contains(&10)
+}
+
+/// Beating the dealer by obtaining cards that sum closer to 21, without going over 21, than the
+/// dealer's cards.
+///
+/// The game starts with the dealer having one face up and one face down card, while the player
+/// has two face up cards. All cards are drawn from an infinite deck, i.e. with replacement. Face
+/// cards count as 10 and an ace counts as either 11 ("usable") or 1.
+///
+/// ## Action Space
+///
+/// The action is an `i64` in `{0, 1}`: `0` sticks and `1` hits, i.e. draws another card.
+///
+/// ## Observation Space
+///
+/// The observation is a `Tuple` of the player's current sum in `Discrete(32)`, the value of the
+/// dealer's face up card in `Discrete(11)` (where `1` is an ace), and whether the player holds a
+/// usable ace in `Discrete(2)`.
+///
+/// ## Rewards
+///
+/// - win game: `+1`
+/// - lose game: `-1`
+/// - draw game: `0`
+/// - win game with natural blackjack: `+1.5` if `natural` is set, else `+1`
+///
+/// ## Episode End
+///
+/// The episode terminates when the player hits and exceeds 21, or sticks. After the player
+/// sticks, the dealer draws cards until their sum is 17 or greater.
+///
+/// If `sab` is set, the rules of Sutton and Barto are followed instead, overriding `natural`: a
+/// natural blackjack wins with `+1` unless the dealer also has a natural blackjack, in which case
+/// the game is drawn.
+pub struct Blackjack {
+    natural: bool,
+    sab: bool,
+    action_space: SpaceEnum,
+    observation_space: SpaceEnum,
+    metadata: Metadata,
+    render_mode: Option<String>,
+    dealer: Vec<i64>,
+    player: Vec<i64>,
+    rs_random: LazyGenerator,
+}
+
+impl Blackjack {
+    /// Creates the environment with the `natural` and `sab` rules, rendering frames in
+    /// `render_mode` if given.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `render_mode` is not one of the render modes of the metadata.
+    pub fn new(render_mode: Option<&str>, natural: bool, sab: bool) -> Self {
+        let metadata = Metadata::new(vec!["ansi".to_string()], Some(4));
+        if let Some(render_mode) = render_mode {
+            assert!(
+                metadata.supports_mode(render_mode),
+                "Unsupported render mode {render_mode:?}"
+            );
+        }
+
+        let observation_space = Tuple::new(
+            vec![
+                Discrete::new(32, 0, None).into(),
+                Discrete::new(11, 0, None).into(),
+                Discrete::new(2, 0, None).into(),
+            ],
+            None,
+        );
+
+        Self {
+            natural,
+            sab,
+            action_space: Discrete::new(2, 0, None).into(),
+            observation_space: observation_space.into(),
+            metadata,
+            render_mode: render_mode.map(str::to_string),
+            dealer: Vec::new(),
+            player: Vec::new(),
+            rs_random: LazyGenerator::default(),
+        }
+    }
+
+    /// The render mode given at construction.
+    pub fn render_mode(&self) -> Option<&str> {
+        self.render_mode.as_deref()
+    }
+
+    fn draw_card(&mut self) -> i64 {
+        DECK[self.rs_random.get().gen_range(0..DECK.len())]
+    }
+
+    fn draw_hand(&mut self) -> Vec<i64> {
+        vec![self.draw_card(), self.draw_card()]
+    }
+
+    fn observation(&self) -> StructuredSample {
+        StructuredSample::Tuple(vec![
+            StructuredSample::Int(sum_hand(&self.player)),
+            StructuredSample::Int(self.dealer[0]),
+            StructuredSample::Int(usable_ace(&self.player) as i64),
+        ])
+    }
+}
+
+impl Default for Blackjack {
+    fn default() -> Self {
+        Self::new(None, false, false)
+    }
+}
+
+impl Env<StructuredSample, i64> for Blackjack {
+    fn step(&mut self, action: i64) -> StepResult<StructuredSample> {
+        assert!(
+            self.action_space.contains(&action.into()),
+            "{action} invalid action"
+        );
+        assert!(
+            !self.player.is_empty(),
+            "Call reset before using step method."
+        );
+
+        let (terminated, reward) = match action {
+            HIT => {
+                let card = self.draw_card();
+                self.player.push(card);
+                if is_bust(&self.player) {
+                    (true, -1.0)
+                } else {
+                    (false, 0.0)
+                }
+            }
+            STICK => {
+                while sum_hand(&self.dealer) < 17 {
+                    let card = self.draw_card();
+                    self.dealer.push(card);
+                }
+                let reward = match score(&self.player).cmp(&score(&self.dealer)) {
+                    Ordering::Greater => 1.0,
+                    Ordering::Equal => 0.0,
+                    Ordering::Less => -1.0,
+                };
+                let reward = if self.sab && is_natural(&self.player) && !is_natural(&self.dealer) {
+                    // Player automatically wins. Rules consistent with Sutton and Barto.
+                    1.0
+                } else if !self.sab && self.natural && is_natural(&self.player) && reward == 1.0 {
+                    // Natural gives extra points, but doesn't autowin. Legacy implementation.
+                    1.5
+                } else {
+                    reward
+                };
+                (true, reward)
+            }
+            _ => unreachable!("Actions are checked against the action space"),
+        };
+
+        StepResult {
+            observation: self.observation(),
+            reward,
+            terminated,
+            truncated: false,
+            info: InfoMap::new(),
+        }
+    }
+
+    fn reset(
+        &mut self,
+        seed: Option<u64>,
+        _options: Option<&InfoMap>,
+    ) -> (StructuredSample, InfoMap) {
+        if let Some(seed) = seed {
+            self.rs_random.reseed(seed);
+        }
+        self.dealer = self.draw_hand();
+        self.player = self.draw_hand();
+
+        (self.observation(), InfoMap::new())
+    }
+
+    fn render(&mut self) -> Option<RenderFrame> {
+        if self.player.is_empty() {
+            return None;
+        }
+        match self.render_mode.as_deref()? {
+            "ansi" => Some(RenderFrame::Ansi(format!(
+                "Player: {:?} (sum {}), Dealer: {} showing",
+                self.player,
+                sum_hand(&self.player),
+                self.dealer[0]
+            ))),
+            _ => None,
+        }
+    }
+
+    fn action_space(&self) -> &SpaceEnum {
+        &self.action_space
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+
+    fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn rs_random(&mut self) -> Option<&mut Generator> {
+        Some(self.rs_random.get())
+    }
+
+    fn rs_random_seed(&self) -> Option<u64> {
+        self.rs_random.seed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_natural, score, sum_hand, usable_ace, Blackjack, HIT, STICK};
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Space, StructuredSample};
+
+    fn unpack(observation: &StructuredSample) -> (i64, i64, i64) {
+        match observation {
+            StructuredSample::Tuple(values) => match values.as_slice() {
+                [StructuredSample::Int(player), StructuredSample::Int(dealer), StructuredSample::Int(ace)] => {
+                    (*player, *dealer, *ace)
+                }
+                _ => panic!("Expected three integers"),
+            },
+            _ => panic!("Expected a tuple"),
+        }
+    }
+
+    fn contains(env: &Blackjack, observation: &StructuredSample) -> bool {
+        env.observation_space().contains(observation)
+    }
+
+    #[test]
+    fn test_blackjack_hands() {
+        assert!(usable_ace(&[1, 5]));
+        assert!(!usable_ace(&[1, 5, 10]));
+        assert_eq!(sum_hand(&[1, 10]), 21);
+        assert_eq!(sum_hand(&[1, 5, 10]), 16);
+        assert_eq!(score(&[10, 10, 5]), 0);
+        assert!(is_natural(&[10, 1]));
+        assert!(!is_natural(&[5, 6, 10]));
+    }
+
+    #[test]
+    fn test_blackjack_observation_bounds() {
+        let mut env = Blackjack::default();
+        let (mut observation, _) = env.reset(Some(0), None);
+        for step in 0..1000 {
+            assert!(contains(&env, &observation));
+            let action = if step % 3 == 0 { STICK } else { HIT };
+            let StepResult {
+                observation: next,
+                reward,
+                terminated,
+                truncated,
+                ..
+            } = env.step(action);
+            assert!(contains(&env, &next));
+            assert!([-1.0, 0.0, 1.0].contains(&reward));
+            assert!(!truncated);
+            observation = if terminated {
+                env.reset(None, None).0
+            } else {
+                next
+            };
+        }
+    }
+
+    #[test]
+    fn test_blackjack_stick_on_21_against_low_card() {
+        let mut env = Blackjack::default();
+        env.reset(Some(0), None);
+
+        let mut rewards = Vec::new();
+        while rewards.len() < 100 {
+            let (observation, _) = env.reset(None, None);
+            let (player, dealer, _) = unpack(&observation);
+            if player == 21 && (2..=6).contains(&dealer) {
+                let StepResult {
+                    reward, terminated, ..
+                } = env.step(STICK);
+                assert!(terminated);
+                rewards.push(reward);
+            }
+        }
+
+        // Sticking on 21 can at worst draw.
+        assert!(rewards.iter().all(|reward| *reward >= 0.0));
+        let mean = rewards.iter().sum::<f32>() / rewards.len() as f32;
+        assert!(mean > 0.8, "mean reward {mean} should be close to 1");
+    }
+
+    #[test]
+    fn test_blackjack_natural() {
+        let find_natural_win = |natural: bool| {
+            let mut env = Blackjack::new(None, natural, false);
+            env.reset(Some(0), None);
+            loop {
+                let (observation, _) = env.reset(None, None);
+                if unpack(&observation).0 == 21 {
+                    let StepResult { reward, .. } = env.step(STICK);
+                    if reward > 0.0 {
+                        return reward;
+                    }
+                }
+            }
+        };
+
+        assert_eq!(find_natural_win(true), 1.5);
+        assert_eq!(find_natural_win(false), 1.0);
+    }
+}
@@ -0,0 +1,278 @@
+//! Cliff walking grid world from Sutton and Barto's "Reinforcement Learning: An Introduction".
+
+use std::fmt::Write;
+
+use rand::Rng;
+
+use crate::common::{InfoMap, InfoValue};
+use crate::core::{Env, Metadata, RenderFrame, StepResult};
+use crate::spaces::{Discrete, Space, SpaceEnum};
+use crate::utils::seeding::{Generator, LazyGenerator};
+
+const SHAPE: (usize, usize) = (4, 12);
+const START_STATE: usize = 36;
+const GOAL_STATE: usize = 47;
+
+const UP: i64 = 0;
+const RIGHT: i64 = 1;
+const DOWN: i64 = 2;
+const LEFT: i64 = 3;
+
+/// Walking from the start to the goal of a `4 x 12` grid while avoiding the cliff.
+///
+/// The player starts in the bottom left corner and the goal is in the bottom right corner, with
+/// the cliff spanning the bottom row between them. Stepping into the cliff returns the player to
+/// the start.
+///
+/// ## Action Space
+///
+/// The action is an `i64` in `{0, 1, 2, 3}`, moving the player up, right, down or left. Moving
+/// out of the grid leaves the player in place.
+///
+/// ## Observation Space
+///
+/// The observation is the current position of the player, `row * 12 + col`. The player is never
+/// on a cliff cell, as stepping into the cliff returns it to the start.
+///
+/// ## Rewards
+///
+/// Each step gives a reward of `-1`, unless the player stepped into the cliff, which gives a
+/// reward of `-100`.
+///
+/// ## Starting State
+///
+/// The episode starts with the player in state `36`, i.e. `[3, 0]`.
+///
+/// ## Episode End
+///
+/// The episode terminates when the player reaches the goal in state `47`, i.e. `[3, 11]`.
+///
+/// If `is_slippery` is set, the player moves in the intended direction with probability `1/3`,
+/// and in either perpendicular direction with probability `1/3` each.
+pub struct CliffWalking {
+    is_slippery: bool,
+    action_space: SpaceEnum,
+    observation_space: SpaceEnum,
+    metadata: Metadata,
+    render_mode: Option<String>,
+    state: Option<usize>,
+    last_action: Option<i64>,
+    rs_random: LazyGenerator,
+}
+
+impl CliffWalking {
+    /// Creates the environment, rendering frames in `render_mode` if given.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `render_mode` is not one of the render modes of the metadata.
+    pub fn new(render_mode: Option<&str>, is_slippery: bool) -> Self {
+        let metadata = Metadata::new(vec!["ansi".to_string()], Some(4));
+        if let Some(render_mode) = render_mode {
+            assert!(
+                metadata.supports_mode(render_mode),
+                "Unsupported render mode {render_mode:?}"
+            );
+        }
+
+        Self {
+            is_slippery,
+            action_space: Discrete::new(4, 0, None).into(),
+            observation_space: Discrete::new(SHAPE.0 * SHAPE.1, 0, None).into(),
+            metadata,
+            render_mode: render_mode.map(str::to_string),
+            state: None,
+            last_action: None,
+            rs_random: LazyGenerator::default(),
+        }
+    }
+
+    /// The render mode given at construction.
+    pub fn render_mode(&self) -> Option<&str> {
+        self.render_mode.as_deref()
+    }
+
+    fn is_cliff(state: usize) -> bool {
+        state / SHAPE.1 == SHAPE.0 - 1 && (1..SHAPE.1 - 1).contains(&(state % SHAPE.1))
+    }
+
+    /// The position reached by moving from `state` in the direction of `action`.
+    fn inc(state: usize, action: i64) -> usize {
+        let (mut row, mut col) = (state / SHAPE.1, state % SHAPE.1);
+        match action {
+            UP => row = row.saturating_sub(1),
+            RIGHT => col = (col + 1).min(SHAPE.1 - 1),
+            DOWN => row = (row + 1).min(SHAPE.0 - 1),
+            LEFT => col = col.saturating_sub(1),
+            _ => unreachable!("Actions are checked against the action space"),
+        }
+        row * SHAPE.1 + col
+    }
+}
+
+impl Default for CliffWalking {
+    fn default() -> Self {
+        Self::new(None, false)
+    }
+}
+
+impl Env<i64, i64> for CliffWalking {
+    fn step(&mut self, action: i64) -> StepResult<i64> {
+        assert!(
+            self.action_space.contains(&action.into()),
+            "{action} invalid action"
+        );
+        let state = self.state.expect("Call reset before using step method.");
+
+        let (state, prob) = if self.is_slippery {
+            let direction = (action + self.rs_random.get().gen_range(-1..=1)).rem_euclid(4);
+            (Self::inc(state, direction), 1.0 / 3.0)
+        } else {
+            (Self::inc(state, action), 1.0)
+        };
+        let (state, reward) = if Self::is_cliff(state) {
+            (START_STATE, -100.0)
+        } else {
+            (state, -1.0)
+        };
+        self.state = Some(state);
+        self.last_action = Some(action);
+
+        let info = InfoMap::from([("prob".to_string(), InfoValue::Float(prob))]);
+        StepResult {
+            observation: state as i64,
+            reward,
+            terminated: state == GOAL_STATE,
+            truncated: false,
+            info,
+        }
+    }
+
+    fn reset(&mut self, seed: Option<u64>, _options: Option<&InfoMap>) -> (i64, InfoMap) {
+        if let Some(seed) = seed {
+            self.rs_random.reseed(seed);
+        }
+        self.state = Some(START_STATE);
+        self.last_action = None;
+
+        (START_STATE as i64, InfoMap::new())
+    }
+
+    fn render(&mut self) -> Option<RenderFrame> {
+        let position = self.state?;
+        match self.render_mode.as_deref()? {
+            "ansi" => {
+                let mut outfile = String::new();
+                for row in 0..SHAPE.0 {
+                    let cells: Vec<&str> = (0..SHAPE.1)
+                        .map(|col| match row * SHAPE.1 + col {
+                            state if state == position => "x",
+                            GOAL_STATE => "T",
+                            state if Self::is_cliff(state) => "C",
+                            _ => "o",
+                        })
+                        .collect();
+                    writeln!(outfile, "{}", cells.join("  ")).unwrap();
+                }
+                outfile.push('\n');
+                Some(RenderFrame::Ansi(outfile))
+            }
+            _ => None,
+        }
+    }
+
+    fn action_space(&self) -> &SpaceEnum {
+        &self.action_space
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+
+    fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn rs_random(&mut self) -> Option<&mut Generator> {
+        Some(self.rs_random.get())
+    }
+
+    fn rs_random_seed(&self) -> Option<u64> {
+        self.rs_random.seed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CliffWalking;
+    use crate::core::{Env, RenderFrame, StepResult};
+
+    const UP: i64 = 0;
+    const RIGHT: i64 = 1;
+    const DOWN: i64 = 2;
+
+    #[test]
+    fn test_cliff_walking_optimal_path() {
+        let mut env = CliffWalking::default();
+        assert_eq!(env.reset(Some(0), None).0, 36);
+
+        let path = [UP]
+            .into_iter()
+            .chain([RIGHT; 11])
+            .chain([DOWN])
+            .collect::<Vec<_>>();
+        let mut episode_return = 0.0;
+        for (step, action) in path.iter().enumerate() {
+            let StepResult {
+                reward,
+                terminated,
+                truncated,
+                ..
+            } = env.step(*action);
+            episode_return += reward;
+            assert_eq!(terminated, step == path.len() - 1);
+            assert!(!truncated);
+        }
+        assert_eq!(episode_return, -13.0);
+    }
+
+    #[test]
+    fn test_cliff_walking_cliff() {
+        let mut env = CliffWalking::default();
+        env.reset(None, None);
+
+        let StepResult {
+            observation,
+            reward,
+            terminated,
+            ..
+        } = env.step(RIGHT);
+        assert_eq!((observation, reward, terminated), (36, -100.0, false));
+    }
+
+    #[test]
+    fn test_cliff_walking_slippery_reproducible() {
+        let rollout = || {
+            let mut env = CliffWalking::new(None, true);
+            env.reset(Some(5), None);
+            (0..30)
+                .map(|_| env.step(UP).observation)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rollout(), rollout());
+    }
+
+    #[test]
+    fn test_cliff_walking_render() {
+        let mut env = CliffWalking::new(Some("ansi"), false);
+        env.reset(None, None);
+        env.step(UP);
+
+        let Some(RenderFrame::Ansi(text)) = env.render() else {
+            panic!("Expected a text frame");
+        };
+        let rows: Vec<&str> = text.lines().collect();
+        assert_eq!(rows[2], "x  o  o  o  o  o  o  o  o  o  o  o");
+        assert_eq!(rows[3], "o  C  C  C  C  C  C  C  C  C  C  T");
+    }
+}
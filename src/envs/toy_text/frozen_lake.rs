@@ -0,0 +1,423 @@
+//! Frozen lake grid world, where the agent crosses a frozen lake without falling into holes.
+
+use std::fmt::Write;
+
+use rand::Rng;
+use thiserror::Error;
+
+use crate::common::{InfoMap, InfoValue};
+use crate::core::{Env, Metadata, RenderFrame, StepResult};
+use crate::spaces::{Discrete, Space, SpaceEnum};
+use crate::utils::colorize::{colorize, Color};
+use crate::utils::seeding::{Generator, LazyGenerator};
+
+const LEFT: i64 = 0;
+const DOWN: i64 = 1;
+const RIGHT: i64 = 2;
+const UP: i64 = 3;
+
+/// The predefined maps, by name.
+const MAPS: [(&str, &[&str]); 2] = [
+    ("4x4", &["SFFF", "FHFH", "FFFH", "HFFG"]),
+    (
+        "8x8",
+        &[
+            "SFFFFFFF", "FFFFFFFF", "FFFHFFFF", "FFFFFHFF", "FFFHFFFF", "FHHFFFHF", "FHFFHFHF",
+            "FFFHFFFG",
+        ],
+    ),
+];
+
+/// Error returned by [`FrozenLake::new`] for an invalid map.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FrozenLakeError {
+    #[error("Unknown map name {0:?}, expected \"4x4\" or \"8x8\"")]
+    UnknownMapName(String),
+    #[error("The map must not be empty")]
+    EmptyMap,
+    #[error("The map must be rectangular")]
+    NotRectangular,
+    #[error("The map must only contain the tiles S, F, H and G, got {0:?}")]
+    UnknownTile(char),
+    #[error("The map must contain a start tile")]
+    MissingStart,
+    #[error("The map must contain a goal tile")]
+    MissingGoal,
+}
+
+/// Crossing a frozen lake from the start to the goal without falling into any holes.
+///
+/// The lake is a grid described by a map of tiles: `S` for the start, `F` for frozen ice, `H` for
+/// a hole and `G` for the goal. The player may not always move in the intended direction due to
+/// the slippery nature of the frozen lake.
+///
+/// ## Action Space
+///
+/// The action is an `i64` in `{0, 1, 2, 3}`, moving the player left, down, right or up. Moving
+/// out of the grid leaves the player in place.
+///
+/// ## Observation Space
+///
+/// The observation is the current position of the player, `row * ncol + col`.
+///
+/// ## Rewards
+///
+/// A reward of `1` is given for reaching the goal, and `0` otherwise.
+///
+/// ## Starting State
+///
+/// The episode starts with the player on a uniformly random start tile.
+///
+/// ## Episode End
+///
+/// The episode terminates when the player moves into a hole or reaches the goal.
+///
+/// If `is_slippery` is set, the player moves in the intended direction with probability `1/3`,
+/// and in either perpendicular direction with probability `1/3` each. The probability of the
+/// transition is reported in the info under `"prob"`.
+pub struct FrozenLake {
+    desc: Vec<Vec<u8>>,
+    nrow: usize,
+    ncol: usize,
+    is_slippery: bool,
+    action_space: SpaceEnum,
+    observation_space: SpaceEnum,
+    metadata: Metadata,
+    render_mode: Option<String>,
+    state: Option<usize>,
+    last_action: Option<i64>,
+    rs_random: LazyGenerator,
+}
+
+impl FrozenLake {
+    /// Creates the environment on the map `desc`, or on the predefined map `map_name` (`"4x4"` or
+    /// `"8x8"`) if no map is given, rendering frames in `render_mode` if given.
+    ///
+    /// Each row of `desc` is a string of tiles, e.g. `vec!["SFFF", "FHFH", "FFFH", "HFFG"]`.
+    ///
+    /// Returns an error if `map_name` is unknown, or if the map is empty, not rectangular, has
+    /// unknown tiles, or has no start or goal tile.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `render_mode` is not one of the render modes of the metadata.
+    pub fn new(
+        render_mode: Option<&str>,
+        desc: Option<Vec<String>>,
+        map_name: &str,
+        is_slippery: bool,
+    ) -> Result<Self, FrozenLakeError> {
+        let metadata = Metadata::new(vec!["ansi".to_string()], Some(4));
+        if let Some(render_mode) = render_mode {
+            assert!(
+                metadata.supports_mode(render_mode),
+                "Unsupported render mode {render_mode:?}"
+            );
+        }
+
+        let desc: Vec<Vec<u8>> = match desc {
+            Some(desc) => desc.into_iter().map(String::into_bytes).collect(),
+            None => {
+                let (_, map) = MAPS
+                    .iter()
+                    .find(|(name, _)| *name == map_name)
+                    .ok_or_else(|| FrozenLakeError::UnknownMapName(map_name.to_string()))?;
+                map.iter().map(|row| row.as_bytes().to_vec()).collect()
+            }
+        };
+        let (nrow, ncol) = (desc.len(), desc.first().map_or(0, Vec::len));
+        if nrow == 0 || ncol == 0 {
+            return Err(FrozenLakeError::EmptyMap);
+        }
+        if desc.iter().any(|row| row.len() != ncol) {
+            return Err(FrozenLakeError::NotRectangular);
+        }
+        if let Some(tile) = desc.iter().flatten().find(|tile| !b"SFHG".contains(tile)) {
+            return Err(FrozenLakeError::UnknownTile(*tile as char));
+        }
+        if !desc.iter().flatten().any(|tile| *tile == b'S') {
+            return Err(FrozenLakeError::MissingStart);
+        }
+        if !desc.iter().flatten().any(|tile| *tile == b'G') {
+            return Err(FrozenLakeError::MissingGoal);
+        }
+
+        Ok(Self {
+            desc,
+            nrow,
+            ncol,
+            is_slippery,
+            action_space: Discrete::new(4, 0, None).into(),
+            observation_space: Discrete::new(nrow * ncol, 0, None).into(),
+            metadata,
+            render_mode: render_mode.map(str::to_string),
+            state: None,
+            last_action: None,
+            rs_random: LazyGenerator::default(),
+        })
+    }
+
+    /// The render mode given at construction.
+    pub fn render_mode(&self) -> Option<&str> {
+        self.render_mode.as_deref()
+    }
+
+    fn tile(&self, state: usize) -> u8 {
+        self.desc[state / self.ncol][state % self.ncol]
+    }
+
+    /// The position reached by moving from `state` in the direction of `action`.
+    fn inc(&self, state: usize, action: i64) -> usize {
+        let (mut row, mut col) = (state / self.ncol, state % self.ncol);
+        match action {
+            LEFT => col = col.saturating_sub(1),
+            DOWN => row = (row + 1).min(self.nrow - 1),
+            RIGHT => col = (col + 1).min(self.ncol - 1),
+            UP => row = row.saturating_sub(1),
+            _ => unreachable!("Actions are checked against the action space"),
+        }
+        row * self.ncol + col
+    }
+}
+
+impl Default for FrozenLake {
+    fn default() -> Self {
+        Self::new(None, None, "4x4", true).expect("The 4x4 map is valid")
+    }
+}
+
+impl Env<i64, i64> for FrozenLake {
+    fn step(&mut self, action: i64) -> StepResult<i64> {
+        assert!(
+            self.action_space.contains(&action.into()),
+            "{action} invalid action"
+        );
+        let state = self.state.expect("Call reset before using step method.");
+
+        // Terminal states are absorbing.
+        let (state, prob) = if b"GH".contains(&self.tile(state)) {
+            (state, 1.0)
+        } else if self.is_slippery {
+            let direction = (action + self.rs_random.get().gen_range(-1..=1)).rem_euclid(4);
+            (self.inc(state, direction), 1.0 / 3.0)
+        } else {
+            (self.inc(state, action), 1.0)
+        };
+        let tile = self.tile(state);
+        let reward = if tile == b'G' { 1.0 } else { 0.0 };
+        let terminated = b"GH".contains(&tile);
+        self.state = Some(state);
+        self.last_action = Some(action);
+
+        let info = InfoMap::from([("prob".to_string(), InfoValue::Float(prob))]);
+        StepResult {
+            observation: state as i64,
+            reward,
+            terminated,
+            truncated: false,
+            info,
+        }
+    }
+
+    fn reset(&mut self, seed: Option<u64>, _options: Option<&InfoMap>) -> (i64, InfoMap) {
+        if let Some(seed) = seed {
+            self.rs_random.reseed(seed);
+        }
+        let starts: Vec<usize> = (0..self.nrow * self.ncol)
+            .filter(|state| self.tile(*state) == b'S')
+            .collect();
+        let state = starts[self.rs_random.get().gen_range(0..starts.len())];
+        self.state = Some(state);
+        self.last_action = None;
+
+        let info = InfoMap::from([("prob".to_string(), InfoValue::Float(1.0))]);
+        (state as i64, info)
+    }
+
+    fn render(&mut self) -> Option<RenderFrame> {
+        let state = self.state?;
+        match self.render_mode.as_deref()? {
+            "ansi" => {
+                let mut outfile = String::new();
+                match self.last_action {
+                    Some(action) => {
+                        let name = ["Left", "Down", "Right", "Up"][action as usize];
+                        writeln!(outfile, "  ({name})").unwrap();
+                    }
+                    None => outfile.push('\n'),
+                }
+                for (row, tiles) in self.desc.iter().enumerate() {
+                    for (col, tile) in tiles.iter().enumerate() {
+                        if row * self.ncol + col == state {
+                            // Highlight the player in red.
+                            let tile = (*tile as char).to_string();
+                            outfile.push_str(&colorize(&tile, Color::Red, false, true));
+                        } else {
+                            outfile.push(*tile as char);
+                        }
+                    }
+                    outfile.push('\n');
+                }
+                Some(RenderFrame::Ansi(outfile))
+            }
+            _ => None,
+        }
+    }
+
+    fn action_space(&self) -> &SpaceEnum {
+        &self.action_space
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+
+    fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn rs_random(&mut self) -> Option<&mut Generator> {
+        Some(self.rs_random.get())
+    }
+
+    fn rs_random_seed(&self) -> Option<u64> {
+        self.rs_random.seed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FrozenLake, FrozenLakeError};
+    use crate::common::InfoValue;
+    use crate::core::{Env, RenderFrame, StepResult};
+    use crate::spaces::SpaceEnum;
+
+    const LEFT: i64 = 0;
+    const DOWN: i64 = 1;
+    const RIGHT: i64 = 2;
+
+    fn deterministic_lake() -> FrozenLake {
+        FrozenLake::new(Some("ansi"), None, "4x4", false).unwrap()
+    }
+
+    #[test]
+    fn test_frozen_lake_goal() {
+        let mut env = deterministic_lake();
+        assert_eq!(env.reset(Some(0), None).0, 0);
+
+        let path = [RIGHT, RIGHT, DOWN, DOWN, DOWN];
+        for action in path {
+            let StepResult {
+                reward, terminated, ..
+            } = env.step(action);
+            assert_eq!(reward, 0.0);
+            assert!(!terminated);
+        }
+        let StepResult {
+            observation,
+            reward,
+            terminated,
+            truncated,
+            info,
+        } = env.step(RIGHT);
+        assert_eq!(observation, 15);
+        assert_eq!(reward, 1.0);
+        assert!(terminated);
+        assert!(!truncated);
+        assert!(matches!(info["prob"], InfoValue::Float(prob) if prob == 1.0));
+    }
+
+    #[test]
+    fn test_frozen_lake_hole() {
+        let mut env = deterministic_lake();
+        env.reset(Some(0), None);
+
+        assert_eq!(env.step(DOWN).observation, 4);
+        let StepResult {
+            observation,
+            reward,
+            terminated,
+            ..
+        } = env.step(RIGHT);
+        assert_eq!(observation, 5);
+        assert_eq!(reward, 0.0);
+        assert!(terminated);
+    }
+
+    #[test]
+    fn test_frozen_lake_slippery_reproducible() {
+        let rollout = || {
+            let mut env = FrozenLake::default();
+            env.reset(Some(3), None);
+            (0..20)
+                .map(|_| env.step(RIGHT).observation)
+                .collect::<Vec<_>>()
+        };
+        let states = rollout();
+        assert_eq!(states, rollout());
+
+        // Slipping sometimes moves the player in an unintended direction.
+        let mut env = FrozenLake::default();
+        env.reset(Some(0), None);
+        let moves: Vec<i64> = (0..50)
+            .map(|_| {
+                env.reset(None, None);
+                env.step(RIGHT).observation
+            })
+            .collect();
+        assert!(moves.contains(&1) && moves.contains(&4));
+    }
+
+    #[test]
+    fn test_frozen_lake_custom_map() {
+        let desc = vec!["SH".to_string(), "FG".to_string()];
+        let mut env = FrozenLake::new(None, Some(desc), "4x4", false).unwrap();
+        assert!(matches!(env.observation_space(), SpaceEnum::Discrete(space) if space.n() == 4));
+
+        env.reset(None, None);
+        assert_eq!(env.step(LEFT).observation, 0);
+        assert_eq!(env.step(DOWN).observation, 2);
+        let StepResult {
+            observation,
+            reward,
+            terminated,
+            ..
+        } = env.step(RIGHT);
+        assert_eq!((observation, reward, terminated), (3, 1.0, true));
+    }
+
+    #[test]
+    fn test_frozen_lake_unknown_map_name() {
+        let err = FrozenLake::new(None, None, "5x5", false).err().unwrap();
+        assert_eq!(err, FrozenLakeError::UnknownMapName("5x5".to_string()));
+        assert_eq!(
+            err.to_string(),
+            "Unknown map name \"5x5\", expected \"4x4\" or \"8x8\""
+        );
+    }
+
+    #[test]
+    fn test_frozen_lake_malformed_maps() {
+        let lake = |rows: &[&str]| {
+            let desc = rows.iter().map(|row| row.to_string()).collect();
+            FrozenLake::new(None, Some(desc), "4x4", false).err()
+        };
+        assert_eq!(lake(&[]), Some(FrozenLakeError::EmptyMap));
+        assert_eq!(lake(&["SF", "FHG"]), Some(FrozenLakeError::NotRectangular));
+        assert_eq!(lake(&["SX", "FG"]), Some(FrozenLakeError::UnknownTile('X')));
+        assert_eq!(lake(&["FH", "FG"]), Some(FrozenLakeError::MissingStart));
+        assert_eq!(lake(&["SH", "FF"]), Some(FrozenLakeError::MissingGoal));
+    }
+
+    #[test]
+    fn test_frozen_lake_render() {
+        let mut env = deterministic_lake();
+        env.reset(Some(0), None);
+        env.step(RIGHT);
+
+        let Some(RenderFrame::Ansi(text)) = env.render() else {
+            panic!("Expected a text frame");
+        };
+        assert_eq!(text, "  (Right)\nS\x1b[41mF\x1b[0mFF\nFHFH\nFFFH\nHFFG\n");
+    }
+}
@@ -0,0 +1,11 @@
+//! Toy text environments with small discrete state spaces, rendered as text.
+
+pub mod blackjack;
+pub mod cliff_walking;
+pub mod frozen_lake;
+pub mod taxi;
+
+pub use blackjack::Blackjack;
+pub use cliff_walking::CliffWalking;
+pub use frozen_lake::{FrozenLake, FrozenLakeError};
+pub use taxi::Taxi;
@@ -0,0 +1,438 @@
+//! Taxi problem from Dietterich's "Hierarchical Reinforcement Learning with the MAXQ Value
+//! Function Decomposition".
+
+use std::fmt::Write;
+
+use candle_core::{Device, Tensor};
+use rand::Rng;
+
+use crate::common::{InfoMap, InfoValue};
+use crate::core::{Env, Metadata, RenderFrame, StepResult};
+use crate::spaces::{Discrete, Space, SpaceEnum};
+use crate::utils::colorize::{colorize, Color};
+use crate::utils::seeding::{Generator, LazyGenerator};
+
+const MAP: [&str; 7] = [
+    "+---------+",
+    "|R: | : :G|",
+    "| : | : : |",
+    "| : : : : |",
+    "| | : | : |",
+    "|Y| : |B: |",
+    "+---------+",
+];
+/// The rows and columns of the red, green, yellow and blue locations.
+const LOCS: [(usize, usize); 4] = [(0, 0), (0, 4), (4, 0), (4, 3)];
+const NUM_ROWS: usize = 5;
+const NUM_COLUMNS: usize = 5;
+/// The passenger location of a passenger inside the taxi.
+const IN_TAXI: usize = 4;
+
+const SOUTH: i64 = 0;
+const NORTH: i64 = 1;
+const EAST: i64 = 2;
+const WEST: i64 = 3;
+const PICKUP: i64 = 4;
+const DROPOFF: i64 = 5;
+
+/// Encodes the position of the taxi, the location of the passenger (`0..=3` for the colored
+/// locations, `4` inside the taxi) and the destination (`0..=3`) into a state in `0..500`.
+pub fn encode(taxi_row: usize, taxi_col: usize, pass_loc: usize, dest_idx: usize) -> usize {
+    ((taxi_row * NUM_COLUMNS + taxi_col) * 5 + pass_loc) * 4 + dest_idx
+}
+
+/// Decodes a state into `(taxi_row, taxi_col, pass_loc, dest_idx)`, the inverse of [`encode`].
+pub fn decode(state: usize) -> (usize, usize, usize, usize) {
+    let (state, dest_idx) = (state / 4, state % 4);
+    let (state, pass_loc) = (state / 5, state % 5);
+    let (taxi_row, taxi_col) = (state / NUM_COLUMNS, state % NUM_COLUMNS);
+    (taxi_row, taxi_col, pass_loc, dest_idx)
+}
+
+/// Navigating a taxi to passengers in a grid world, picking them up and dropping them off at one
+/// of four locations.
+///
+/// There are four designated pick-up and drop-off locations (red, green, yellow and blue) in the
+/// `5 x 5` grid world. The taxi starts off at a random square and the passenger at one of the
+/// designated locations. The goal is to move the taxi to the passenger's location, pick up the
+/// passenger, move to the passenger's desired destination, and drop off the passenger.
+///
+/// ## Action Space
+///
+/// The action is an `i64` in `{0, ..., 5}`: move south, move north, move east, move west, pick
+/// up the passenger, and drop off the passenger.
+///
+/// ## Observation Space
+///
+/// The observation is one of the 500 states given by [`encode`], combining 25 taxi positions, 5
+/// passenger locations and 4 destinations.
+///
+/// ## Rewards
+///
+/// - `-1` per step unless another reward is triggered.
+/// - `+20` for delivering the passenger.
+/// - `-10` for executing the pickup or drop-off actions illegally.
+///
+/// ## Starting State
+///
+/// The taxi starts on a uniformly random square, and the passenger at a uniformly random location
+/// different from the destination.
+///
+/// ## Episode End
+///
+/// The episode terminates when the passenger is dropped off at the destination.
+///
+/// The info holds the probability of the transition under `"prob"`, and the actions that change
+/// the state under `"action_mask"`, a `U8` tensor of shape `(6,)`.
+pub struct Taxi {
+    desc: Vec<Vec<u8>>,
+    action_space: SpaceEnum,
+    observation_space: SpaceEnum,
+    metadata: Metadata,
+    render_mode: Option<String>,
+    state: Option<usize>,
+    last_action: Option<i64>,
+    rs_random: LazyGenerator,
+}
+
+impl Taxi {
+    /// Creates the environment, rendering frames in `render_mode` if given.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `render_mode` is not one of the render modes of the metadata.
+    pub fn new(render_mode: Option<&str>) -> Self {
+        let metadata = Metadata::new(vec!["ansi".to_string()], Some(4));
+        if let Some(render_mode) = render_mode {
+            assert!(
+                metadata.supports_mode(render_mode),
+                "Unsupported render mode {render_mode:?}"
+            );
+        }
+
+        Self {
+            desc: MAP.iter().map(|row| row.as_bytes().to_vec()).collect(),
+            action_space: Discrete::new(6, 0, None).into(),
+            observation_space: Discrete::new(500, 0, None).into(),
+            metadata,
+            render_mode: render_mode.map(str::to_string),
+            state: None,
+            last_action: None,
+            rs_random: LazyGenerator::default(),
+        }
+    }
+
+    /// The render mode given at construction.
+    pub fn render_mode(&self) -> Option<&str> {
+        self.render_mode.as_deref()
+    }
+
+    /// Whether the taxi can move east from `(row, col)` without hitting a wall.
+    fn can_move_east(&self, row: usize, col: usize) -> bool {
+        col < NUM_COLUMNS - 1 && self.desc[1 + row][2 * col + 2] == b':'
+    }
+
+    /// Whether the taxi can move west from `(row, col)` without hitting a wall.
+    fn can_move_west(&self, row: usize, col: usize) -> bool {
+        col > 0 && self.desc[1 + row][2 * col] == b':'
+    }
+
+    /// The actions that change `state`, as a `U8` tensor.
+    fn action_mask(&self, state: usize) -> Tensor {
+        let (taxi_row, taxi_col, pass_loc, dest_idx) = decode(state);
+        let taxi_loc = (taxi_row, taxi_col);
+        let mask = [
+            taxi_row < NUM_ROWS - 1,
+            taxi_row > 0,
+            self.can_move_east(taxi_row, taxi_col),
+            self.can_move_west(taxi_row, taxi_col),
+            pass_loc < IN_TAXI && taxi_loc == LOCS[pass_loc],
+            pass_loc == IN_TAXI && (taxi_loc == LOCS[dest_idx] || LOCS.contains(&taxi_loc)),
+        ];
+        Tensor::new(&mask.map(u8::from), &Device::Cpu).expect("The mask is a valid tensor")
+    }
+
+    fn info(&self, state: usize) -> InfoMap {
+        InfoMap::from([
+            ("prob".to_string(), InfoValue::Float(1.0)),
+            (
+                "action_mask".to_string(),
+                InfoValue::Tensor(self.action_mask(state)),
+            ),
+        ])
+    }
+}
+
+impl Default for Taxi {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl Env<i64, i64> for Taxi {
+    fn step(&mut self, action: i64) -> StepResult<i64> {
+        assert!(
+            self.action_space.contains(&action.into()),
+            "{action} invalid action"
+        );
+        let state = self.state.expect("Call reset before using step method.");
+        let (row, col, pass_loc, dest_idx) = decode(state);
+        let taxi_loc = (row, col);
+
+        let (mut new_row, mut new_col, mut new_pass_loc) = (row, col, pass_loc);
+        // Default reward when there is no pickup/dropoff.
+        let mut reward = -1.0;
+        let mut terminated = false;
+        match action {
+            SOUTH => new_row = (row + 1).min(NUM_ROWS - 1),
+            NORTH => new_row = row.saturating_sub(1),
+            EAST if self.can_move_east(row, col) => new_col = col + 1,
+            WEST if self.can_move_west(row, col) => new_col = col - 1,
+            EAST | WEST => {}
+            PICKUP => {
+                if pass_loc < IN_TAXI && taxi_loc == LOCS[pass_loc] {
+                    new_pass_loc = IN_TAXI;
+                } else {
+                    // Passenger not at location.
+                    reward = -10.0;
+                }
+            }
+            DROPOFF => {
+                if taxi_loc == LOCS[dest_idx] && pass_loc == IN_TAXI {
+                    new_pass_loc = dest_idx;
+                    terminated = true;
+                    reward = 20.0;
+                } else if let Some(loc) = LOCS
+                    .iter()
+                    .position(|loc| *loc == taxi_loc)
+                    .filter(|_| pass_loc == IN_TAXI)
+                {
+                    new_pass_loc = loc;
+                } else {
+                    // Dropoff at wrong location.
+                    reward = -10.0;
+                }
+            }
+            _ => unreachable!("Actions are checked against the action space"),
+        }
+
+        let state = encode(new_row, new_col, new_pass_loc, dest_idx);
+        self.state = Some(state);
+        self.last_action = Some(action);
+        StepResult {
+            observation: state as i64,
+            reward,
+            terminated,
+            truncated: false,
+            info: self.info(state),
+        }
+    }
+
+    fn reset(&mut self, seed: Option<u64>, _options: Option<&InfoMap>) -> (i64, InfoMap) {
+        if let Some(seed) = seed {
+            self.rs_random.reseed(seed);
+        }
+        let initial_states: Vec<usize> = (0..500)
+            .filter(|state| {
+                let (_, _, pass_loc, dest_idx) = decode(*state);
+                pass_loc < IN_TAXI && pass_loc != dest_idx
+            })
+            .collect();
+        let state = initial_states[self.rs_random.get().gen_range(0..initial_states.len())];
+        self.state = Some(state);
+        self.last_action = None;
+
+        (state as i64, self.info(state))
+    }
+
+    fn render(&mut self) -> Option<RenderFrame> {
+        let state = self.state?;
+        match self.render_mode.as_deref()? {
+            "ansi" => {
+                let mut out: Vec<Vec<String>> = self
+                    .desc
+                    .iter()
+                    .map(|row| row.iter().map(|c| (*c as char).to_string()).collect())
+                    .collect();
+                let (taxi_row, taxi_col, pass_loc, dest_idx) = decode(state);
+                let ul = |x: &str| {
+                    if x == " " {
+                        "_".to_string()
+                    } else {
+                        x.to_string()
+                    }
+                };
+
+                let taxi = &mut out[1 + taxi_row][2 * taxi_col + 1];
+                if pass_loc < IN_TAXI {
+                    *taxi = colorize(&ul(taxi), Color::Yellow, false, true);
+                    let (pi, pj) = LOCS[pass_loc];
+                    let passenger = &mut out[1 + pi][2 * pj + 1];
+                    *passenger = colorize(passenger, Color::Blue, true, false);
+                } else {
+                    // The passenger is in the taxi.
+                    *taxi = colorize(&ul(taxi), Color::Green, false, true);
+                }
+                let (di, dj) = LOCS[dest_idx];
+                let destination = &mut out[1 + di][2 * dj + 1];
+                *destination = colorize(destination, Color::Magenta, false, false);
+
+                let mut outfile = String::new();
+                for row in out {
+                    writeln!(outfile, "{}", row.concat()).unwrap();
+                }
+                match self.last_action {
+                    Some(action) => {
+                        let names = ["South", "North", "East", "West", "Pickup", "Dropoff"];
+                        writeln!(outfile, "  ({})", names[action as usize]).unwrap();
+                    }
+                    None => outfile.push('\n'),
+                }
+                Some(RenderFrame::Ansi(outfile))
+            }
+            _ => None,
+        }
+    }
+
+    fn action_space(&self) -> &SpaceEnum {
+        &self.action_space
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+
+    fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn rs_random(&mut self) -> Option<&mut Generator> {
+        Some(self.rs_random.get())
+    }
+
+    fn rs_random_seed(&self) -> Option<u64> {
+        self.rs_random.seed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, Taxi};
+    use crate::common::InfoValue;
+    use crate::core::{Env, RenderFrame, StepResult};
+
+    const SOUTH: i64 = 0;
+    const NORTH: i64 = 1;
+    const EAST: i64 = 2;
+    const WEST: i64 = 3;
+    const PICKUP: i64 = 4;
+    const DROPOFF: i64 = 5;
+
+    /// Resets `env` until the taxi is at `(0, 0)` with the passenger at red and the destination at
+    /// green.
+    fn reset_to_red_green(env: &mut Taxi) {
+        let target = encode(0, 0, 0, 1) as i64;
+        for seed in 0.. {
+            if env.reset(Some(seed), None).0 == target {
+                return;
+            }
+        }
+    }
+
+    #[test]
+    fn test_taxi_encode_decode() {
+        for state in 0..500 {
+            let (taxi_row, taxi_col, pass_loc, dest_idx) = decode(state);
+            assert!(taxi_row < 5 && taxi_col < 5 && pass_loc < 5 && dest_idx < 4);
+            assert_eq!(encode(taxi_row, taxi_col, pass_loc, dest_idx), state);
+        }
+    }
+
+    #[test]
+    fn test_taxi_successful_dropoff() {
+        let mut env = Taxi::default();
+        reset_to_red_green(&mut env);
+
+        let StepResult {
+            observation: state,
+            reward,
+            ..
+        } = env.step(PICKUP);
+        assert_eq!(decode(state as usize), (0, 0, 4, 1));
+        assert_eq!(reward, -1.0);
+
+        // The wall east of the second column forces a detour through the third row.
+        let path = [SOUTH, SOUTH, EAST, EAST, EAST, EAST, NORTH, NORTH];
+        for action in path {
+            let StepResult {
+                reward, terminated, ..
+            } = env.step(action);
+            assert_eq!(reward, -1.0);
+            assert!(!terminated);
+        }
+        let StepResult {
+            observation: state,
+            reward,
+            terminated,
+            truncated,
+            ..
+        } = env.step(DROPOFF);
+        assert_eq!(decode(state as usize), (0, 4, 1, 1));
+        assert_eq!(reward, 20.0);
+        assert!(terminated);
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn test_taxi_illegal_actions() {
+        let mut env = Taxi::default();
+        reset_to_red_green(&mut env);
+
+        let StepResult {
+            observation: state,
+            reward,
+            ..
+        } = env.step(DROPOFF);
+        assert_eq!((state as usize, reward), (encode(0, 0, 0, 1), -10.0));
+        env.step(EAST);
+        let StepResult { reward, .. } = env.step(PICKUP);
+        assert_eq!(reward, -10.0);
+
+        // The wall blocks moving east.
+        let StepResult {
+            observation: state, ..
+        } = env.step(EAST);
+        assert_eq!(decode(state as usize), (0, 1, 0, 1));
+        let StepResult {
+            observation: state, ..
+        } = env.step(WEST);
+        assert_eq!(decode(state as usize), (0, 0, 0, 1));
+    }
+
+    #[test]
+    fn test_taxi_action_mask() {
+        let mut env = Taxi::default();
+        let (_, info) = env.reset(Some(0), None);
+        assert!(matches!(info["prob"], InfoValue::Float(prob) if prob == 1.0));
+
+        reset_to_red_green(&mut env);
+        let info = env.step(SOUTH).info;
+        let InfoValue::Tensor(mask) = &info["action_mask"] else {
+            panic!("Expected the action mask to be a tensor");
+        };
+        assert_eq!(mask.to_vec1::<u8>().unwrap(), [1, 1, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_taxi_render() {
+        let mut env = Taxi::new(Some("ansi"));
+        reset_to_red_green(&mut env);
+        env.step(PICKUP);
+
+        let Some(RenderFrame::Ansi(text)) = env.render() else {
+            panic!("Expected a text frame");
+        };
+        assert!(text.starts_with("+---------+\n|\x1b[42mR\x1b[0m: | : :\x1b[35mG\x1b[0m|\n"));
+        assert!(text.ends_with("  (Pickup)\n"));
+    }
+}
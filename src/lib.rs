@@ -0,0 +1,11 @@
+//! Minimalistic GPU-enabled reinforcement learning environment API, inspired by Gymnasium.
+
+pub mod common;
+pub mod core;
+pub mod envs;
+pub mod spaces;
+pub mod utils;
+pub mod vector;
+pub mod wrappers;
+
+pub use envs::registration::{make, register, registry};
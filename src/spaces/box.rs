@@ -0,0 +1,1244 @@
+//! Implementation of a space that represents closed boxes in euclidean space.
+
+use std::fmt;
+
+use candle_core::{DType, Device, Tensor};
+use rand::Rng;
+use thiserror::Error;
+
+use super::space::{tensor_eq, Bound, SampleMask, Space};
+use super::utils::{BatchError, FlattenError};
+use crate::utils::seeding::{rs_random, Generator};
+
+/// Error returned by [`Box::new`], [`Box::from_vecs`] and [`Bound::from_slice`].
+#[derive(Debug, Error, PartialEq)]
+pub enum BoxError {
+    #[error(
+        "A shape must be provided when both low and high are scalars, as it cannot be inferred \
+         from them"
+    )]
+    MissingShape,
+    #[error("A bound of shape {bound:?} cannot be broadcast to the shape {shape:?} of the box")]
+    ShapeMismatch {
+        bound: Vec<usize>,
+        shape: Vec<usize>,
+    },
+    #[error("A bound of {len} values does not have one value per element of the shape {shape:?}")]
+    LengthMismatch { len: usize, shape: Vec<usize> },
+}
+
+/// Direction in which [`Box::is_bounded`] checks the bounds of a box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bounded {
+    /// Bounded both below and above.
+    Both,
+    /// Bounded below.
+    Below,
+    /// Bounded above.
+    Above,
+}
+
+/// A (possibly unbounded) box in R^n.
+///
+/// Specifically, a Box represents the Cartesian product of n closed intervals. Each interval has the
+/// form of one of `[a, b]`, `(-oo, b]`, `[a, oo)`, or `(-oo, oo)`.
+///
+/// There are two common use cases:
+/// - Identical bound for each dimension: `Box::new(Bound::F64(-1.0), Bound::F64(2.0), Some(vec![3, 4]), DType::F32, &Device::Cpu, None)?`
+/// - Independent bound for each dimension: `Box::new(Bound::Tensor(low), Bound::Tensor(high), None, DType::F32, &Device::Cpu, None)?`
+#[derive(Debug, Clone)]
+pub struct Box {
+    shape: Vec<usize>,
+    dtype: DType,
+    low: Tensor,
+    high: Tensor,
+    bounded_below: Vec<bool>,
+    bounded_above: Vec<bool>,
+    rs_random: Generator,
+}
+
+impl Box {
+    /// Constructor of `Box`.
+    ///
+    /// If `shape` is `None`, it is inferred from whichever bound is a tensor.
+    ///
+    /// Returns an error if `shape` is `None` while both `low` and `high` are scalars, or if a tensor
+    /// bound cannot be broadcast to `shape`.
+    pub fn new(
+        low: Bound,
+        high: Bound,
+        shape: Option<Vec<usize>>,
+        dtype: DType,
+        device: &Device,
+        seed: Option<u64>,
+    ) -> Result<Self, BoxError> {
+        let shape = match shape {
+            Some(shape) => shape,
+            None => match (&low, &high) {
+                (Bound::Tensor(tensor), _) | (_, Bound::Tensor(tensor)) => tensor.dims().to_vec(),
+                _ => return Err(BoxError::MissingShape),
+            },
+        };
+        for bound in [&low, &high] {
+            if let Bound::Tensor(tensor) = bound {
+                if tensor.broadcast_as(shape.as_slice()).is_err() {
+                    return Err(BoxError::ShapeMismatch {
+                        bound: tensor.dims().to_vec(),
+                        shape,
+                    });
+                }
+            }
+        }
+
+        let (low, bounded_below) = _broadcast(low, dtype, &shape, device);
+        let (high, bounded_above) = _broadcast(high, dtype, &shape, device);
+        let (rs_random, _) = rs_random(seed);
+
+        Ok(Self {
+            shape,
+            dtype,
+            low,
+            high,
+            bounded_below,
+            bounded_above,
+            rs_random,
+        })
+    }
+
+    /// Creates a `Box` with the per-element bounds `low` and `high`, given in row-major order for
+    /// `shape`.
+    ///
+    /// Returns an error if `low` or `high` does not have one value per element of `shape`.
+    pub fn from_vecs(
+        low: Vec<f32>,
+        high: Vec<f32>,
+        shape: Vec<usize>,
+        dtype: DType,
+        device: &Device,
+    ) -> Result<Self, BoxError> {
+        let size: usize = shape.iter().product();
+        for bound in [&low, &high] {
+            if bound.len() != size {
+                return Err(BoxError::LengthMismatch {
+                    len: bound.len(),
+                    shape,
+                });
+            }
+        }
+
+        let bound = |values: Vec<f32>| {
+            let tensor = Tensor::from_vec(values, shape.as_slice(), device)
+                .expect("The values match the shape");
+            Bound::Tensor(tensor)
+        };
+        Self::new(
+            bound(low),
+            bound(high),
+            Some(shape.clone()),
+            dtype,
+            device,
+            None,
+        )
+    }
+
+    /// Creates an `F32` `Box` on the CPU with the same bounds `low` and `high` for every element of
+    /// `shape`.
+    pub fn bounded(low: f32, high: f32, shape: Vec<usize>) -> Self {
+        Self::new(
+            Bound::F64(low as f64),
+            Bound::F64(high as f64),
+            Some(shape),
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .expect("A shape is given")
+    }
+
+    /// Shape of the elements of this space.
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    /// Data type of the elements of this space.
+    pub fn dtype(&self) -> DType {
+        self.dtype
+    }
+
+    /// Lower bound of each dimension.
+    pub fn low(&self) -> &Tensor {
+        &self.low
+    }
+
+    /// Upper bound of each dimension.
+    pub fn high(&self) -> &Tensor {
+        &self.high
+    }
+
+    /// Clamps `x` element-wise to the bounds of the box, keeping its dtype.
+    ///
+    /// The bounds are broadcast to the shape of `x`, so a batch of elements stacked along leading
+    /// dimensions is clipped at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the shape of the box cannot be broadcast to the shape of `x`.
+    pub fn clip(&self, x: Tensor) -> Tensor {
+        let clip = || {
+            let low = self.low.to_dtype(x.dtype())?;
+            let high = self.high.to_dtype(x.dtype())?;
+            x.broadcast_maximum(&low)?.broadcast_minimum(&high)
+        };
+        clip().unwrap_or_else(|_| {
+            panic!(
+                "Cannot clip a tensor of shape {:?} to a box of shape {:?}",
+                x.dims(),
+                self.shape
+            )
+        })
+    }
+
+    /// Lower bound of each dimension as `f32` values, in row-major order.
+    ///
+    /// Unbounded dimensions have the lowest finite value of the dtype of the box, cast to `f32`.
+    pub fn low_vec(&self) -> Vec<f32> {
+        _to_f32_vec(&self.low)
+    }
+
+    /// Upper bound of each dimension as `f32` values, in row-major order.
+    ///
+    /// Unbounded dimensions have the highest finite value of the dtype of the box, cast to `f32`.
+    pub fn high_vec(&self) -> Vec<f32> {
+        _to_f32_vec(&self.high)
+    }
+
+    /// Returns `true` if every dimension of this space is bounded in the given `manner`.
+    pub fn is_bounded(&self, manner: Bounded) -> bool {
+        let below = self.bounded_below.iter().all(|bounded| *bounded);
+        let above = self.bounded_above.iter().all(|bounded| *bounded);
+        match manner {
+            Bounded::Both => below && above,
+            Bounded::Below => below,
+            Bounded::Above => above,
+        }
+    }
+
+    /// Draws the flattened values of `count` samples, one sample after the other.
+    ///
+    /// Boxes of integer dtypes draw uniform integers in `[low, high]` in the bounded dimensions,
+    /// and round the other draws down.
+    fn sample_values(&mut self, count: usize) -> Vec<f64> {
+        let low = _to_f64_vec(&self.low);
+        let high = _to_f64_vec(&self.high);
+        let is_float = self.dtype.is_float();
+
+        (0..count * low.len())
+            .map(|i| i % low.len())
+            .map(|i| match (self.bounded_below[i], self.bounded_above[i]) {
+                (true, true) if is_float => self.rs_random.gen_range(low[i]..=high[i]),
+                (true, true) => self.rs_random.gen_range(low[i] as i64..=high[i] as i64) as f64,
+                (true, false) => low[i] + _exponential(&mut self.rs_random),
+                (false, true) => high[i] - _exponential(&mut self.rs_random),
+                (false, false) => _normal(&mut self.rs_random),
+            })
+            .map(|x| if is_float { x } else { x.floor() })
+            .collect()
+    }
+}
+
+/// Two boxes are equal if they have the same shape, dtype and bounds, regardless of their generators.
+impl PartialEq for Box {
+    fn eq(&self, other: &Self) -> bool {
+        self.shape == other.shape
+            && self.dtype == other.dtype
+            && tensor_eq(&self.low, &other.low)
+            && tensor_eq(&self.high, &other.high)
+    }
+}
+
+/// A string representation of this box, e.g. `Box([-1], [1], [3, 4], F32)`.
+impl fmt::Display for Box {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Box({}, {}, {:?}, {:?})",
+            _short_repr(&self.low),
+            _short_repr(&self.high),
+            self.shape,
+            self.dtype
+        )
+    }
+}
+
+/// The serialized form of a [`Box`], whose bounds are `None` in the unbounded directions.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BoxRepr {
+    shape: Vec<usize>,
+    #[serde(with = "super::serialization::dtype")]
+    dtype: DType,
+    low: Vec<Option<f64>>,
+    high: Vec<Option<f64>>,
+    #[serde(with = "super::serialization::device")]
+    device: Device,
+}
+
+/// Serializes the shape, dtype, bounds and device of the box, but not its generator.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Box {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let bound = |values: Vec<f64>, bounded: &[bool]| -> Vec<Option<f64>> {
+            values
+                .into_iter()
+                .zip(bounded)
+                .map(|(value, bounded)| bounded.then_some(value))
+                .collect()
+        };
+        BoxRepr {
+            shape: self.shape.clone(),
+            dtype: self.dtype,
+            low: bound(_to_f64_vec(&self.low), &self.bounded_below),
+            high: bound(_to_f64_vec(&self.high), &self.bounded_above),
+            device: self.low.device().clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Deserializes a box with a generator seeded from OS entropy.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Box {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let repr = BoxRepr::deserialize(deserializer)?;
+        let len: usize = repr.shape.iter().product();
+        let bound = |values: Vec<Option<f64>>, unbounded: f64| {
+            if values.len() != len {
+                return Err(D::Error::custom(format!(
+                    "expected {len} bounds for the shape {:?}, got {}",
+                    repr.shape,
+                    values.len()
+                )));
+            }
+            let values: Vec<f64> = values.into_iter().map(|x| x.unwrap_or(unbounded)).collect();
+            Tensor::from_vec(values, repr.shape.as_slice(), &repr.device)
+                .map(Bound::Tensor)
+                .map_err(D::Error::custom)
+        };
+        let low = bound(repr.low, f64::NEG_INFINITY)?;
+        let high = bound(repr.high, f64::INFINITY)?;
+
+        Box::new(
+            low,
+            high,
+            Some(repr.shape.clone()),
+            repr.dtype,
+            &repr.device,
+            None,
+        )
+        .map_err(D::Error::custom)
+    }
+}
+
+impl Space<Tensor> for Box {
+    /// Generates a single random sample inside the Box.
+    ///
+    /// In creating a sample of the box, each coordinate is sampled (independently) from a distribution
+    /// that is chosen according to the form of the interval:
+    /// - `[a, b]`: uniform distribution
+    /// - `[a, oo)`: shifted exponential distribution
+    /// - `(-oo, b]`: shifted negative exponential distribution
+    /// - `(-oo, oo)`: normal distribution
+    ///
+    /// For integer dtypes, `[a, b]` is sampled uniformly over its integers, both ends included, and the
+    /// samples of the other intervals are rounded down.
+    fn sample(&mut self, _mask: Option<&SampleMask>) -> Tensor {
+        let sample = self.sample_values(1);
+        Tensor::from_vec(sample, self.shape.as_slice(), self.low.device())
+            .and_then(|tensor| tensor.to_dtype(self.dtype))
+            .expect("Sample must match the shape of the box")
+    }
+
+    /// Generates `count` samples as a single batched tensor, reading the bounds only once.
+    fn sample_n(&mut self, count: usize, _mask: Option<&SampleMask>) -> Result<Tensor, BatchError> {
+        let samples = self.sample_values(count);
+        let shape: Vec<usize> = std::iter::once(count)
+            .chain(self.shape.iter().copied())
+            .collect();
+        let samples = Tensor::from_vec(samples, shape, self.low.device())
+            .and_then(|tensor| tensor.to_dtype(self.dtype))
+            .expect("Samples must match the shape of the box");
+        Ok(samples)
+    }
+
+    fn seed(&mut self, seed: Option<u64>) -> Vec<u64> {
+        let (rs_random, seed) = rs_random(seed);
+        self.rs_random = rs_random;
+        vec![seed]
+    }
+
+    /// Returns `true` if `x` has the shape and dtype of the box and lies within its bounds.
+    fn contains(&self, x: &Tensor) -> bool {
+        if x.dims() != self.shape.as_slice() || x.dtype() != self.dtype {
+            return false;
+        }
+
+        let low = _to_f64_vec(&self.low);
+        let high = _to_f64_vec(&self.high);
+        _to_f64_vec(x)
+            .iter()
+            .zip(low.iter().zip(high.iter()))
+            .all(|(x, (low, high))| low <= x && x <= high)
+    }
+
+    fn is_flattenable(&self) -> bool {
+        true
+    }
+
+    /// Flattens `x` in row-major order, without converting the box into a [`SpaceEnum`](super::SpaceEnum).
+    fn flatten(&self, x: &Tensor) -> Result<Tensor, FlattenError> {
+        Ok(x.flatten_all()?)
+    }
+
+    /// Reshapes `x` to the shape of the box and casts it to its dtype.
+    fn unflatten(&self, x: &Tensor) -> Result<Tensor, FlattenError> {
+        let expected = self.shape.iter().product();
+        if x.dims() != [expected] {
+            return Err(FlattenError::LengthMismatch {
+                expected,
+                actual: x.dims().to_vec(),
+            });
+        }
+        Ok(x.reshape(self.shape.as_slice())?.to_dtype(self.dtype)?)
+    }
+}
+
+/// Broadcasts a bound to a tensor of the given shape and dtype.
+///
+/// Infinite entries are replaced by the finite extremes of `dtype` so that the stored bounds can be
+/// represented in any dtype. Alongside the tensor, returns for each element whether the bound was
+/// finite, i.e. whether the box is bounded in that direction.
+fn _broadcast(value: Bound, dtype: DType, shape: &[usize], device: &Device) -> (Tensor, Vec<bool>) {
+    let value = match value {
+        Bound::F64(value) => Tensor::full(value, shape, device),
+        Bound::Tensor(tensor) => tensor.broadcast_as(shape),
+    }
+    .expect("Bound must be broadcastable to the shape of the box");
+
+    let (min, max) = dtype_extremes(dtype);
+    let values = _to_f64_vec(&value);
+    let bounded = values
+        .iter()
+        .map(|x| x.is_finite() && !(dtype.is_float() && x.abs() >= max))
+        .collect();
+    let values: Vec<f64> = values.into_iter().map(|x| x.clamp(min, max)).collect();
+    let tensor = Tensor::from_vec(values, shape, device)
+        .and_then(|tensor| tensor.to_dtype(dtype))
+        .expect("Bound must be representable in the dtype of the box");
+
+    (tensor, bounded)
+}
+
+/// Smallest and largest finite values representable by `dtype`.
+pub(crate) fn dtype_extremes(dtype: DType) -> (f64, f64) {
+    match dtype {
+        DType::U8 => (u8::MIN as f64, u8::MAX as f64),
+        DType::U32 => (u32::MIN as f64, u32::MAX as f64),
+        DType::I16 => (i16::MIN as f64, i16::MAX as f64),
+        DType::I32 => (i32::MIN as f64, i32::MAX as f64),
+        DType::I64 => (i64::MIN as f64, i64::MAX as f64),
+        DType::F16 => (-65504.0, 65504.0),
+        DType::BF16 => (-3.3895313892515355e38, 3.3895313892515355e38),
+        DType::F32 => (f32::MIN as f64, f32::MAX as f64),
+        _ => (f64::MIN, f64::MAX),
+    }
+}
+
+/// Short representation of a bound: `[v]` if all of its elements equal `v`, else all its elements.
+fn _short_repr(arr: &Tensor) -> String {
+    let format = |tensor: &Tensor| -> candle_core::Result<Vec<String>> {
+        let tensor = tensor.flatten_all()?;
+        Ok(match tensor.dtype() {
+            DType::F64 => tensor
+                .to_vec1::<f64>()?
+                .iter()
+                .map(f64::to_string)
+                .collect(),
+            dtype if dtype.is_float() => tensor
+                .to_dtype(DType::F32)?
+                .to_vec1::<f32>()?
+                .iter()
+                .map(f32::to_string)
+                .collect(),
+            _ => tensor
+                .to_dtype(DType::I64)?
+                .to_vec1::<i64>()?
+                .iter()
+                .map(i64::to_string)
+                .collect(),
+        })
+    };
+    let collapse = || -> candle_core::Result<Option<Tensor>> {
+        if arr.elem_count() == 0 {
+            return Ok(None);
+        }
+        let (min, max) = (arr.min_all()?, arr.max_all()?);
+        let equal = min.eq(&max)?.to_dtype(DType::U8)?.to_scalar::<u8>()? == 1;
+        Ok(equal.then_some(min))
+    };
+
+    let values = match collapse() {
+        Ok(Some(min)) => format(&min),
+        _ => format(arr),
+    };
+    format!("[{}]", values.unwrap_or_default().join(", "))
+}
+
+/// Copies a tensor to the host as a flat vector of `f64`.
+fn _to_f64_vec(tensor: &Tensor) -> Vec<f64> {
+    tensor
+        .flatten_all()
+        .and_then(|tensor| tensor.to_dtype(DType::F64))
+        .and_then(|tensor| tensor.to_vec1::<f64>())
+        .expect("Tensor must be convertible to f64")
+}
+
+/// Copies a tensor to the host as a flat vector of `f32`.
+fn _to_f32_vec(tensor: &Tensor) -> Vec<f32> {
+    tensor
+        .flatten_all()
+        .and_then(|tensor| tensor.to_dtype(DType::F32))
+        .and_then(|tensor| tensor.to_vec1::<f32>())
+        .expect("Tensor must be convertible to f32")
+}
+
+/// Draws from the standard exponential distribution.
+fn _exponential(rng: &mut Generator) -> f64 {
+    -(1.0 - rng.gen::<f64>()).ln()
+}
+
+/// Draws from the standard normal distribution using the Box-Muller transform.
+fn _normal(rng: &mut Generator) -> f64 {
+    let u1 = 1.0 - rng.gen::<f64>();
+    let u2 = rng.gen::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_box_from_scalar_bounds() {
+        let space = Box::new(
+            Bound::F64(-1.0),
+            Bound::F64(2.0),
+            Some(vec![3, 4]),
+            DType::F32,
+            &Device::Cpu,
+            Some(0),
+        )
+        .unwrap();
+
+        assert_eq!(space.shape(), &[3, 4]);
+        assert_eq!(space.low().dims(), &[3, 4]);
+        assert_eq!(space.low().dtype(), DType::F32);
+        let low = space.low().flatten_all().unwrap().to_vec1::<f32>().unwrap();
+        let high = space
+            .high()
+            .flatten_all()
+            .unwrap()
+            .to_vec1::<f32>()
+            .unwrap();
+        assert!(low.iter().all(|&x| x == -1.0));
+        assert!(high.iter().all(|&x| x == 2.0));
+    }
+
+    #[test]
+    fn test_box_from_tensor_bounds() {
+        let low = Tensor::new(&[-1.0f32, -2.0], &Device::Cpu).unwrap();
+        let high = Tensor::new(&[2.0f32, 4.0], &Device::Cpu).unwrap();
+        let space = Box::new(
+            Bound::Tensor(low),
+            Bound::Tensor(high),
+            None,
+            DType::F32,
+            &Device::Cpu,
+            Some(0),
+        )
+        .unwrap();
+
+        assert_eq!(space.shape(), &[2]);
+        assert_eq!(space.low().to_vec1::<f32>().unwrap(), vec![-1.0, -2.0]);
+        assert_eq!(space.high().to_vec1::<f32>().unwrap(), vec![2.0, 4.0]);
+    }
+
+    #[test]
+    fn test_box_scalar_bounds_without_shape() {
+        let err = Box::new(
+            Bound::F64(-1.0),
+            Bound::F64(1.0),
+            None,
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap_err();
+
+        assert_eq!(err, BoxError::MissingShape);
+        assert!(err.to_string().starts_with("A shape must be provided"));
+    }
+
+    #[test]
+    fn test_box_infers_shape_from_either_tensor_bound() {
+        let high = Tensor::new(&[[1.0f32, 2.0], [3.0, 4.0]], &Device::Cpu).unwrap();
+        let space = Box::new(
+            Bound::F64(0.0),
+            Bound::Tensor(high),
+            None,
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(space.shape(), &[2, 2]);
+        assert_eq!(
+            space.low().to_vec2::<f32>().unwrap(),
+            [[0.0, 0.0], [0.0, 0.0]]
+        );
+    }
+
+    #[test]
+    fn test_box_broadcasts_scalar_bounds_to_shape() {
+        let space = Box::new(
+            Bound::F64(-1.0),
+            Bound::F64(1.0),
+            Some(vec![3, 4]),
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(space.low().dims(), [3, 4]);
+        assert_eq!(space.high().dims(), [3, 4]);
+        assert_eq!(
+            space.low().to_vec2::<f32>().unwrap(),
+            vec![vec![-1.0; 4]; 3]
+        );
+        assert_eq!(
+            space.high().to_vec2::<f32>().unwrap(),
+            vec![vec![1.0; 4]; 3]
+        );
+
+        // A tensor bound of a broadcastable shape is expanded as well.
+        let low = Tensor::new(&[[0.0f32, 1.0, 2.0, 3.0]], &Device::Cpu).unwrap();
+        let space = Box::new(
+            Bound::Tensor(low),
+            Bound::F64(5.0),
+            Some(vec![3, 4]),
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            space.low().to_vec2::<f32>().unwrap(),
+            vec![vec![0.0, 1.0, 2.0, 3.0]; 3]
+        );
+    }
+
+    #[test]
+    fn test_box_tensor_bound_conflicting_with_shape() {
+        let high = Tensor::new(&[1.0f32, 2.0, 3.0], &Device::Cpu).unwrap();
+        let err = Box::new(
+            Bound::F64(0.0),
+            Bound::Tensor(high),
+            Some(vec![2, 2]),
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            err,
+            BoxError::ShapeMismatch {
+                bound: vec![3],
+                shape: vec![2, 2],
+            }
+        );
+    }
+
+    #[test]
+    fn test_box_is_bounded() {
+        let bounded = Box::bounded(-1.0, 1.0, vec![2]);
+        assert!(bounded.is_bounded(Bounded::Both));
+        assert!(bounded.is_bounded(Bounded::Below));
+        assert!(bounded.is_bounded(Bounded::Above));
+
+        let half_bounded = Box::bounded(0.0, f32::INFINITY, vec![2]);
+        assert!(!half_bounded.is_bounded(Bounded::Both));
+        assert!(half_bounded.is_bounded(Bounded::Below));
+        assert!(!half_bounded.is_bounded(Bounded::Above));
+
+        // A single unbounded dimension is enough for the box not to be bounded.
+        let partially_bounded = Box::from_vecs(
+            vec![-1.0, -1.0],
+            vec![1.0, f32::INFINITY],
+            vec![2],
+            DType::F32,
+            &Device::Cpu,
+        )
+        .unwrap();
+        assert!(!partially_bounded.is_bounded(Bounded::Both));
+        assert!(partially_bounded.is_bounded(Bounded::Below));
+        assert!(!partially_bounded.is_bounded(Bounded::Above));
+
+        let unbounded = Box::bounded(f32::NEG_INFINITY, f32::INFINITY, vec![2]);
+        assert!(!unbounded.is_bounded(Bounded::Both));
+        assert!(!unbounded.is_bounded(Bounded::Below));
+        assert!(!unbounded.is_bounded(Bounded::Above));
+    }
+
+    #[test]
+    fn test_box_flatten_methods() {
+        let space = Box::new(
+            Bound::F64(-1.0),
+            Bound::F64(1.0),
+            Some(vec![2, 2]),
+            DType::F64,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+        let x = Tensor::new(&[[0.5f64, -0.5], [0.25, 1.0]], &Device::Cpu).unwrap();
+
+        let flattened = space.flatten(&x).unwrap();
+        assert_eq!(flattened.to_vec1::<f64>().unwrap(), [0.5, -0.5, 0.25, 1.0]);
+        let expected = crate::spaces::utils::flatten(&space.clone().into(), &x.clone().into());
+        assert!(tensor_eq(&flattened, &expected.unwrap()));
+
+        let unflattened = space.unflatten(&flattened.to_dtype(DType::F32).unwrap());
+        let unflattened = unflattened.unwrap();
+        assert_eq!(unflattened.dtype(), DType::F64);
+        assert!(tensor_eq(&unflattened, &x));
+
+        let err = space
+            .unflatten(&flattened.narrow(0, 0, 3).unwrap())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected a 1-D tensor of 4 elements to unflatten, got shape [3]"
+        );
+    }
+
+    #[test]
+    fn test_box_clip() {
+        let space = Box::from_vecs(
+            vec![-1.0, 0.0],
+            vec![1.0, 5.0],
+            vec![2],
+            DType::F32,
+            &Device::Cpu,
+        )
+        .unwrap();
+        let x = Tensor::new(&[-3.0f32, 7.5], &Device::Cpu).unwrap();
+        assert_eq!(space.clip(x).to_vec1::<f32>().unwrap(), [-1.0, 5.0]);
+
+        let x = Tensor::new(&[0.25f32, 4.0], &Device::Cpu).unwrap();
+        assert!(tensor_eq(&space.clip(x.clone()), &x));
+
+        // Batches are clipped at once, and the dtype of the tensor is kept.
+        let x = Tensor::new(&[[-2f64, 0.5], [0.5, 6.0]], &Device::Cpu).unwrap();
+        let clipped = space.clip(x);
+        assert_eq!(clipped.dtype(), DType::F64);
+        assert_eq!(clipped.to_vec2::<f64>().unwrap(), [[-1.0, 0.5], [0.5, 5.0]]);
+        let x = Tensor::new(&[-4i64, 9], &Device::Cpu).unwrap();
+        assert_eq!(space.clip(x).to_vec1::<i64>().unwrap(), [-1, 5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot clip a tensor of shape [3] to a box of shape [2]")]
+    fn test_box_clip_wrong_shape() {
+        Box::bounded(-1.0, 1.0, vec![2]).clip(Tensor::zeros(3, DType::F32, &Device::Cpu).unwrap());
+    }
+
+    #[test]
+    fn test_box_low_and_high_vecs() {
+        let low = Tensor::new(&[[-1.0f32, 0.0], [0.5, -2.0]], &Device::Cpu).unwrap();
+        let high = Tensor::new(&[[1.0f32, 2.0], [1.5, 3.0]], &Device::Cpu).unwrap();
+        let space = Box::new(
+            Bound::Tensor(low.clone()),
+            Bound::Tensor(high.clone()),
+            None,
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            space.low_vec(),
+            low.flatten_all().unwrap().to_vec1::<f32>().unwrap()
+        );
+        assert_eq!(
+            space.high_vec(),
+            high.flatten_all().unwrap().to_vec1::<f32>().unwrap()
+        );
+
+        // Other dtypes are converted, and unbounded dimensions have the extremes of the dtype.
+        let space = Box::new(
+            Bound::F64(f64::NEG_INFINITY),
+            Bound::Tensor(Tensor::new(&[3i64, 7], &Device::Cpu).unwrap()),
+            None,
+            DType::U8,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+        assert_eq!(space.low_vec(), [0.0, 0.0]);
+        assert_eq!(space.high_vec(), [3.0, 7.0]);
+    }
+
+    #[test]
+    fn test_box_from_vecs_and_bounded() {
+        let shape = vec![2, 3];
+        let space = Box::from_vecs(
+            vec![-1.0; 6],
+            vec![2.0; 6],
+            shape.clone(),
+            DType::F32,
+            &Device::Cpu,
+        )
+        .unwrap();
+        let bounded = Box::bounded(-1.0, 2.0, shape);
+
+        assert_eq!(space, bounded);
+        assert_eq!(space.shape(), &[2, 3]);
+        assert_eq!(
+            space.low().to_vec2::<f32>().unwrap(),
+            bounded.low().to_vec2::<f32>().unwrap()
+        );
+        assert_eq!(
+            space.high().to_vec2::<f32>().unwrap(),
+            bounded.high().to_vec2::<f32>().unwrap()
+        );
+
+        let space = Box::from_vecs(
+            vec![0.0, -5.0],
+            vec![1.0, 5.0],
+            vec![2],
+            DType::F64,
+            &Device::Cpu,
+        )
+        .unwrap();
+        assert_eq!(space.low().to_vec1::<f64>().unwrap(), [0.0, -5.0]);
+        assert_eq!(space.high().to_vec1::<f64>().unwrap(), [1.0, 5.0]);
+    }
+
+    #[test]
+    fn test_box_from_vecs_wrong_length() {
+        let err = Box::from_vecs(
+            vec![0.0; 4],
+            vec![1.0; 3],
+            vec![2, 2],
+            DType::F32,
+            &Device::Cpu,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            BoxError::LengthMismatch {
+                len: 3,
+                shape: vec![2, 2]
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "A bound of 3 values does not have one value per element of the shape [2, 2]"
+        );
+    }
+
+    #[test]
+    fn test_box_from_bound_conversions() {
+        let explicit = Box::new(
+            Bound::F64(-1.0),
+            Bound::F64(1.0),
+            Some(vec![2, 2]),
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+        let space = Box::new(
+            (-1.0).into(),
+            1.0f32.into(),
+            Some(vec![2, 2]),
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+        assert_eq!(space, explicit);
+
+        let low = Tensor::new(&[0.0f32, -5.0], &Device::Cpu).unwrap();
+        let high = Tensor::new(&[[1.0f32, 5.0], [2.0, 6.0]], &Device::Cpu).unwrap();
+        let explicit = Box::new(
+            Bound::Tensor(low),
+            Bound::Tensor(high),
+            Some(vec![2, 2]),
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+        let space = Box::new(
+            vec![0.0, -5.0].into(),
+            Bound::from_slice(&[1.0, 5.0, 2.0, 6.0], &[2, 2]).unwrap(),
+            Some(vec![2, 2]),
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+        assert_eq!(space, explicit);
+        assert_eq!(
+            space.low().to_vec2::<f32>().unwrap(),
+            [[0.0, -5.0], [0.0, -5.0]]
+        );
+    }
+
+    #[test]
+    fn test_bound_from_slice_wrong_length() {
+        assert_eq!(
+            Bound::from_slice(&[0.0; 3], &[2, 2]).err(),
+            Some(BoxError::LengthMismatch {
+                len: 3,
+                shape: vec![2, 2]
+            })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_box_serde_round_trip() {
+        let low = Tensor::new(&[-1.0f64, f64::NEG_INFINITY, 0.0], &Device::Cpu).unwrap();
+        let high = Tensor::new(&[2.0f64, 3.0, f64::INFINITY], &Device::Cpu).unwrap();
+        let space = Box::new(
+            Bound::Tensor(low),
+            Bound::Tensor(high),
+            None,
+            DType::F32,
+            &Device::Cpu,
+            Some(0),
+        )
+        .unwrap();
+
+        let json = serde_json::to_string(&space).unwrap();
+        assert_eq!(
+            json,
+            r#"{"shape":[3],"dtype":"f32","low":[-1.0,null,0.0],"high":[2.0,3.0,null],"device":"cpu"}"#
+        );
+        let deserialized: Box = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, space);
+        assert_eq!(deserialized.shape(), space.shape());
+        assert_eq!(deserialized.dtype(), space.dtype());
+        assert_eq!(
+            deserialized.low().to_vec1::<f32>().unwrap(),
+            space.low().to_vec1::<f32>().unwrap()
+        );
+        assert_eq!(
+            deserialized.high().to_vec1::<f32>().unwrap(),
+            space.high().to_vec1::<f32>().unwrap()
+        );
+        assert_eq!(deserialized.bounded_below, [true, false, true]);
+        assert_eq!(deserialized.bounded_above, [true, true, false]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_box_serde_rejects_mismatched_bounds() {
+        let json = r#"{"shape":[2],"dtype":"f32","low":[0.0],"high":[1.0,1.0],"device":"cpu"}"#;
+        let err = serde_json::from_str::<Box>(json).unwrap_err();
+        assert!(err.to_string().contains("expected 2 bounds"));
+    }
+
+    #[test]
+    fn test_box_sample_n_matches_repeated_samples() {
+        let low = Tensor::new(&[-1.0f64, f64::NEG_INFINITY, 0.0], &Device::Cpu).unwrap();
+        let high = Tensor::new(&[2.0f64, 3.0, f64::INFINITY], &Device::Cpu).unwrap();
+        let space = Box::new(
+            Bound::Tensor(low),
+            Bound::Tensor(high),
+            None,
+            DType::F32,
+            &Device::Cpu,
+            Some(0),
+        )
+        .unwrap();
+        let count = 10_000;
+
+        let batched = space.clone().sample_n(count, None).unwrap();
+        assert_eq!(batched.dims(), [count, 3]);
+        assert_eq!(batched.dtype(), DType::F32);
+
+        let mut repeated = space.clone();
+        let samples: Vec<Tensor> = (0..count).map(|_| repeated.sample(None)).collect();
+        let samples = Tensor::stack(&samples, 0).unwrap();
+        assert_eq!(
+            batched.to_vec2::<f32>().unwrap(),
+            samples.to_vec2::<f32>().unwrap()
+        );
+        assert!((0..count).all(|i| space.contains(&batched.get(i).unwrap())));
+
+        let empty = space.clone().sample_n(0, None).unwrap();
+        assert_eq!(empty.dims(), [0, 3]);
+        assert_eq!(empty.dtype(), DType::F32);
+    }
+
+    #[test]
+    fn test_box_integer_sample() {
+        let mut space = Box::new(
+            Bound::F64(0.0),
+            Bound::F64(5.0),
+            Some(vec![4]),
+            DType::I64,
+            &Device::Cpu,
+            Some(0),
+        )
+        .unwrap();
+
+        let samples = space.sample_n(250, None).unwrap();
+        assert_eq!(samples.dtype(), DType::I64);
+        let values = samples.flatten_all().unwrap().to_vec1::<i64>().unwrap();
+        assert!(values.iter().all(|x| (0..=5).contains(x)));
+        for value in [0, 5] {
+            assert!(values.contains(&value), "{value} is never sampled");
+        }
+        assert!(space.contains(&space.clone().sample(None)));
+    }
+
+    #[test]
+    fn test_box_integer_sample_half_bounded() {
+        let low = Tensor::new(&[-3.0f64, f64::NEG_INFINITY], &Device::Cpu).unwrap();
+        let high = Tensor::new(&[f64::INFINITY, 2.0], &Device::Cpu).unwrap();
+        let mut space = Box::new(
+            Bound::Tensor(low),
+            Bound::Tensor(high),
+            None,
+            DType::I64,
+            &Device::Cpu,
+            Some(0),
+        )
+        .unwrap();
+
+        for sample in space.sample_n(100, None).unwrap().to_vec2::<i64>().unwrap() {
+            assert!(sample[0] >= -3 && sample[1] <= 2);
+        }
+    }
+
+    #[test]
+    fn test_box_seed_reproduces_samples() {
+        let mut space = Box::new(
+            Bound::F64(-1.0),
+            Bound::F64(1.0),
+            Some(vec![2, 3]),
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(space.seed(Some(7)), vec![7]);
+        let first = space
+            .sample(None)
+            .flatten_all()
+            .unwrap()
+            .to_vec1::<f32>()
+            .unwrap();
+        space.seed(Some(7));
+        let second = space
+            .sample(None)
+            .flatten_all()
+            .unwrap()
+            .to_vec1::<f32>()
+            .unwrap();
+
+        assert_eq!(first, second);
+        assert!(first.iter().all(|x| (-1.0..=1.0).contains(x)));
+    }
+
+    #[test]
+    fn test_box_successive_samples_advance_generator() {
+        let mut space = Box::bounded(-1.0, 1.0, vec![3]);
+        let sample_pair = |space: &mut Box| {
+            let first = space.sample(None).to_vec1::<f32>().unwrap();
+            let second = space.sample(None).to_vec1::<f32>().unwrap();
+            (first, second)
+        };
+
+        space.seed(Some(42));
+        let (first, second) = sample_pair(&mut space);
+        assert_ne!(first, second);
+        space.seed(Some(42));
+        assert_eq!(sample_pair(&mut space), (first, second));
+    }
+
+    fn unit_box() -> Box {
+        Box::new(
+            Bound::F64(-1.0),
+            Bound::F64(1.0),
+            Some(vec![2, 2]),
+            DType::F32,
+            &Device::Cpu,
+            Some(0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_box_contains_in_bounds() {
+        let mut space = unit_box();
+        let x = Tensor::new(&[[-1.0f32, 0.0], [0.5, 1.0]], &Device::Cpu).unwrap();
+
+        assert!(space.contains(&x));
+        let sample = space.sample(None);
+        assert!(space.contains(&sample));
+    }
+
+    #[test]
+    fn test_box_contains_out_of_bounds() {
+        let space = unit_box();
+        let x = Tensor::new(&[[-1.0f32, 0.0], [0.5, 1.5]], &Device::Cpu).unwrap();
+
+        assert!(!space.contains(&x));
+    }
+
+    #[test]
+    fn test_box_contains_wrong_shape_or_dtype() {
+        let space = unit_box();
+        let wrong_shape = Tensor::new(&[0.0f32, 0.0, 0.0, 0.0], &Device::Cpu).unwrap();
+        let wrong_dtype = Tensor::new(&[[0.0f64, 0.0], [0.0, 0.0]], &Device::Cpu).unwrap();
+
+        assert!(!space.contains(&wrong_shape));
+        assert!(!space.contains(&wrong_dtype));
+    }
+
+    #[test]
+    fn test_box_equality_ignores_generator() {
+        let mut a = unit_box();
+        let b = Box::new(
+            Bound::F64(-1.0),
+            Bound::F64(1.0),
+            Some(vec![2, 2]),
+            DType::F32,
+            &Device::Cpu,
+            Some(123),
+        )
+        .unwrap();
+        a.sample(None);
+        assert_eq!(a, b);
+
+        let wider = Box::new(
+            Bound::F64(-2.0),
+            Bound::F64(1.0),
+            Some(vec![2, 2]),
+            DType::F32,
+            &Device::Cpu,
+            Some(0),
+        )
+        .unwrap();
+        let other_dtype = Box::new(
+            Bound::F64(-1.0),
+            Bound::F64(1.0),
+            Some(vec![2, 2]),
+            DType::F64,
+            &Device::Cpu,
+            Some(0),
+        )
+        .unwrap();
+        assert_ne!(a, wider);
+        assert_ne!(a, other_dtype);
+    }
+
+    #[test]
+    fn test_box_replaces_infinite_bounds() {
+        let mut space = Box::new(
+            Bound::F64(f64::NEG_INFINITY),
+            Bound::F64(5.0),
+            Some(vec![3]),
+            DType::F32,
+            &Device::Cpu,
+            Some(0),
+        )
+        .unwrap();
+
+        let low = space.low().to_vec1::<f32>().unwrap();
+        assert!(low.iter().all(|x| *x == f32::MIN));
+        assert_eq!(space.bounded_below, vec![false; 3]);
+        assert_eq!(space.bounded_above, vec![true; 3]);
+
+        let sample = space.sample(None).to_vec1::<f32>().unwrap();
+        assert!(sample.iter().all(|x| x.is_finite() && *x <= 5.0));
+    }
+
+    #[test]
+    fn test_box_integer_dtype_infinite_bounds() {
+        let space = Box::new(
+            Bound::F64(f64::NEG_INFINITY),
+            Bound::F64(f64::INFINITY),
+            Some(vec![2]),
+            DType::U8,
+            &Device::Cpu,
+            Some(0),
+        )
+        .unwrap();
+
+        assert_eq!(space.low().to_vec1::<u8>().unwrap(), vec![0, 0]);
+        assert_eq!(space.high().to_vec1::<u8>().unwrap(), vec![255, 255]);
+    }
+
+    #[test]
+    fn test_box_short_repr_collapses_equal_entries() {
+        let low = Tensor::new(&[[-1.0f32, -1.0], [-1.0, -1.0]], &Device::Cpu).unwrap();
+        let high = Tensor::new(&[[1.0f32, 2.0], [3.0, 0.5]], &Device::Cpu).unwrap();
+
+        assert_eq!(_short_repr(&low), "[-1]");
+        assert_eq!(_short_repr(&high), "[1, 2, 3, 0.5]");
+
+        let space = Box::new(
+            Bound::Tensor(low),
+            Bound::Tensor(high),
+            None,
+            DType::F32,
+            &Device::Cpu,
+            Some(0),
+        )
+        .unwrap();
+        assert_eq!(space.to_string(), "Box([-1], [1, 2, 3, 0.5], [2, 2], F32)");
+    }
+
+    #[test]
+    fn test_box_seed_above_u32_max() {
+        let seed = u64::MAX - 1;
+        let mut space = unit_box();
+
+        assert_eq!(space.seed(Some(seed)), vec![seed]);
+        let first = space
+            .sample(None)
+            .flatten_all()
+            .unwrap()
+            .to_vec1::<f32>()
+            .unwrap();
+        space.seed(Some(seed));
+        let second = space
+            .sample(None)
+            .flatten_all()
+            .unwrap()
+            .to_vec1::<f32>()
+            .unwrap();
+        assert_eq!(first, second);
+    }
+}
@@ -0,0 +1,217 @@
+//! Implementation of a space that is a dictionary of simpler spaces.
+
+use indexmap::IndexMap;
+use rand::Rng;
+
+use super::space::{SampleMask, Space, SpaceEnum, StructuredSample};
+use crate::utils::seeding::rs_random;
+
+/// A dictionary of [`SpaceEnum`] instances.
+///
+/// Elements of this space are ordered maps of elements of the constituent spaces. Keys keep their
+/// insertion order so that sampling, seeding and flattening are reproducible.
+///
+/// Example: `Dict::new([("position", position.into()), ("velocity", velocity.into())], None)`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dict {
+    spaces: IndexMap<String, SpaceEnum>,
+}
+
+impl Dict {
+    /// Constructor of `Dict`.
+    ///
+    /// If `seed` is given, the subspaces are seeded with seeds derived from it in key order.
+    pub fn new<I, K>(spaces: I, seed: Option<u64>) -> Self
+    where
+        I: IntoIterator<Item = (K, SpaceEnum)>,
+        K: Into<String>,
+    {
+        let mut space = Self {
+            spaces: spaces
+                .into_iter()
+                .map(|(key, space)| (key.into(), space))
+                .collect(),
+        };
+        if seed.is_some() {
+            space.seed(seed);
+        }
+        space
+    }
+
+    /// Subspaces of this space, in insertion order.
+    pub fn spaces(&self) -> &IndexMap<String, SpaceEnum> {
+        &self.spaces
+    }
+}
+
+impl Space<IndexMap<String, StructuredSample>> for Dict {
+    /// Generates a single random sample by sampling each subspace in key order.
+    ///
+    /// A [`SampleMask::Dict`] gives the masks of the subspaces by key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mask` is not a `Dict` mask or has keys that are not subspaces.
+    fn sample(&mut self, mask: Option<&SampleMask>) -> IndexMap<String, StructuredSample> {
+        let masks = match mask {
+            None => None,
+            Some(SampleMask::Dict(masks)) => {
+                if let Some(key) = masks.keys().find(|key| !self.spaces.contains_key(*key)) {
+                    panic!("The mask key {key:?} is not a subspace");
+                }
+                Some(masks)
+            }
+            Some(mask) => panic!("Expected a Dict mask, got {mask:?}"),
+        };
+        self.spaces
+            .iter_mut()
+            .map(|(key, space)| {
+                let mask = masks.and_then(|masks| masks.get(key));
+                (key.clone(), space.sample(mask))
+            })
+            .collect()
+    }
+
+    /// Seeds the subspaces and returns their seeds in key order.
+    ///
+    /// An integer seed is used to deterministically derive one sub-seed per subspace, while `None`
+    /// seeds every subspace from OS entropy.
+    fn seed(&mut self, seed: Option<u64>) -> Vec<u64> {
+        match seed {
+            Some(seed) => {
+                let (mut rng, _) = rs_random(Some(seed));
+                self.spaces
+                    .values_mut()
+                    .flat_map(|space| space.seed(Some(rng.gen())))
+                    .collect()
+            }
+            None => self
+                .spaces
+                .values_mut()
+                .flat_map(|space| space.seed(None))
+                .collect(),
+        }
+    }
+
+    fn contains(&self, x: &IndexMap<String, StructuredSample>) -> bool {
+        x.len() == self.spaces.len()
+            && self
+                .spaces
+                .iter()
+                .all(|(key, space)| x.get(key).is_some_and(|x| space.contains(x)))
+    }
+
+    fn is_flattenable(&self) -> bool {
+        self.spaces.values().all(|space| space.is_flattenable())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spaces::{Bound, Box, Discrete};
+    use candle_core::{DType, Device};
+
+    fn dict() -> Dict {
+        Dict::new(
+            [
+                (
+                    "position",
+                    Box::new(
+                        Bound::F64(-1.0),
+                        Bound::F64(1.0),
+                        Some(vec![2]),
+                        DType::F32,
+                        &Device::Cpu,
+                        None,
+                    )
+                    .unwrap()
+                    .into(),
+                ),
+                ("gear", Discrete::new(5, 0, None).into()),
+                ("alive", Discrete::new(2, 0, None).into()),
+            ],
+            None,
+        )
+    }
+
+    #[test]
+    fn test_dict_preserves_insertion_order() {
+        let mut space = dict();
+        let keys: Vec<&String> = space.spaces().keys().collect();
+        assert_eq!(keys, ["position", "gear", "alive"]);
+
+        let sample = space.sample(None);
+        let keys: Vec<&String> = sample.keys().collect();
+        assert_eq!(keys, ["position", "gear", "alive"]);
+        assert!(space.contains(&sample));
+    }
+
+    #[test]
+    fn test_dict_contains_rejects_missing_keys() {
+        let mut space = dict();
+        let mut sample = space.sample(None);
+        sample.shift_remove("gear");
+
+        assert!(!space.contains(&sample));
+    }
+
+    #[test]
+    fn test_dict_sample_n_errors() {
+        let mut space = SpaceEnum::from(dict());
+        for count in [0, 3] {
+            let err = space.sample_n(count, None).unwrap_err();
+            assert_eq!(err.to_string(), "elements of Dict spaces cannot be stacked");
+        }
+    }
+
+    #[test]
+    fn test_dict_seed_reproduces_samples() {
+        let mut space = dict();
+        let seeds = space.seed(Some(42));
+        let first = space.sample(None);
+        assert_eq!(space.seed(Some(42)), seeds);
+        let second = space.sample(None);
+
+        assert_eq!(seeds.len(), 3);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_dict_successive_samples_advance_generator() {
+        let mut space = dict();
+        space.seed(Some(42));
+        let (first, second) = (space.sample(None), space.sample(None));
+        assert_ne!(first, second);
+
+        space.seed(Some(42));
+        assert_eq!((space.sample(None), space.sample(None)), (first, second));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_dict_serde_round_trip() {
+        use crate::spaces::{MultiBinary, MultiDiscrete, OneOf, Tuple};
+
+        let nvec = candle_core::Tensor::new(&[3i64, 4], &Device::Cpu).unwrap();
+        let mut spaces = dict().spaces().clone();
+        spaces.insert(
+            "nested".to_string(),
+            Tuple::new(
+                vec![
+                    MultiBinary::new(3, &Device::Cpu, None).into(),
+                    MultiDiscrete::new(nvec, None, DType::I64, None).into(),
+                    OneOf::new(vec![Discrete::new(2, -1, None).into()], None).into(),
+                ],
+                None,
+            )
+            .into(),
+        );
+        let space = SpaceEnum::Dict(Dict::new(spaces, None));
+
+        let json = serde_json::to_string(&space).unwrap();
+        let deserialized: SpaceEnum = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, space);
+    }
+}
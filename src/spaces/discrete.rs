@@ -0,0 +1,305 @@
+//! Implementation of a space consisting of finitely many elements.
+
+use candle_core::{DType, Device, Tensor};
+use rand::Rng;
+
+use super::space::{SampleMask, Space};
+use super::utils::BatchError;
+use crate::utils::seeding::{rs_random, Generator};
+
+/// A value that can be checked for membership in a [`Discrete`] space by [`Discrete::contains`].
+///
+/// Actions are usually plain integers, whereas observations may come back as scalar tensors.
+pub trait DiscreteValue {
+    /// Returns the value as an integer, or `None` if it does not represent one.
+    fn to_int(&self) -> Option<i64>;
+}
+
+impl DiscreteValue for i64 {
+    fn to_int(&self) -> Option<i64> {
+        Some(*self)
+    }
+}
+
+/// A tensor represents an integer if it is a scalar of an integer dtype.
+impl DiscreteValue for Tensor {
+    fn to_int(&self) -> Option<i64> {
+        if !self.dtype().is_int() {
+            return None;
+        }
+        self.to_dtype(DType::I64)
+            .and_then(|x| x.to_scalar::<i64>())
+            .ok()
+    }
+}
+
+/// A space consisting of finitely many elements.
+///
+/// This class represents a finite subset of integers, more specifically a set of the form
+/// `{a, a+1, ..., a+n-1}`.
+///
+/// Example: `Discrete::new(3, -1, None)` covers `{-1, 0, 1}`.
+#[derive(Debug, Clone)]
+pub struct Discrete {
+    n: usize,
+    start: i64,
+    rs_random: Generator,
+}
+
+impl Discrete {
+    /// Constructor of `Discrete`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    pub fn new(n: usize, start: i64, seed: Option<u64>) -> Self {
+        assert!(n > 0, "n (counts) have to be positive");
+        let (rs_random, _) = rs_random(seed);
+
+        Self {
+            n,
+            start,
+            rs_random,
+        }
+    }
+
+    /// Number of elements of this space.
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Smallest element of this space.
+    pub fn start(&self) -> i64 {
+        self.start
+    }
+
+    /// Returns `true` if `x` is an integer in `{start, ..., start+n-1}`, given either as an `i64`
+    /// or as a scalar tensor.
+    pub fn contains<T: DiscreteValue + ?Sized>(&self, x: &T) -> bool {
+        x.to_int()
+            .is_some_and(|x| self.start <= x && x < self.start + self.n as i64)
+    }
+}
+
+/// Two discrete spaces are equal if they cover the same elements, regardless of their generators.
+impl PartialEq for Discrete {
+    fn eq(&self, other: &Self) -> bool {
+        self.n == other.n && self.start == other.start
+    }
+}
+
+/// The serialized form of a [`Discrete`] space.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct DiscreteRepr {
+    n: usize,
+    start: i64,
+}
+
+/// Serializes the elements of the space, but not its generator.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Discrete {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        DiscreteRepr {
+            n: self.n,
+            start: self.start,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Deserializes a space with a generator seeded from OS entropy.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Discrete {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let DiscreteRepr { n, start } = DiscreteRepr::deserialize(deserializer)?;
+        if n == 0 {
+            return Err(serde::de::Error::custom("n (counts) have to be positive"));
+        }
+        Ok(Discrete::new(n, start, None))
+    }
+}
+
+impl Space<i64> for Discrete {
+    /// Generates a single random sample from this space, uniformly over `{start, ..., start+n-1}`.
+    ///
+    /// A [`SampleMask::Discrete`] restricts the sample to the elements whose mask entry is non-zero.
+    /// If no element is allowed, `start` is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mask` is not a `Discrete` mask of shape `(n,)`.
+    fn sample(&mut self, mask: Option<&SampleMask>) -> i64 {
+        let Some(mask) = mask else {
+            return self.start + self.rs_random.gen_range(0..self.n as i64);
+        };
+        let SampleMask::Discrete(mask) = mask else {
+            panic!("Expected a Discrete mask, got {mask:?}");
+        };
+        assert_eq!(
+            mask.dims(),
+            [self.n],
+            "The mask must have the shape (n,) = ({},)",
+            self.n
+        );
+
+        let allowed: Vec<i64> = mask
+            .to_dtype(DType::F64)
+            .and_then(|mask| mask.ne(0f64))
+            .and_then(|mask| mask.to_vec1::<u8>())
+            .expect("The mask is a vector")
+            .into_iter()
+            .zip(0..)
+            .filter_map(|(allowed, i)| (allowed == 1).then_some(i))
+            .collect();
+        match allowed.len() {
+            0 => self.start,
+            len => self.start + allowed[self.rs_random.gen_range(0..len)],
+        }
+    }
+
+    /// Generates `count` samples as a 1-D `I64` tensor on the CPU, which is empty if `count` is 0.
+    fn sample_n(&mut self, count: usize, mask: Option<&SampleMask>) -> Result<Tensor, BatchError> {
+        let samples: Vec<i64> = (0..count).map(|_| self.sample(mask)).collect();
+        Ok(Tensor::from_vec(samples, count, &Device::Cpu).expect("Samples are integers"))
+    }
+
+    fn seed(&mut self, seed: Option<u64>) -> Vec<u64> {
+        let (rs_random, seed) = rs_random(seed);
+        self.rs_random = rs_random;
+        vec![seed]
+    }
+
+    fn contains(&self, x: &i64) -> bool {
+        Discrete::contains(self, x)
+    }
+
+    fn is_flattenable(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candle_core::{DType, Device, Tensor};
+
+    #[test]
+    fn test_discrete_sample_bounds() {
+        let mut space = Discrete::new(3, -1, Some(0));
+        let samples: Vec<i64> = (0..100).map(|_| space.sample(None)).collect();
+
+        assert!(samples.iter().all(|x| space.contains(x)));
+        for x in -1..=1 {
+            assert!(samples.contains(&x));
+        }
+    }
+
+    #[test]
+    fn test_discrete_contains() {
+        let space = Discrete::new(3, -1, None);
+
+        assert!(space.contains(&-1));
+        assert!(space.contains(&1));
+        assert!(!space.contains(&-2));
+        assert!(!space.contains(&2));
+    }
+
+    #[test]
+    fn test_discrete_contains_ints_and_tensors() {
+        let space = Discrete::new(3, 1, None);
+
+        assert!(space.contains(&2i64));
+        assert!(!space.contains(&4i64));
+        assert!(!space.contains(&0i64));
+
+        let scalar = |x: i64| Tensor::new(x, &Device::Cpu).unwrap();
+        assert!(space.contains(&scalar(3)));
+        assert!(!space.contains(&scalar(4)));
+        assert!(space.contains(&Tensor::new(1u8, &Device::Cpu).unwrap()));
+        // Only tensors of an integer dtype are elements of the space, even with integral values.
+        assert!(!space.contains(&Tensor::new(1f32, &Device::Cpu).unwrap()));
+        assert!(!space.contains(&Tensor::new(1.5f32, &Device::Cpu).unwrap()));
+        // Only scalar tensors are elements of the space.
+        assert!(!space.contains(&Tensor::new(&[2i64], &Device::Cpu).unwrap()));
+    }
+
+    #[test]
+    fn test_discrete_flatten_methods() {
+        let space = Discrete::new(3, -1, None);
+        let flattened = space.flatten(&1).unwrap();
+        assert_eq!(flattened.to_vec1::<i64>().unwrap(), [0, 0, 1]);
+        assert_eq!(space.unflatten(&flattened).unwrap(), 1);
+
+        let one_hot = Tensor::new(&[0i64, 1, 0], &Device::Cpu).unwrap();
+        assert_eq!(space.unflatten(&one_hot).unwrap(), 0);
+        assert!(space
+            .unflatten(&Tensor::zeros(2, DType::I64, &Device::Cpu).unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_discrete_seed() {
+        let mut space = Discrete::new(10, 0, None);
+        space.seed(Some(3));
+        let first: Vec<i64> = (0..10).map(|_| space.sample(None)).collect();
+        space.seed(Some(3));
+        let second: Vec<i64> = (0..10).map(|_| space.sample(None)).collect();
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_discrete_successive_samples_advance_generator() {
+        let mut space = Discrete::new(1000, 0, None);
+        space.seed(Some(42));
+        let (first, second) = (space.sample(None), space.sample(None));
+        assert_ne!(first, second);
+
+        space.seed(Some(42));
+        assert_eq!((space.sample(None), space.sample(None)), (first, second));
+    }
+
+    #[test]
+    fn test_discrete_sample_n() {
+        let mut space = Discrete::new(3, -1, Some(0));
+        let samples = space.sample_n(50, None).unwrap();
+
+        assert_eq!(samples.dims(), [50]);
+        assert!(samples
+            .to_vec1::<i64>()
+            .unwrap()
+            .iter()
+            .all(|x| space.contains(x)));
+
+        let samples = space.sample_n(0, None).unwrap();
+        assert_eq!(samples.dims(), [0]);
+        assert_eq!(samples.dtype(), DType::I64);
+    }
+
+    #[test]
+    fn test_discrete_masked_sample() {
+        let mut space = Discrete::new(4, 0, Some(0));
+        let mask = SampleMask::Discrete(Tensor::new(&[1u8, 0, 1, 0], &Device::Cpu).unwrap());
+        let samples: Vec<i64> = (0..100).map(|_| space.sample(Some(&mask))).collect();
+
+        assert!(samples.iter().all(|x| [0, 2].contains(x)));
+        assert!(samples.contains(&0) && samples.contains(&2));
+    }
+
+    #[test]
+    fn test_discrete_masked_sample_nothing_allowed() {
+        let mut space = Discrete::new(3, 5, Some(0));
+        let mask = SampleMask::Discrete(Tensor::zeros(3, DType::U8, &Device::Cpu).unwrap());
+
+        assert_eq!(space.sample(Some(&mask)), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "The mask must have the shape (n,) = (4,)")]
+    fn test_discrete_masked_sample_wrong_shape() {
+        let mut space = Discrete::new(4, 0, Some(0));
+        let mask = SampleMask::Discrete(Tensor::new(&[1u8, 0], &Device::Cpu).unwrap());
+        space.sample(Some(&mask));
+    }
+}
@@ -0,0 +1,22 @@
+//! Spaces describe the set of valid actions and observations of an environment.
+
+pub mod r#box;
+pub mod dict;
+pub mod discrete;
+pub mod multi_binary;
+pub mod multi_discrete;
+pub mod one_of;
+#[cfg(feature = "serde")]
+mod serialization;
+pub mod space;
+pub mod tuple;
+pub mod utils;
+
+pub use dict::Dict;
+pub use discrete::{Discrete, DiscreteValue};
+pub use multi_binary::MultiBinary;
+pub use multi_discrete::MultiDiscrete;
+pub use one_of::OneOf;
+pub use r#box::{Bounded, Box, BoxError};
+pub use space::{Bound, SampleMask, Space, SpaceEnum, StructuredSample};
+pub use tuple::Tuple;
@@ -0,0 +1,201 @@
+//! Implementation of a space that consists of binary tensors of a fixed shape.
+
+use candle_core::{DType, Device, Shape, Tensor};
+use rand::Rng;
+
+use super::space::{SampleMask, Space};
+use super::utils::BatchError;
+use crate::utils::seeding::{rs_random, Generator};
+
+/// An n-shape binary space.
+///
+/// Elements of this space are tensors of `0`s and `1`s with dtype `U8`. The shape is fixed during
+/// construction: a scalar `n` gives a 1-D space, a shape vector gives an N-D space.
+///
+/// Example: `MultiBinary::new(5, &Device::Cpu, None)` or `MultiBinary::new(vec![3, 2], &Device::Cpu, None)`
+#[derive(Debug, Clone)]
+pub struct MultiBinary {
+    n: Vec<usize>,
+    device: Device,
+    rs_random: Generator,
+}
+
+impl MultiBinary {
+    /// Constructor of `MultiBinary`.
+    pub fn new<S: Into<Shape>>(n: S, device: &Device, seed: Option<u64>) -> Self {
+        let (rs_random, _) = rs_random(seed);
+
+        Self {
+            n: n.into().dims().to_vec(),
+            device: device.clone(),
+            rs_random,
+        }
+    }
+
+    /// Shape of the elements of this space.
+    pub fn shape(&self) -> &[usize] {
+        &self.n
+    }
+
+    /// Data type of the elements of this space.
+    pub fn dtype(&self) -> DType {
+        DType::U8
+    }
+}
+
+/// Two multi-binary spaces are equal if they have the same shape, regardless of their generators.
+impl PartialEq for MultiBinary {
+    fn eq(&self, other: &Self) -> bool {
+        self.n == other.n
+    }
+}
+
+/// The serialized form of a [`MultiBinary`] space.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct MultiBinaryRepr {
+    n: Vec<usize>,
+    #[serde(with = "super::serialization::device")]
+    device: Device,
+}
+
+/// Serializes the shape and device of the space, but not its generator.
+#[cfg(feature = "serde")]
+impl serde::Serialize for MultiBinary {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        MultiBinaryRepr {
+            n: self.n.clone(),
+            device: self.device.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Deserializes a space with a generator seeded from OS entropy.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MultiBinary {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let MultiBinaryRepr { n, device } = MultiBinaryRepr::deserialize(deserializer)?;
+        Ok(MultiBinary::new(n, &device, None))
+    }
+}
+
+impl Space<Tensor> for MultiBinary {
+    /// Generates a single random sample where each element is `0` or `1` with equal probability.
+    fn sample(&mut self, _mask: Option<&SampleMask>) -> Tensor {
+        let size = self.n.iter().product();
+        let sample: Vec<u8> = (0..size).map(|_| self.rs_random.gen_range(0..=1)).collect();
+
+        Tensor::from_vec(sample, self.n.as_slice(), &self.device)
+            .expect("Sample must match the shape of the space")
+    }
+
+    /// Generates `count` samples as a single batched tensor, which is empty if `count` is 0.
+    fn sample_n(&mut self, count: usize, _mask: Option<&SampleMask>) -> Result<Tensor, BatchError> {
+        let size = count * self.n.iter().product::<usize>();
+        let samples: Vec<u8> = (0..size).map(|_| self.rs_random.gen_range(0..=1)).collect();
+        let shape: Vec<usize> = std::iter::once(count)
+            .chain(self.n.iter().copied())
+            .collect();
+
+        let samples = Tensor::from_vec(samples, shape, &self.device)
+            .expect("Samples must match the shape of the space");
+        Ok(samples)
+    }
+
+    fn seed(&mut self, seed: Option<u64>) -> Vec<u64> {
+        let (rs_random, seed) = rs_random(seed);
+        self.rs_random = rs_random;
+        vec![seed]
+    }
+
+    /// Returns `true` if `x` has the shape of the space and only contains `0`s and `1`s.
+    fn contains(&self, x: &Tensor) -> bool {
+        if x.dims() != self.n.as_slice() {
+            return false;
+        }
+
+        x.flatten_all()
+            .and_then(|x| x.to_dtype(DType::F64))
+            .and_then(|x| x.to_vec1::<f64>())
+            .map(|x| x.iter().all(|x| *x == 0.0 || *x == 1.0))
+            .unwrap_or(false)
+    }
+
+    fn is_flattenable(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_binary_1d() {
+        let mut space = MultiBinary::new(5, &Device::Cpu, Some(0));
+        let sample = space.sample(None);
+
+        assert_eq!(sample.dims(), &[5]);
+        assert!(space.contains(&sample));
+    }
+
+    #[test]
+    fn test_multi_binary_nd_only_binary_values() {
+        let mut space = MultiBinary::new(vec![3, 2], &Device::Cpu, Some(0));
+
+        for _ in 0..20 {
+            let sample = space.sample(None);
+            assert_eq!(sample.dims(), &[3, 2]);
+            let values = sample.flatten_all().unwrap().to_vec1::<u8>().unwrap();
+            assert!(values.iter().all(|x| *x == 0 || *x == 1));
+        }
+    }
+
+    #[test]
+    fn test_multi_binary_sample_n_matches_repeated_samples() {
+        let space = MultiBinary::new(vec![3, 2], &Device::Cpu, Some(0));
+
+        let batched = space.clone().sample_n(20, None).unwrap();
+        assert_eq!(batched.dims(), [20, 3, 2]);
+        let mut repeated = space.clone();
+        let samples: Vec<Tensor> = (0..20).map(|_| repeated.sample(None)).collect();
+        assert_eq!(
+            batched.to_vec3::<u8>().unwrap(),
+            Tensor::stack(&samples, 0).unwrap().to_vec3::<u8>().unwrap()
+        );
+
+        let empty = space.clone().sample_n(0, None).unwrap();
+        assert_eq!(empty.dims(), [0, 3, 2]);
+        assert_eq!(empty.dtype(), DType::U8);
+    }
+
+    #[test]
+    fn test_multi_binary_successive_samples_advance_generator() {
+        let mut space = MultiBinary::new(32, &Device::Cpu, None);
+        space.seed(Some(42));
+        let (first, second) = (
+            space.sample(None).to_vec1::<u8>().unwrap(),
+            space.sample(None).to_vec1::<u8>().unwrap(),
+        );
+        assert_ne!(first, second);
+
+        space.seed(Some(42));
+        assert_eq!(
+            (
+                space.sample(None).to_vec1::<u8>().unwrap(),
+                space.sample(None).to_vec1::<u8>().unwrap()
+            ),
+            (first, second)
+        );
+    }
+
+    #[test]
+    fn test_multi_binary_contains() {
+        let space = MultiBinary::new(3, &Device::Cpu, None);
+
+        assert!(space.contains(&Tensor::new(&[0u8, 1, 1], &Device::Cpu).unwrap()));
+        assert!(!space.contains(&Tensor::new(&[0u8, 2, 1], &Device::Cpu).unwrap()));
+        assert!(!space.contains(&Tensor::new(&[0u8, 1], &Device::Cpu).unwrap()));
+    }
+}
@@ -0,0 +1,289 @@
+//! Implementation of a space that represents the cartesian product of `Discrete` spaces.
+
+use candle_core::{DType, Tensor};
+use rand::Rng;
+
+use super::space::{tensor_eq, SampleMask, Space};
+use super::utils::BatchError;
+use crate::utils::seeding::{rs_random, Generator};
+
+/// This represents the cartesian product of arbitrary [`Discrete`](super::Discrete) spaces.
+///
+/// It is useful to represent game controllers or keyboards where each key can be represented as a
+/// discrete action space. Each element `i` ranges over `{start[i], ..., start[i] + nvec[i] - 1}`.
+///
+/// Example: `MultiDiscrete::new(Tensor::new(&[3i64, 5, 2], &Device::Cpu)?, None, DType::I64, None)`
+#[derive(Debug, Clone)]
+pub struct MultiDiscrete {
+    nvec: Tensor,
+    start: Tensor,
+    dtype: DType,
+    rs_random: Generator,
+}
+
+impl MultiDiscrete {
+    /// Constructor of `MultiDiscrete`.
+    ///
+    /// `start` defaults to zeros and is broadcast to the shape of `nvec`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any element of `nvec` is not positive or if `start` cannot be broadcast to `nvec`.
+    pub fn new(nvec: Tensor, start: Option<Tensor>, dtype: DType, seed: Option<u64>) -> Self {
+        let nvec = nvec
+            .to_dtype(dtype)
+            .expect("nvec must be castable to dtype");
+        assert!(
+            _to_i64_vec(&nvec).iter().all(|n| *n > 0),
+            "nvec (counts) have to be positive"
+        );
+
+        let start = match start {
+            Some(start) => start
+                .to_dtype(dtype)
+                .and_then(|start| start.broadcast_as(nvec.shape()))
+                .and_then(|start| start.contiguous()),
+            None => nvec.zeros_like(),
+        }
+        .expect("start must be broadcastable to the shape of nvec");
+        let (rs_random, _) = rs_random(seed);
+
+        Self {
+            nvec,
+            start,
+            dtype,
+            rs_random,
+        }
+    }
+
+    /// Shape of the elements of this space.
+    pub fn shape(&self) -> &[usize] {
+        self.nvec.dims()
+    }
+
+    /// Data type of the elements of this space.
+    pub fn dtype(&self) -> DType {
+        self.dtype
+    }
+
+    /// Number of values each element can take.
+    pub fn nvec(&self) -> &Tensor {
+        &self.nvec
+    }
+
+    /// Smallest value of each element.
+    pub fn start(&self) -> &Tensor {
+        &self.start
+    }
+}
+
+/// Two multi-discrete spaces are equal if they have the same `nvec`, `start` and dtype, regardless of
+/// their generators.
+impl PartialEq for MultiDiscrete {
+    fn eq(&self, other: &Self) -> bool {
+        self.dtype == other.dtype
+            && tensor_eq(&self.nvec, &other.nvec)
+            && tensor_eq(&self.start, &other.start)
+    }
+}
+
+/// The serialized form of a [`MultiDiscrete`] space, whose `nvec` and `start` are flattened.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct MultiDiscreteRepr {
+    shape: Vec<usize>,
+    #[serde(with = "super::serialization::dtype")]
+    dtype: DType,
+    nvec: Vec<i64>,
+    start: Vec<i64>,
+    #[serde(with = "super::serialization::device")]
+    device: candle_core::Device,
+}
+
+/// Serializes the shape, dtype, counts, starts and device of the space, but not its generator.
+#[cfg(feature = "serde")]
+impl serde::Serialize for MultiDiscrete {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        MultiDiscreteRepr {
+            shape: self.shape().to_vec(),
+            dtype: self.dtype,
+            nvec: _to_i64_vec(&self.nvec),
+            start: _to_i64_vec(&self.start),
+            device: self.nvec.device().clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Deserializes a space with a generator seeded from OS entropy.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MultiDiscrete {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let repr = MultiDiscreteRepr::deserialize(deserializer)?;
+        if repr.nvec.iter().any(|n| *n <= 0) {
+            return Err(D::Error::custom("nvec (counts) have to be positive"));
+        }
+        let tensor = |values: Vec<i64>| {
+            Tensor::from_vec(values, repr.shape.as_slice(), &repr.device).map_err(D::Error::custom)
+        };
+        let nvec = tensor(repr.nvec)?;
+        let start = tensor(repr.start)?;
+        Ok(MultiDiscrete::new(nvec, Some(start), repr.dtype, None))
+    }
+}
+
+impl Space<Tensor> for MultiDiscrete {
+    /// Generates a single random sample by independently drawing each element uniformly.
+    fn sample(&mut self, _mask: Option<&SampleMask>) -> Tensor {
+        let sample: Vec<i64> = _to_i64_vec(&self.nvec)
+            .iter()
+            .zip(_to_i64_vec(&self.start))
+            .map(|(n, start)| start + self.rs_random.gen_range(0..*n))
+            .collect();
+
+        Tensor::from_vec(sample, self.nvec.shape(), self.nvec.device())
+            .and_then(|tensor| tensor.to_dtype(self.dtype))
+            .expect("Sample must match the shape of nvec")
+    }
+
+    /// Generates `count` samples as a single batched tensor, which is empty if `count` is 0.
+    fn sample_n(&mut self, count: usize, _mask: Option<&SampleMask>) -> Result<Tensor, BatchError> {
+        let nvec = _to_i64_vec(&self.nvec);
+        let start = _to_i64_vec(&self.start);
+        let samples: Vec<i64> = (0..count)
+            .flat_map(|_| nvec.iter().zip(&start).collect::<Vec<_>>())
+            .map(|(n, start)| start + self.rs_random.gen_range(0..*n))
+            .collect();
+        let shape: Vec<usize> = std::iter::once(count)
+            .chain(self.shape().iter().copied())
+            .collect();
+
+        let samples = Tensor::from_vec(samples, shape, self.nvec.device())
+            .and_then(|tensor| tensor.to_dtype(self.dtype))
+            .expect("Samples must match the shape of nvec");
+        Ok(samples)
+    }
+
+    fn seed(&mut self, seed: Option<u64>) -> Vec<u64> {
+        let (rs_random, seed) = rs_random(seed);
+        self.rs_random = rs_random;
+        vec![seed]
+    }
+
+    /// Returns `true` if `x` has the shape and dtype of the space and each element is within its range.
+    fn contains(&self, x: &Tensor) -> bool {
+        if x.dims() != self.shape() || x.dtype() != self.dtype {
+            return false;
+        }
+
+        _to_i64_vec(x)
+            .iter()
+            .zip(_to_i64_vec(&self.nvec).iter().zip(_to_i64_vec(&self.start)))
+            .all(|(x, (n, start))| start <= *x && *x < start + n)
+    }
+
+    fn is_flattenable(&self) -> bool {
+        true
+    }
+}
+
+/// Copies a tensor to the host as a flat vector of `i64`.
+fn _to_i64_vec(tensor: &Tensor) -> Vec<i64> {
+    tensor
+        .flatten_all()
+        .and_then(|tensor| tensor.to_dtype(DType::I64))
+        .and_then(|tensor| tensor.to_vec1::<i64>())
+        .expect("Tensor must be convertible to i64")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candle_core::Device;
+
+    fn nvec() -> Tensor {
+        Tensor::new(&[3i64, 5, 2], &Device::Cpu).unwrap()
+    }
+
+    #[test]
+    fn test_multi_discrete_sample_ranges() {
+        let mut space = MultiDiscrete::new(nvec(), None, DType::I64, Some(0));
+
+        for _ in 0..100 {
+            let sample = space.sample(None);
+            assert_eq!(sample.dims(), &[3]);
+            assert!(space.contains(&sample));
+
+            let sample = sample.to_vec1::<i64>().unwrap();
+            assert!((0..3).contains(&sample[0]));
+            assert!((0..5).contains(&sample[1]));
+            assert!((0..2).contains(&sample[2]));
+        }
+    }
+
+    #[test]
+    fn test_multi_discrete_sample_n_matches_repeated_samples() {
+        let space = MultiDiscrete::new(nvec(), None, DType::U8, Some(0));
+
+        let batched = space.clone().sample_n(20, None).unwrap();
+        assert_eq!(batched.dims(), [20, 3]);
+        assert_eq!(batched.dtype(), DType::U8);
+        let mut repeated = space.clone();
+        let samples: Vec<Tensor> = (0..20).map(|_| repeated.sample(None)).collect();
+        assert_eq!(
+            batched.to_vec2::<u8>().unwrap(),
+            Tensor::stack(&samples, 0).unwrap().to_vec2::<u8>().unwrap()
+        );
+
+        let empty = space.clone().sample_n(0, None).unwrap();
+        assert_eq!(empty.dims(), [0, 3]);
+        assert_eq!(empty.dtype(), DType::U8);
+    }
+
+    #[test]
+    fn test_multi_discrete_successive_samples_advance_generator() {
+        let nvec = Tensor::new(&[1000i64, 1000], &Device::Cpu).unwrap();
+        let mut space = MultiDiscrete::new(nvec, None, DType::I64, None);
+        space.seed(Some(42));
+        let (first, second) = (
+            space.sample(None).to_vec1::<i64>().unwrap(),
+            space.sample(None).to_vec1::<i64>().unwrap(),
+        );
+        assert_ne!(first, second);
+
+        space.seed(Some(42));
+        assert_eq!(
+            (
+                space.sample(None).to_vec1::<i64>().unwrap(),
+                space.sample(None).to_vec1::<i64>().unwrap()
+            ),
+            (first, second)
+        );
+    }
+
+    #[test]
+    fn test_multi_discrete_broadcast_start() {
+        let start = Tensor::new(-1i64, &Device::Cpu).unwrap();
+        let mut space = MultiDiscrete::new(nvec(), Some(start), DType::I64, Some(0));
+
+        assert_eq!(space.start().to_vec1::<i64>().unwrap(), vec![-1, -1, -1]);
+        let sample = space.sample(None).to_vec1::<i64>().unwrap();
+        assert!((-1..2).contains(&sample[0]));
+        assert!((-1..4).contains(&sample[1]));
+        assert!((-1..1).contains(&sample[2]));
+    }
+
+    #[test]
+    fn test_multi_discrete_contains() {
+        let space = MultiDiscrete::new(nvec(), None, DType::I64, None);
+        let inside = Tensor::new(&[2i64, 4, 1], &Device::Cpu).unwrap();
+        let outside = Tensor::new(&[2i64, 5, 1], &Device::Cpu).unwrap();
+        let wrong_shape = Tensor::new(&[0i64, 0], &Device::Cpu).unwrap();
+
+        assert!(space.contains(&inside));
+        assert!(!space.contains(&outside));
+        assert!(!space.contains(&wrong_shape));
+    }
+}
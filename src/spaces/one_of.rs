@@ -0,0 +1,192 @@
+//! Implementation of a space that represents a choice between several subspaces.
+
+use rand::Rng;
+
+use super::space::{SampleMask, Space, SpaceEnum, StructuredSample};
+use crate::utils::seeding::{rs_random, Generator};
+
+/// An exclusive tuple (more precisely: the direct sum) of [`SpaceEnum`] instances.
+///
+/// Elements of this space are pairs `(index, value)` where `value` is an element of the subspace at
+/// `index`.
+#[derive(Debug, Clone)]
+pub struct OneOf {
+    spaces: Vec<SpaceEnum>,
+    rs_random: Generator,
+}
+
+impl OneOf {
+    /// Constructor of `OneOf`.
+    ///
+    /// If `seed` is given, the subspaces are seeded with seeds derived from it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `spaces` is empty.
+    pub fn new(spaces: Vec<SpaceEnum>, seed: Option<u64>) -> Self {
+        assert!(!spaces.is_empty(), "Empty `OneOf` spaces are not supported");
+        let (rs_random, _) = rs_random(None);
+        let mut space = Self { spaces, rs_random };
+        if seed.is_some() {
+            space.seed(seed);
+        }
+        space
+    }
+
+    /// Subspaces of this space.
+    pub fn spaces(&self) -> &[SpaceEnum] {
+        &self.spaces
+    }
+}
+
+/// Two `OneOf` spaces are equal if their subspaces are equal, regardless of their generators.
+impl PartialEq for OneOf {
+    fn eq(&self, other: &Self) -> bool {
+        self.spaces == other.spaces
+    }
+}
+
+/// Serializes the subspaces, but not the generator choosing between them.
+#[cfg(feature = "serde")]
+impl serde::Serialize for OneOf {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("OneOf", 1)?;
+        state.serialize_field("spaces", &self.spaces)?;
+        state.end()
+    }
+}
+
+/// Deserializes a space with a generator seeded from OS entropy.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for OneOf {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        struct OneOfRepr {
+            spaces: Vec<SpaceEnum>,
+        }
+
+        let OneOfRepr { spaces } = OneOfRepr::deserialize(deserializer)?;
+        if spaces.is_empty() {
+            return Err(serde::de::Error::custom(
+                "Empty `OneOf` spaces are not supported",
+            ));
+        }
+        Ok(OneOf::new(spaces, None))
+    }
+}
+
+impl Space<(usize, StructuredSample)> for OneOf {
+    /// Generates a single random sample by picking a subspace uniformly and then sampling from it.
+    ///
+    /// A [`SampleMask::Tuple`] gives the mask of each subspace, used if that subspace is picked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mask` is not a `Tuple` mask with one entry per subspace.
+    fn sample(&mut self, mask: Option<&SampleMask>) -> (usize, StructuredSample) {
+        let masks = match mask {
+            None => None,
+            Some(SampleMask::Tuple(masks)) => {
+                assert_eq!(
+                    masks.len(),
+                    self.spaces.len(),
+                    "Expected one mask per subspace"
+                );
+                Some(masks)
+            }
+            Some(mask) => panic!("Expected a Tuple mask, got {mask:?}"),
+        };
+        let index = self.rs_random.gen_range(0..self.spaces.len());
+        let mask = masks.and_then(|masks| masks[index].as_ref());
+        (index, self.spaces[index].sample(mask))
+    }
+
+    /// Seeds the index generator and the subspaces, returning the seed of this space followed by the
+    /// seeds of the subspaces.
+    fn seed(&mut self, seed: Option<u64>) -> Vec<u64> {
+        let (generator, seed) = rs_random(seed);
+        self.rs_random = generator;
+
+        let mut seeds = vec![seed];
+        for space in self.spaces.iter_mut() {
+            seeds.extend(space.seed(Some(self.rs_random.gen())));
+        }
+        seeds
+    }
+
+    fn contains(&self, x: &(usize, StructuredSample)) -> bool {
+        let (index, value) = x;
+        self.spaces
+            .get(*index)
+            .is_some_and(|space| space.contains(value))
+    }
+
+    fn is_flattenable(&self) -> bool {
+        self.spaces.iter().all(|space| space.is_flattenable())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spaces::{Bound, Box, Discrete};
+    use candle_core::{DType, Device};
+
+    fn one_of() -> OneOf {
+        OneOf::new(
+            vec![
+                Discrete::new(3, 0, None).into(),
+                Box::new(
+                    Bound::F64(-1.0),
+                    Bound::F64(1.0),
+                    Some(vec![2]),
+                    DType::F32,
+                    &Device::Cpu,
+                    None,
+                )
+                .unwrap()
+                .into(),
+            ],
+            None,
+        )
+    }
+
+    #[test]
+    fn test_one_of_sample_belongs_to_chosen_subspace() {
+        let mut space = one_of();
+
+        for _ in 0..20 {
+            let (index, value) = space.sample(None);
+            assert!(index < 2);
+            assert!(space.spaces()[index].contains(&value));
+            assert!(space.contains(&(index, value)));
+        }
+        assert!(!space.contains(&(2, StructuredSample::Int(0))));
+        assert!(!space.contains(&(0, StructuredSample::Int(5))));
+    }
+
+    #[test]
+    fn test_one_of_seed_reproduces_samples() {
+        let mut space = one_of();
+        let seeds = space.seed(Some(42));
+        let first: Vec<_> = (0..10).map(|_| space.sample(None)).collect();
+        assert_eq!(space.seed(Some(42)), seeds);
+        let second: Vec<_> = (0..10).map(|_| space.sample(None)).collect();
+
+        assert_eq!(seeds.len(), 3);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_one_of_successive_samples_advance_generator() {
+        let mut space = one_of();
+        space.seed(Some(42));
+        let (first, second) = (space.sample(None), space.sample(None));
+        assert_ne!(first, second);
+
+        space.seed(Some(42));
+        assert_eq!((space.sample(None), space.sample(None)), (first, second));
+    }
+}
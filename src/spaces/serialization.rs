@@ -0,0 +1,55 @@
+//! Helpers to serialize spaces with `serde`, for the candle types that do not implement it.
+//!
+//! They are meant to be used with `#[serde(with = "...")]` on the fields of the serialized
+//! representations of the spaces.
+
+/// Serializes a [`DType`](candle_core::DType) as its name, e.g. `"f32"`.
+pub(super) mod dtype {
+    use std::str::FromStr;
+
+    use candle_core::DType;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(dtype: &DType, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(dtype.as_str())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DType, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        DType::from_str(&name).map_err(D::Error::custom)
+    }
+}
+
+/// Serializes a [`Device`](candle_core::Device) as its location, e.g. `"cpu"` or `"cuda:0"`.
+///
+/// Deserializing a GPU location fails if the device is not available.
+pub(super) mod device {
+    use candle_core::{Device, DeviceLocation};
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(device: &Device, serializer: S) -> Result<S::Ok, S::Error> {
+        match device.location() {
+            DeviceLocation::Cpu => serializer.serialize_str("cpu"),
+            DeviceLocation::Cuda { gpu_id } => serializer.serialize_str(&format!("cuda:{gpu_id}")),
+            DeviceLocation::Metal { gpu_id } => {
+                serializer.serialize_str(&format!("metal:{gpu_id}"))
+            }
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Device, D::Error> {
+        let location = String::deserialize(deserializer)?;
+        let gpu_id = |id: &str| {
+            id.parse::<usize>()
+                .map_err(|_| D::Error::custom(format!("invalid device `{location}`")))
+        };
+        match location.split_once(':') {
+            None if location == "cpu" => Ok(Device::Cpu),
+            Some(("cuda", id)) => Device::new_cuda(gpu_id(id)?).map_err(D::Error::custom),
+            Some(("metal", id)) => Device::new_metal(gpu_id(id)?).map_err(D::Error::custom),
+            _ => Err(D::Error::custom(format!("invalid device `{location}`"))),
+        }
+    }
+}
@@ -0,0 +1,419 @@
+//! Core trait shared by all spaces.
+
+use candle_core::{DType, Device, Tensor};
+use indexmap::IndexMap;
+
+use super::utils::{self, BatchError, FlattenError};
+use super::{Box, BoxError, Dict, Discrete, MultiBinary, MultiDiscrete, OneOf, Tuple};
+
+/// Lower or upper bound of a [`Box`](super::Box) space.
+///
+/// A scalar bound is broadcast to the shape of the space, whereas a tensor bound gives one value per element.
+#[derive(Debug, Clone)]
+pub enum Bound {
+    F64(f64),
+    Tensor(Tensor),
+}
+
+impl Bound {
+    /// Creates a tensor bound on the CPU from the `F32` `values` of the elements of `shape`.
+    ///
+    /// Returns an error if `values` does not have one value per element of `shape`.
+    pub fn from_slice(values: &[f32], shape: &[usize]) -> Result<Self, BoxError> {
+        if values.len() != shape.iter().product::<usize>() {
+            return Err(BoxError::LengthMismatch {
+                len: values.len(),
+                shape: shape.to_vec(),
+            });
+        }
+        let tensor =
+            Tensor::from_slice(values, shape, &Device::Cpu).expect("The values match the shape");
+        Ok(Bound::Tensor(tensor))
+    }
+}
+
+impl From<f32> for Bound {
+    fn from(value: f32) -> Self {
+        Bound::F64(value as f64)
+    }
+}
+
+impl From<f64> for Bound {
+    fn from(value: f64) -> Self {
+        Bound::F64(value)
+    }
+}
+
+/// Creates a 1-D tensor bound on the CPU.
+impl From<Vec<f32>> for Bound {
+    fn from(values: Vec<f32>) -> Self {
+        let len = values.len();
+        Bound::Tensor(Tensor::from_vec(values, len, &Device::Cpu).expect("The length matches"))
+    }
+}
+
+impl From<Tensor> for Bound {
+    fn from(tensor: Tensor) -> Self {
+        Bound::Tensor(tensor)
+    }
+}
+
+/// A mask restricting the elements drawn by [`Space::sample`].
+///
+/// Each space documents the masks it supports. Spaces without masked sampling, such as `Box`,
+/// ignore the mask.
+#[derive(Debug, Clone)]
+pub enum SampleMask {
+    /// Mask of a `Discrete` space: a tensor of shape `(n,)` whose non-zero entries mark the
+    /// elements that can be drawn.
+    Discrete(Tensor),
+    /// Masks of the subspaces of a `Tuple` or `OneOf` space, in order.
+    Tuple(Vec<Option<SampleMask>>),
+    /// Masks of the subspaces of a `Dict` space, by key. Subspaces without a mask are sampled
+    /// without one.
+    Dict(IndexMap<String, SampleMask>),
+}
+
+/// Superclass that is used to define observation and action spaces.
+///
+/// Spaces are crucially used to define the format of valid actions and observations. They serve
+/// various purposes:
+/// - They clearly define how to interact with environments, i.e. they specify what actions need to
+///   look like and what observations will look like.
+/// - They allow us to work with highly structured data (e.g. in the form of elements of `Dict` spaces)
+///   and painlessly transform them into flat tensors that can be used in learning code.
+/// - They provide a method to sample random elements. This is especially useful for exploration and
+///   debugging.
+pub trait Space<T> {
+    /// Randomly samples an element of this space.
+    ///
+    /// `mask` restricts the elements that can be drawn by spaces that support masked sampling,
+    /// and is ignored by spaces that do not.
+    fn sample(&mut self, mask: Option<&SampleMask>) -> T;
+
+    /// Randomly samples `count` elements of this space, stacked along a new first dimension of size
+    /// `count`.
+    ///
+    /// By default, the elements are drawn one at a time with [`sample`](Space::sample). Only tensor
+    /// and integer elements can be stacked, i.e. those of `Box`, `Discrete`, `MultiDiscrete` and
+    /// `MultiBinary` spaces, which return an empty batch with a first dimension of size 0 if `count`
+    /// is 0.
+    ///
+    /// Returns an error if the elements cannot be stacked. The default implementation also
+    /// returns an error if `count` is 0, as the shape of the elements is then unknown.
+    fn sample_n(&mut self, count: usize, mask: Option<&SampleMask>) -> Result<Tensor, BatchError>
+    where
+        T: Into<StructuredSample>,
+    {
+        let samples = (0..count).map(|_| self.sample(mask).into()).collect();
+        _stack(samples)
+    }
+
+    /// Seeds the random number generator of this space and returns the seeds that were used.
+    ///
+    /// If `seed` is `None`, the generator is seeded from OS entropy.
+    fn seed(&mut self, seed: Option<u64>) -> Vec<u64>;
+
+    /// Returns `true` if `x` is a valid member of this space.
+    fn contains(&self, x: &T) -> bool;
+
+    /// Checks whether this space can be flattened to a `Box`.
+    fn is_flattenable(&self) -> bool;
+
+    /// Flattens the element `x` of this space into a 1-D tensor, as [`utils::flatten`] does.
+    ///
+    /// Returns an error if this space cannot be flattened or `x` does not match its structure.
+    fn flatten(&self, x: &T) -> Result<Tensor, FlattenError>
+    where
+        Self: Clone + Into<SpaceEnum>,
+        T: Clone + Into<StructuredSample>,
+    {
+        utils::flatten(&self.clone().into(), &x.clone().into())
+    }
+
+    /// Turns the 1-D tensor `x` back into an element of this space, as [`utils::unflatten`] does.
+    ///
+    /// Returns an error if this space cannot be flattened or `x` does not have the length of its
+    /// flattened form.
+    fn unflatten(&self, x: &Tensor) -> Result<T, FlattenError>
+    where
+        Self: Clone + Into<SpaceEnum>,
+        StructuredSample: TryInto<T>,
+    {
+        let sample = utils::unflatten(&self.clone().into(), x)?;
+        let Ok(sample) = sample.try_into() else {
+            unreachable!("Unflattening returns an element of the space");
+        };
+        Ok(sample)
+    }
+}
+
+/// Any of the concrete spaces, used as children of container spaces.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpaceEnum {
+    Box(Box),
+    Discrete(Discrete),
+    MultiDiscrete(MultiDiscrete),
+    MultiBinary(MultiBinary),
+    Tuple(Tuple),
+    Dict(Dict),
+    OneOf(OneOf),
+}
+
+/// An element of a [`SpaceEnum`].
+#[derive(Debug, Clone)]
+pub enum StructuredSample {
+    /// Element of a `Box`, `MultiDiscrete` or `MultiBinary` space.
+    Tensor(Tensor),
+    /// Element of a `Discrete` space.
+    Int(i64),
+    /// Element of a `Tuple` space.
+    Tuple(Vec<StructuredSample>),
+    /// Element of a `Dict` space.
+    Dict(IndexMap<String, StructuredSample>),
+    /// Element of a `OneOf` space: the index of the chosen subspace and its value.
+    OneOf(usize, std::boxed::Box<StructuredSample>),
+}
+
+impl PartialEq for StructuredSample {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Tensor(a), Self::Tensor(b)) => tensor_eq(a, b),
+            (Self::Int(a), Self::Int(b)) => a == b,
+            (Self::Tuple(a), Self::Tuple(b)) => a == b,
+            (Self::Dict(a), Self::Dict(b)) => a == b,
+            (Self::OneOf(i, a), Self::OneOf(j, b)) => i == j && a == b,
+            _ => false,
+        }
+    }
+}
+
+impl From<Tensor> for StructuredSample {
+    fn from(x: Tensor) -> Self {
+        Self::Tensor(x)
+    }
+}
+
+impl From<i64> for StructuredSample {
+    fn from(x: i64) -> Self {
+        Self::Int(x)
+    }
+}
+
+/// Returns the sample back if it is not a `Tensor`.
+impl TryFrom<StructuredSample> for Tensor {
+    type Error = StructuredSample;
+
+    fn try_from(x: StructuredSample) -> Result<Self, Self::Error> {
+        match x {
+            StructuredSample::Tensor(x) => Ok(x),
+            x => Err(x),
+        }
+    }
+}
+
+/// Returns the sample back if it is not an `Int`.
+impl TryFrom<StructuredSample> for i64 {
+    type Error = StructuredSample;
+
+    fn try_from(x: StructuredSample) -> Result<Self, Self::Error> {
+        match x {
+            StructuredSample::Int(x) => Ok(x),
+            x => Err(x),
+        }
+    }
+}
+
+/// Returns the sample back if it is not a `Tuple`.
+impl TryFrom<StructuredSample> for Vec<StructuredSample> {
+    type Error = StructuredSample;
+
+    fn try_from(x: StructuredSample) -> Result<Self, Self::Error> {
+        match x {
+            StructuredSample::Tuple(x) => Ok(x),
+            x => Err(x),
+        }
+    }
+}
+
+/// Returns the sample back if it is not a `Dict`.
+impl TryFrom<StructuredSample> for IndexMap<String, StructuredSample> {
+    type Error = StructuredSample;
+
+    fn try_from(x: StructuredSample) -> Result<Self, Self::Error> {
+        match x {
+            StructuredSample::Dict(x) => Ok(x),
+            x => Err(x),
+        }
+    }
+}
+
+/// Returns the sample back if it is not a `OneOf`.
+impl TryFrom<StructuredSample> for (usize, StructuredSample) {
+    type Error = StructuredSample;
+
+    fn try_from(x: StructuredSample) -> Result<Self, Self::Error> {
+        match x {
+            StructuredSample::OneOf(index, x) => Ok((index, *x)),
+            x => Err(x),
+        }
+    }
+}
+
+/// Compares two tensors by shape, dtype and contents.
+pub(crate) fn tensor_eq(a: &Tensor, b: &Tensor) -> bool {
+    let to_vec = |tensor: &Tensor| {
+        tensor
+            .flatten_all()
+            .and_then(|tensor| tensor.to_dtype(DType::F64))
+            .and_then(|tensor| tensor.to_vec1::<f64>())
+    };
+
+    a.dims() == b.dims()
+        && a.dtype() == b.dtype()
+        && matches!((to_vec(a), to_vec(b)), (Ok(a), Ok(b)) if a == b)
+}
+
+/// Stacks tensor or integer samples along a new first dimension.
+fn _stack(samples: Vec<StructuredSample>) -> Result<Tensor, BatchError> {
+    match samples.first() {
+        None => Err(BatchError::EmptyBatch),
+        Some(StructuredSample::Tensor(_)) => {
+            let tensors: Vec<Tensor> = samples
+                .into_iter()
+                .map(|sample| Tensor::try_from(sample).expect("Samples have the same type"))
+                .collect();
+            Ok(Tensor::stack(&tensors, 0)?)
+        }
+        Some(StructuredSample::Int(_)) => {
+            let values: Vec<i64> = samples
+                .into_iter()
+                .map(|sample| i64::try_from(sample).expect("Samples have the same type"))
+                .collect();
+            Ok(Tensor::new(values, &candle_core::Device::Cpu)?)
+        }
+        Some(_) => Err(BatchError::NotStackable("structured")),
+    }
+}
+
+impl Space<StructuredSample> for SpaceEnum {
+    fn sample(&mut self, mask: Option<&SampleMask>) -> StructuredSample {
+        match self {
+            Self::Box(space) => StructuredSample::Tensor(space.sample(mask)),
+            Self::Discrete(space) => StructuredSample::Int(space.sample(mask)),
+            Self::MultiDiscrete(space) => StructuredSample::Tensor(space.sample(mask)),
+            Self::MultiBinary(space) => StructuredSample::Tensor(space.sample(mask)),
+            Self::Tuple(space) => StructuredSample::Tuple(space.sample(mask)),
+            Self::Dict(space) => StructuredSample::Dict(space.sample(mask)),
+            Self::OneOf(space) => {
+                let (index, value) = space.sample(mask);
+                StructuredSample::OneOf(index, std::boxed::Box::new(value))
+            }
+        }
+    }
+
+    /// Returns an error for `Tuple`, `Dict` and `OneOf` spaces, whose elements cannot be stacked,
+    /// whatever `count`.
+    fn sample_n(&mut self, count: usize, mask: Option<&SampleMask>) -> Result<Tensor, BatchError> {
+        match self {
+            Self::Box(space) => space.sample_n(count, mask),
+            Self::Discrete(space) => space.sample_n(count, mask),
+            Self::MultiDiscrete(space) => space.sample_n(count, mask),
+            Self::MultiBinary(space) => space.sample_n(count, mask),
+            Self::Tuple(_) => Err(BatchError::NotStackable("Tuple")),
+            Self::Dict(_) => Err(BatchError::NotStackable("Dict")),
+            Self::OneOf(_) => Err(BatchError::NotStackable("OneOf")),
+        }
+    }
+
+    fn seed(&mut self, seed: Option<u64>) -> Vec<u64> {
+        match self {
+            Self::Box(space) => space.seed(seed),
+            Self::Discrete(space) => space.seed(seed),
+            Self::MultiDiscrete(space) => space.seed(seed),
+            Self::MultiBinary(space) => space.seed(seed),
+            Self::Tuple(space) => space.seed(seed),
+            Self::Dict(space) => space.seed(seed),
+            Self::OneOf(space) => space.seed(seed),
+        }
+    }
+
+    fn contains(&self, x: &StructuredSample) -> bool {
+        match (self, x) {
+            (Self::Box(space), StructuredSample::Tensor(x)) => space.contains(x),
+            (Self::Discrete(space), StructuredSample::Int(x)) => space.contains(x),
+            (Self::MultiDiscrete(space), StructuredSample::Tensor(x)) => space.contains(x),
+            (Self::MultiBinary(space), StructuredSample::Tensor(x)) => space.contains(x),
+            (Self::Tuple(space), StructuredSample::Tuple(x)) => space.contains(x),
+            (Self::Dict(space), StructuredSample::Dict(x)) => space.contains(x),
+            (Self::OneOf(space), StructuredSample::OneOf(index, x)) => space
+                .spaces()
+                .get(*index)
+                .is_some_and(|space| space.contains(x)),
+            _ => false,
+        }
+    }
+
+    fn is_flattenable(&self) -> bool {
+        match self {
+            Self::Box(space) => space.is_flattenable(),
+            Self::Discrete(space) => space.is_flattenable(),
+            Self::MultiDiscrete(space) => space.is_flattenable(),
+            Self::MultiBinary(space) => space.is_flattenable(),
+            Self::Tuple(space) => space.is_flattenable(),
+            Self::Dict(space) => space.is_flattenable(),
+            Self::OneOf(space) => space.is_flattenable(),
+        }
+    }
+
+    fn flatten(&self, x: &StructuredSample) -> Result<Tensor, FlattenError> {
+        utils::flatten(self, x)
+    }
+
+    fn unflatten(&self, x: &Tensor) -> Result<StructuredSample, FlattenError> {
+        utils::unflatten(self, x)
+    }
+}
+
+impl From<Box> for SpaceEnum {
+    fn from(space: Box) -> Self {
+        Self::Box(space)
+    }
+}
+
+impl From<Discrete> for SpaceEnum {
+    fn from(space: Discrete) -> Self {
+        Self::Discrete(space)
+    }
+}
+
+impl From<MultiDiscrete> for SpaceEnum {
+    fn from(space: MultiDiscrete) -> Self {
+        Self::MultiDiscrete(space)
+    }
+}
+
+impl From<MultiBinary> for SpaceEnum {
+    fn from(space: MultiBinary) -> Self {
+        Self::MultiBinary(space)
+    }
+}
+
+impl From<Tuple> for SpaceEnum {
+    fn from(space: Tuple) -> Self {
+        Self::Tuple(space)
+    }
+}
+
+impl From<Dict> for SpaceEnum {
+    fn from(space: Dict) -> Self {
+        Self::Dict(space)
+    }
+}
+
+impl From<OneOf> for SpaceEnum {
+    fn from(space: OneOf) -> Self {
+        Self::OneOf(space)
+    }
+}
@@ -0,0 +1,202 @@
+//! Implementation of a space that represents the cartesian product of other spaces.
+
+use rand::Rng;
+
+use super::space::{SampleMask, Space, SpaceEnum, StructuredSample};
+use crate::utils::seeding::rs_random;
+
+/// A tuple (more precisely: the cartesian product) of [`SpaceEnum`] instances.
+///
+/// Elements of this space are vectors of elements of the constituent spaces, in order.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tuple {
+    spaces: Vec<SpaceEnum>,
+}
+
+impl Tuple {
+    /// Constructor of `Tuple`.
+    ///
+    /// If `seed` is given, the subspaces are seeded with seeds derived from it.
+    pub fn new(spaces: Vec<SpaceEnum>, seed: Option<u64>) -> Self {
+        let mut space = Self { spaces };
+        if seed.is_some() {
+            space.seed(seed);
+        }
+        space
+    }
+
+    /// Subspaces of this space.
+    pub fn spaces(&self) -> &[SpaceEnum] {
+        &self.spaces
+    }
+}
+
+impl Space<Vec<StructuredSample>> for Tuple {
+    /// Generates a single random sample by sampling each subspace in order.
+    ///
+    /// A [`SampleMask::Tuple`] gives the mask of each subspace.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mask` is not a `Tuple` mask with one entry per subspace.
+    fn sample(&mut self, mask: Option<&SampleMask>) -> Vec<StructuredSample> {
+        match mask {
+            None => self
+                .spaces
+                .iter_mut()
+                .map(|space| space.sample(None))
+                .collect(),
+            Some(SampleMask::Tuple(masks)) => {
+                assert_eq!(
+                    masks.len(),
+                    self.spaces.len(),
+                    "Expected one mask per subspace"
+                );
+                self.spaces
+                    .iter_mut()
+                    .zip(masks)
+                    .map(|(space, mask)| space.sample(mask.as_ref()))
+                    .collect()
+            }
+            Some(mask) => panic!("Expected a Tuple mask, got {mask:?}"),
+        }
+    }
+
+    /// Seeds the subspaces and returns their seeds in order.
+    ///
+    /// An integer seed is used to deterministically derive one sub-seed per subspace, while `None`
+    /// seeds every subspace from OS entropy.
+    fn seed(&mut self, seed: Option<u64>) -> Vec<u64> {
+        match seed {
+            Some(seed) => {
+                let (mut rng, _) = rs_random(Some(seed));
+                self.spaces
+                    .iter_mut()
+                    .flat_map(|space| space.seed(Some(rng.gen())))
+                    .collect()
+            }
+            None => self
+                .spaces
+                .iter_mut()
+                .flat_map(|space| space.seed(None))
+                .collect(),
+        }
+    }
+
+    fn contains(&self, x: &Vec<StructuredSample>) -> bool {
+        x.len() == self.spaces.len()
+            && self
+                .spaces
+                .iter()
+                .zip(x)
+                .all(|(space, x)| space.contains(x))
+    }
+
+    fn is_flattenable(&self) -> bool {
+        self.spaces.iter().all(|space| space.is_flattenable())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spaces::{Bound, Box, Discrete};
+    use candle_core::{DType, Device, Tensor};
+
+    fn tuple() -> Tuple {
+        Tuple::new(
+            vec![
+                Box::new(
+                    Bound::F64(0.0),
+                    Bound::F64(1.0),
+                    Some(vec![2]),
+                    DType::F32,
+                    &Device::Cpu,
+                    None,
+                )
+                .unwrap()
+                .into(),
+                Discrete::new(3, 0, None).into(),
+            ],
+            None,
+        )
+    }
+
+    #[test]
+    fn test_tuple_sample_contains() {
+        let mut space = tuple();
+        let sample = space.sample(None);
+
+        assert_eq!(sample.len(), 2);
+        assert!(matches!(sample[0], StructuredSample::Tensor(_)));
+        assert!(matches!(sample[1], StructuredSample::Int(_)));
+        assert!(space.contains(&sample));
+        assert!(!space.contains(&sample[..1].to_vec()));
+        assert!(!space.contains(&vec![sample[1].clone(), sample[0].clone()]));
+    }
+
+    #[test]
+    fn test_tuple_seed_reproduces_samples() {
+        let mut space = tuple();
+        let seeds = space.seed(Some(42));
+        let first = space.sample(None);
+        assert_eq!(space.seed(Some(42)), seeds);
+        let second = space.sample(None);
+
+        assert_eq!(seeds.len(), 2);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_tuple_sample_n_errors() {
+        let mut space = SpaceEnum::from(tuple());
+        for count in [0, 3] {
+            let err = space.sample_n(count, None).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "elements of Tuple spaces cannot be stacked"
+            );
+        }
+    }
+
+    #[test]
+    fn test_tuple_successive_samples_advance_generator() {
+        let mut space = tuple();
+        space.seed(Some(42));
+        let (first, second) = (space.sample(None), space.sample(None));
+        assert_ne!(first, second);
+
+        space.seed(Some(42));
+        assert_eq!((space.sample(None), space.sample(None)), (first, second));
+    }
+
+    #[test]
+    fn test_tuple_equality_is_recursive() {
+        let mut a = tuple();
+        a.seed(Some(0));
+        assert_eq!(a, tuple());
+
+        let b = Tuple::new(vec![Discrete::new(3, 0, None).into()], None);
+        let c = Tuple::new(vec![Discrete::new(4, 0, None).into()], None);
+        assert_ne!(a, b);
+        assert_ne!(b, c);
+    }
+
+    #[test]
+    fn test_tuple_masked_sample() {
+        let mut space = tuple();
+        let mask = SampleMask::Tuple(vec![
+            None,
+            Some(SampleMask::Discrete(
+                Tensor::new(&[0u8, 1, 0], &Device::Cpu).unwrap(),
+            )),
+        ]);
+
+        for _ in 0..20 {
+            let sample = space.sample(Some(&mask));
+            assert!(space.contains(&sample));
+            assert_eq!(sample[1], StructuredSample::Int(1));
+        }
+    }
+}
@@ -0,0 +1,1314 @@
+//! Implementation of utility functions that can be applied to spaces.
+
+use candle_core::{DType, Device, Tensor};
+use indexmap::IndexMap;
+use thiserror::Error;
+
+use super::space::{Bound, Space, SpaceEnum, StructuredSample};
+use super::{Box, Dict, MultiDiscrete, Tuple};
+
+/// Error returned when a space or one of its elements cannot be (un)flattened.
+#[derive(Debug, Error)]
+pub enum FlattenError {
+    #[error("{0} space cannot be flattened")]
+    NotFlattenable(&'static str),
+    #[error("sample does not match the structure of the {0} space")]
+    SampleMismatch(&'static str),
+    #[error("{value} is not an element of the Discrete space {{{start}, ..., {}}}", start + *n as i64 - 1)]
+    OutOfRange { value: i64, start: i64, n: usize },
+    #[error("expected a 1-D tensor of {expected} elements to unflatten, got shape {actual:?}")]
+    LengthMismatch { expected: usize, actual: Vec<usize> },
+    #[error(
+        "sample of shape {actual:?} does not match the shape {expected:?} of the {space} space"
+    )]
+    ShapeMismatch {
+        space: &'static str,
+        expected: Vec<usize>,
+        actual: Vec<usize>,
+    },
+    #[error(transparent)]
+    Tensor(#[from] candle_core::Error),
+}
+
+/// Error returned when a space or its elements cannot be batched for vectorized environments.
+#[derive(Debug, Error)]
+pub enum BatchError {
+    #[error("{0} space cannot be batched")]
+    Unsupported(&'static str),
+    #[error("sample does not match the structure of the {0} space")]
+    SampleMismatch(&'static str),
+    #[error("elements of {0} spaces cannot be stacked")]
+    NotStackable(&'static str),
+    #[error("an empty batch cannot be stacked without the shape of its elements")]
+    EmptyBatch,
+    #[error("at least one environment is required")]
+    NoEnvs,
+    #[error("{space} space of sub-environment {index} does not match the given one")]
+    SpaceMismatch { index: usize, space: &'static str },
+    #[error(transparent)]
+    Tensor(#[from] candle_core::Error),
+}
+
+/// Error returned when elements of a space cannot be converted to or from JSON.
+#[cfg(feature = "serde")]
+#[derive(Debug, Error)]
+pub enum JsonableError {
+    #[error("sample does not match the structure of the {0} space")]
+    SampleMismatch(&'static str),
+    #[error("JSON value does not match the structure of the {0} space")]
+    ValueMismatch(&'static str),
+    #[error(transparent)]
+    Tensor(#[from] candle_core::Error),
+}
+
+/// Flattens an element of a space into a 1-D tensor.
+///
+/// - `Box` and `MultiBinary` elements are flattened in row-major order.
+/// - `Discrete` elements are one-hot encoded.
+/// - `MultiDiscrete` elements are the concatenation of the one-hot encodings of each component.
+/// - `Tuple` and `Dict` elements are the concatenation of their flattened children, in order.
+/// - `OneOf` elements are the index of the chosen subspace followed by its flattened value, padded
+///   with zeros to the largest flattened subspace.
+///
+/// Children of container spaces are cast to a common dtype before being concatenated.
+///
+/// Returns an error if `x` does not match the structure of `space`, or if a tensor sample does not
+/// have the shape of its space.
+pub fn flatten(space: &SpaceEnum, x: &StructuredSample) -> Result<Tensor, FlattenError> {
+    match (space, x) {
+        (SpaceEnum::Box(space), StructuredSample::Tensor(x)) => {
+            _check_shape("Box", space.shape(), x)?;
+            Ok(x.flatten_all()?)
+        }
+        (SpaceEnum::MultiBinary(space), StructuredSample::Tensor(x)) => {
+            _check_shape("MultiBinary", space.shape(), x)?;
+            Ok(x.flatten_all()?)
+        }
+        (SpaceEnum::Discrete(space), StructuredSample::Int(x)) => {
+            _one_hot(*x, space.start(), space.n(), DType::I64)
+        }
+        (SpaceEnum::MultiDiscrete(space), StructuredSample::Tensor(x)) => {
+            _check_shape("MultiDiscrete", space.shape(), x)?;
+            let x = _to_i64_vec(x)?;
+            let nvec = _to_i64_vec(space.nvec())?;
+            let start = _to_i64_vec(space.start())?;
+            let one_hots = x
+                .iter()
+                .zip(nvec.iter().zip(start.iter()))
+                .map(|(x, (n, start))| _one_hot(*x, *start, *n as usize, space.dtype()))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Tensor::cat(&one_hots, 0)?)
+        }
+        (SpaceEnum::Tuple(space), StructuredSample::Tuple(x))
+            if x.len() == space.spaces().len() =>
+        {
+            let flattened = space
+                .spaces()
+                .iter()
+                .zip(x)
+                .map(|(space, x)| flatten(space, x))
+                .collect::<Result<Vec<_>, _>>()?;
+            _concatenate(&flattened)
+        }
+        (SpaceEnum::Dict(space), StructuredSample::Dict(x)) if x.len() == space.spaces().len() => {
+            let flattened = space
+                .spaces()
+                .iter()
+                .map(|(key, space)| match x.get(key) {
+                    Some(x) => flatten(space, x),
+                    None => Err(FlattenError::SampleMismatch("Dict")),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            _concatenate(&flattened)
+        }
+        (SpaceEnum::OneOf(space), StructuredSample::OneOf(index, x)) => {
+            let subspace = space
+                .spaces()
+                .get(*index)
+                .ok_or(FlattenError::SampleMismatch("OneOf"))?;
+            let flattened = flatten(subspace, x)?;
+            let width = space
+                .spaces()
+                .iter()
+                .map(flatdim)
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .max()
+                .unwrap_or(0);
+            let dtype = flattened.dtype();
+            let index = Tensor::new(&[*index as i64], flattened.device())?.to_dtype(dtype)?;
+            let padding = Tensor::zeros(width - flattened.elem_count(), dtype, flattened.device())?;
+            Ok(Tensor::cat(&[index, flattened, padding], 0)?)
+        }
+        (space, _) => Err(FlattenError::SampleMismatch(_space_name(space))),
+    }
+}
+
+/// Reverses [`flatten`], turning a 1-D tensor back into an element of `space`.
+///
+/// - `Box` and `MultiBinary` elements are reshaped to the shape of the space and cast to its dtype.
+/// - `Discrete` elements are recovered with an argmax over the one-hot encoding.
+/// - `MultiDiscrete`, `Tuple`, `Dict` and `OneOf` elements are reconstructed recursively.
+///
+/// `x` must contain exactly as many elements as the flattened form of `space`.
+pub fn unflatten(space: &SpaceEnum, x: &Tensor) -> Result<StructuredSample, FlattenError> {
+    let expected = flatdim(space)?;
+    if x.dims() != [expected] {
+        return Err(FlattenError::LengthMismatch {
+            expected,
+            actual: x.dims().to_vec(),
+        });
+    }
+
+    match space {
+        SpaceEnum::Box(space) => Ok(StructuredSample::Tensor(
+            x.reshape(space.shape())?.to_dtype(space.dtype())?,
+        )),
+        SpaceEnum::MultiBinary(space) => Ok(StructuredSample::Tensor(
+            x.reshape(space.shape())?.to_dtype(space.dtype())?,
+        )),
+        SpaceEnum::Discrete(space) => {
+            let index = x.argmax(0)?.to_dtype(DType::I64)?.to_scalar::<i64>()?;
+            Ok(StructuredSample::Int(space.start() + index))
+        }
+        SpaceEnum::MultiDiscrete(space) => {
+            let nvec = _to_i64_vec(space.nvec())?;
+            let start = _to_i64_vec(space.start())?;
+            let mut offset = 0;
+            let mut values = Vec::with_capacity(nvec.len());
+            for (n, start) in nvec.iter().zip(start.iter()) {
+                let one_hot = x.narrow(0, offset, *n as usize)?;
+                values.push(
+                    start
+                        + one_hot
+                            .argmax(0)?
+                            .to_dtype(DType::I64)?
+                            .to_scalar::<i64>()?,
+                );
+                offset += *n as usize;
+            }
+            Ok(StructuredSample::Tensor(
+                Tensor::from_vec(values, space.shape(), x.device())?.to_dtype(space.dtype())?,
+            ))
+        }
+        SpaceEnum::Tuple(space) => {
+            let mut offset = 0;
+            let mut values = Vec::with_capacity(space.spaces().len());
+            for space in space.spaces() {
+                let dim = flatdim(space)?;
+                values.push(unflatten(space, &x.narrow(0, offset, dim)?)?);
+                offset += dim;
+            }
+            Ok(StructuredSample::Tuple(values))
+        }
+        SpaceEnum::Dict(space) => {
+            let mut offset = 0;
+            let mut values = IndexMap::with_capacity(space.spaces().len());
+            for (key, space) in space.spaces() {
+                let dim = flatdim(space)?;
+                values.insert(key.clone(), unflatten(space, &x.narrow(0, offset, dim)?)?);
+                offset += dim;
+            }
+            Ok(StructuredSample::Dict(values))
+        }
+        SpaceEnum::OneOf(space) => {
+            let index = x.get(0)?.to_dtype(DType::I64)?.to_scalar::<i64>()?;
+            let subspace = usize::try_from(index)
+                .ok()
+                .and_then(|index| space.spaces().get(index))
+                .ok_or(FlattenError::SampleMismatch("OneOf"))?;
+            let value = unflatten(subspace, &x.narrow(0, 1, flatdim(subspace)?)?)?;
+            Ok(StructuredSample::OneOf(
+                index as usize,
+                std::boxed::Box::new(value),
+            ))
+        }
+    }
+}
+
+/// Flattens a space into an equivalent 1-D `Box`.
+///
+/// The bounds are such that every element of `space` is flattened by [`flatten`] into an element of
+/// the returned box, whose shape is `[flatdim(space)]`:
+/// - `Box` bounds are flattened in row-major order.
+/// - `Discrete`, `MultiDiscrete` and `MultiBinary` become `[0, 1]` boxes over their one-hot encodings.
+/// - `Tuple` and `Dict` concatenate the bounds of their flattened children.
+/// - `OneOf` bounds the index by the number of subspaces and every other dimension by the overall
+///   extremes of its subspaces.
+pub fn flatten_space(space: &SpaceEnum) -> Result<Box, FlattenError> {
+    let (low, high) = match space {
+        SpaceEnum::Box(space) => (space.low().flatten_all()?, space.high().flatten_all()?),
+        SpaceEnum::Discrete(_) | SpaceEnum::MultiDiscrete(_) | SpaceEnum::MultiBinary(_) => {
+            let dtype = match space {
+                SpaceEnum::MultiDiscrete(space) => space.dtype(),
+                SpaceEnum::MultiBinary(space) => space.dtype(),
+                _ => DType::I64,
+            };
+            let n = flatdim(space)?;
+            (
+                Tensor::zeros(n, dtype, &Device::Cpu)?,
+                Tensor::ones(n, dtype, &Device::Cpu)?,
+            )
+        }
+        SpaceEnum::Tuple(space) => _concatenate_bounds(space.spaces().iter())?,
+        SpaceEnum::Dict(space) => _concatenate_bounds(space.spaces().values())?,
+        SpaceEnum::OneOf(space) => {
+            let flattened = space
+                .spaces()
+                .iter()
+                .map(flatten_space)
+                .collect::<Result<Vec<_>, _>>()?;
+            let dtype = _promote(flattened.iter().map(|space| space.dtype()));
+            let mut low = 0f64;
+            let mut high = 0f64;
+            for space in flattened.iter() {
+                low = low.min(
+                    space
+                        .low()
+                        .to_dtype(DType::F64)?
+                        .min_all()?
+                        .to_scalar::<f64>()?,
+                );
+                high = high.max(
+                    space
+                        .high()
+                        .to_dtype(DType::F64)?
+                        .max_all()?
+                        .to_scalar::<f64>()?,
+                );
+            }
+
+            let width = flatdim(&SpaceEnum::OneOf(space.clone()))? - 1;
+            let mut lows = vec![0f64];
+            let mut highs = vec![(space.spaces().len() - 1) as f64];
+            lows.extend(std::iter::repeat_n(low, width));
+            highs.extend(std::iter::repeat_n(high, width));
+            (
+                Tensor::new(lows, &Device::Cpu)?.to_dtype(dtype)?,
+                Tensor::new(highs, &Device::Cpu)?.to_dtype(dtype)?,
+            )
+        }
+    };
+
+    let dtype = low.dtype();
+    let device = low.device().clone();
+    Ok(Box::new(
+        Bound::Tensor(low),
+        Bound::Tensor(high),
+        None,
+        dtype,
+        &device,
+        None,
+    )
+    .expect("The bounds are tensors"))
+}
+
+/// Concatenates the flattened bounds of `spaces` after casting them to a common dtype.
+fn _concatenate_bounds<'a>(
+    spaces: impl Iterator<Item = &'a SpaceEnum>,
+) -> Result<(Tensor, Tensor), FlattenError> {
+    let flattened = spaces.map(flatten_space).collect::<Result<Vec<_>, _>>()?;
+    let lows: Vec<Tensor> = flattened.iter().map(|space| space.low().clone()).collect();
+    let highs: Vec<Tensor> = flattened.iter().map(|space| space.high().clone()).collect();
+    Ok((_concatenate(&lows)?, _concatenate(&highs)?))
+}
+
+/// Creates a space that represents `n` copies of `space`, as used by vectorized environments.
+///
+/// - `Box` bounds gain a leading dimension of size `n`.
+/// - `Discrete` becomes a `MultiDiscrete` of length `n`.
+/// - `MultiDiscrete` and `MultiBinary` become integer `Box`es with a leading dimension of size `n`.
+/// - `Tuple` and `Dict` batch their children recursively.
+///
+/// The batched space is seeded from OS entropy. `OneOf` spaces cannot be batched.
+pub fn batch_space(space: &SpaceEnum, n: usize) -> Result<SpaceEnum, BatchError> {
+    let batched_box = |low: Tensor, high: Tensor, shape: &[usize], dtype: DType| {
+        let device = low.device().clone();
+        let shape = [&[n], shape].concat();
+        Ok::<_, BatchError>(SpaceEnum::Box(
+            Box::new(
+                Bound::Tensor(low.unsqueeze(0)?),
+                Bound::Tensor(high.unsqueeze(0)?),
+                Some(shape),
+                dtype,
+                &device,
+                None,
+            )
+            .expect("The bounds are tensors"),
+        ))
+    };
+
+    match space {
+        SpaceEnum::Box(space) => batched_box(
+            space.low().clone(),
+            space.high().clone(),
+            space.shape(),
+            space.dtype(),
+        ),
+        SpaceEnum::Discrete(space) => {
+            let nvec = Tensor::full(space.n() as i64, n, &Device::Cpu)?;
+            let start = Tensor::full(space.start(), n, &Device::Cpu)?;
+            Ok(SpaceEnum::MultiDiscrete(MultiDiscrete::new(
+                nvec,
+                Some(start),
+                DType::I64,
+                None,
+            )))
+        }
+        SpaceEnum::MultiDiscrete(space) => {
+            let high = (space.start() + space.nvec())?.affine(1.0, -1.0)?;
+            batched_box(space.start().clone(), high, space.shape(), space.dtype())
+        }
+        SpaceEnum::MultiBinary(space) => {
+            let low = Tensor::zeros(space.shape(), space.dtype(), &Device::Cpu)?;
+            let high = Tensor::ones(space.shape(), space.dtype(), &Device::Cpu)?;
+            batched_box(low, high, space.shape(), space.dtype())
+        }
+        SpaceEnum::Tuple(space) => Ok(SpaceEnum::Tuple(Tuple::new(
+            space
+                .spaces()
+                .iter()
+                .map(|space| batch_space(space, n))
+                .collect::<Result<Vec<_>, _>>()?,
+            None,
+        ))),
+        SpaceEnum::Dict(space) => Ok(SpaceEnum::Dict(Dict::new(
+            space
+                .spaces()
+                .iter()
+                .map(|(key, space)| Ok((key.clone(), batch_space(space, n)?)))
+                .collect::<Result<Vec<_>, BatchError>>()?,
+            None,
+        ))),
+        SpaceEnum::OneOf(_) => Err(BatchError::Unsupported("OneOf")),
+    }
+}
+
+/// Allocates a buffer able to hold `n` elements of `space`, with a leading batch dimension.
+///
+/// Only `Box` and `Discrete` spaces are supported. The buffer is zero-initialized.
+pub fn create_empty_array(space: &SpaceEnum, n: usize) -> Result<Tensor, BatchError> {
+    match space {
+        SpaceEnum::Box(space) => Ok(Tensor::zeros(
+            [&[n], space.shape()].concat(),
+            space.dtype(),
+            space.low().device(),
+        )?),
+        SpaceEnum::Discrete(_) => Ok(Tensor::zeros(n, DType::I64, &Device::Cpu)?),
+        space => Err(BatchError::Unsupported(_space_name(space))),
+    }
+}
+
+/// Writes `items`, one element of `space` per environment, into the batched buffer `out`.
+///
+/// `out` is typically created with [`create_empty_array`] and is updated in place, so that repeated
+/// calls do not reallocate. Only `Box` and `Discrete` spaces are supported.
+pub fn concatenate(
+    space: &SpaceEnum,
+    items: &[StructuredSample],
+    out: &Tensor,
+) -> Result<(), BatchError> {
+    for (i, item) in items.iter().enumerate() {
+        let item = match (space, item) {
+            (SpaceEnum::Box(_), StructuredSample::Tensor(x)) => x.to_dtype(out.dtype())?,
+            (SpaceEnum::Discrete(_), StructuredSample::Int(x)) => {
+                Tensor::new(*x, out.device())?.to_dtype(out.dtype())?
+            }
+            (SpaceEnum::Box(_) | SpaceEnum::Discrete(_), _) => {
+                return Err(BatchError::SampleMismatch(_space_name(space)))
+            }
+            (space, _) => return Err(BatchError::Unsupported(_space_name(space))),
+        };
+        out.slice_set(&item.unsqueeze(0)?.contiguous()?, 0, i)?;
+    }
+    Ok(())
+}
+
+/// Iterates over the per-environment elements of `batched`, a batch of elements of `space`.
+///
+/// This is the inverse of [`concatenate`]: `Box` and `Discrete` batches are split along their leading
+/// dimension, while `Tuple` and `Dict` batches are split child by child and zipped back together.
+pub fn iterate(
+    space: &SpaceEnum,
+    batched: &StructuredSample,
+) -> Result<impl Iterator<Item = StructuredSample>, BatchError> {
+    Ok(_unbatch(space, batched)?.into_iter())
+}
+
+/// Splits a batch of elements of `space` into one element per environment.
+fn _unbatch(
+    space: &SpaceEnum,
+    batched: &StructuredSample,
+) -> Result<Vec<StructuredSample>, BatchError> {
+    match (space, batched) {
+        (SpaceEnum::Box(space), StructuredSample::Tensor(x)) => {
+            (0..x.dims().first().copied().unwrap_or(0))
+                .map(|i| Ok(StructuredSample::Tensor(x.get(i)?.to_dtype(space.dtype())?)))
+                .collect()
+        }
+        (SpaceEnum::Discrete(_), StructuredSample::Tensor(x)) => Ok(x
+            .flatten_all()?
+            .to_dtype(DType::I64)?
+            .to_vec1::<i64>()?
+            .into_iter()
+            .map(StructuredSample::Int)
+            .collect()),
+        (SpaceEnum::Tuple(space), StructuredSample::Tuple(x))
+            if x.len() == space.spaces().len() =>
+        {
+            let children = space
+                .spaces()
+                .iter()
+                .zip(x)
+                .map(|(space, x)| _unbatch(space, x))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(_transpose(children)?
+                .into_iter()
+                .map(StructuredSample::Tuple)
+                .collect())
+        }
+        (SpaceEnum::Dict(space), StructuredSample::Dict(x)) if x.len() == space.spaces().len() => {
+            let children = space
+                .spaces()
+                .iter()
+                .map(|(key, space)| match x.get(key) {
+                    Some(x) => _unbatch(space, x),
+                    None => Err(BatchError::SampleMismatch("Dict")),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(_transpose(children)?
+                .into_iter()
+                .map(|values| {
+                    StructuredSample::Dict(space.spaces().keys().cloned().zip(values).collect())
+                })
+                .collect())
+        }
+        (
+            SpaceEnum::Box(_) | SpaceEnum::Discrete(_) | SpaceEnum::Tuple(_) | SpaceEnum::Dict(_),
+            _,
+        ) => Err(BatchError::SampleMismatch(_space_name(space))),
+        (space, _) => Err(BatchError::Unsupported(_space_name(space))),
+    }
+}
+
+/// Turns per-child batches into per-environment lists of children.
+fn _transpose(
+    children: Vec<Vec<StructuredSample>>,
+) -> Result<Vec<Vec<StructuredSample>>, BatchError> {
+    let n = children.first().map_or(0, Vec::len);
+    if children.iter().any(|child| child.len() != n) {
+        return Err(BatchError::SampleMismatch("container"));
+    }
+
+    let mut children: Vec<_> = children.into_iter().map(Vec::into_iter).collect();
+    Ok((0..n)
+        .map(|_| children.iter_mut().filter_map(Iterator::next).collect())
+        .collect())
+}
+
+/// Converts `samples`, elements of `space`, into plain JSON, e.g. to log trajectories.
+///
+/// - `Box`, `MultiDiscrete` and `MultiBinary` samples become a list of nested lists of numbers.
+/// - `Discrete` samples become a list of integers.
+/// - `Tuple` samples become a list of the JSON of each child, and `Dict` samples an object of the
+///   JSON of each child by key.
+/// - `OneOf` samples become a list of `[index, value]` pairs.
+///
+/// Returns an error if one of `samples` does not match the structure of `space`.
+#[cfg(feature = "serde")]
+pub fn to_jsonable(
+    space: &SpaceEnum,
+    samples: &[StructuredSample],
+) -> Result<serde_json::Value, JsonableError> {
+    use serde_json::Value;
+
+    let mismatch = || JsonableError::SampleMismatch(_space_name(space));
+    match space {
+        SpaceEnum::Box(_) | SpaceEnum::MultiDiscrete(_) | SpaceEnum::MultiBinary(_) => samples
+            .iter()
+            .map(|sample| match sample {
+                StructuredSample::Tensor(x) => _tensor_to_json(x),
+                _ => Err(mismatch()),
+            })
+            .collect(),
+        SpaceEnum::Discrete(_) => samples
+            .iter()
+            .map(|sample| match sample {
+                StructuredSample::Int(x) => Ok(Value::from(*x)),
+                _ => Err(mismatch()),
+            })
+            .collect(),
+        SpaceEnum::Tuple(space) => {
+            let n = space.spaces().len();
+            let mut children = vec![Vec::with_capacity(samples.len()); n];
+            for sample in samples {
+                match sample {
+                    StructuredSample::Tuple(x) if x.len() == n => {
+                        for (child, x) in children.iter_mut().zip(x) {
+                            child.push(x.clone());
+                        }
+                    }
+                    _ => return Err(mismatch()),
+                }
+            }
+            space
+                .spaces()
+                .iter()
+                .zip(&children)
+                .map(|(space, children)| to_jsonable(space, children))
+                .collect()
+        }
+        SpaceEnum::Dict(space) => space
+            .spaces()
+            .iter()
+            .map(|(key, subspace)| {
+                let children = samples
+                    .iter()
+                    .map(|sample| match sample {
+                        StructuredSample::Dict(x) => x.get(key).cloned().ok_or_else(mismatch),
+                        _ => Err(mismatch()),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((key.clone(), to_jsonable(subspace, &children)?))
+            })
+            .collect::<Result<serde_json::Map<_, _>, _>>()
+            .map(Value::Object),
+        SpaceEnum::OneOf(space) => samples
+            .iter()
+            .map(|sample| {
+                let StructuredSample::OneOf(index, x) = sample else {
+                    return Err(mismatch());
+                };
+                let subspace = space.spaces().get(*index).ok_or_else(mismatch)?;
+                let Value::Array(mut value) = to_jsonable(subspace, &[(**x).clone()])? else {
+                    unreachable!("Samples are converted to a list");
+                };
+                Ok(Value::Array(vec![Value::from(*index), value.remove(0)]))
+            })
+            .collect(),
+    }
+}
+
+/// Reverses [`to_jsonable`], turning plain JSON back into elements of `space`.
+///
+/// Tensors are created on the CPU, with the shape and dtype of the space.
+///
+/// Returns an error if `value` does not match the structure of `space`.
+#[cfg(feature = "serde")]
+pub fn from_jsonable(
+    space: &SpaceEnum,
+    value: &serde_json::Value,
+) -> Result<Vec<StructuredSample>, JsonableError> {
+    use serde_json::Value;
+
+    let mismatch = || JsonableError::ValueMismatch(_space_name(space));
+    let tensors = |shape: &[usize], dtype: DType| -> Result<_, JsonableError> {
+        let values = value.as_array().ok_or_else(mismatch)?;
+        values
+            .iter()
+            .map(|value| {
+                let mut flat = Vec::with_capacity(shape.iter().product());
+                if !_json_to_values(value, shape, &mut flat) {
+                    return Err(mismatch());
+                }
+                let x = Tensor::from_vec(flat, shape, &Device::Cpu)?.to_dtype(dtype)?;
+                Ok(StructuredSample::Tensor(x))
+            })
+            .collect()
+    };
+
+    match space {
+        SpaceEnum::Box(space) => tensors(space.shape(), space.dtype()),
+        SpaceEnum::MultiDiscrete(space) => tensors(space.shape(), space.dtype()),
+        SpaceEnum::MultiBinary(space) => tensors(space.shape(), space.dtype()),
+        SpaceEnum::Discrete(_) => value
+            .as_array()
+            .ok_or_else(mismatch)?
+            .iter()
+            .map(|value| {
+                value
+                    .as_i64()
+                    .map(StructuredSample::Int)
+                    .ok_or_else(mismatch)
+            })
+            .collect(),
+        SpaceEnum::Tuple(space) => {
+            let values = value.as_array().ok_or_else(mismatch)?;
+            if values.len() != space.spaces().len() {
+                return Err(mismatch());
+            }
+            let children = space
+                .spaces()
+                .iter()
+                .zip(values)
+                .map(|(space, value)| from_jsonable(space, value))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(_transpose(children)
+                .map_err(|_| mismatch())?
+                .into_iter()
+                .map(StructuredSample::Tuple)
+                .collect())
+        }
+        SpaceEnum::Dict(space) => {
+            let values = value.as_object().ok_or_else(mismatch)?;
+            let children = space
+                .spaces()
+                .iter()
+                .map(|(key, space)| from_jsonable(space, values.get(key).ok_or_else(mismatch)?))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(_transpose(children)
+                .map_err(|_| mismatch())?
+                .into_iter()
+                .map(|children| {
+                    let keys = space.spaces().keys().cloned();
+                    StructuredSample::Dict(keys.zip(children).collect())
+                })
+                .collect())
+        }
+        SpaceEnum::OneOf(space) => value
+            .as_array()
+            .ok_or_else(mismatch)?
+            .iter()
+            .map(|pair| match pair.as_array().map(Vec::as_slice) {
+                Some([index, value]) => {
+                    let index = index.as_u64().ok_or_else(mismatch)? as usize;
+                    let subspace = space.spaces().get(index).ok_or_else(mismatch)?;
+                    let mut x = from_jsonable(subspace, &Value::Array(vec![value.clone()]))?;
+                    Ok(StructuredSample::OneOf(
+                        index,
+                        std::boxed::Box::new(x.remove(0)),
+                    ))
+                }
+                _ => Err(mismatch()),
+            })
+            .collect(),
+    }
+}
+
+/// Converts `x` into nested lists of numbers, integers for integer dtypes.
+#[cfg(feature = "serde")]
+fn _tensor_to_json(x: &Tensor) -> Result<serde_json::Value, JsonableError> {
+    use serde_json::Value;
+
+    let values: Vec<Value> = if x.dtype().is_float() {
+        let values = x.flatten_all()?.to_dtype(DType::F64)?.to_vec1::<f64>()?;
+        values.into_iter().map(Value::from).collect()
+    } else {
+        _to_i64_vec(x)?.into_iter().map(Value::from).collect()
+    };
+    Ok(_nest(&mut values.into_iter(), x.dims()))
+}
+
+/// Nests the next values of `values` into lists of `shape`.
+#[cfg(feature = "serde")]
+fn _nest(
+    values: &mut impl Iterator<Item = serde_json::Value>,
+    shape: &[usize],
+) -> serde_json::Value {
+    match shape {
+        [] => values.next().expect("There is one value per element"),
+        [n, rest @ ..] => (0..*n).map(|_| _nest(values, rest)).collect(),
+    }
+}
+
+/// Appends the numbers of the nested lists `value` to `flat`, returning `false` if `value` does
+/// not have the given `shape`.
+#[cfg(feature = "serde")]
+fn _json_to_values(value: &serde_json::Value, shape: &[usize], flat: &mut Vec<f64>) -> bool {
+    match (shape, value) {
+        ([], value) => value.as_f64().map(|x| flat.push(x)).is_some(),
+        ([n, rest @ ..], serde_json::Value::Array(values)) => {
+            values.len() == *n
+                && values
+                    .iter()
+                    .all(|value| _json_to_values(value, rest, flat))
+        }
+        _ => false,
+    }
+}
+
+/// Returns the number of dimensions a flattened equivalent of this space would have.
+///
+/// This is the product of the shape for `Box` and `MultiBinary`, `n` for `Discrete`, the sum of `nvec`
+/// for `MultiDiscrete`, and the sum over the children for `Tuple` and `Dict`. `OneOf` adds one
+/// dimension for the index to its largest child.
+///
+/// Returns an error if the space is not flattenable.
+pub fn flatdim(space: &SpaceEnum) -> Result<usize, FlattenError> {
+    if !space.is_flattenable() {
+        return Err(FlattenError::NotFlattenable(_space_name(space)));
+    }
+
+    match space {
+        SpaceEnum::Box(space) => Ok(space.shape().iter().product()),
+        SpaceEnum::MultiBinary(space) => Ok(space.shape().iter().product()),
+        SpaceEnum::Discrete(space) => Ok(space.n()),
+        SpaceEnum::MultiDiscrete(space) => {
+            Ok(_to_i64_vec(space.nvec())?.iter().sum::<i64>() as usize)
+        }
+        SpaceEnum::Tuple(space) => space.spaces().iter().map(flatdim).sum(),
+        SpaceEnum::Dict(space) => space.spaces().values().map(flatdim).sum(),
+        SpaceEnum::OneOf(space) => {
+            let widths = space
+                .spaces()
+                .iter()
+                .map(flatdim)
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(1 + widths.into_iter().max().unwrap_or(0))
+        }
+    }
+}
+
+/// Name of the variant of a space, used in error messages.
+fn _space_name(space: &SpaceEnum) -> &'static str {
+    match space {
+        SpaceEnum::Box(_) => "Box",
+        SpaceEnum::Discrete(_) => "Discrete",
+        SpaceEnum::MultiDiscrete(_) => "MultiDiscrete",
+        SpaceEnum::MultiBinary(_) => "MultiBinary",
+        SpaceEnum::Tuple(_) => "Tuple",
+        SpaceEnum::Dict(_) => "Dict",
+        SpaceEnum::OneOf(_) => "OneOf",
+    }
+}
+
+/// Checks that the tensor sample `x` has the shape of its space.
+fn _check_shape(space: &'static str, expected: &[usize], x: &Tensor) -> Result<(), FlattenError> {
+    if x.dims() != expected {
+        return Err(FlattenError::ShapeMismatch {
+            space,
+            expected: expected.to_vec(),
+            actual: x.dims().to_vec(),
+        });
+    }
+    Ok(())
+}
+
+/// One-hot encodes `x` as an element of `{start, ..., start+n-1}`.
+fn _one_hot(x: i64, start: i64, n: usize, dtype: DType) -> Result<Tensor, FlattenError> {
+    let index = x - start;
+    if index < 0 || index >= n as i64 {
+        return Err(FlattenError::OutOfRange { value: x, start, n });
+    }
+
+    let mut one_hot = vec![0i64; n];
+    one_hot[index as usize] = 1;
+    Ok(Tensor::from_vec(one_hot, n, &Device::Cpu)?.to_dtype(dtype)?)
+}
+
+/// Concatenates 1-D tensors after casting them to a common dtype.
+fn _concatenate(tensors: &[Tensor]) -> Result<Tensor, FlattenError> {
+    let dtype = _promote(tensors.iter().map(|tensor| tensor.dtype()));
+    let tensors = tensors
+        .iter()
+        .map(|tensor| tensor.to_dtype(dtype))
+        .collect::<Result<Vec<_>, _>>()?;
+    if tensors.is_empty() {
+        return Ok(Tensor::zeros(0, dtype, &Device::Cpu)?);
+    }
+    Ok(Tensor::cat(&tensors, 0)?)
+}
+
+/// Smallest dtype that can hold every dtype in `dtypes`.
+fn _promote(dtypes: impl Iterator<Item = DType>) -> DType {
+    let dtypes: Vec<DType> = dtypes.collect();
+    match dtypes.first() {
+        Some(first) if dtypes.iter().all(|dtype| dtype == first) => *first,
+        None => DType::I64,
+        _ if dtypes.contains(&DType::F64) => DType::F64,
+        _ if dtypes.iter().any(|dtype| dtype.is_float()) => DType::F32,
+        _ => DType::I64,
+    }
+}
+
+/// Copies a tensor to the host as a flat vector of `i64`.
+fn _to_i64_vec(tensor: &Tensor) -> Result<Vec<i64>, candle_core::Error> {
+    tensor.flatten_all()?.to_dtype(DType::I64)?.to_vec1::<i64>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spaces::{
+        Bound, Box, Dict, Discrete, MultiBinary, MultiDiscrete, OneOf, Space, Tuple,
+    };
+
+    fn unit_box(shape: Vec<usize>) -> Box {
+        Box::new(
+            Bound::F64(0.0),
+            Bound::F64(1.0),
+            Some(shape),
+            DType::F32,
+            &Device::Cpu,
+            Some(0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_flatten_box_row_major() {
+        let space = SpaceEnum::from(unit_box(vec![2, 2]));
+        let x = Tensor::new(&[[0.1f32, 0.2], [0.3, 0.4]], &Device::Cpu).unwrap();
+        let flat = flatten(&space, &StructuredSample::Tensor(x)).unwrap();
+
+        assert_eq!(flat.to_vec1::<f32>().unwrap(), vec![0.1, 0.2, 0.3, 0.4]);
+    }
+
+    #[test]
+    fn test_flatten_discrete_one_hot() {
+        let space = SpaceEnum::from(Discrete::new(4, -1, None));
+        let flat = flatten(&space, &StructuredSample::Int(1)).unwrap();
+
+        assert_eq!(flat.to_vec1::<i64>().unwrap(), vec![0, 0, 1, 0]);
+        assert!(flatten(&space, &StructuredSample::Int(3)).is_err());
+    }
+
+    #[test]
+    fn test_flatten_multi_discrete_concatenates_one_hots() {
+        let nvec = Tensor::new(&[2i64, 3], &Device::Cpu).unwrap();
+        let space = SpaceEnum::from(MultiDiscrete::new(nvec, None, DType::I64, None));
+        let x = Tensor::new(&[1i64, 0], &Device::Cpu).unwrap();
+        let flat = flatten(&space, &StructuredSample::Tensor(x)).unwrap();
+
+        assert_eq!(flat.to_vec1::<i64>().unwrap(), vec![0, 1, 1, 0, 0]);
+    }
+
+    #[test]
+    fn test_flatten_containers_in_order() {
+        let tuple = SpaceEnum::from(Tuple::new(
+            vec![unit_box(vec![2]).into(), Discrete::new(2, 0, None).into()],
+            None,
+        ));
+        let x = StructuredSample::Tuple(vec![
+            StructuredSample::Tensor(Tensor::new(&[0.5f32, 0.25], &Device::Cpu).unwrap()),
+            StructuredSample::Int(1),
+        ]);
+        let flat = flatten(&tuple, &x).unwrap();
+        assert_eq!(flat.to_vec1::<f32>().unwrap(), vec![0.5, 0.25, 0.0, 1.0]);
+
+        let dict = SpaceEnum::from(Dict::new(
+            [
+                ("b", Discrete::new(2, 0, None).into()),
+                ("a", Discrete::new(3, 0, None).into()),
+            ],
+            None,
+        ));
+        let x = StructuredSample::Dict(
+            [
+                ("a".to_string(), StructuredSample::Int(2)),
+                ("b".to_string(), StructuredSample::Int(0)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let flat = flatten(&dict, &x).unwrap();
+        assert_eq!(flat.to_vec1::<i64>().unwrap(), vec![1, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_flatten_mismatched_sample_errors() {
+        let space = SpaceEnum::from(Discrete::new(2, 0, None));
+        let x = StructuredSample::Tensor(Tensor::new(&[0.0f32], &Device::Cpu).unwrap());
+
+        assert!(matches!(
+            flatten(&space, &x),
+            Err(FlattenError::SampleMismatch("Discrete"))
+        ));
+
+        let nvec = Tensor::new(&[2i64, 3], &Device::Cpu).unwrap();
+        let space = SpaceEnum::from(MultiDiscrete::new(nvec, None, DType::I64, None));
+        let x = StructuredSample::Tensor(Tensor::new(&[1i64], &Device::Cpu).unwrap());
+        let err = flatten(&space, &x).unwrap_err();
+        assert!(matches!(
+            &err,
+            FlattenError::ShapeMismatch { space: "MultiDiscrete", expected, actual }
+                if expected == &[2] && actual == &[1]
+        ));
+        assert_eq!(
+            err.to_string(),
+            "sample of shape [1] does not match the shape [2] of the MultiDiscrete space"
+        );
+
+        let space = SpaceEnum::from(unit_box(vec![2, 2]));
+        let x = StructuredSample::Tensor(Tensor::zeros(4, DType::F32, &Device::Cpu).unwrap());
+        assert!(matches!(
+            flatten(&space, &x),
+            Err(FlattenError::ShapeMismatch { space: "Box", .. })
+        ));
+
+        let space = SpaceEnum::from(MultiBinary::new(vec![3], &Device::Cpu, None));
+        let x = StructuredSample::Tensor(Tensor::zeros((1, 3), DType::U8, &Device::Cpu).unwrap());
+        assert!(matches!(
+            flatten(&space, &x),
+            Err(FlattenError::ShapeMismatch {
+                space: "MultiBinary",
+                ..
+            })
+        ));
+    }
+
+    fn assert_round_trip(space: SpaceEnum) {
+        let mut space = space;
+        space.seed(Some(0));
+        for _ in 0..10 {
+            let x = space.sample(None);
+            let flat = flatten(&space, &x).unwrap();
+            assert_eq!(unflatten(&space, &flat).unwrap(), x);
+        }
+    }
+
+    #[test]
+    fn test_unflatten_round_trip_simple_spaces() {
+        let nvec = Tensor::new(&[2i64, 3], &Device::Cpu).unwrap();
+
+        assert_round_trip(unit_box(vec![2, 3]).into());
+        assert_round_trip(Discrete::new(5, -2, None).into());
+        assert_round_trip(MultiDiscrete::new(nvec, None, DType::I64, None).into());
+        assert_round_trip(MultiBinary::new(vec![2, 2], &Device::Cpu, None).into());
+    }
+
+    #[test]
+    fn test_unflatten_round_trip_containers() {
+        let tuple = Tuple::new(
+            vec![unit_box(vec![2]).into(), Discrete::new(3, 0, None).into()],
+            None,
+        );
+        let dict = Dict::new(
+            [
+                ("position", unit_box(vec![3]).into()),
+                ("tuple", tuple.clone().into()),
+            ],
+            None,
+        );
+        let one_of = OneOf::new(
+            vec![Discrete::new(4, 0, None).into(), unit_box(vec![2]).into()],
+            None,
+        );
+
+        assert_round_trip(tuple.into());
+        assert_round_trip(dict.into());
+        assert_round_trip(one_of.into());
+    }
+
+    #[test]
+    fn test_unflatten_length_mismatch_errors() {
+        let space = SpaceEnum::from(Discrete::new(3, 0, None));
+        let x = Tensor::new(&[0i64, 1], &Device::Cpu).unwrap();
+
+        assert!(matches!(
+            unflatten(&space, &x),
+            Err(FlattenError::LengthMismatch { expected: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_flatdim_simple_spaces() {
+        let nvec = Tensor::new(&[2i64, 3, 4], &Device::Cpu).unwrap();
+
+        assert_eq!(flatdim(&unit_box(vec![2, 3]).into()).unwrap(), 6);
+        assert_eq!(flatdim(&Discrete::new(5, 0, None).into()).unwrap(), 5);
+        assert_eq!(
+            flatdim(&MultiDiscrete::new(nvec, None, DType::I64, None).into()).unwrap(),
+            9
+        );
+        assert_eq!(
+            flatdim(&MultiBinary::new(vec![2, 2], &Device::Cpu, None).into()).unwrap(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_flatdim_nested_containers() {
+        let tuple = Tuple::new(
+            vec![unit_box(vec![2]).into(), Discrete::new(3, 0, None).into()],
+            None,
+        );
+        let dict = SpaceEnum::from(Dict::new(
+            [
+                ("position", unit_box(vec![4]).into()),
+                ("tuple", tuple.into()),
+            ],
+            None,
+        ));
+        let one_of = SpaceEnum::from(OneOf::new(
+            vec![Discrete::new(4, 0, None).into(), unit_box(vec![2]).into()],
+            None,
+        ));
+
+        assert_eq!(flatdim(&dict).unwrap(), 9);
+        assert_eq!(flatdim(&one_of).unwrap(), 5);
+    }
+
+    fn assert_flatten_space_matches(space: SpaceEnum) {
+        let mut space = space;
+        let flat_space = flatten_space(&space).unwrap();
+        assert_eq!(flat_space.shape(), &[flatdim(&space).unwrap()]);
+
+        space.seed(Some(0));
+        for _ in 0..10 {
+            let sample = space.sample(None);
+            let x = flatten(&space, &sample).unwrap();
+            assert!(flat_space.contains(&x.to_dtype(flat_space.dtype()).unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_flatten_space_simple_spaces() {
+        let nvec = Tensor::new(&[2i64, 3], &Device::Cpu).unwrap();
+
+        assert_flatten_space_matches(unit_box(vec![2, 3]).into());
+        assert_flatten_space_matches(Discrete::new(5, -2, None).into());
+        assert_flatten_space_matches(MultiDiscrete::new(nvec, None, DType::I64, None).into());
+        assert_flatten_space_matches(MultiBinary::new(vec![2, 2], &Device::Cpu, None).into());
+    }
+
+    #[test]
+    fn test_flatten_space_containers() {
+        let tuple = Tuple::new(
+            vec![unit_box(vec![2]).into(), Discrete::new(3, 0, None).into()],
+            None,
+        );
+        let dict = Dict::new(
+            [
+                ("position", unit_box(vec![3]).into()),
+                ("tuple", tuple.clone().into()),
+            ],
+            None,
+        );
+        let one_of = OneOf::new(
+            vec![Discrete::new(4, 0, None).into(), unit_box(vec![2]).into()],
+            None,
+        );
+
+        assert_flatten_space_matches(tuple.into());
+        assert_flatten_space_matches(dict.into());
+        assert_flatten_space_matches(one_of.into());
+    }
+
+    #[test]
+    fn test_flatten_space_box_bounds() {
+        let low = Tensor::new(&[[-1.0f32, -2.0], [-3.0, -4.0]], &Device::Cpu).unwrap();
+        let high = Tensor::new(&[[1.0f32, 2.0], [3.0, 4.0]], &Device::Cpu).unwrap();
+        let space = SpaceEnum::from(
+            Box::new(
+                Bound::Tensor(low),
+                Bound::Tensor(high),
+                None,
+                DType::F32,
+                &Device::Cpu,
+                None,
+            )
+            .unwrap(),
+        );
+        let flat_space = flatten_space(&space).unwrap();
+
+        assert_eq!(
+            flat_space.low().to_vec1::<f32>().unwrap(),
+            vec![-1.0, -2.0, -3.0, -4.0]
+        );
+        assert_eq!(
+            flat_space.high().to_vec1::<f32>().unwrap(),
+            vec![1.0, 2.0, 3.0, 4.0]
+        );
+    }
+
+    #[test]
+    fn test_batch_space_leading_dimension() {
+        let nvec = Tensor::new(&[2i64, 3], &Device::Cpu).unwrap();
+        let spaces: Vec<(SpaceEnum, Vec<usize>)> = vec![
+            (unit_box(vec![2, 3]).into(), vec![4, 2, 3]),
+            (Discrete::new(5, -2, None).into(), vec![4]),
+            (
+                MultiDiscrete::new(nvec, None, DType::I64, None).into(),
+                vec![4, 2],
+            ),
+            (
+                MultiBinary::new(vec![2, 2], &Device::Cpu, None).into(),
+                vec![4, 2, 2],
+            ),
+        ];
+
+        for (space, shape) in spaces {
+            let mut batched = batch_space(&space, 4).unwrap();
+            match batched.sample(None) {
+                StructuredSample::Tensor(x) => assert_eq!(x.dims(), shape.as_slice()),
+                sample => panic!("unexpected sample {sample:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_batch_space_discrete_bounds() {
+        let space = SpaceEnum::from(Discrete::new(3, -1, None));
+        let mut batched = batch_space(&space, 5).unwrap();
+        let SpaceEnum::MultiDiscrete(multi_discrete) = &batched else {
+            panic!("Discrete must be batched into a MultiDiscrete");
+        };
+
+        assert_eq!(
+            multi_discrete.start().to_vec1::<i64>().unwrap(),
+            vec![-1; 5]
+        );
+        for _ in 0..10 {
+            let sample = batched.sample(None);
+            assert!(batched.contains(&sample));
+        }
+    }
+
+    #[test]
+    fn test_batch_space_containers() {
+        let tuple = Tuple::new(
+            vec![unit_box(vec![2]).into(), Discrete::new(3, 0, None).into()],
+            None,
+        );
+        let dict = SpaceEnum::from(Dict::new(
+            [
+                ("position", unit_box(vec![3]).into()),
+                ("tuple", tuple.into()),
+            ],
+            None,
+        ));
+        let mut batched = batch_space(&dict, 2).unwrap();
+        let StructuredSample::Dict(sample) = batched.sample(None) else {
+            panic!("Dict must be batched into a Dict");
+        };
+
+        let StructuredSample::Tensor(position) = &sample["position"] else {
+            panic!("Box must be batched into a Box");
+        };
+        assert_eq!(position.dims(), &[2, 3]);
+        let StructuredSample::Tuple(tuple) = &sample["tuple"] else {
+            panic!("Tuple must be batched into a Tuple");
+        };
+        assert!(matches!(&tuple[1], StructuredSample::Tensor(x) if x.dims() == [2]));
+    }
+
+    #[test]
+    fn test_batch_space_one_of_unsupported() {
+        let space = SpaceEnum::from(OneOf::new(vec![Discrete::new(2, 0, None).into()], None));
+
+        assert!(matches!(
+            batch_space(&space, 2),
+            Err(BatchError::Unsupported("OneOf"))
+        ));
+    }
+
+    #[test]
+    fn test_concatenate_box_matches_stack() {
+        let mut space = SpaceEnum::from(unit_box(vec![2, 3]));
+        let items: Vec<StructuredSample> = (0..4).map(|_| space.sample(None)).collect();
+        let out = create_empty_array(&space, 4).unwrap();
+        assert_eq!(out.dims(), &[4, 2, 3]);
+
+        concatenate(&space, &items, &out).unwrap();
+        let tensors: Vec<Tensor> = items
+            .iter()
+            .map(|item| match item {
+                StructuredSample::Tensor(x) => x.clone(),
+                _ => unreachable!(),
+            })
+            .collect();
+        let stacked = Tensor::stack(&tensors, 0).unwrap();
+        assert_eq!(
+            StructuredSample::Tensor(out),
+            StructuredSample::Tensor(stacked)
+        );
+    }
+
+    #[test]
+    fn test_concatenate_discrete_matches_stack() {
+        let space = SpaceEnum::from(Discrete::new(5, 0, None));
+        let items: Vec<StructuredSample> =
+            [3, 0, 4].into_iter().map(StructuredSample::Int).collect();
+        let out = create_empty_array(&space, 3).unwrap();
+
+        concatenate(&space, &items, &out).unwrap();
+        assert_eq!(out.to_vec1::<i64>().unwrap(), vec![3, 0, 4]);
+    }
+
+    #[test]
+    fn test_concatenate_unsupported_space() {
+        let space = SpaceEnum::from(MultiBinary::new(2, &Device::Cpu, None));
+
+        assert!(matches!(
+            create_empty_array(&space, 2),
+            Err(BatchError::Unsupported("MultiBinary"))
+        ));
+    }
+
+    #[test]
+    fn test_iterate_round_trips_concatenate() {
+        let mut space = SpaceEnum::from(unit_box(vec![2]));
+        let items: Vec<StructuredSample> = (0..3).map(|_| space.sample(None)).collect();
+        let out = create_empty_array(&space, 3).unwrap();
+        concatenate(&space, &items, &out).unwrap();
+        let iterated: Vec<StructuredSample> = iterate(&space, &StructuredSample::Tensor(out))
+            .unwrap()
+            .collect();
+        assert_eq!(iterated, items);
+
+        let space = SpaceEnum::from(Discrete::new(5, 0, None));
+        let items: Vec<StructuredSample> = [1, 4].into_iter().map(StructuredSample::Int).collect();
+        let out = create_empty_array(&space, 2).unwrap();
+        concatenate(&space, &items, &out).unwrap();
+        let iterated: Vec<StructuredSample> = iterate(&space, &StructuredSample::Tensor(out))
+            .unwrap()
+            .collect();
+        assert_eq!(iterated, items);
+    }
+
+    #[test]
+    fn test_iterate_containers() {
+        let tuple = Tuple::new(
+            vec![unit_box(vec![2]).into(), Discrete::new(3, 0, None).into()],
+            None,
+        );
+        let space = SpaceEnum::from(Dict::new([("tuple", tuple.into())], None));
+        let mut batched_space = batch_space(&space, 3).unwrap();
+        let batched = batched_space.sample(None);
+
+        let items: Vec<StructuredSample> = iterate(&space, &batched).unwrap().collect();
+        assert_eq!(items.len(), 3);
+        assert!(items.iter().all(|item| space.contains(item)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_jsonable_round_trip() {
+        let space = SpaceEnum::from(Dict::new(
+            [
+                ("position", unit_box(vec![2, 2]).into()),
+                ("count", Discrete::new(5, 0, None).into()),
+            ],
+            None,
+        ));
+        let sample = |position: [[f32; 2]; 2], count: i64| {
+            let position = Tensor::new(&position, &Device::Cpu).unwrap();
+            StructuredSample::Dict(IndexMap::from([
+                ("position".to_string(), position.into()),
+                ("count".to_string(), StructuredSample::Int(count)),
+            ]))
+        };
+        let samples = [
+            sample([[0.5, 0.25], [0.0, 1.0]], 3),
+            sample([[1.0, 0.75], [0.5, 0.0]], 0),
+        ];
+
+        let json = to_jsonable(&space, &samples).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "position": [[[0.5, 0.25], [0.0, 1.0]], [[1.0, 0.75], [0.5, 0.0]]],
+                "count": [3, 0],
+            })
+        );
+        assert_eq!(from_jsonable(&space, &json).unwrap(), samples);
+
+        let json = serde_json::json!({"position": [[0.5, 0.25]], "count": [3]});
+        let err = from_jsonable(&space, &json).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "JSON value does not match the structure of the Box space"
+        );
+    }
+}
@@ -0,0 +1,60 @@
+//! A set of common utilities used within the environments: coloring text for terminal output.
+
+/// The colors of terminal text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Gray,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Crimson,
+}
+
+impl Color {
+    /// The ANSI code of the foreground color.
+    fn code(self) -> u8 {
+        match self {
+            Color::Gray => 30,
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+            Color::White => 37,
+            Color::Crimson => 38,
+        }
+    }
+}
+
+/// Returns `string` surrounded by the ANSI escape codes displaying it in `color`, in bold if
+/// `bold` is set, and with `color` as the background instead of the foreground if `highlight` is
+/// set.
+pub fn colorize(string: &str, color: Color, bold: bool, highlight: bool) -> String {
+    let code = color.code() + if highlight { 10 } else { 0 };
+    let attrs = if bold {
+        format!("{code};1")
+    } else {
+        code.to_string()
+    };
+    format!("\x1b[{attrs}m{string}\x1b[0m")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colorize() {
+        assert_eq!(colorize("a", Color::Red, false, false), "\x1b[31ma\x1b[0m");
+        assert_eq!(colorize("a", Color::Red, false, true), "\x1b[41ma\x1b[0m");
+        assert_eq!(
+            colorize("ab", Color::Blue, true, false),
+            "\x1b[34;1mab\x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,437 @@
+//! A set of functions for checking an environment implementation.
+
+use std::fmt;
+
+use candle_core::{DType, Tensor};
+
+use crate::core::{Env, RenderFrame};
+use crate::spaces::{Space, StructuredSample};
+
+/// How serious an [`EnvCheckIssue`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckLevel {
+    /// The environment works, but likely not as intended.
+    Warning,
+    /// The environment does not follow the API.
+    Error,
+}
+
+/// An issue found by [`check_env`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvCheckIssue {
+    /// A sample of the observation space is not contained by the observation space.
+    ObservationSpaceSample,
+    /// A sample of the action space is not contained by the action space.
+    ActionSpaceSample,
+    /// A sample of the action space cannot be converted to the action type of the environment.
+    ActionTypeMismatch,
+    /// The observation returned by `reset` is not an element of the observation space.
+    ResetObservationNotInSpace,
+    /// Resetting twice with the same seed returns different observations.
+    ResetNotReproducible,
+    /// The observation returned by `step` is not an element of the observation space.
+    StepObservationNotInSpace,
+    /// The observation returned by `step` does not have the shape and dtype of that of `reset`.
+    StepObservationInconsistent,
+    /// The reward returned by `step` is NaN or infinite.
+    NonFiniteReward,
+    /// The frame returned by `render` is malformed, e.g. an RGB array that is not `(h, w, 3)` `U8`.
+    InvalidRenderFrame,
+}
+
+impl EnvCheckIssue {
+    /// How serious the issue is.
+    pub fn level(&self) -> CheckLevel {
+        match self {
+            Self::NonFiniteReward => CheckLevel::Warning,
+            _ => CheckLevel::Error,
+        }
+    }
+}
+
+impl fmt::Display for EnvCheckIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Self::ObservationSpaceSample => {
+                "A sample of the observation space is not within the observation space."
+            }
+            Self::ActionSpaceSample => {
+                "A sample of the action space is not within the action space."
+            }
+            Self::ActionTypeMismatch => {
+                "A sample of the action space cannot be converted to an action of the environment."
+            }
+            Self::ResetObservationNotInSpace => {
+                "The obs returned by the `reset()` method is not within the observation space."
+            }
+            Self::ResetNotReproducible => {
+                "Using `reset(seed)` twice with the same seed returns different observations."
+            }
+            Self::StepObservationNotInSpace => {
+                "The obs returned by the `step()` method is not within the observation space."
+            }
+            Self::StepObservationInconsistent => {
+                "The obs returned by the `step()` method does not have the structure of the obs \
+                 returned by the `reset()` method."
+            }
+            Self::NonFiniteReward => "The reward returned by the `step()` method is not finite.",
+            Self::InvalidRenderFrame => "The frame returned by the `render()` method is malformed.",
+        };
+        f.write_str(message)
+    }
+}
+
+/// The number of steps taken by [`check_env`].
+const CHECK_STEPS: usize = 10;
+
+/// Checks that `env` follows the [`Env`] API, returning the issues found.
+///
+/// The checks are:
+/// - The samples of the observation and action spaces of `env` are contained by the spaces, and action samples convert to
+///   `ActType`.
+/// - The observation of `reset` is in the observation space, and reproducible for a fixed seed.
+/// - The observations of a few random steps are in the observation space with the structure of
+///   that of `reset`, and their rewards are finite.
+/// - Unless `skip_render` is `true`, the frame rendered after these steps is well-formed.
+///
+/// The environment is left in the middle of an episode, so it should be reset before further use.
+pub fn check_env<ObsType, ActType>(
+    env: &mut dyn Env<ObsType, ActType>,
+    skip_render: bool,
+) -> Vec<EnvCheckIssue>
+where
+    ObsType: Into<StructuredSample>,
+    ActType: TryFrom<StructuredSample>,
+{
+    let mut issues = Vec::new();
+    let mut report = |failed: bool, issue: EnvCheckIssue| {
+        if failed && !issues.contains(&issue) {
+            issues.push(issue);
+        }
+    };
+
+    let mut observation_space = env.observation_space().clone();
+    observation_space.seed(Some(0));
+    let sample = observation_space.sample(None);
+    report(
+        !observation_space.contains(&sample),
+        EnvCheckIssue::ObservationSpaceSample,
+    );
+    let mut action_space = env.action_space().clone();
+    action_space.seed(Some(0));
+    let sample = action_space.sample(None);
+    report(
+        !action_space.contains(&sample),
+        EnvCheckIssue::ActionSpaceSample,
+    );
+
+    let (observation, _) = env.reset(Some(42), None);
+    let observation: StructuredSample = observation.into();
+    report(
+        !observation_space.contains(&observation),
+        EnvCheckIssue::ResetObservationNotInSpace,
+    );
+    let (other, _) = env.reset(Some(42), None);
+    report(
+        !data_equivalence(&observation, &other.into(), None),
+        EnvCheckIssue::ResetNotReproducible,
+    );
+
+    for _ in 0..CHECK_STEPS {
+        let Ok(action) = ActType::try_from(action_space.sample(None)) else {
+            report(true, EnvCheckIssue::ActionTypeMismatch);
+            break;
+        };
+        let result = env.step(action);
+        let next: StructuredSample = result.observation.into();
+        report(
+            !observation_space.contains(&next),
+            EnvCheckIssue::StepObservationNotInSpace,
+        );
+        report(
+            !same_structure(&observation, &next),
+            EnvCheckIssue::StepObservationInconsistent,
+        );
+        report(!result.reward.is_finite(), EnvCheckIssue::NonFiniteReward);
+        if result.terminated || result.truncated {
+            env.reset(None, None);
+        }
+    }
+
+    if !skip_render {
+        if let Some(frame) = env.render() {
+            report(!valid_frame(&frame), EnvCheckIssue::InvalidRenderFrame);
+        }
+    }
+    issues
+}
+
+/// Asserts the equality of data structures `a` and `b`.
+///
+/// Tensors must have the same shape and dtype, and `Tuple`, `Dict` and `OneOf` elements the same
+/// structure, with equal children. Values are compared exactly unless `atol` is given, in which
+/// case they may differ by up to `atol`. NaN values are considered equal to each other.
+pub fn data_equivalence(a: &StructuredSample, b: &StructuredSample, atol: Option<f64>) -> bool {
+    match (a, b) {
+        (StructuredSample::Tensor(a), StructuredSample::Tensor(b)) => {
+            if a.dims() != b.dims() || a.dtype() != b.dtype() {
+                return false;
+            }
+            let values = |x: &Tensor| x.flatten_all()?.to_dtype(DType::F64)?.to_vec1::<f64>();
+            let (Ok(a), Ok(b)) = (values(a), values(b)) else {
+                return false;
+            };
+            let atol = atol.unwrap_or(0.0);
+            a.iter()
+                .zip(&b)
+                .all(|(a, b)| (a.is_nan() && b.is_nan()) || a == b || (a - b).abs() <= atol)
+        }
+        (StructuredSample::Int(a), StructuredSample::Int(b)) => a == b,
+        (StructuredSample::Tuple(a), StructuredSample::Tuple(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| data_equivalence(a, b, atol))
+        }
+        (StructuredSample::Dict(a), StructuredSample::Dict(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| data_equivalence(a, b, atol)))
+        }
+        (StructuredSample::OneOf(i, a), StructuredSample::OneOf(j, b)) => {
+            i == j && data_equivalence(a, b, atol)
+        }
+        _ => false,
+    }
+}
+
+/// Returns `true` if `a` and `b` have the same structure, with tensors of the same shape and dtype.
+fn same_structure(a: &StructuredSample, b: &StructuredSample) -> bool {
+    match (a, b) {
+        (StructuredSample::Tensor(a), StructuredSample::Tensor(b)) => {
+            a.dims() == b.dims() && a.dtype() == b.dtype()
+        }
+        (StructuredSample::Int(_), StructuredSample::Int(_)) => true,
+        (StructuredSample::Tuple(a), StructuredSample::Tuple(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_structure(a, b))
+        }
+        (StructuredSample::Dict(a), StructuredSample::Dict(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| same_structure(a, b)))
+        }
+        (StructuredSample::OneOf(_, _), StructuredSample::OneOf(_, _)) => true,
+        _ => false,
+    }
+}
+
+/// Returns `true` if `frame` is an RGB array of shape `(h, w, 3)` and dtype `U8`, a non-empty text,
+/// or a list of such frames.
+fn valid_frame(frame: &RenderFrame) -> bool {
+    match frame {
+        RenderFrame::RgbArray(frame) => {
+            matches!(frame.dims(), [_, _, 3]) && frame.dtype() == DType::U8
+        }
+        RenderFrame::Ansi(text) => !text.is_empty(),
+        RenderFrame::List(frames) => frames.iter().all(valid_frame),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::{DType, Device, Tensor};
+
+    use indexmap::IndexMap;
+
+    use super::{check_env, data_equivalence, CheckLevel, EnvCheckIssue};
+    use crate::common::InfoMap;
+    use crate::core::{Env, RenderFrame, StepResult, Wrapper};
+    use crate::envs::classic_control::CartPole;
+    use crate::spaces::{Bound, Box, Discrete, SpaceEnum, StructuredSample};
+
+    /// Breaks most of the checked rules: its observations leave the space and change shape, its
+    /// resets ignore the seed, its rewards are NaN and its frames have a single channel.
+    struct BrokenEnv {
+        resets: u32,
+        observation_space: SpaceEnum,
+        action_space: SpaceEnum,
+    }
+
+    impl BrokenEnv {
+        fn new() -> Self {
+            Self {
+                resets: 0,
+                observation_space: Box::new(
+                    Bound::F64(0.0),
+                    Bound::F64(1.0),
+                    Some(vec![2]),
+                    DType::F32,
+                    &Device::Cpu,
+                    None,
+                )
+                .unwrap()
+                .into(),
+                action_space: Discrete::new(2, 0, None).into(),
+            }
+        }
+    }
+
+    impl Env<Tensor, i64> for BrokenEnv {
+        fn step(&mut self, _action: i64) -> StepResult<Tensor> {
+            let observation = Tensor::full(5.0f32, 3, &Device::Cpu).unwrap();
+            (observation, f32::NAN, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (Tensor, InfoMap) {
+            self.resets += 1;
+            let observation = Tensor::full(self.resets as f32 / 10.0, 2, &Device::Cpu).unwrap();
+            (observation, InfoMap::new())
+        }
+
+        fn render(&mut self) -> Option<RenderFrame> {
+            let frame = Tensor::zeros((4, 4, 1), DType::U8, &Device::Cpu).unwrap();
+            Some(RenderFrame::RgbArray(frame))
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.action_space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.observation_space
+        }
+    }
+
+    /// Declares a `Box` action space for CartPole, whose actions are integers.
+    struct BoxActions {
+        env: CartPole,
+        action_space: SpaceEnum,
+    }
+
+    impl Wrapper for BoxActions {
+        type ObsType = Tensor;
+        type ActType = i64;
+
+        fn env(&self) -> &dyn Env<Tensor, i64> {
+            &self.env
+        }
+
+        fn env_mut(&mut self) -> &mut dyn Env<Tensor, i64> {
+            &mut self.env
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.action_space
+        }
+    }
+
+    fn tensor(values: &[f32]) -> StructuredSample {
+        StructuredSample::Tensor(Tensor::new(values, &Device::Cpu).unwrap())
+    }
+
+    #[test]
+    fn test_data_equivalence_tensors() {
+        let a = tensor(&[1.0, 2.0, 3.0]);
+        assert!(data_equivalence(&a, &tensor(&[1.0, 2.0, 3.0]), None));
+        assert!(!data_equivalence(&a, &tensor(&[1.0, 2.0, 3.001]), None));
+        assert!(data_equivalence(
+            &a,
+            &tensor(&[1.0, 2.0, 3.001]),
+            Some(0.01)
+        ));
+        assert!(!data_equivalence(&a, &tensor(&[1.0, 2.0]), Some(0.01)));
+
+        let doubles =
+            StructuredSample::Tensor(Tensor::new(&[1.0f64, 2.0, 3.0], &Device::Cpu).unwrap());
+        assert!(!data_equivalence(&a, &doubles, None));
+        let matrix =
+            StructuredSample::Tensor(Tensor::new(&[[1.0f32, 2.0, 3.0]], &Device::Cpu).unwrap());
+        assert!(!data_equivalence(&a, &matrix, None));
+    }
+
+    #[test]
+    fn test_data_equivalence_nan() {
+        let a = tensor(&[f32::NAN, 1.0]);
+        assert!(data_equivalence(&a, &tensor(&[f32::NAN, 1.0]), None));
+        assert!(!data_equivalence(&a, &tensor(&[0.0, 1.0]), Some(1.0)));
+    }
+
+    #[test]
+    fn test_data_equivalence_structures() {
+        let tuple =
+            |x: f32, n: i64| StructuredSample::Tuple(vec![tensor(&[x]), StructuredSample::Int(n)]);
+        assert!(data_equivalence(&tuple(1.0, 2), &tuple(1.0, 2), None));
+        assert!(!data_equivalence(&tuple(1.0, 2), &tuple(1.0, 3), None));
+        assert!(!data_equivalence(
+            &tuple(1.0, 2),
+            &StructuredSample::Tuple(vec![tensor(&[1.0])]),
+            None
+        ));
+
+        let dict = |key: &str| {
+            StructuredSample::Dict(IndexMap::from([
+                (key.to_string(), StructuredSample::Int(0)),
+                ("b".to_string(), tensor(&[0.5])),
+            ]))
+        };
+        assert!(data_equivalence(&dict("a"), &dict("a"), None));
+        assert!(!data_equivalence(&dict("a"), &dict("c"), None));
+        assert!(!data_equivalence(&dict("a"), &tuple(0.5, 0), None));
+        assert!(!data_equivalence(
+            &StructuredSample::Int(1),
+            &tensor(&[1.0]),
+            None
+        ));
+    }
+
+    #[test]
+    fn test_check_env_valid() {
+        for render_mode in [None, Some("rgb_array"), Some("ansi")] {
+            let mut env = CartPole::new(render_mode);
+
+            let issues = check_env(&mut env, false);
+            assert!(issues.is_empty(), "{issues:?}");
+        }
+    }
+
+    #[test]
+    fn test_check_env_broken() {
+        let mut env = BrokenEnv::new();
+
+        let issues = check_env(&mut env, false);
+        assert_eq!(
+            issues,
+            [
+                EnvCheckIssue::ResetNotReproducible,
+                EnvCheckIssue::StepObservationNotInSpace,
+                EnvCheckIssue::StepObservationInconsistent,
+                EnvCheckIssue::NonFiniteReward,
+                EnvCheckIssue::InvalidRenderFrame,
+            ]
+        );
+        assert_eq!(EnvCheckIssue::NonFiniteReward.level(), CheckLevel::Warning);
+        assert!(issues
+            .iter()
+            .any(|issue| issue.level() == CheckLevel::Error));
+
+        let issues = check_env(&mut env, true);
+        assert!(!issues.contains(&EnvCheckIssue::InvalidRenderFrame));
+    }
+
+    #[test]
+    fn test_check_env_action_type_mismatch() {
+        let mut env = BoxActions {
+            env: CartPole::default(),
+            action_space: Box::new(
+                Bound::F64(0.0),
+                Bound::F64(1.0),
+                Some(vec![1]),
+                DType::F32,
+                &Device::Cpu,
+                None,
+            )
+            .unwrap()
+            .into(),
+        };
+
+        let issues = check_env(&mut env, true);
+        assert_eq!(issues, [EnvCheckIssue::ActionTypeMismatch]);
+    }
+}
@@ -0,0 +1,293 @@
+//! Reading and writing tensors in the NumPy `.npy` format, e.g. to inspect trajectories in Python.
+
+use std::fs;
+use std::path::Path;
+
+use candle_core::{DType, Device, Tensor};
+use thiserror::Error;
+
+/// Magic string starting every `.npy` file.
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// Alignment in bytes of the data, after the header.
+const ALIGNMENT: usize = 64;
+
+/// Error returned by [`save_npy`] and [`load_npy`].
+#[derive(Debug, Error)]
+pub enum NpyError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Invalid .npy file: {0}")]
+    InvalidFormat(String),
+    #[error("Unsupported dtype `{0}`, expected one of u8, u32, i64, f32 or f64")]
+    UnsupportedDtype(String),
+}
+
+/// Writes `tensor` to `path` in the `.npy` format, in little-endian and row-major order.
+///
+/// Returns an error if the file cannot be written or the dtype of the tensor is not `U8`, `U32`,
+/// `I64`, `F32` or `F64`.
+pub fn save_npy(path: impl AsRef<Path>, tensor: &Tensor) -> Result<(), NpyError> {
+    let descr = match tensor.dtype() {
+        DType::U8 => "|u1",
+        DType::U32 => "<u4",
+        DType::I64 => "<i8",
+        DType::F32 => "<f4",
+        DType::F64 => "<f8",
+        dtype => return Err(NpyError::UnsupportedDtype(dtype.as_str().to_string())),
+    };
+    let shape = match tensor.dims() {
+        [size] => format!("({size},)"),
+        dims => {
+            let dims: Vec<String> = dims.iter().map(usize::to_string).collect();
+            format!("({})", dims.join(", "))
+        }
+    };
+    let mut header = format!("{{'descr': '{descr}', 'fortran_order': False, 'shape': {shape}, }}");
+    // The header is padded with spaces and ends with a newline, so that the data is aligned.
+    let unpadded = MAGIC.len() + 4 + header.len() + 1;
+    header.push_str(&" ".repeat(unpadded.next_multiple_of(ALIGNMENT) - unpadded));
+    header.push('\n');
+
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend([1, 0]);
+    bytes.extend((header.len() as u16).to_le_bytes());
+    bytes.extend(header.as_bytes());
+    let values = tensor.flatten_all().expect("A tensor can be flattened");
+    match tensor.dtype() {
+        DType::U8 => bytes.extend(_to_vec::<u8>(&values)),
+        DType::U32 => bytes.extend(_to_vec::<u32>(&values).iter().flat_map(|x| x.to_le_bytes())),
+        DType::I64 => bytes.extend(_to_vec::<i64>(&values).iter().flat_map(|x| x.to_le_bytes())),
+        DType::F32 => bytes.extend(_to_vec::<f32>(&values).iter().flat_map(|x| x.to_le_bytes())),
+        DType::F64 => bytes.extend(_to_vec::<f64>(&values).iter().flat_map(|x| x.to_le_bytes())),
+        _ => unreachable!("The dtype is supported"),
+    }
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Reads a tensor from the `.npy` file at `path`, on the CPU.
+///
+/// Both byte orders and both C and Fortran orders are supported, for the dtypes supported by
+/// [`save_npy`].
+pub fn load_npy(path: impl AsRef<Path>) -> Result<Tensor, NpyError> {
+    let bytes = fs::read(path)?;
+    let invalid = |message: &str| NpyError::InvalidFormat(message.to_string());
+
+    let rest = bytes
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| invalid("missing magic string"))?;
+    let (header_len, rest) = match rest {
+        [1, _, len @ ..] if len.len() >= 2 => {
+            (u16::from_le_bytes([len[0], len[1]]) as usize, &len[2..])
+        }
+        [2 | 3, _, len @ ..] if len.len() >= 4 => (
+            u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize,
+            &len[4..],
+        ),
+        _ => return Err(invalid("unsupported version")),
+    };
+    if rest.len() < header_len {
+        return Err(invalid("truncated header"));
+    }
+    let (header, data) = rest.split_at(header_len);
+    let header = std::str::from_utf8(header).map_err(|_| invalid("header is not text"))?;
+
+    let descr = _header_value(header, "descr")
+        .map(|descr| descr.trim_matches('\''))
+        .ok_or_else(|| invalid("missing descr"))?;
+    let fortran_order = match _header_value(header, "fortran_order") {
+        Some("True") => true,
+        Some("False") => false,
+        _ => return Err(invalid("missing fortran_order")),
+    };
+    let shape: Vec<usize> = _header_value(header, "shape")
+        .and_then(|shape| shape.strip_prefix('('))
+        .and_then(|shape| shape.strip_suffix(')'))
+        .ok_or_else(|| invalid("missing shape"))?
+        .split(',')
+        .map(str::trim)
+        .filter(|dim| !dim.is_empty())
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .map_err(|_| invalid("malformed shape"))?;
+
+    let (byte_order, kind) = descr.split_at(1.min(descr.len()));
+    let big_endian = match byte_order {
+        "<" | "|" => false,
+        ">" => true,
+        "=" => cfg!(target_endian = "big"),
+        _ => return Err(NpyError::UnsupportedDtype(descr.to_string())),
+    };
+    let size: usize = shape.iter().product();
+    let item_size = match kind {
+        "u1" => 1,
+        "u4" | "f4" => 4,
+        "i8" | "f8" => 8,
+        _ => return Err(NpyError::UnsupportedDtype(descr.to_string())),
+    };
+    if data.len() != size * item_size {
+        return Err(invalid("the data does not match the shape"));
+    }
+
+    // Fortran-ordered data is read with the reversed shape, then transposed back.
+    let storage_shape: Vec<usize> = if fortran_order {
+        shape.iter().rev().copied().collect()
+    } else {
+        shape.clone()
+    };
+    let device = &Device::Cpu;
+    let tensor = match kind {
+        "u1" => Tensor::from_vec(data.to_vec(), storage_shape, device),
+        "u4" => Tensor::from_vec(
+            _decode(data, big_endian, u32::from_le_bytes, u32::from_be_bytes),
+            storage_shape,
+            device,
+        ),
+        "i8" => Tensor::from_vec(
+            _decode(data, big_endian, i64::from_le_bytes, i64::from_be_bytes),
+            storage_shape,
+            device,
+        ),
+        "f4" => Tensor::from_vec(
+            _decode(data, big_endian, f32::from_le_bytes, f32::from_be_bytes),
+            storage_shape,
+            device,
+        ),
+        _ => Tensor::from_vec(
+            _decode(data, big_endian, f64::from_le_bytes, f64::from_be_bytes),
+            storage_shape,
+            device,
+        ),
+    }
+    .expect("The data matches the shape");
+
+    if fortran_order && shape.len() > 1 {
+        let dims: Vec<usize> = (0..shape.len()).rev().collect();
+        let tensor = tensor
+            .permute(dims)
+            .and_then(|tensor| tensor.contiguous())
+            .expect("The dimensions are a permutation");
+        return Ok(tensor);
+    }
+    Ok(tensor)
+}
+
+/// Returns the flattened values of `tensor`.
+fn _to_vec<T: candle_core::WithDType>(tensor: &Tensor) -> Vec<T> {
+    tensor.to_vec1::<T>().expect("The tensor is flat")
+}
+
+/// Decodes `data` as a sequence of `N`-byte values in the given byte order.
+fn _decode<T, const N: usize>(
+    data: &[u8],
+    big_endian: bool,
+    from_le: fn([u8; N]) -> T,
+    from_be: fn([u8; N]) -> T,
+) -> Vec<T> {
+    data.chunks_exact(N)
+        .map(|chunk| {
+            let chunk = chunk.try_into().expect("The chunk has N bytes");
+            if big_endian {
+                from_be(chunk)
+            } else {
+                from_le(chunk)
+            }
+        })
+        .collect()
+}
+
+/// Returns the raw value of `key` in the Python dict literal `header`.
+///
+/// The value ends at the next comma outside of parentheses, so that tuples are returned whole.
+fn _header_value<'a>(header: &'a str, key: &str) -> Option<&'a str> {
+    let start = header.find(&format!("'{key}'"))? + key.len() + 2;
+    let value = header[start..].trim_start().strip_prefix(':')?.trim_start();
+    let mut depth = 0;
+    let end = value
+        .char_indices()
+        .find(|&(_, c)| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            depth == 0 && (c == ',' || c == '}')
+        })
+        .map_or(value.len(), |(index, _)| index);
+    Some(value[..end].trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("gymnust-io-{name}.npy"))
+    }
+
+    #[test]
+    fn test_npy_round_trip() {
+        let values = Tensor::new(&[[1.5f32, -2.0, 3.25], [0.0, 4.0, -0.5]], &Device::Cpu).unwrap();
+        let path = temp_path("f32");
+        save_npy(&path, &values).unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        assert_eq!((10 + header_len) % ALIGNMENT, 0);
+
+        let loaded = load_npy(&path).unwrap();
+        assert_eq!(loaded.dims(), [2, 3]);
+        assert_eq!(loaded.dtype(), DType::F32);
+        assert_eq!(
+            loaded.to_vec2::<f32>().unwrap(),
+            values.to_vec2::<f32>().unwrap()
+        );
+
+        let integers = Tensor::new(&[7i64, -3, i64::MAX], &Device::Cpu).unwrap();
+        let path = temp_path("i64");
+        save_npy(&path, &integers).unwrap();
+        let loaded = load_npy(&path).unwrap();
+        assert_eq!(loaded.dtype(), DType::I64);
+        assert_eq!(loaded.to_vec1::<i64>().unwrap(), [7, -3, i64::MAX]);
+
+        let scalar = Tensor::new(2.5f64, &Device::Cpu).unwrap();
+        let path = temp_path("scalar");
+        save_npy(&path, &scalar).unwrap();
+        let loaded = load_npy(&path).unwrap();
+        assert_eq!(loaded.dims(), [] as [usize; 0]);
+        assert_eq!(loaded.to_scalar::<f64>().unwrap(), 2.5);
+    }
+
+    #[test]
+    fn test_load_npy_big_endian_fortran_order() {
+        // The 2x2 matrix [[1, 2], [3, 4]], stored column by column in big-endian.
+        let header = "{'descr': '>i8', 'fortran_order': True, 'shape': (2, 2), }\n";
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend([1, 0]);
+        bytes.extend((header.len() as u16).to_le_bytes());
+        bytes.extend(header.as_bytes());
+        for value in [1i64, 3, 2, 4] {
+            bytes.extend(value.to_be_bytes());
+        }
+        let path = temp_path("big-endian");
+        fs::write(&path, bytes).unwrap();
+
+        let loaded = load_npy(&path).unwrap();
+        assert_eq!(loaded.to_vec2::<i64>().unwrap(), [[1, 2], [3, 4]]);
+    }
+
+    #[test]
+    fn test_npy_unsupported_dtype() {
+        let values = Tensor::zeros(2, DType::F16, &Device::Cpu).unwrap();
+        let err = save_npy(temp_path("f16"), &values).unwrap_err();
+        assert!(matches!(err, NpyError::UnsupportedDtype(dtype) if dtype == "f16"));
+
+        let path = temp_path("invalid");
+        fs::write(&path, b"not a npy file").unwrap();
+        assert!(matches!(
+            load_npy(&path).unwrap_err(),
+            NpyError::InvalidFormat(_)
+        ));
+    }
+}
@@ -0,0 +1,9 @@
+//! Utilities shared across environments, spaces and wrappers.
+
+pub mod colorize;
+pub mod env_checker;
+pub mod io;
+pub mod seeding;
+pub mod testing;
+
+pub use env_checker::data_equivalence;
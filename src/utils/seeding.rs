@@ -0,0 +1,246 @@
+//! Set of random number generator functions: seeding, generator creation and state checkpointing.
+
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256Plus;
+
+/// Random number generator owned by spaces and environments.
+///
+/// With the `serde` feature, generators implement `Serialize` and `Deserialize`.
+pub type Generator = Xoshiro256Plus;
+
+/// Size in bytes of the state of a [`Generator`], as returned by [`save_state`].
+pub const STATE_SIZE: usize = 32;
+
+/// Returns a generator seeded with `seed` together with the seed that was used.
+///
+/// If `seed` is `None`, a seed is drawn from OS entropy. Every `u64`, including `0`, is a valid seed,
+/// and the returned seed always round-trips: `rs_random(Some(seed))` reproduces the same generator.
+pub fn rs_random(seed: Option<u64>) -> (Generator, u64) {
+    let seed = seed.unwrap_or_else(rand::random::<u64>);
+    (Generator::seed_from_u64(seed), seed)
+}
+
+/// A generator that is seeded on first use, as owned by environments.
+///
+/// The generator is seeded from OS entropy when it is first accessed through
+/// [`get`](LazyGenerator::get), unless it was seeded beforehand with
+/// [`reseed`](LazyGenerator::reseed).
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LazyGenerator {
+    generator: Option<Generator>,
+    seed: Option<u64>,
+}
+
+impl LazyGenerator {
+    /// Returns the generator, seeding it from OS entropy if it was not seeded yet.
+    pub fn get(&mut self) -> &mut Generator {
+        let seed = &mut self.seed;
+        self.generator.get_or_insert_with(|| {
+            let (generator, entropy) = rs_random(None);
+            *seed = Some(entropy);
+            generator
+        })
+    }
+
+    /// Replaces the generator with one seeded with `seed`.
+    pub fn reseed(&mut self, seed: u64) {
+        let (generator, seed) = rs_random(Some(seed));
+        self.generator = Some(generator);
+        self.seed = Some(seed);
+    }
+
+    /// The seed of the generator, or `None` if it was not seeded yet.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+}
+
+/// Returns the internal state of `generator`, from which [`restore_state`] recreates a generator
+/// producing exactly the same stream of values.
+///
+/// The state is made of the four `u64` words of the generator, in little-endian order.
+pub fn save_state(generator: &Generator) -> [u8; STATE_SIZE] {
+    // The generator does not expose its state, except through its derived `Debug`
+    // representation, `Xoshiro256Plus { s: [s0, s1, s2, s3] }`.
+    let repr = format!("{generator:?}");
+    let words = repr
+        .split_once('[')
+        .and_then(|(_, rest)| rest.split_once(']'))
+        .map(|(words, _)| words)
+        .expect("The generator state is a list of words");
+
+    let mut state = [0; STATE_SIZE];
+    let mut count = 0;
+    for (chunk, word) in state.chunks_exact_mut(8).zip(words.split(", ")) {
+        let word: u64 = word
+            .parse()
+            .expect("The generator state is made of u64 words");
+        chunk.copy_from_slice(&word.to_le_bytes());
+        count += 1;
+    }
+    assert_eq!(count, 4, "The generator state is made of four words");
+    state
+}
+
+/// Recreates a generator from a state returned by [`save_state`].
+///
+/// # Panics
+///
+/// Panics if `state` is not [`STATE_SIZE`] bytes long.
+pub fn restore_state(state: &[u8]) -> Generator {
+    let state: [u8; STATE_SIZE] = state.try_into().unwrap_or_else(|_| {
+        panic!(
+            "Expected a generator state of {STATE_SIZE} bytes, got {}",
+            state.len()
+        )
+    });
+    Generator::from_seed(state)
+}
+
+/// Splits `base` into `n` generators whose streams do not overlap, e.g. to give each
+/// sub-environment of a vectorized environment an independent generator.
+///
+/// The `i`-th generator is `base` jumped `i` times, each jump being equivalent to 2^128 draws, so
+/// the generators are reproducible from the same `base`.
+pub fn spawn_substreams(base: Generator, n: usize) -> Vec<Generator> {
+    std::iter::successors(Some(base), |generator| {
+        let mut generator = generator.clone();
+        generator.jump();
+        Some(generator)
+    })
+    .take(n)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_rs_random() {
+        let (mut rng, seed) = rs_random(Some(42));
+        assert_eq!(seed, 42);
+
+        let (mut other, _) = rs_random(Some(seed));
+        assert_eq!(rng.gen::<u64>(), other.gen::<u64>());
+    }
+
+    #[test]
+    fn test_rs_random_seed_round_trips() {
+        let (mut rng, seed) = rs_random(None);
+        let (mut other, other_seed) = rs_random(Some(seed));
+        assert_eq!(seed, other_seed);
+
+        let first: Vec<u64> = (0..16).map(|_| rng.gen()).collect();
+        let second: Vec<u64> = (0..16).map(|_| other.gen()).collect();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_rs_random_zero_seed() {
+        let (mut rng, seed) = rs_random(Some(0));
+        let (mut other, _) = rs_random(Some(0));
+        let (mut different, _) = rs_random(Some(1));
+
+        assert_eq!(seed, 0);
+        let first: Vec<u64> = (0..16).map(|_| rng.gen()).collect();
+        assert_eq!(first, (0..16).map(|_| other.gen()).collect::<Vec<u64>>());
+        assert_ne!(
+            first,
+            (0..16).map(|_| different.gen()).collect::<Vec<u64>>()
+        );
+    }
+
+    #[test]
+    fn test_rs_random_seed_above_u32_max() {
+        let seed = u32::MAX as u64 + 12345;
+        let (mut rng, returned) = rs_random(Some(seed));
+        let (mut other, _) = rs_random(Some(seed));
+        let (mut truncated, _) = rs_random(Some(seed as u32 as u64));
+
+        assert_eq!(returned, seed);
+        let first: Vec<u64> = (0..16).map(|_| rng.gen()).collect();
+        assert_eq!(first, (0..16).map(|_| other.gen()).collect::<Vec<u64>>());
+        assert_ne!(
+            first,
+            (0..16).map(|_| truncated.gen()).collect::<Vec<u64>>()
+        );
+    }
+
+    #[test]
+    fn test_lazy_generator() {
+        let mut lazy = LazyGenerator::default();
+        assert_eq!(lazy.seed(), None);
+
+        let first = lazy.get().gen::<u64>();
+        let seed = lazy.seed().unwrap();
+        assert_eq!(first, rs_random(Some(seed)).0.gen::<u64>());
+
+        lazy.reseed(42);
+        assert_eq!(lazy.seed(), Some(42));
+        assert_eq!(lazy.get().gen::<u64>(), rs_random(Some(42)).0.gen::<u64>());
+    }
+
+    #[test]
+    fn test_save_and_restore_state() {
+        let (mut rng, _) = rs_random(Some(42));
+        let _: Vec<u64> = (0..10).map(|_| rng.gen()).collect();
+
+        let state = save_state(&rng);
+        let expected: Vec<f64> = (0..16).map(|_| rng.gen()).collect();
+        let mut restored = restore_state(&state);
+        let actual: Vec<f64> = (0..16).map(|_| restored.gen()).collect();
+        assert_eq!(actual, expected);
+        assert_eq!(restored, rng);
+        assert_eq!(save_state(&restore_state(&state)), state);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected a generator state of 32 bytes, got 3")]
+    fn test_restore_state_wrong_size() {
+        restore_state(&[1, 2, 3]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_generator_serde_round_trip() {
+        let (mut rng, _) = rs_random(Some(7));
+        rng.gen::<u64>();
+
+        let json = serde_json::to_string(&rng).unwrap();
+        let mut deserialized: Generator = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.gen::<u64>(), rng.gen::<u64>());
+    }
+
+    #[test]
+    fn test_spawn_substreams() {
+        let (base, _) = rs_random(Some(3));
+        let draws = |mut generator: Generator| -> Vec<f64> {
+            (0..1000).map(|_| generator.gen::<f64>()).collect()
+        };
+
+        let substreams = spawn_substreams(base.clone(), 3);
+        assert_eq!(substreams.len(), 3);
+        assert_eq!(substreams[0], base);
+        assert_eq!(spawn_substreams(base.clone(), 3), substreams);
+
+        let first = draws(substreams[1].clone());
+        let second = draws(substreams[2].clone());
+        assert_eq!(first, draws(spawn_substreams(base, 2).pop().unwrap()));
+        assert!(first.iter().zip(&second).all(|(a, b)| a != b));
+
+        // Independent uniform streams are uncorrelated.
+        let mean = |x: &[f64]| x.iter().sum::<f64>() / x.len() as f64;
+        let (mean_first, mean_second) = (mean(&first), mean(&second));
+        let covariance = first
+            .iter()
+            .zip(&second)
+            .map(|(a, b)| (a - mean_first) * (b - mean_second))
+            .sum::<f64>()
+            / first.len() as f64;
+        let correlation = covariance / (1.0 / 12.0);
+        assert!(correlation.abs() < 0.1, "correlation {correlation}");
+    }
+}
@@ -0,0 +1,145 @@
+//! Helpers for testing environment implementations.
+
+use crate::core::Env;
+use crate::spaces::{Space, StructuredSample};
+use crate::utils::data_equivalence;
+
+/// Asserts that environments created by `env_factory` are deterministic given `seed`.
+///
+/// Two environments are created and reset with `seed`, then take the same `steps` actions, sampled
+/// from the action space seeded with `seed`. Both environments are reset without a seed whenever
+/// an episode ends. Their observations, rewards, terminations and truncations must match exactly,
+/// as compared by [`data_equivalence`].
+///
+/// # Panics
+///
+/// Panics if the environments diverge, or if a sample of the action space cannot be converted to
+/// `ActType`.
+pub fn assert_reproducible<ObsType, ActType, F>(env_factory: F, seed: u64, steps: usize)
+where
+    ObsType: Into<StructuredSample>,
+    ActType: TryFrom<StructuredSample>,
+    F: Fn() -> Box<dyn Env<ObsType, ActType>>,
+{
+    let mut a = env_factory();
+    let mut b = env_factory();
+    let mut action_space = a.action_space().clone();
+    action_space.seed(Some(seed));
+
+    let (observation_a, _) = a.reset(Some(seed), None);
+    let (observation_b, _) = b.reset(Some(seed), None);
+    assert!(
+        data_equivalence(&observation_a.into(), &observation_b.into(), None),
+        "The observations of `reset` with the seed {seed} differ"
+    );
+
+    for step in 0..steps {
+        let action = action_space.sample(None);
+        let convert = |action| {
+            ActType::try_from(action)
+                .unwrap_or_else(|_| panic!("Action samples must convert to the action type"))
+        };
+        let result_a = a.step(convert(action.clone()));
+        let result_b = b.step(convert(action));
+
+        assert!(
+            data_equivalence(
+                &result_a.observation.into(),
+                &result_b.observation.into(),
+                None
+            ),
+            "The observations of step {step} differ"
+        );
+        assert_eq!(
+            result_a.reward.to_bits(),
+            result_b.reward.to_bits(),
+            "The rewards of step {step} differ"
+        );
+        assert_eq!(
+            (result_a.terminated, result_a.truncated),
+            (result_b.terminated, result_b.truncated),
+            "The terminations or truncations of step {step} differ"
+        );
+
+        if result_a.terminated || result_a.truncated {
+            let (observation_a, _) = a.reset(None, None);
+            let (observation_b, _) = b.reset(None, None);
+            assert!(
+                data_equivalence(&observation_a.into(), &observation_b.into(), None),
+                "The observations of `reset` after step {step} differ"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::assert_reproducible;
+    use crate::common::InfoMap;
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Discrete, SpaceEnum};
+    use crate::utils::seeding::{rs_random, LazyGenerator};
+
+    /// Walks randomly on `{0, ..., 9}`, moving by the action minus one plus a random step, and
+    /// terminating at either end.
+    struct RandomWalkEnv {
+        position: i64,
+        rs_random: LazyGenerator,
+        /// Whether `reset` ignores its seed, drawing from OS entropy instead.
+        ignore_seed: bool,
+        action_space: SpaceEnum,
+        observation_space: SpaceEnum,
+    }
+
+    impl RandomWalkEnv {
+        fn boxed(ignore_seed: bool) -> Box<dyn Env<i64, i64>> {
+            Box::new(Self {
+                position: 0,
+                rs_random: LazyGenerator::default(),
+                ignore_seed,
+                action_space: Discrete::new(3, 0, None).into(),
+                observation_space: Discrete::new(10, 0, None).into(),
+            })
+        }
+    }
+
+    impl Env<i64, i64> for RandomWalkEnv {
+        fn step(&mut self, action: i64) -> StepResult<i64> {
+            let noise = self.rs_random.get().gen_range(-1..=1);
+            self.position = (self.position + action - 1 + noise).clamp(0, 9);
+            let terminated = self.position == 0 || self.position == 9;
+            (self.position, 1.0, terminated, false, InfoMap::new()).into()
+        }
+
+        fn reset(&mut self, seed: Option<u64>, _options: Option<&InfoMap>) -> (i64, InfoMap) {
+            if self.ignore_seed {
+                self.rs_random.reseed(rs_random(None).1);
+            } else if let Some(seed) = seed {
+                self.rs_random.reseed(seed);
+            }
+            self.position = self.rs_random.get().gen_range(3..=6);
+            (self.position, InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.action_space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.observation_space
+        }
+    }
+
+    #[test]
+    fn test_assert_reproducible() {
+        assert_reproducible(|| RandomWalkEnv::boxed(false), 42, 200);
+    }
+
+    #[test]
+    #[should_panic(expected = "differ")]
+    fn test_assert_reproducible_detects_divergence() {
+        assert_reproducible(|| RandomWalkEnv::boxed(true), 42, 200);
+    }
+}
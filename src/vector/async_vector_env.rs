@@ -0,0 +1,543 @@
+//! A vectorized environment that runs multiple environments in parallel worker threads.
+
+use std::marker::PhantomData;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+use candle_core::Tensor;
+use thiserror::Error;
+
+use crate::common::InfoMap;
+use crate::core::{Env, StepResult};
+use crate::spaces::utils::{batch_space, create_empty_array, iterate, BatchError};
+use crate::spaces::{SpaceEnum, StructuredSample};
+use crate::vector::utils::{check_spaces, stack_samples, step_autoreset, vector_step_result};
+use crate::vector::{VectorEnv, VectorStepResult};
+
+/// A function creating a sub-environment within its worker thread.
+pub type EnvFn<ObsType, ActType> = Box<dyn FnOnce() -> Box<dyn Env<ObsType, ActType>> + Send>;
+
+/// Error returned by the methods of [`AsyncVectorEnv`].
+#[derive(Debug, Error)]
+pub enum AsyncVectorEnvError {
+    #[error("The worker of sub-environment {index} panicked: {message}")]
+    WorkerPanicked { index: usize, message: String },
+    #[error("The environment is closed")]
+    Closed,
+}
+
+/// A command sent to a worker.
+enum Command {
+    Reset {
+        seed: Option<u64>,
+        options: Option<InfoMap>,
+    },
+    Step(StructuredSample),
+    Close,
+}
+
+/// A response sent back by a worker.
+enum Response {
+    /// The observation and action spaces of the sub-environment, sent once it is created.
+    Spaces(SpaceEnum, SpaceEnum),
+    Reset(StructuredSample, InfoMap),
+    Step(StepResult<StructuredSample>),
+}
+
+/// A worker thread owning a sub-environment.
+struct Worker {
+    commands: Sender<Command>,
+    responses: Receiver<Response>,
+    handle: Option<JoinHandle<()>>,
+}
+
+/// Vectorized environment that runs multiple environments in parallel, each on its own thread.
+///
+/// The sub-environments are created within their worker threads, so that they need not be `Send`.
+/// Commands are fanned out to every worker before their results are gathered, and the results are
+/// batched as in [`SyncVectorEnv`](crate::vector::SyncVectorEnv), including the autoreset of the
+/// sub-environments that terminate or truncate.
+///
+/// If a worker panics, the panic is returned as an [`AsyncVectorEnvError::WorkerPanicked`] and the
+/// environment is closed.
+pub struct AsyncVectorEnv<ObsType, ActType> {
+    workers: Vec<Worker>,
+    single_observation_space: SpaceEnum,
+    single_action_space: SpaceEnum,
+    observation_space: SpaceEnum,
+    action_space: SpaceEnum,
+    closed: bool,
+    _env: PhantomData<fn(ActType) -> ObsType>,
+}
+
+impl<ObsType, ActType> AsyncVectorEnv<ObsType, ActType>
+where
+    ObsType: Into<StructuredSample> + 'static,
+    ActType: TryFrom<StructuredSample> + 'static,
+{
+    /// Vectorizes the environments created by `env_fns`, whose observations and actions are
+    /// elements of `observation_space` and `action_space` respectively.
+    ///
+    /// Returns an error if `env_fns` is empty, if the spaces of one of the created environments
+    /// differ from the given ones, or if either space cannot be batched.
+    pub fn new(
+        env_fns: Vec<EnvFn<ObsType, ActType>>,
+        observation_space: SpaceEnum,
+        action_space: SpaceEnum,
+    ) -> Result<Self, BatchError> {
+        if env_fns.is_empty() {
+            return Err(BatchError::NoEnvs);
+        }
+        let num_envs = env_fns.len();
+        // Fails early for observation spaces whose elements cannot be stacked.
+        create_empty_array(&observation_space, num_envs)?;
+        let observation_space_batched = batch_space(&observation_space, num_envs)?;
+        let action_space_batched = batch_space(&action_space, num_envs)?;
+
+        let workers = env_fns
+            .into_iter()
+            .map(|env_fn| {
+                let (commands, command_receiver) = channel();
+                let (response_sender, responses) = channel();
+                let handle =
+                    thread::spawn(move || worker(env_fn, command_receiver, response_sender));
+                Worker {
+                    commands,
+                    responses,
+                    handle: Some(handle),
+                }
+            })
+            .collect();
+
+        // The environment is dropped, closing the workers, if the spaces do not match.
+        let env = Self {
+            workers,
+            single_observation_space: observation_space,
+            single_action_space: action_space,
+            observation_space: observation_space_batched,
+            action_space: action_space_batched,
+            closed: false,
+            _env: PhantomData,
+        };
+        for (index, worker) in env.workers.iter().enumerate() {
+            // A worker that panicked while creating its environment is reported by the next call.
+            if let Ok(Response::Spaces(observation_space, action_space)) = worker.responses.recv() {
+                let spaces = (&observation_space, &action_space);
+                check_spaces(
+                    index,
+                    spaces,
+                    &env.single_observation_space,
+                    &env.single_action_space,
+                )?;
+            }
+        }
+        Ok(env)
+    }
+
+    /// The number of sub-environments.
+    pub fn num_envs(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// The observation space of a single sub-environment.
+    pub fn single_observation_space(&self) -> &SpaceEnum {
+        &self.single_observation_space
+    }
+
+    /// The action space of a single sub-environment.
+    pub fn single_action_space(&self) -> &SpaceEnum {
+        &self.single_action_space
+    }
+
+    /// The batched observation space of all sub-environments.
+    pub fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+
+    /// The batched action space of all sub-environments.
+    pub fn action_space(&self) -> &SpaceEnum {
+        &self.action_space
+    }
+
+    /// Resets every sub-environment, returning the stacked observations and the info of each.
+    ///
+    /// If `seed` is given, the `i`-th sub-environment is reset with the seed `seed + i`.
+    pub fn reset(
+        &mut self,
+        seed: Option<u64>,
+        options: Option<&InfoMap>,
+    ) -> Result<(Tensor, Vec<InfoMap>), AsyncVectorEnvError> {
+        let commands = (0..self.num_envs())
+            .map(|i| Command::Reset {
+                seed: seed.map(|seed| seed.wrapping_add(i as u64)),
+                options: options.cloned(),
+            })
+            .collect();
+
+        let (observations, infos): (Vec<_>, Vec<_>) = self
+            .call(commands)?
+            .into_iter()
+            .map(|response| match response {
+                Response::Reset(observation, info) => (observation, info),
+                _ => unreachable!("Workers respond to a reset with a reset"),
+            })
+            .unzip();
+        let observations = stack_samples(&self.single_observation_space, &observations)
+            .expect("Observations must be elements of the observation space");
+        Ok((observations, infos))
+    }
+
+    /// Steps every sub-environment with its action from the batched `actions`, resetting those
+    /// that terminate or truncate.
+    pub fn step(&mut self, actions: &Tensor) -> Result<VectorStepResult, AsyncVectorEnvError> {
+        let actions: Vec<_> = iterate(
+            &self.single_action_space,
+            &StructuredSample::Tensor(actions.clone()),
+        )
+        .expect("Actions must be an element of the batched action space")
+        .collect();
+        assert_eq!(
+            actions.len(),
+            self.num_envs(),
+            "Expected one action per sub-environment"
+        );
+
+        let results = self
+            .call(actions.into_iter().map(Command::Step).collect())?
+            .into_iter()
+            .map(|response| match response {
+                Response::Step(result) => result,
+                _ => unreachable!("Workers respond to a step with a step"),
+            })
+            .collect();
+        Ok(vector_step_result(&self.single_observation_space, results))
+    }
+}
+
+impl<ObsType, ActType> AsyncVectorEnv<ObsType, ActType> {
+    /// Closes every sub-environment and joins the worker threads.
+    ///
+    /// Closing an already closed environment does nothing.
+    pub fn close(&mut self) {
+        if self.closed {
+            return;
+        }
+        self.closed = true;
+        for worker in &mut self.workers {
+            // A worker that has exited no longer receives commands.
+            let _ = worker.commands.send(Command::Close);
+        }
+        for worker in &mut self.workers {
+            if let Some(handle) = worker.handle.take() {
+                let _ = handle.join();
+            }
+        }
+    }
+
+    /// Sends one command to each worker, then gathers their responses.
+    fn call(&mut self, commands: Vec<Command>) -> Result<Vec<Response>, AsyncVectorEnvError> {
+        if self.closed {
+            return Err(AsyncVectorEnvError::Closed);
+        }
+
+        let mut failed = None;
+        for (index, (worker, command)) in self.workers.iter().zip(commands).enumerate() {
+            if worker.commands.send(command).is_err() {
+                failed = failed.or(Some(index));
+            }
+        }
+        let mut responses = Vec::with_capacity(self.workers.len());
+        if failed.is_none() {
+            for (index, worker) in self.workers.iter().enumerate() {
+                match worker.responses.recv() {
+                    Ok(response) => responses.push(response),
+                    Err(_) => {
+                        failed = Some(index);
+                        break;
+                    }
+                }
+            }
+        }
+
+        match failed {
+            None => Ok(responses),
+            Some(index) => {
+                let message = self.workers[index]
+                    .handle
+                    .take()
+                    .map(|handle| match handle.join() {
+                        Ok(()) => "The worker exited".to_string(),
+                        Err(payload) => panic_message(payload),
+                    })
+                    .unwrap_or_else(|| "The worker exited".to_string());
+                self.close();
+                Err(AsyncVectorEnvError::WorkerPanicked { index, message })
+            }
+        }
+    }
+}
+
+impl<ObsType, ActType> Drop for AsyncVectorEnv<ObsType, ActType> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+impl<ObsType, ActType> VectorEnv<ObsType, ActType> for AsyncVectorEnv<ObsType, ActType>
+where
+    ObsType: Into<StructuredSample> + 'static,
+    ActType: TryFrom<StructuredSample> + 'static,
+{
+    type Error = AsyncVectorEnvError;
+
+    fn num_envs(&self) -> usize {
+        self.workers.len()
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+
+    fn action_space(&self) -> &SpaceEnum {
+        &self.action_space
+    }
+
+    fn reset(
+        &mut self,
+        seed: Option<u64>,
+        options: Option<&InfoMap>,
+    ) -> Result<(Tensor, Vec<InfoMap>), AsyncVectorEnvError> {
+        AsyncVectorEnv::reset(self, seed, options)
+    }
+
+    fn step(&mut self, actions: &Tensor) -> Result<VectorStepResult, AsyncVectorEnvError> {
+        AsyncVectorEnv::step(self, actions)
+    }
+
+    fn close(&mut self) {
+        AsyncVectorEnv::close(self)
+    }
+}
+
+/// Runs the sub-environment created by `env_fn`, answering `commands` until it is closed.
+fn worker<ObsType, ActType>(
+    env_fn: EnvFn<ObsType, ActType>,
+    commands: Receiver<Command>,
+    responses: Sender<Response>,
+) where
+    ObsType: Into<StructuredSample>,
+    ActType: TryFrom<StructuredSample>,
+{
+    let mut env = env_fn();
+    let spaces = Response::Spaces(env.observation_space().clone(), env.action_space().clone());
+    if responses.send(spaces).is_err() {
+        return;
+    }
+    for command in commands {
+        let response = match command {
+            Command::Reset { seed, options } => {
+                let (observation, info) = env.reset(seed, options.as_ref());
+                Response::Reset(observation.into(), info)
+            }
+            Command::Step(action) => Response::Step(step_autoreset(env.as_mut(), action)),
+            Command::Close => break,
+        };
+        if responses.send(response).is_err() {
+            break;
+        }
+    }
+    env.close();
+}
+
+/// Extracts the message of a panic from its payload.
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "Unknown panic".to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::{Device, Tensor};
+
+    use super::{AsyncVectorEnv, AsyncVectorEnvError, EnvFn};
+    use crate::common::InfoMap;
+    use crate::core::{Env, StepResult};
+    use crate::envs::classic_control::CartPole;
+    use crate::spaces::utils::BatchError;
+    use crate::spaces::{Discrete, Space, SpaceEnum, StructuredSample};
+    use crate::vector::SyncVectorEnv;
+
+    fn spaces() -> (SpaceEnum, SpaceEnum) {
+        let env = CartPole::default();
+        (env.observation_space().clone(), env.action_space().clone())
+    }
+
+    fn cartpole_fns(num_envs: usize) -> Vec<EnvFn<Tensor, i64>> {
+        (0..num_envs)
+            .map(|_| {
+                Box::new(|| Box::new(CartPole::default()) as Box<dyn Env<Tensor, i64>>)
+                    as EnvFn<Tensor, i64>
+            })
+            .collect()
+    }
+
+    /// Panics on its third step.
+    struct PanickingEnv {
+        steps: i64,
+        space: SpaceEnum,
+    }
+
+    impl Env<i64, i64> for PanickingEnv {
+        fn step(&mut self, _action: i64) -> StepResult<i64> {
+            self.steps += 1;
+            assert!(self.steps < 3, "The environment broke");
+            (self.steps, 0.0, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (i64, InfoMap) {
+            self.steps = 0;
+            (self.steps, InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    #[test]
+    fn test_async_vector_env_matches_sync() {
+        let (observation_space, action_space) = spaces();
+        let mut async_env = AsyncVectorEnv::new(
+            cartpole_fns(4),
+            observation_space.clone(),
+            action_space.clone(),
+        )
+        .unwrap();
+        let envs = (0..4)
+            .map(|_| Box::new(CartPole::default()) as Box<dyn Env<Tensor, i64>>)
+            .collect();
+        let mut sync_env = SyncVectorEnv::new(envs, observation_space, action_space).unwrap();
+        assert_eq!(async_env.num_envs(), 4);
+        assert_eq!(async_env.observation_space(), sync_env.observation_space());
+
+        let (observations, infos) = async_env.reset(Some(7), None).unwrap();
+        let (expected, _) = sync_env.reset(Some(7), None);
+        assert_eq!(infos.len(), 4);
+        assert_eq!(
+            observations.to_vec2::<f32>().unwrap(),
+            expected.to_vec2::<f32>().unwrap()
+        );
+
+        let mut action_space = async_env.action_space().clone();
+        action_space.seed(Some(0));
+        for _ in 0..100 {
+            let StructuredSample::Tensor(actions) = action_space.sample(None) else {
+                unreachable!("A batched Discrete space is a MultiDiscrete space");
+            };
+            let result = async_env.step(&actions).unwrap();
+            let expected = sync_env.step(&actions);
+            assert_eq!(
+                result.observations.to_vec2::<f32>().unwrap(),
+                expected.observations.to_vec2::<f32>().unwrap()
+            );
+            for (result, expected) in [
+                (&result.terminated, &expected.terminated),
+                (&result.truncated, &expected.truncated),
+            ] {
+                assert_eq!(
+                    result.to_vec1::<u8>().unwrap(),
+                    expected.to_vec1::<u8>().unwrap()
+                );
+            }
+            assert_eq!(
+                result.rewards.to_vec1::<f32>().unwrap(),
+                expected.rewards.to_vec1::<f32>().unwrap()
+            );
+            for (info, expected) in result.infos.iter().zip(&expected.infos) {
+                assert_eq!(
+                    info.contains_key("final_observation"),
+                    expected.contains_key("final_observation")
+                );
+            }
+        }
+        async_env.close();
+    }
+
+    #[test]
+    fn test_async_vector_env_worker_panic() {
+        let env_fns = (0..2)
+            .map(|_| {
+                Box::new(|| {
+                    let env = PanickingEnv {
+                        steps: 0,
+                        space: Discrete::new(10, 0, None).into(),
+                    };
+                    Box::new(env) as Box<dyn Env<i64, i64>>
+                }) as EnvFn<i64, i64>
+            })
+            .collect();
+        let space = SpaceEnum::from(Discrete::new(10, 0, None));
+        let mut env = AsyncVectorEnv::new(env_fns, space.clone(), space).unwrap();
+        env.reset(None, None).unwrap();
+
+        let actions = Tensor::new(&[0i64, 0], &Device::Cpu).unwrap();
+        assert!(env.step(&actions).is_ok());
+        assert!(env.step(&actions).is_ok());
+        let error = env.step(&actions).unwrap_err();
+        assert!(matches!(
+            &error,
+            AsyncVectorEnvError::WorkerPanicked { index: 0, message }
+                if message == "The environment broke"
+        ));
+        assert!(matches!(
+            env.step(&actions),
+            Err(AsyncVectorEnvError::Closed)
+        ));
+    }
+
+    #[test]
+    fn test_async_vector_env_rejects_no_envs() {
+        let (observation_space, action_space) = spaces();
+        let result = AsyncVectorEnv::<Tensor, i64>::new(vec![], observation_space, action_space);
+        assert!(matches!(result, Err(BatchError::NoEnvs)));
+    }
+
+    #[test]
+    fn test_async_vector_env_rejects_mismatched_spaces() {
+        let (observation_space, _) = spaces();
+        let action_space = Discrete::new(3, 0, None).into();
+        let result = AsyncVectorEnv::new(cartpole_fns(2), observation_space, action_space);
+        assert!(matches!(
+            result,
+            Err(BatchError::SpaceMismatch {
+                index: 0,
+                space: "action"
+            })
+        ));
+    }
+
+    #[test]
+    fn test_async_vector_env_close() {
+        let (observation_space, action_space) = spaces();
+        let mut env =
+            AsyncVectorEnv::new(cartpole_fns(2), observation_space, action_space).unwrap();
+        env.reset(Some(0), None).unwrap();
+        env.close();
+        env.close();
+
+        assert!(env.workers.iter().all(|worker| worker.handle.is_none()));
+        assert!(matches!(
+            env.reset(None, None),
+            Err(AsyncVectorEnvError::Closed)
+        ));
+    }
+}
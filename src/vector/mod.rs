@@ -0,0 +1,127 @@
+//! Vectorized environments, running multiple copies of an environment in lockstep.
+
+use candle_core::Tensor;
+
+use crate::common::InfoMap;
+use crate::spaces::SpaceEnum;
+
+pub mod async_vector_env;
+pub mod sync_vector_env;
+pub mod utils;
+
+pub use async_vector_env::AsyncVectorEnv;
+pub use sync_vector_env::SyncVectorEnv;
+
+/// The result of stepping every environment of a vectorized environment.
+#[derive(Debug, Clone)]
+pub struct VectorStepResult {
+    /// The next observations of the environments, stacked along the first dimension.
+    pub observations: Tensor,
+    /// The rewards of the environments as an `F32` tensor of shape `(num_envs,)`.
+    pub rewards: Tensor,
+    /// Whether each environment terminated, as a `U8` tensor of shape `(num_envs,)`.
+    pub terminated: Tensor,
+    /// Whether each environment was truncated, as a `U8` tensor of shape `(num_envs,)`.
+    pub truncated: Tensor,
+    /// The info of each environment.
+    ///
+    /// The info of an environment that ended and was reset holds its last observation under
+    /// `"final_observation"` and the info of its last step under `"final_info"`.
+    pub infos: Vec<InfoMap>,
+}
+
+/// The API of vectorized environments, shared by [`SyncVectorEnv`] and [`AsyncVectorEnv`] so that
+/// agents can be written independently of how the sub-environments are run.
+///
+/// `ObsType` and `ActType` are the observation and action types of a single sub-environment. The
+/// observations and actions of all the sub-environments are batched into tensors with a leading
+/// dimension of size [`num_envs`](VectorEnv::num_envs).
+pub trait VectorEnv<ObsType, ActType> {
+    /// The error returned when the sub-environments cannot be reset or stepped.
+    type Error: std::error::Error;
+
+    /// The number of sub-environments.
+    fn num_envs(&self) -> usize;
+
+    /// The batched observation space of all sub-environments.
+    fn observation_space(&self) -> &SpaceEnum;
+
+    /// The batched action space of all sub-environments.
+    fn action_space(&self) -> &SpaceEnum;
+
+    /// Resets every sub-environment, returning the stacked observations and the info of each.
+    ///
+    /// If `seed` is given, the `i`-th sub-environment is reset with the seed `seed + i`.
+    fn reset(
+        &mut self,
+        seed: Option<u64>,
+        options: Option<&InfoMap>,
+    ) -> Result<(Tensor, Vec<InfoMap>), Self::Error>;
+
+    /// Steps every sub-environment with its action from the batched `actions`, resetting those
+    /// that terminate or truncate.
+    fn step(&mut self, actions: &Tensor) -> Result<VectorStepResult, Self::Error>;
+
+    /// Closes every sub-environment.
+    fn close(&mut self);
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::Tensor;
+
+    use super::{AsyncVectorEnv, SyncVectorEnv, VectorEnv};
+    use crate::core::Env;
+    use crate::envs::classic_control::CartPole;
+    use crate::spaces::{Space, SpaceEnum, StructuredSample};
+
+    fn spaces() -> (SpaceEnum, SpaceEnum) {
+        let env = CartPole::default();
+        (env.observation_space().clone(), env.action_space().clone())
+    }
+
+    /// Runs a few random steps through any vectorized environment of `CartPole`s.
+    fn check_vector_env<V: VectorEnv<Tensor, i64>>(env: &mut V) -> Vec<Vec<f32>> {
+        let num_envs = env.num_envs();
+        let (observations, infos) = env.reset(Some(3), None).unwrap();
+        assert_eq!(observations.dims(), [num_envs, 4]);
+        assert_eq!(infos.len(), num_envs);
+
+        let mut action_space = env.action_space().clone();
+        action_space.seed(Some(3));
+        let mut trajectory = Vec::new();
+        for _ in 0..20 {
+            let StructuredSample::Tensor(actions) = action_space.sample(None) else {
+                unreachable!("A batched Discrete space is a MultiDiscrete space");
+            };
+            let result = env.step(&actions).unwrap();
+            assert!(env
+                .observation_space()
+                .contains(&StructuredSample::Tensor(result.observations.clone())));
+            assert_eq!(result.rewards.dims(), [num_envs]);
+            assert_eq!(result.infos.len(), num_envs);
+            trajectory.extend(result.observations.to_vec2::<f32>().unwrap());
+        }
+        env.close();
+        trajectory
+    }
+
+    #[test]
+    fn test_vector_env() {
+        let (observation_space, action_space) = spaces();
+        let envs = (0..3)
+            .map(|_| Box::new(CartPole::default()) as Box<dyn Env<Tensor, i64>>)
+            .collect();
+        let mut sync_env =
+            SyncVectorEnv::new(envs, observation_space.clone(), action_space.clone()).unwrap();
+        let env_fns = (0..3)
+            .map(|_| Box::new(|| Box::new(CartPole::default()) as Box<dyn Env<Tensor, i64>>) as _)
+            .collect();
+        let mut async_env = AsyncVectorEnv::new(env_fns, observation_space, action_space).unwrap();
+
+        assert_eq!(
+            check_vector_env(&mut sync_env),
+            check_vector_env(&mut async_env)
+        );
+    }
+}
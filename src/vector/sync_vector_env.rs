@@ -0,0 +1,314 @@
+//! A vectorized environment that serially runs multiple environments.
+
+use std::convert::Infallible;
+
+use candle_core::Tensor;
+
+use crate::common::InfoMap;
+use crate::core::Env;
+use crate::spaces::utils::{batch_space, create_empty_array, iterate, BatchError};
+use crate::spaces::{SpaceEnum, StructuredSample};
+use crate::vector::utils::{check_spaces, stack_samples, step_autoreset, vector_step_result};
+use crate::vector::{VectorEnv, VectorStepResult};
+
+/// Vectorized environment that serially runs multiple environments.
+///
+/// The observations of the sub-environments are stacked along a new first dimension, and the
+/// batched actions are split along their first dimension, one per sub-environment. Only `Box` and
+/// `Discrete` observation spaces can be stacked.
+///
+/// A sub-environment that terminates or truncates is reset within the same step: the returned
+/// observation is the first of its next episode, while its last observation and info are kept
+/// under `"final_observation"` and `"final_info"` of its info.
+pub struct SyncVectorEnv<ObsType, ActType> {
+    envs: Vec<Box<dyn Env<ObsType, ActType>>>,
+    single_observation_space: SpaceEnum,
+    single_action_space: SpaceEnum,
+    observation_space: SpaceEnum,
+    action_space: SpaceEnum,
+}
+
+impl<ObsType, ActType> SyncVectorEnv<ObsType, ActType>
+where
+    ObsType: Into<StructuredSample>,
+    ActType: TryFrom<StructuredSample>,
+{
+    /// Vectorizes `envs`, whose observations and actions are elements of `observation_space` and
+    /// `action_space` respectively.
+    ///
+    /// Returns an error if `envs` is empty, if the spaces of one of `envs` differ from the given
+    /// ones, or if either space cannot be batched.
+    pub fn new(
+        envs: Vec<Box<dyn Env<ObsType, ActType>>>,
+        observation_space: SpaceEnum,
+        action_space: SpaceEnum,
+    ) -> Result<Self, BatchError> {
+        if envs.is_empty() {
+            return Err(BatchError::NoEnvs);
+        }
+        for (index, env) in envs.iter().enumerate() {
+            let spaces = (env.observation_space(), env.action_space());
+            check_spaces(index, spaces, &observation_space, &action_space)?;
+        }
+        let num_envs = envs.len();
+        // Fails early for observation spaces whose elements cannot be stacked.
+        create_empty_array(&observation_space, num_envs)?;
+
+        Ok(Self {
+            observation_space: batch_space(&observation_space, num_envs)?,
+            action_space: batch_space(&action_space, num_envs)?,
+            single_observation_space: observation_space,
+            single_action_space: action_space,
+            envs,
+        })
+    }
+
+    /// The number of sub-environments.
+    pub fn num_envs(&self) -> usize {
+        self.envs.len()
+    }
+
+    /// The observation space of a single sub-environment.
+    pub fn single_observation_space(&self) -> &SpaceEnum {
+        &self.single_observation_space
+    }
+
+    /// The action space of a single sub-environment.
+    pub fn single_action_space(&self) -> &SpaceEnum {
+        &self.single_action_space
+    }
+
+    /// The batched observation space of all sub-environments.
+    pub fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+
+    /// The batched action space of all sub-environments.
+    pub fn action_space(&self) -> &SpaceEnum {
+        &self.action_space
+    }
+
+    /// Resets every sub-environment, returning the stacked observations and the info of each.
+    ///
+    /// If `seed` is given, the `i`-th sub-environment is reset with the seed `seed + i`.
+    pub fn reset(
+        &mut self,
+        seed: Option<u64>,
+        options: Option<&InfoMap>,
+    ) -> (Tensor, Vec<InfoMap>) {
+        let (observations, infos): (Vec<_>, Vec<_>) = self
+            .envs
+            .iter_mut()
+            .enumerate()
+            .map(|(i, env)| {
+                let (observation, info) =
+                    env.reset(seed.map(|seed| seed.wrapping_add(i as u64)), options);
+                (observation.into(), info)
+            })
+            .unzip();
+
+        (self.stack(&observations), infos)
+    }
+
+    /// Steps every sub-environment with its action from the batched `actions`, resetting those
+    /// that terminate or truncate.
+    pub fn step(&mut self, actions: &Tensor) -> VectorStepResult {
+        let actions: Vec<_> = iterate(
+            &self.single_action_space,
+            &StructuredSample::Tensor(actions.clone()),
+        )
+        .expect("Actions must be an element of the batched action space")
+        .collect();
+        assert_eq!(
+            actions.len(),
+            self.num_envs(),
+            "Expected one action per sub-environment"
+        );
+
+        let results = self
+            .envs
+            .iter_mut()
+            .zip(actions)
+            .map(|(env, action)| step_autoreset(env.as_mut(), action))
+            .collect();
+        vector_step_result(&self.single_observation_space, results)
+    }
+
+    /// Closes every sub-environment.
+    pub fn close(&mut self) {
+        self.envs.iter_mut().for_each(|env| env.close());
+    }
+
+    fn stack(&self, observations: &[StructuredSample]) -> Tensor {
+        stack_samples(&self.single_observation_space, observations)
+            .expect("Observations must be elements of the observation space")
+    }
+}
+
+impl<ObsType, ActType> VectorEnv<ObsType, ActType> for SyncVectorEnv<ObsType, ActType>
+where
+    ObsType: Into<StructuredSample>,
+    ActType: TryFrom<StructuredSample>,
+{
+    type Error = Infallible;
+
+    fn num_envs(&self) -> usize {
+        self.envs.len()
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+
+    fn action_space(&self) -> &SpaceEnum {
+        &self.action_space
+    }
+
+    fn reset(
+        &mut self,
+        seed: Option<u64>,
+        options: Option<&InfoMap>,
+    ) -> Result<(Tensor, Vec<InfoMap>), Infallible> {
+        Ok(SyncVectorEnv::reset(self, seed, options))
+    }
+
+    fn step(&mut self, actions: &Tensor) -> Result<VectorStepResult, Infallible> {
+        Ok(SyncVectorEnv::step(self, actions))
+    }
+
+    fn close(&mut self) {
+        SyncVectorEnv::close(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::Tensor;
+
+    use super::SyncVectorEnv;
+    use crate::common::InfoValue;
+    use crate::core::Env;
+    use crate::envs::classic_control::CartPole;
+    use crate::spaces::utils::BatchError;
+    use crate::spaces::{Discrete, Space, StructuredSample};
+
+    fn cartpoles(num_envs: usize) -> SyncVectorEnv<Tensor, i64> {
+        let env = CartPole::default();
+        let observation_space = env.observation_space().clone();
+        let action_space = env.action_space().clone();
+        let envs = (0..num_envs)
+            .map(|_| Box::new(CartPole::default()) as Box<dyn Env<Tensor, i64>>)
+            .collect();
+        SyncVectorEnv::new(envs, observation_space, action_space).unwrap()
+    }
+
+    fn sample_actions(env: &SyncVectorEnv<Tensor, i64>, seed: u64) -> Tensor {
+        let mut action_space = env.action_space().clone();
+        action_space.seed(Some(seed));
+        match action_space.sample(None) {
+            StructuredSample::Tensor(actions) => actions,
+            _ => unreachable!("A batched Discrete space is a MultiDiscrete space"),
+        }
+    }
+
+    #[test]
+    fn test_sync_vector_env_shapes() {
+        let mut env = cartpoles(4);
+        assert_eq!(env.num_envs(), 4);
+
+        let (observations, infos) = env.reset(Some(0), None);
+        assert_eq!(observations.dims(), [4, 4]);
+        assert_eq!(infos.len(), 4);
+        assert!(env
+            .observation_space()
+            .contains(&StructuredSample::Tensor(observations)));
+
+        let result = env.step(&sample_actions(&env, 0));
+        assert_eq!(result.observations.dims(), [4, 4]);
+        assert_eq!(result.rewards.dims(), [4]);
+        assert_eq!(result.terminated.dims(), [4]);
+        assert_eq!(result.truncated.dims(), [4]);
+        assert_eq!(result.infos.len(), 4);
+        assert_eq!(result.rewards.to_vec1::<f32>().unwrap(), [1.0; 4]);
+    }
+
+    #[test]
+    fn test_sync_vector_env_rejects_no_envs() {
+        let env = CartPole::default();
+        let (observation_space, action_space) = (env.observation_space(), env.action_space());
+        let result = SyncVectorEnv::<Tensor, i64>::new(
+            vec![],
+            observation_space.clone(),
+            action_space.clone(),
+        );
+        assert!(matches!(result, Err(BatchError::NoEnvs)));
+    }
+
+    #[test]
+    fn test_sync_vector_env_rejects_mismatched_spaces() {
+        let env = CartPole::default();
+        let observation_space = env.observation_space().clone();
+        let envs = (0..2)
+            .map(|_| Box::new(CartPole::default()) as Box<dyn Env<Tensor, i64>>)
+            .collect();
+        let action_space = Discrete::new(3, 0, None).into();
+        let result = SyncVectorEnv::new(envs, observation_space, action_space);
+        assert!(matches!(
+            result,
+            Err(BatchError::SpaceMismatch {
+                index: 0,
+                space: "action"
+            })
+        ));
+    }
+
+    #[test]
+    fn test_sync_vector_env_seeds() {
+        let mut env = cartpoles(4);
+        let (observations, _) = env.reset(Some(42), None);
+
+        for i in 0..4 {
+            let (expected, _) = CartPole::default().reset(Some(42 + i as u64), None);
+            assert_eq!(
+                observations.get(i).unwrap().to_vec1::<f32>().unwrap(),
+                expected.to_vec1::<f32>().unwrap()
+            );
+        }
+        let (other, _) = cartpoles(4).reset(Some(42), None);
+        assert_eq!(
+            observations.to_vec2::<f32>().unwrap(),
+            other.to_vec2::<f32>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_sync_vector_env_autoreset() {
+        let mut env = cartpoles(4);
+        env.reset(Some(0), None);
+
+        // Pushing every cart to the right makes every pole fall.
+        let actions = Tensor::new(&[1i64; 4], &candle_core::Device::Cpu).unwrap();
+        let mut ended = [false; 4];
+        for _ in 0..100 {
+            let result = env.step(&actions);
+            let terminated = result.terminated.to_vec1::<u8>().unwrap();
+            let observations = result.observations.to_vec2::<f32>().unwrap();
+            for i in 0..4 {
+                let info = &result.infos[i];
+                if terminated[i] == 1 {
+                    ended[i] = true;
+                    // The returned observation is the first of the next episode.
+                    assert!(observations[i].iter().all(|x| x.abs() <= 0.05));
+                    assert!(matches!(
+                        &info["final_observation"],
+                        InfoValue::Tensor(x) if x.dims() == [4]
+                    ));
+                    assert!(matches!(info["final_info"], InfoValue::Map(_)));
+                } else {
+                    assert!(!info.contains_key("final_observation"));
+                }
+            }
+        }
+        assert_eq!(ended, [true; 4]);
+    }
+}
@@ -0,0 +1,140 @@
+//! Utility functions for the vectorized environments.
+
+use candle_core::Tensor;
+
+use crate::common::{InfoMap, InfoValue};
+use crate::core::{Env, StepResult};
+use crate::spaces::utils::{concatenate, create_empty_array, BatchError};
+use crate::spaces::{SpaceEnum, StructuredSample};
+use crate::vector::VectorStepResult;
+
+/// Stacks one element of `space` per environment into a tensor with a leading batch dimension.
+pub fn stack_samples(space: &SpaceEnum, items: &[StructuredSample]) -> Result<Tensor, BatchError> {
+    let out = create_empty_array(space, items.len())?;
+    concatenate(space, items, &out)?;
+    Ok(out)
+}
+
+/// Checks that the spaces of the `index`-th sub-environment are `observation_space` and
+/// `action_space`.
+pub fn check_spaces(
+    index: usize,
+    spaces: (&SpaceEnum, &SpaceEnum),
+    observation_space: &SpaceEnum,
+    action_space: &SpaceEnum,
+) -> Result<(), BatchError> {
+    let (env_observation_space, env_action_space) = spaces;
+    if env_observation_space != observation_space {
+        return Err(BatchError::SpaceMismatch {
+            index,
+            space: "observation",
+        });
+    }
+    if env_action_space != action_space {
+        return Err(BatchError::SpaceMismatch {
+            index,
+            space: "action",
+        });
+    }
+    Ok(())
+}
+
+/// Merges the `"final_observation"` and `"final_info"` of an episode that just ended into the
+/// `reset_info` of the next episode.
+pub fn final_info(
+    final_observation: StructuredSample,
+    final_info: InfoMap,
+    mut reset_info: InfoMap,
+) -> InfoMap {
+    let final_observation = match final_observation {
+        StructuredSample::Tensor(x) => InfoValue::Tensor(x),
+        StructuredSample::Int(x) => InfoValue::Int(x),
+        _ => panic!("Observations must be elements of a Box or Discrete space"),
+    };
+    reset_info.insert("final_observation".to_string(), final_observation);
+    reset_info.insert("final_info".to_string(), InfoValue::Map(final_info));
+    reset_info
+}
+
+/// Steps `env` with `action`, resetting it if it terminates or truncates.
+///
+/// The returned observation is then the first of the next episode, and the last observation and
+/// info of the ended episode are merged into the reset info with [`final_info`].
+pub fn step_autoreset<ObsType, ActType>(
+    env: &mut dyn Env<ObsType, ActType>,
+    action: StructuredSample,
+) -> StepResult<StructuredSample>
+where
+    ObsType: Into<StructuredSample>,
+    ActType: TryFrom<StructuredSample>,
+{
+    let action = ActType::try_from(action)
+        .unwrap_or_else(|_| panic!("Actions must be elements of the single action space"));
+    let StepResult {
+        observation,
+        reward,
+        terminated,
+        truncated,
+        info,
+    } = env.step(action);
+
+    let (observation, info) = if terminated || truncated {
+        let (reset_observation, reset_info) = env.reset(None, None);
+        let info = final_info(observation.into(), info, reset_info);
+        (reset_observation.into(), info)
+    } else {
+        (observation.into(), info)
+    };
+    StepResult {
+        observation,
+        reward,
+        terminated,
+        truncated,
+        info,
+    }
+}
+
+/// Collects the per-environment results of a step into a [`VectorStepResult`], stacking the
+/// observations as elements of `space`.
+///
+/// The rewards and flags are placed on the device of the stacked observations.
+pub fn vector_step_result(
+    space: &SpaceEnum,
+    results: Vec<StepResult<StructuredSample>>,
+) -> VectorStepResult {
+    let mut observations = Vec::with_capacity(results.len());
+    let mut rewards = Vec::with_capacity(results.len());
+    let mut terminated = Vec::with_capacity(results.len());
+    let mut truncated = Vec::with_capacity(results.len());
+    let mut infos = Vec::with_capacity(results.len());
+    for result in results {
+        observations.push(result.observation);
+        rewards.push(result.reward);
+        terminated.push(result.terminated);
+        truncated.push(result.truncated);
+        infos.push(result.info);
+    }
+
+    let observations = stack_samples(space, &observations)
+        .expect("Observations must be elements of the observation space");
+    let device = observations.device().clone();
+    let flags = |flags: Vec<bool>| {
+        Tensor::new(flags.into_iter().map(u8::from).collect::<Vec<_>>(), &device)
+    };
+    let tensors = || {
+        Ok::<_, candle_core::Error>((
+            Tensor::new(rewards, &device)?,
+            flags(terminated)?,
+            flags(truncated)?,
+        ))
+    };
+    let (rewards, terminated, truncated) = tensors().expect("The results are one-dimensional");
+
+    VectorStepResult {
+        observations,
+        rewards,
+        terminated,
+        truncated,
+        infos,
+    }
+}
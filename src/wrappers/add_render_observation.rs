@@ -0,0 +1,309 @@
+//! Wrapper that adds the rendered frames of an environment to its observations.
+
+use candle_core::{DType, Tensor};
+use indexmap::IndexMap;
+use thiserror::Error;
+
+use crate::core::{Env, ObservationWrapper, ObservationWrapperKind, RenderFrame, WrapperKind};
+use crate::spaces::{Bound, Box, Dict, SpaceEnum, StructuredSample};
+
+/// Key of the rendered frame in the `Dict` observations.
+const RENDER_KEY: &str = "pixels";
+
+/// Key of the observation of the wrapped environment in the `Dict` observations, unless its
+/// observation space is already a `Dict`.
+const OBS_KEY: &str = "state";
+
+/// Error returned by [`AddRenderObservation::new`].
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AddRenderObservationError {
+    #[error("The wrapped environment must render in the \"rgb_array\" mode")]
+    NotRgbArray,
+    #[error("Expected an RGB array of shape (H, W, 3), got {0:?}")]
+    FrameShape(Vec<usize>),
+    #[error("The observation space already has a \"{RENDER_KEY}\" entry")]
+    RenderKeyExists,
+}
+
+/// Adds the "rgb_array" frame rendered after each [`step`](Env::step) and [`reset`](Env::reset)
+/// to the observations, e.g. to learn from pixels.
+///
+/// If `render_only` is set, the frame replaces the observation, as a `U8` `Box` of the shape of
+/// the frames. Otherwise, the frame is added under `"pixels"` to the entries of `Dict`
+/// observations, and other observations are returned as a `Dict` of the original observation
+/// under `"state"` and the frame under `"pixels"`.
+pub struct AddRenderObservation<ObsType, ActType> {
+    env: std::boxed::Box<dyn Env<ObsType, ActType>>,
+    observation_space: SpaceEnum,
+    render_only: bool,
+}
+
+impl<ObsType, ActType> AddRenderObservation<ObsType, ActType> {
+    /// Wraps `env`, which renders "rgb_array" frames, adding its frames to the observations.
+    ///
+    /// The environment is reset once to render a frame, giving the shape of the frames.
+    ///
+    /// Returns an error if `env` does not render `(H, W, 3)` RGB arrays, or if `render_only` is not
+    /// set and its observation space is a `Dict` that already has a `"pixels"` entry.
+    pub fn new(
+        mut env: std::boxed::Box<dyn Env<ObsType, ActType>>,
+        render_only: bool,
+    ) -> Result<Self, AddRenderObservationError> {
+        if let SpaceEnum::Dict(space) = env.observation_space() {
+            if !render_only && space.spaces().contains_key(RENDER_KEY) {
+                return Err(AddRenderObservationError::RenderKeyExists);
+            }
+        }
+
+        env.reset(None, None);
+        let frame = _render(env.as_mut()).ok_or(AddRenderObservationError::NotRgbArray)?;
+        let &[height, width, 3] = frame.dims() else {
+            return Err(AddRenderObservationError::FrameShape(frame.dims().to_vec()));
+        };
+        let pixel_space: SpaceEnum = Box::new(
+            Bound::F64(0.0),
+            Bound::F64(255.0),
+            Some(vec![height, width, 3]),
+            DType::U8,
+            frame.device(),
+            None,
+        )
+        .expect("A shape is given")
+        .into();
+
+        let observation_space = match env.observation_space() {
+            _ if render_only => pixel_space,
+            SpaceEnum::Dict(space) => {
+                let mut spaces = space.spaces().clone();
+                spaces.insert(RENDER_KEY.to_string(), pixel_space);
+                Dict::new(spaces, None).into()
+            }
+            space => Dict::new([(OBS_KEY, space.clone()), (RENDER_KEY, pixel_space)], None).into(),
+        };
+
+        Ok(Self {
+            env,
+            observation_space,
+            render_only,
+        })
+    }
+}
+
+/// Renders an "rgb_array" frame of `env`, or returns `None` if it renders in another mode.
+fn _render<ObsType, ActType>(env: &mut dyn Env<ObsType, ActType>) -> Option<Tensor> {
+    match env.render() {
+        Some(RenderFrame::RgbArray(frame)) => Some(frame),
+        _ => None,
+    }
+}
+
+impl<ObsType, ActType> ObservationWrapper for AddRenderObservation<ObsType, ActType>
+where
+    ObsType: Into<StructuredSample>,
+{
+    type ObsType = ObsType;
+    type ActType = ActType;
+    type WrapperObsType = StructuredSample;
+
+    fn env(&self) -> &dyn Env<ObsType, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<ObsType, ActType> {
+        self.env.as_mut()
+    }
+
+    fn observation(&mut self, observation: ObsType) -> StructuredSample {
+        let frame = _render(self.env.as_mut())
+            .expect("The wrapped environment rendered \"rgb_array\" frames before");
+        let frame = StructuredSample::Tensor(frame);
+        if self.render_only {
+            return frame;
+        }
+        let mut entries = match observation.into() {
+            StructuredSample::Dict(entries) => entries,
+            observation => IndexMap::from([(OBS_KEY.to_string(), observation)]),
+        };
+        entries.insert(RENDER_KEY.to_string(), frame);
+        StructuredSample::Dict(entries)
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+}
+
+impl<ObsType, ActType> WrapperKind for AddRenderObservation<ObsType, ActType>
+where
+    ObsType: Into<StructuredSample>,
+{
+    type Kind = ObservationWrapperKind;
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::{DType, Device, Tensor};
+    use indexmap::IndexMap;
+
+    use super::{AddRenderObservation, AddRenderObservationError};
+    use crate::common::InfoMap;
+    use crate::core::{Env, RenderFrame, StepResult};
+    use crate::spaces::{Bound, Box, Dict, Discrete, Space, SpaceEnum, StructuredSample};
+
+    /// Observes a fixed `observation`, rendering the step count as a uniform image of
+    /// `frame_shape`, or nothing if `frame_shape` is `None`.
+    struct FrameEnv<ObsType> {
+        count: u8,
+        observation: ObsType,
+        space: SpaceEnum,
+        frame_shape: Option<Vec<usize>>,
+    }
+
+    impl<ObsType: Clone> Env<ObsType, ()> for FrameEnv<ObsType> {
+        fn step(&mut self, _action: ()) -> StepResult<ObsType> {
+            self.count += 1;
+            (self.observation.clone(), 0.0, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (ObsType, InfoMap) {
+            self.count = 0;
+            (self.observation.clone(), InfoMap::new())
+        }
+
+        fn render(&mut self) -> Option<RenderFrame> {
+            let frame_shape = self.frame_shape.as_deref()?;
+            let frame = Tensor::full(self.count, frame_shape, &Device::Cpu).unwrap();
+            Some(RenderFrame::RgbArray(frame))
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    fn frame_env<ObsType: Clone + 'static>(
+        observation: ObsType,
+        space: SpaceEnum,
+    ) -> std::boxed::Box<dyn Env<ObsType, ()>> {
+        std::boxed::Box::new(FrameEnv {
+            count: 0,
+            observation,
+            space,
+            frame_shape: Some(vec![2, 3, 3]),
+        })
+    }
+
+    fn pixel_space() -> SpaceEnum {
+        Box::new(
+            Bound::F64(0.0),
+            Bound::F64(255.0),
+            Some(vec![2, 3, 3]),
+            DType::U8,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap()
+        .into()
+    }
+
+    fn pixels(sample: &StructuredSample) -> Vec<u8> {
+        let StructuredSample::Tensor(frame) = sample else {
+            panic!("Expected a frame, got {sample:?}");
+        };
+        frame.flatten_all().unwrap().to_vec1::<u8>().unwrap()
+    }
+
+    #[test]
+    fn test_add_render_observation_render_only() {
+        let env = frame_env(7i64, Discrete::new(10, 0, None).into());
+        let mut env = AddRenderObservation::new(env, true).unwrap();
+        assert_eq!(Env::observation_space(&env), &pixel_space());
+
+        let (observation, _) = Env::reset(&mut env, None, None);
+        assert_eq!(pixels(&observation), [0; 18]);
+        let StepResult { observation, .. } = Env::step(&mut env, ());
+        assert_eq!(pixels(&observation), [1; 18]);
+        assert!(Env::observation_space(&env).contains(&observation));
+    }
+
+    #[test]
+    fn test_add_render_observation_dict() {
+        let state_space: SpaceEnum = Discrete::new(10, 0, None).into();
+        let env = frame_env(7i64, state_space.clone());
+        let mut env = AddRenderObservation::new(env, false).unwrap();
+        let expected = Dict::new([("state", state_space), ("pixels", pixel_space())], None);
+        assert_eq!(Env::observation_space(&env), &SpaceEnum::from(expected));
+
+        Env::reset(&mut env, None, None);
+        let StepResult { observation, .. } = Env::step(&mut env, ());
+        assert!(Env::observation_space(&env).contains(&observation));
+        let StructuredSample::Dict(entries) = observation else {
+            panic!("Expected a Dict observation");
+        };
+        assert_eq!(entries["state"], StructuredSample::Int(7));
+        assert_eq!(pixels(&entries["pixels"]), [1; 18]);
+
+        // The frame is added to the entries of Dict observations.
+        let count_space: SpaceEnum = Discrete::new(10, 0, None).into();
+        let observation = StructuredSample::Dict(IndexMap::from([(
+            "count".to_string(),
+            StructuredSample::Int(3),
+        )]));
+        let space = Dict::new([("count", count_space.clone())], None);
+        let env = frame_env(observation, space.into());
+        let env = AddRenderObservation::new(env, false).unwrap();
+        let expected = Dict::new([("count", count_space), ("pixels", pixel_space())], None);
+        assert_eq!(Env::observation_space(&env), &SpaceEnum::from(expected));
+    }
+
+    #[test]
+    fn test_add_render_observation_rejects_non_rgb_array_rendering() {
+        let env = std::boxed::Box::new(FrameEnv {
+            count: 0,
+            observation: 7i64,
+            space: Discrete::new(10, 0, None).into(),
+            frame_shape: None,
+        });
+        let result = AddRenderObservation::new(env, false);
+        assert_eq!(result.err(), Some(AddRenderObservationError::NotRgbArray));
+    }
+
+    #[test]
+    fn test_add_render_observation_rejects_non_rgb_frames() {
+        let env = std::boxed::Box::new(FrameEnv {
+            count: 0,
+            observation: 7i64,
+            space: Discrete::new(10, 0, None).into(),
+            frame_shape: Some(vec![2, 3]),
+        });
+        let err = AddRenderObservation::new(env, false).err().unwrap();
+        assert_eq!(err, AddRenderObservationError::FrameShape(vec![2, 3]));
+        assert_eq!(
+            err.to_string(),
+            "Expected an RGB array of shape (H, W, 3), got [2, 3]"
+        );
+    }
+
+    #[test]
+    fn test_add_render_observation_rejects_existing_render_key() {
+        let space = Dict::new([("pixels", pixel_space())], None);
+        let observation = space.clone().sample(None);
+        let env = frame_env(StructuredSample::Dict(observation), space.clone().into());
+        let result = AddRenderObservation::new(env, false);
+        assert_eq!(
+            result.err(),
+            Some(AddRenderObservationError::RenderKeyExists)
+        );
+
+        // The frame replaces the observation if `render_only` is set.
+        let env = frame_env(
+            StructuredSample::Dict(space.clone().sample(None)),
+            space.into(),
+        );
+        assert!(AddRenderObservation::new(env, true).is_ok());
+    }
+}
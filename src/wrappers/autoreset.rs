@@ -0,0 +1,167 @@
+//! Wrapper that automatically resets the environment once an episode ends.
+
+use crate::common::{InfoMap, InfoValue};
+use crate::core::{Env, StepResult, Wrapper};
+
+/// Automatically resets the environment on the step following the end of an episode.
+///
+/// The step on which the wrapped environment terminates or truncates is returned unchanged. The
+/// next call to [`step`](Env::step) ignores its action and resets the environment instead,
+/// returning the first observation of the new episode with a reward of `0` and neither
+/// `terminated` nor `truncated` set. The info of that step is the reset info, with the last
+/// observation of the ended episode under `"final_observation"` and its last info under
+/// `"final_info"`.
+pub struct Autoreset<ObsType, ActType> {
+    env: Box<dyn Env<ObsType, ActType>>,
+    final_step: Option<(ObsType, InfoMap)>,
+}
+
+impl<ObsType, ActType> Autoreset<ObsType, ActType> {
+    /// Wraps `env`, resetting it automatically after each episode.
+    pub fn new(env: Box<dyn Env<ObsType, ActType>>) -> Self {
+        Self {
+            env,
+            final_step: None,
+        }
+    }
+}
+
+impl<ObsType, ActType> Wrapper for Autoreset<ObsType, ActType>
+where
+    ObsType: Clone + Into<InfoValue>,
+{
+    type ObsType = ObsType;
+    type ActType = ActType;
+
+    fn env(&self) -> &dyn Env<ObsType, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<ObsType, ActType> {
+        self.env.as_mut()
+    }
+
+    fn step(&mut self, action: ActType) -> StepResult<ObsType> {
+        if let Some((final_observation, final_info)) = self.final_step.take() {
+            let (observation, mut info) = self.env.reset(None, None);
+            info.insert("final_observation".to_string(), final_observation.into());
+            info.insert("final_info".to_string(), InfoValue::Map(final_info));
+            return StepResult {
+                observation,
+                reward: 0.0,
+                terminated: false,
+                truncated: false,
+                info,
+            };
+        }
+
+        let result = self.env.step(action);
+        if result.terminated || result.truncated {
+            self.final_step = Some((result.observation.clone(), result.info.clone()));
+        }
+        result
+    }
+
+    fn reset(&mut self, seed: Option<u64>, options: Option<&InfoMap>) -> (ObsType, InfoMap) {
+        self.final_step = None;
+        self.env.reset(seed, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Autoreset;
+    use crate::common::{InfoMap, InfoValue};
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Discrete, SpaceEnum};
+
+    /// Counts the steps since the last reset, terminating after three of them.
+    struct CountingEnv {
+        count: i64,
+        resets: i64,
+        space: SpaceEnum,
+    }
+
+    impl Env<i64, ()> for CountingEnv {
+        fn step(&mut self, _action: ()) -> StepResult<i64> {
+            self.count += 1;
+            let info = InfoMap::from([("count".to_string(), InfoValue::Int(self.count))]);
+            (self.count, 1.0, self.count == 3, false, info).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (i64, InfoMap) {
+            self.count = 0;
+            self.resets += 1;
+            let info = InfoMap::from([("resets".to_string(), InfoValue::Int(self.resets))]);
+            (self.count, info)
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    fn autoreset_env() -> Autoreset<i64, ()> {
+        Autoreset::new(Box::new(CountingEnv {
+            count: 0,
+            resets: 0,
+            space: Discrete::new(100, 0, None).into(),
+        }))
+    }
+
+    #[test]
+    fn test_autoreset() {
+        let mut env = autoreset_env();
+        env.reset(None, None);
+
+        assert_eq!(env.step(()).observation, 1);
+        assert_eq!(env.step(()).observation, 2);
+        let result = env.step(());
+        assert_eq!(
+            (result.observation, result.terminated, result.truncated),
+            (3, true, false)
+        );
+
+        // The step after the terminal step resets the environment.
+        let StepResult {
+            observation,
+            reward,
+            terminated,
+            truncated,
+            info,
+        } = env.step(());
+        assert_eq!(
+            (observation, reward, terminated, truncated),
+            (0, 0.0, false, false)
+        );
+        assert!(matches!(info["resets"], InfoValue::Int(2)));
+        assert!(matches!(info["final_observation"], InfoValue::Int(3)));
+        assert!(matches!(
+            &info["final_info"],
+            InfoValue::Map(final_info) if matches!(final_info["count"], InfoValue::Int(3))
+        ));
+
+        let result = env.step(());
+        assert_eq!(result.observation, 1);
+        assert!(!result.info.contains_key("final_observation"));
+    }
+
+    #[test]
+    fn test_autoreset_cleared_by_reset() {
+        let mut env = autoreset_env();
+        env.reset(None, None);
+        for _ in 0..3 {
+            env.step(());
+        }
+
+        // A manual reset starts the new episode, so the next step is not an autoreset.
+        env.reset(None, None);
+        let result = env.step(());
+        assert_eq!((result.observation, result.reward), (1, 1.0));
+        assert!(!result.info.contains_key("final_observation"));
+    }
+}
@@ -0,0 +1,153 @@
+//! Wrapper that clips continuous actions to the bounds of a `Box` action space.
+
+use candle_core::Tensor;
+
+use crate::core::{ActionWrapper, ActionWrapperKind, Env, WrapperKind};
+use crate::spaces::{Box, SpaceEnum};
+use crate::wrappers::utils::UnsupportedSpaceError;
+
+/// Clips a continuous action to the valid bound specified by the environment's action space.
+///
+/// The agent keeps acting in the unclipped action space of the wrapped environment, while each
+/// component of the action is clamped to its `low` and `high` bounds before being forwarded.
+pub struct ClipAction<ObsType> {
+    env: std::boxed::Box<dyn Env<ObsType, Tensor>>,
+    action_space: Box,
+}
+
+impl<ObsType> ClipAction<ObsType> {
+    /// Wraps `env`, clipping its actions to the bounds of its action space.
+    ///
+    /// Returns an error if the action space of `env` is not a `Box`.
+    pub fn new(
+        env: std::boxed::Box<dyn Env<ObsType, Tensor>>,
+    ) -> Result<Self, UnsupportedSpaceError> {
+        let SpaceEnum::Box(action_space) = env.action_space() else {
+            return Err(UnsupportedSpaceError {
+                space: "action",
+                expected: "a Box",
+            });
+        };
+        let action_space = action_space.clone();
+        Ok(Self { env, action_space })
+    }
+}
+
+impl<ObsType> ActionWrapper for ClipAction<ObsType> {
+    type ObsType = ObsType;
+    type ActType = Tensor;
+    type WrapperActType = Tensor;
+
+    fn env(&self) -> &dyn Env<ObsType, Tensor> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<ObsType, Tensor> {
+        self.env.as_mut()
+    }
+
+    fn action(&mut self, action: Tensor) -> Tensor {
+        self.action_space.clip(action)
+    }
+}
+
+impl<ObsType> WrapperKind for ClipAction<ObsType> {
+    type Kind = ActionWrapperKind;
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::{DType, Device, Tensor};
+
+    use super::ClipAction;
+    use crate::common::InfoMap;
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Bound, Box, Discrete, Space, SpaceEnum};
+
+    /// Returns the action it received as the observation.
+    struct EchoEnv {
+        space: SpaceEnum,
+    }
+
+    impl Env<Tensor, Tensor> for EchoEnv {
+        fn step(&mut self, action: Tensor) -> StepResult<Tensor> {
+            (action, 0.0, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (Tensor, InfoMap) {
+            (
+                Tensor::zeros(3, DType::F32, &Device::Cpu).unwrap(),
+                InfoMap::new(),
+            )
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    fn action_space() -> Box {
+        let low = Tensor::new(&[-1.0f32, 0.0, -2.0], &Device::Cpu).unwrap();
+        let high = Tensor::new(&[1.0f32, 0.5, 2.0], &Device::Cpu).unwrap();
+        Box::new(
+            Bound::Tensor(low),
+            Bound::Tensor(high),
+            None,
+            DType::F32,
+            &Device::Cpu,
+            Some(0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_clip_action() {
+        let mut env = ClipAction::new(std::boxed::Box::new(EchoEnv {
+            space: action_space().into(),
+        }))
+        .unwrap();
+        env.reset(None, None);
+
+        let action = Tensor::new(&[-3.0f32, 0.7, 1.5], &Device::Cpu).unwrap();
+        let StepResult { observation, .. } = env.step(action);
+        assert_eq!(observation.to_vec1::<f32>().unwrap(), [-1.0, 0.5, 1.5]);
+
+        let action = Tensor::new(&[0.25f32, 0.0, 9.0], &Device::Cpu).unwrap();
+        let StepResult { observation, .. } = env.step(action);
+        assert_eq!(observation.to_vec1::<f32>().unwrap(), [0.25, 0.0, 2.0]);
+    }
+
+    #[test]
+    fn test_clip_action_rejects_discrete() {
+        let result = ClipAction::new(std::boxed::Box::new(EchoEnv {
+            space: Discrete::new(3, 0, None).into(),
+        }));
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "The action space of the wrapped environment must be a Box"
+        );
+    }
+
+    #[test]
+    fn test_clip_action_in_bounds_unchanged() {
+        let mut space = action_space();
+        let mut env = ClipAction::new(std::boxed::Box::new(EchoEnv {
+            space: action_space().into(),
+        }))
+        .unwrap();
+        env.reset(None, None);
+
+        for _ in 0..10 {
+            let action = space.sample(None);
+            let StepResult { observation, .. } = env.step(action.clone());
+            assert_eq!(
+                observation.to_vec1::<f32>().unwrap(),
+                action.to_vec1::<f32>().unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Wrapper that clips the rewards of an environment to a range.
+
+use crate::core::{Env, RewardWrapper};
+
+/// Clips the reward of each [`step`](Env::step) to `[min_reward, max_reward]`, e.g. to `[-1, 1]`
+/// as is standard when training on Atari games.
+///
+/// The observation, termination and truncation signals and info are forwarded unchanged.
+pub struct ClipReward<ObsType, ActType> {
+    env: Box<dyn Env<ObsType, ActType>>,
+    min_reward: f32,
+    max_reward: f32,
+}
+
+impl<ObsType, ActType> ClipReward<ObsType, ActType> {
+    /// Wraps `env`, clipping its rewards to `[-1, 1]`.
+    pub fn new(env: Box<dyn Env<ObsType, ActType>>) -> Self {
+        Self::with_range(env, -1.0, 1.0)
+    }
+
+    /// Wraps `env`, clipping its rewards to `[min_reward, max_reward]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min_reward` is greater than `max_reward`, or if either is NaN.
+    pub fn with_range(
+        env: Box<dyn Env<ObsType, ActType>>,
+        min_reward: f32,
+        max_reward: f32,
+    ) -> Self {
+        assert!(
+            min_reward <= max_reward,
+            "The min reward ({min_reward}) must be less than or equal to the max reward \
+             ({max_reward})"
+        );
+        Self {
+            env,
+            min_reward,
+            max_reward,
+        }
+    }
+
+    /// The lower bound of the clipped rewards.
+    pub fn min_reward(&self) -> f32 {
+        self.min_reward
+    }
+
+    /// The upper bound of the clipped rewards.
+    pub fn max_reward(&self) -> f32 {
+        self.max_reward
+    }
+}
+
+impl<ObsType, ActType> RewardWrapper for ClipReward<ObsType, ActType> {
+    type ObsType = ObsType;
+    type ActType = ActType;
+
+    fn env(&self) -> &dyn Env<ObsType, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<ObsType, ActType> {
+        self.env.as_mut()
+    }
+
+    fn reward(&mut self, reward: f32) -> f32 {
+        reward.clamp(self.min_reward, self.max_reward)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClipReward;
+    use crate::common::InfoMap;
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Discrete, SpaceEnum};
+
+    /// Gives the action as the reward, observing the number of steps taken.
+    struct RewardEnv {
+        count: i64,
+        space: SpaceEnum,
+    }
+
+    impl Env<i64, f32> for RewardEnv {
+        fn step(&mut self, action: f32) -> StepResult<i64> {
+            self.count += 1;
+            (self.count, action, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (i64, InfoMap) {
+            self.count = 0;
+            (self.count, InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    fn reward_env() -> Box<dyn Env<i64, f32>> {
+        Box::new(RewardEnv {
+            count: 0,
+            space: Discrete::new(100, 0, None).into(),
+        })
+    }
+
+    #[test]
+    fn test_clip_reward_default_range() {
+        let mut env = ClipReward::new(reward_env());
+        assert_eq!((env.min_reward(), env.max_reward()), (-1.0, 1.0));
+        env.reset(None, None);
+
+        for (action, expected) in [(5.0, 1.0), (-3.5, -1.0), (0.25, 0.25), (-1.0, -1.0)] {
+            let StepResult {
+                reward,
+                observation,
+                ..
+            } = env.step(action);
+            assert_eq!(reward, expected);
+            assert!(observation > 0);
+        }
+    }
+
+    #[test]
+    fn test_clip_reward_custom_range() {
+        let mut env = ClipReward::with_range(reward_env(), 0.0, 10.0);
+        env.reset(None, None);
+
+        assert_eq!(env.step(-2.0).reward, 0.0);
+        assert_eq!(env.step(12.0).reward, 10.0);
+        assert_eq!(env.step(7.5).reward, 7.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be less than or equal to the max reward")]
+    fn test_clip_reward_invalid_range() {
+        ClipReward::with_range(reward_env(), 1.0, -1.0);
+    }
+}
@@ -0,0 +1,202 @@
+//! Wrapper that casts the observations of an environment to another dtype.
+
+use candle_core::{DType, Tensor};
+
+use crate::core::{Env, ObservationWrapper, ObservationWrapperKind, WrapperKind};
+use crate::spaces::r#box::dtype_extremes;
+use crate::spaces::{Bound, Box, SpaceEnum};
+use crate::wrappers::utils::UnsupportedSpaceError;
+
+/// Casts the observations of a `Box` observation space to `dtype`, e.g. to feed the `U8` frames of
+/// image environments to networks expecting `F32` inputs.
+///
+/// Casting to an integer dtype rounds the observations to the nearest integer and clamps them to
+/// the range of the dtype. The bounds of the observation space are cast the same way.
+pub struct DtypeObservation<ActType> {
+    env: std::boxed::Box<dyn Env<Tensor, ActType>>,
+    observation_space: SpaceEnum,
+    dtype: DType,
+}
+
+impl<ActType> DtypeObservation<ActType> {
+    /// Wraps `env`, whose observation space is a `Box`, casting its observations to `dtype`.
+    ///
+    /// Returns an error if the observation space of `env` is not a `Box`.
+    pub fn new(
+        env: std::boxed::Box<dyn Env<Tensor, ActType>>,
+        dtype: DType,
+    ) -> Result<Self, UnsupportedSpaceError> {
+        let SpaceEnum::Box(space) = env.observation_space() else {
+            return Err(UnsupportedSpaceError {
+                space: "observation",
+                expected: "a Box",
+            });
+        };
+        let observation_space = Box::new(
+            Bound::Tensor(_cast(space.low(), dtype)),
+            Bound::Tensor(_cast(space.high(), dtype)),
+            Some(space.shape().to_vec()),
+            dtype,
+            space.low().device(),
+            None,
+        )
+        .expect("The bounds have the shape of the space");
+
+        Ok(Self {
+            env,
+            observation_space: observation_space.into(),
+            dtype,
+        })
+    }
+
+    /// The dtype of the observations.
+    pub fn dtype(&self) -> DType {
+        self.dtype
+    }
+}
+
+/// Casts `x` to `dtype`, rounding and clamping it to the range of `dtype` if it is an integer
+/// dtype.
+fn _cast(x: &Tensor, dtype: DType) -> Tensor {
+    let cast = || {
+        if dtype.is_float() {
+            return x.to_dtype(dtype);
+        }
+        let (min, max) = dtype_extremes(dtype);
+        x.to_dtype(DType::F64)?
+            .round()?
+            .clamp(min, max)?
+            .to_dtype(dtype)
+    };
+    cast().expect("Tensors can be cast to any dtype")
+}
+
+impl<ActType> ObservationWrapper for DtypeObservation<ActType> {
+    type ObsType = Tensor;
+    type ActType = ActType;
+    type WrapperObsType = Tensor;
+
+    fn env(&self) -> &dyn Env<Tensor, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<Tensor, ActType> {
+        self.env.as_mut()
+    }
+
+    fn observation(&mut self, observation: Tensor) -> Tensor {
+        _cast(&observation, self.dtype)
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+}
+
+impl<ActType> WrapperKind for DtypeObservation<ActType> {
+    type Kind = ObservationWrapperKind;
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::{DType, Device, Tensor};
+
+    use super::DtypeObservation;
+    use crate::common::InfoMap;
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Bound, Box, Discrete, SpaceEnum};
+    use crate::wrappers::utils::UnsupportedSpaceError;
+
+    /// Observes the fixed tensor `observation`.
+    struct FixedEnv {
+        observation: Tensor,
+        space: SpaceEnum,
+    }
+
+    impl Env<Tensor, ()> for FixedEnv {
+        fn step(&mut self, _action: ()) -> StepResult<Tensor> {
+            (self.observation.clone(), 0.0, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (Tensor, InfoMap) {
+            (self.observation.clone(), InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    fn fixed_env(observation: Tensor, space: Box) -> std::boxed::Box<dyn Env<Tensor, ()>> {
+        std::boxed::Box::new(FixedEnv {
+            observation,
+            space: space.into(),
+        })
+    }
+
+    #[test]
+    fn test_dtype_observation_float_to_u8() {
+        let observation = Tensor::new(&[-3.5f32, 0.4, 127.5, 254.6, 300.0], &Device::Cpu).unwrap();
+        let space = Box::bounded(-10.0, 300.0, vec![5]);
+        let mut env = DtypeObservation::new(fixed_env(observation, space), DType::U8).unwrap();
+
+        let expected = Box::new(
+            Bound::F64(0.0),
+            Bound::F64(255.0),
+            Some(vec![5]),
+            DType::U8,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+        assert_eq!(Env::observation_space(&env), &SpaceEnum::from(expected));
+
+        let (observation, _) = Env::reset(&mut env, None, None);
+        assert_eq!(observation.dtype(), DType::U8);
+        assert_eq!(observation.to_vec1::<u8>().unwrap(), [0, 0, 128, 255, 255]);
+        let StepResult { observation, .. } = Env::step(&mut env, ());
+        assert_eq!(observation.to_vec1::<u8>().unwrap(), [0, 0, 128, 255, 255]);
+    }
+
+    #[test]
+    fn test_dtype_observation_rejects_discrete() {
+        let env = FixedEnv {
+            observation: Tensor::new(0i64, &Device::Cpu).unwrap(),
+            space: Discrete::new(3, 0, None).into(),
+        };
+        let result = DtypeObservation::new(std::boxed::Box::new(env), DType::F32);
+        assert_eq!(
+            result.err(),
+            Some(UnsupportedSpaceError {
+                space: "observation",
+                expected: "a Box",
+            })
+        );
+    }
+
+    #[test]
+    fn test_dtype_observation_u8_to_float() {
+        let observation = Tensor::new(&[0u8, 128, 255], &Device::Cpu).unwrap();
+        let space = Box::new(
+            Bound::F64(0.0),
+            Bound::F64(255.0),
+            Some(vec![3]),
+            DType::U8,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+        let mut env = DtypeObservation::new(fixed_env(observation, space), DType::F32).unwrap();
+
+        assert_eq!(
+            Env::observation_space(&env),
+            &SpaceEnum::from(Box::bounded(0.0, 255.0, vec![3]))
+        );
+        let (observation, _) = Env::reset(&mut env, None, None);
+        assert_eq!(observation.to_vec1::<f32>().unwrap(), [0.0, 128.0, 255.0]);
+    }
+}
@@ -0,0 +1,230 @@
+//! Wrapper that keeps only some of the entries of `Dict` observations.
+
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+use thiserror::Error;
+
+use crate::core::{Env, ObservationWrapper, ObservationWrapperKind, WrapperKind};
+use crate::spaces::{Dict, SpaceEnum, StructuredSample};
+
+/// Error returned by [`FilterObservation::new`].
+#[derive(Debug, Error, PartialEq)]
+pub enum FilterObservationError {
+    #[error("The observation space must be a Dict to filter its keys")]
+    NotDict,
+    #[error("The key `{0}` is not in the observation space")]
+    MissingKey(String),
+    #[error("The key `{0}` is given more than once")]
+    DuplicateKey(String),
+}
+
+/// Filters the entries of `Dict` observations, keeping only those of `filter_keys`.
+///
+/// This lets agents ignore irrelevant components of the observations without changing the
+/// environment. The observation space is the `Dict` of the kept subspaces, in the order of
+/// `filter_keys`.
+pub struct FilterObservation<ActType> {
+    env: Box<dyn Env<StructuredSample, ActType>>,
+    observation_space: SpaceEnum,
+    filter_keys: Vec<String>,
+}
+
+impl<ActType> FilterObservation<ActType> {
+    /// Wraps `env`, whose observation space is a `Dict`, keeping the entries of `filter_keys` in
+    /// its observations.
+    ///
+    /// Returns an error if the observation space of `env` is not a `Dict`, or if one of
+    /// `filter_keys` is not one of its keys or is given more than once.
+    pub fn new<K: Into<String>>(
+        env: Box<dyn Env<StructuredSample, ActType>>,
+        filter_keys: impl IntoIterator<Item = K>,
+    ) -> Result<Self, FilterObservationError> {
+        let SpaceEnum::Dict(space) = env.observation_space() else {
+            return Err(FilterObservationError::NotDict);
+        };
+        let filter_keys: Vec<String> = filter_keys.into_iter().map(Into::into).collect();
+        let mut seen = HashSet::new();
+        if let Some(key) = filter_keys.iter().find(|key| !seen.insert(*key)) {
+            return Err(FilterObservationError::DuplicateKey(key.clone()));
+        }
+        let spaces = filter_keys
+            .iter()
+            .map(|key| match space.spaces().get(key) {
+                Some(subspace) => Ok((key.clone(), subspace.clone())),
+                None => Err(FilterObservationError::MissingKey(key.clone())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            env,
+            observation_space: Dict::new(spaces, None).into(),
+            filter_keys,
+        })
+    }
+
+    /// The keys kept in the observations.
+    pub fn filter_keys(&self) -> &[String] {
+        &self.filter_keys
+    }
+}
+
+impl<ActType> ObservationWrapper for FilterObservation<ActType> {
+    type ObsType = StructuredSample;
+    type ActType = ActType;
+    type WrapperObsType = StructuredSample;
+
+    fn env(&self) -> &dyn Env<StructuredSample, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<StructuredSample, ActType> {
+        self.env.as_mut()
+    }
+
+    fn observation(&mut self, observation: StructuredSample) -> StructuredSample {
+        let StructuredSample::Dict(mut observation) = observation else {
+            panic!("Expected a Dict observation, got {observation:?}");
+        };
+        let observation: IndexMap<String, StructuredSample> = self
+            .filter_keys
+            .iter()
+            .map(|key| {
+                let value = observation
+                    .swap_remove(key)
+                    .unwrap_or_else(|| panic!("The observation has no entry `{key}`"));
+                (key.clone(), value)
+            })
+            .collect();
+        StructuredSample::Dict(observation)
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+}
+
+impl<ActType> WrapperKind for FilterObservation<ActType> {
+    type Kind = ObservationWrapperKind;
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::{Device, Tensor};
+    use indexmap::IndexMap;
+
+    use super::{FilterObservation, FilterObservationError};
+    use crate::common::InfoMap;
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Box, Dict, Discrete, Space, SpaceEnum, StructuredSample};
+
+    /// Observes a fixed `Dict` of a position, a velocity and a step count.
+    struct DictEnv {
+        space: SpaceEnum,
+    }
+
+    impl DictEnv {
+        fn new() -> Self {
+            let space = Dict::new(
+                [
+                    ("position", Box::bounded(-1.0, 1.0, vec![2]).into()),
+                    ("velocity", Box::bounded(-5.0, 5.0, vec![2]).into()),
+                    ("count", Discrete::new(10, 0, None).into()),
+                ],
+                None,
+            );
+            Self {
+                space: space.into(),
+            }
+        }
+
+        fn observation() -> StructuredSample {
+            let tensor = |values: &[f32]| Tensor::new(values, &Device::Cpu).unwrap().into();
+            StructuredSample::Dict(IndexMap::from([
+                ("position".to_string(), tensor(&[0.5, -0.5])),
+                ("velocity".to_string(), tensor(&[1.0, 2.0])),
+                ("count".to_string(), StructuredSample::Int(3)),
+            ]))
+        }
+    }
+
+    impl Env<StructuredSample, ()> for DictEnv {
+        fn step(&mut self, _action: ()) -> StepResult<StructuredSample> {
+            (Self::observation(), 0.0, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(
+            &mut self,
+            _seed: Option<u64>,
+            _options: Option<&InfoMap>,
+        ) -> (StructuredSample, InfoMap) {
+            (Self::observation(), InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    #[test]
+    fn test_filter_observation() {
+        let mut env =
+            FilterObservation::new(std::boxed::Box::new(DictEnv::new()), ["velocity"]).unwrap();
+        assert_eq!(env.filter_keys(), ["velocity"]);
+
+        let expected = Dict::new(
+            [("velocity", Box::bounded(-5.0, 5.0, vec![2]).into())],
+            None,
+        );
+        assert_eq!(Env::observation_space(&env), &SpaceEnum::from(expected));
+
+        let (observation, _) = Env::reset(&mut env, None, None);
+        let StructuredSample::Dict(observation) = observation else {
+            panic!("Expected a Dict observation");
+        };
+        assert_eq!(observation.len(), 1);
+        let StructuredSample::Tensor(velocity) = &observation["velocity"] else {
+            panic!("Expected a tensor velocity");
+        };
+        assert_eq!(velocity.to_vec1::<f32>().unwrap(), [1.0, 2.0]);
+        let StepResult { observation, .. } = Env::step(&mut env, ());
+        assert!(Env::observation_space(&env).contains(&observation));
+    }
+
+    #[test]
+    fn test_filter_observation_errors() {
+        let err = FilterObservation::new(
+            std::boxed::Box::new(DictEnv::new()),
+            ["position", "acceleration"],
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            err,
+            FilterObservationError::MissingKey("acceleration".to_string())
+        );
+        assert_eq!(
+            err.to_string(),
+            "The key `acceleration` is not in the observation space"
+        );
+
+        let err = FilterObservation::new(
+            std::boxed::Box::new(DictEnv::new()),
+            ["velocity", "count", "velocity"],
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            err,
+            FilterObservationError::DuplicateKey("velocity".to_string())
+        );
+        assert_eq!(
+            err.to_string(),
+            "The key `velocity` is given more than once"
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! Wrapper that takes structured actions as flat 1-D tensors.
+
+use candle_core::Tensor;
+
+use crate::core::{ActionWrapper, ActionWrapperKind, Env, WrapperKind};
+use crate::spaces::utils::{flatten_space, unflatten, FlattenError};
+use crate::spaces::{SpaceEnum, StructuredSample};
+
+/// Takes the actions of an environment as 1-D tensors, which are [`unflatten`]ed into its action
+/// space before being forwarded.
+///
+/// This is the counterpart of [`FlattenObservation`](super::FlattenObservation) for actions, and
+/// lets agents producing vector outputs, such as MLPs, act in `Dict`, `Tuple` or `Discrete`
+/// action spaces. The flattened `Box` given by [`flatten_space`] is reported as the action space.
+pub struct FlattenAction<ObsType> {
+    env: Box<dyn Env<ObsType, StructuredSample>>,
+    action_space: SpaceEnum,
+}
+
+impl<ObsType> FlattenAction<ObsType> {
+    /// Wraps `env`, taking its actions as flattened tensors.
+    ///
+    /// Returns an error if the action space of `env` cannot be flattened.
+    pub fn new(env: Box<dyn Env<ObsType, StructuredSample>>) -> Result<Self, FlattenError> {
+        let action_space = flatten_space(env.action_space())?.into();
+        Ok(Self { env, action_space })
+    }
+}
+
+impl<ObsType> ActionWrapper for FlattenAction<ObsType> {
+    type ObsType = ObsType;
+    type ActType = StructuredSample;
+    type WrapperActType = Tensor;
+
+    fn env(&self) -> &dyn Env<ObsType, StructuredSample> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<ObsType, StructuredSample> {
+        self.env.as_mut()
+    }
+
+    fn action(&mut self, action: Tensor) -> StructuredSample {
+        unflatten(self.env.action_space(), &action)
+            .expect("Action must be an element of the flattened action space")
+    }
+
+    fn action_space(&self) -> &SpaceEnum {
+        &self.action_space
+    }
+}
+
+impl<ObsType> WrapperKind for FlattenAction<ObsType> {
+    type Kind = ActionWrapperKind;
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::{Device, Tensor};
+
+    use super::FlattenAction;
+    use crate::common::InfoMap;
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Box, Discrete, Space, SpaceEnum, StructuredSample, Tuple};
+
+    /// Returns the action it received as the observation.
+    struct EchoEnv {
+        space: SpaceEnum,
+    }
+
+    impl Env<StructuredSample, StructuredSample> for EchoEnv {
+        fn step(&mut self, action: StructuredSample) -> StepResult<StructuredSample> {
+            (action, 0.0, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(
+            &mut self,
+            _seed: Option<u64>,
+            _options: Option<&InfoMap>,
+        ) -> (StructuredSample, InfoMap) {
+            (StructuredSample::Int(0), InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    #[test]
+    fn test_flatten_action_tuple() {
+        let action_space = Tuple::new(
+            vec![
+                Box::bounded(-1.0, 1.0, vec![2]).into(),
+                Discrete::new(3, 0, None).into(),
+            ],
+            None,
+        );
+        let env = EchoEnv {
+            space: action_space.clone().into(),
+        };
+        let mut env = FlattenAction::new(std::boxed::Box::new(env)).unwrap();
+
+        let SpaceEnum::Box(space) = Env::action_space(&env).clone() else {
+            panic!("The flattened action space must be a Box");
+        };
+        assert_eq!(space.shape(), [5]);
+
+        Env::reset(&mut env, None, None);
+        let action = Tensor::new(&[0.5f32, -0.25, 0.0, 1.0, 0.0], &Device::Cpu).unwrap();
+        assert!(space.contains(&action));
+        let StepResult { observation, .. } = Env::step(&mut env, action);
+
+        let position = Tensor::new(&[0.5f32, -0.25], &Device::Cpu).unwrap();
+        let expected = StructuredSample::Tuple(vec![position.into(), StructuredSample::Int(1)]);
+        assert_eq!(observation, expected);
+        assert!(SpaceEnum::from(action_space).contains(&observation));
+    }
+}
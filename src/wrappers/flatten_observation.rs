@@ -0,0 +1,132 @@
+//! Wrapper that flattens structured observations into 1-D tensors.
+
+use candle_core::Tensor;
+
+use crate::core::{Env, ObservationWrapper, ObservationWrapperKind, WrapperKind};
+use crate::spaces::utils::{flatten, flatten_space, FlattenError};
+use crate::spaces::{SpaceEnum, StructuredSample};
+
+/// Flattens the observations of an environment into 1-D tensors, following [`flatten`].
+///
+/// This lets agents expecting vector inputs, such as MLPs, consume elements of `Dict`, `Tuple` or
+/// `Discrete` spaces directly. The flattened `Box` given by [`flatten_space`] is reported as the
+/// observation space.
+pub struct FlattenObservation<ActType> {
+    env: Box<dyn Env<StructuredSample, ActType>>,
+    env_observation_space: SpaceEnum,
+    observation_space: SpaceEnum,
+}
+
+impl<ActType> FlattenObservation<ActType> {
+    /// Wraps `env`, flattening its observations.
+    ///
+    /// Returns an error if the observation space of `env` cannot be flattened.
+    pub fn new(env: Box<dyn Env<StructuredSample, ActType>>) -> Result<Self, FlattenError> {
+        let observation_space = env.observation_space().clone();
+        Ok(Self {
+            env,
+            observation_space: flatten_space(&observation_space)?.into(),
+            env_observation_space: observation_space,
+        })
+    }
+}
+
+impl<ActType> ObservationWrapper for FlattenObservation<ActType> {
+    type ObsType = StructuredSample;
+    type ActType = ActType;
+    type WrapperObsType = Tensor;
+
+    fn env(&self) -> &dyn Env<StructuredSample, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<StructuredSample, ActType> {
+        self.env.as_mut()
+    }
+
+    fn observation(&mut self, observation: StructuredSample) -> Tensor {
+        flatten(&self.env_observation_space, &observation)
+            .expect("Observation must be an element of the observation space")
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+}
+
+impl<ActType> WrapperKind for FlattenObservation<ActType> {
+    type Kind = ObservationWrapperKind;
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::{DType, Device};
+
+    use super::FlattenObservation;
+    use crate::common::InfoMap;
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Bound, Box, Dict, Discrete, Space, SpaceEnum, StructuredSample};
+
+    /// Observes random elements of its observation space.
+    struct SpaceEnv {
+        observation_space: SpaceEnum,
+    }
+
+    impl Env<StructuredSample, ()> for SpaceEnv {
+        fn step(&mut self, _action: ()) -> StepResult<StructuredSample> {
+            let observation = self.observation_space.sample(None);
+            (observation, 0.0, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(
+            &mut self,
+            _seed: Option<u64>,
+            _options: Option<&InfoMap>,
+        ) -> (StructuredSample, InfoMap) {
+            (self.observation_space.sample(None), InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.observation_space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.observation_space
+        }
+    }
+
+    #[test]
+    fn test_flatten_observation_dict() {
+        let position = Box::new(
+            Bound::F64(-1.0),
+            Bound::F64(1.0),
+            Some(vec![2]),
+            DType::F32,
+            &Device::Cpu,
+            Some(0),
+        )
+        .unwrap();
+        let observation_space = SpaceEnum::from(Dict::new(
+            [
+                ("position", position.into()),
+                ("mode", Discrete::new(3, 0, Some(0)).into()),
+            ],
+            None,
+        ));
+        let env = SpaceEnv { observation_space };
+        let mut env = FlattenObservation::new(std::boxed::Box::new(env)).unwrap();
+
+        let SpaceEnum::Box(space) = Env::observation_space(&env).clone() else {
+            panic!("The flattened observation space must be a Box");
+        };
+        assert_eq!(space.shape(), [5]);
+
+        let (observation, _) = Env::reset(&mut env, None, None);
+        assert_eq!(observation.dims(), [5]);
+        for _ in 0..5 {
+            let StepResult { observation, .. } = Env::step(&mut env, ());
+            assert_eq!(observation.dims(), [5]);
+            assert!(space.contains(&observation));
+        }
+    }
+}
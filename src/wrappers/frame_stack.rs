@@ -0,0 +1,244 @@
+//! Wrapper that stacks the most recent observations of an environment.
+
+use std::collections::VecDeque;
+
+use candle_core::Tensor;
+
+use crate::common::InfoMap;
+use crate::core::{Env, ObservationWrapper, ObservationWrapperKind, WrapperKind};
+use crate::spaces::{Bound, Box, SpaceEnum};
+use crate::wrappers::utils::UnsupportedSpaceError;
+
+/// Stacks the last `stack_size` observations along a new leading axis, giving agents short-term
+/// history.
+///
+/// The observations are kept in a ring buffer, which is filled with copies of the initial
+/// observation on [`reset`](Env::reset). The stacked observation starts with the oldest frame and
+/// ends with the most recent one.
+pub struct FrameStack<ActType> {
+    env: std::boxed::Box<dyn Env<Tensor, ActType>>,
+    observation_space: SpaceEnum,
+    stack_size: usize,
+    frames: VecDeque<Tensor>,
+}
+
+impl<ActType> FrameStack<ActType> {
+    /// Wraps `env`, whose observation space is a `Box`, stacking its last `stack_size`
+    /// observations.
+    ///
+    /// Returns an error if the observation space of `env` is not a `Box`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stack_size` is zero.
+    pub fn new(
+        env: std::boxed::Box<dyn Env<Tensor, ActType>>,
+        stack_size: usize,
+    ) -> Result<Self, UnsupportedSpaceError> {
+        assert!(stack_size > 0, "The stack size must be positive");
+        let SpaceEnum::Box(observation_space) = env.observation_space() else {
+            return Err(UnsupportedSpaceError {
+                space: "observation",
+                expected: "a Box",
+            });
+        };
+
+        let stack = |bound: &Tensor| {
+            let bound = Tensor::stack(&vec![bound; stack_size], 0)
+                .expect("Copies of a bound can always be stacked");
+            Bound::Tensor(bound)
+        };
+        let observation_space = Box::new(
+            stack(observation_space.low()),
+            stack(observation_space.high()),
+            None,
+            observation_space.dtype(),
+            observation_space.low().device(),
+            None,
+        )
+        .expect("The bounds are tensors");
+
+        Ok(Self {
+            env,
+            observation_space: observation_space.into(),
+            stack_size,
+            frames: VecDeque::with_capacity(stack_size),
+        })
+    }
+
+    /// The number of stacked observations.
+    pub fn stack_size(&self) -> usize {
+        self.stack_size
+    }
+
+    fn stacked(&self) -> Tensor {
+        Tensor::stack(&self.frames.iter().collect::<Vec<_>>(), 0)
+            .expect("Observations must keep the same shape")
+    }
+}
+
+impl<ActType> ObservationWrapper for FrameStack<ActType> {
+    type ObsType = Tensor;
+    type ActType = ActType;
+    type WrapperObsType = Tensor;
+
+    fn env(&self) -> &dyn Env<Tensor, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<Tensor, ActType> {
+        self.env.as_mut()
+    }
+
+    /// Pushes `observation` into the buffer, dropping the oldest frame, and returns the stack.
+    fn observation(&mut self, observation: Tensor) -> Tensor {
+        if self.frames.len() == self.stack_size {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(observation);
+        self.stacked()
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+
+    /// Fills the buffer with the initial observation.
+    fn reset(&mut self, seed: Option<u64>, options: Option<&InfoMap>) -> (Tensor, InfoMap) {
+        let (observation, info) = self.env.reset(seed, options);
+        self.frames.clear();
+        self.frames
+            .extend(std::iter::repeat_n(observation, self.stack_size));
+        (self.stacked(), info)
+    }
+}
+
+impl<ActType> WrapperKind for FrameStack<ActType> {
+    type Kind = ObservationWrapperKind;
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::{DType, Device, Tensor};
+
+    use super::FrameStack;
+    use crate::common::InfoMap;
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Bound, Box, Discrete, SpaceEnum};
+    use crate::wrappers::utils::UnsupportedSpaceError;
+
+    /// Observes `[count, -count]`, where `count` is the number of steps since the last reset.
+    struct CounterEnv {
+        count: f32,
+        space: SpaceEnum,
+    }
+
+    impl CounterEnv {
+        fn observe(&self) -> Tensor {
+            Tensor::new(&[self.count, -self.count], &Device::Cpu).unwrap()
+        }
+    }
+
+    impl Env<Tensor, ()> for CounterEnv {
+        fn step(&mut self, _action: ()) -> StepResult<Tensor> {
+            self.count += 1.0;
+            (self.observe(), 0.0, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (Tensor, InfoMap) {
+            self.count = 0.0;
+            (self.observe(), InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    fn stacked_env() -> FrameStack<()> {
+        let observation_space = Box::new(
+            Bound::F64(-10.0),
+            Bound::F64(10.0),
+            Some(vec![2]),
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+        FrameStack::new(
+            std::boxed::Box::new(CounterEnv {
+                count: 0.0,
+                space: observation_space.into(),
+            }),
+            3,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_frame_stack_shape() {
+        let mut env = stacked_env();
+        let expected = Box::new(
+            Bound::F64(-10.0),
+            Bound::F64(10.0),
+            Some(vec![3, 2]),
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+        assert_eq!(Env::observation_space(&env), &SpaceEnum::from(expected));
+
+        let (observation, _) = Env::reset(&mut env, None, None);
+        assert_eq!(observation.dims(), [3, 2]);
+        assert_eq!(Env::step(&mut env, ()).observation.dims(), [3, 2]);
+    }
+
+    #[test]
+    fn test_frame_stack_rejects_discrete() {
+        let env = CounterEnv {
+            count: 0.0,
+            space: Discrete::new(3, 0, None).into(),
+        };
+        let result = FrameStack::new(std::boxed::Box::new(env), 3);
+        assert_eq!(
+            result.err(),
+            Some(UnsupportedSpaceError {
+                space: "observation",
+                expected: "a Box",
+            })
+        );
+    }
+
+    #[test]
+    fn test_frame_stack_shifts() {
+        let mut env = stacked_env();
+        let (observation, _) = Env::reset(&mut env, None, None);
+        assert_eq!(
+            observation.to_vec2::<f32>().unwrap(),
+            [[0.0, 0.0], [0.0, 0.0], [0.0, 0.0]]
+        );
+
+        let expected = [
+            [[0.0, 0.0], [0.0, 0.0], [1.0, -1.0]],
+            [[0.0, 0.0], [1.0, -1.0], [2.0, -2.0]],
+            [[1.0, -1.0], [2.0, -2.0], [3.0, -3.0]],
+            [[2.0, -2.0], [3.0, -3.0], [4.0, -4.0]],
+        ];
+        for expected in expected {
+            let StepResult { observation, .. } = Env::step(&mut env, ());
+            assert_eq!(observation.to_vec2::<f32>().unwrap(), expected);
+        }
+
+        // Resetting refills the buffer with the new initial observation.
+        let (observation, _) = Env::reset(&mut env, None, None);
+        assert_eq!(
+            observation.to_vec2::<f32>().unwrap(),
+            [[0.0, 0.0], [0.0, 0.0], [0.0, 0.0]]
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! Wrapper that converts RGB image observations to grayscale.
+
+use candle_core::{DType, Tensor};
+
+use crate::core::{Env, ObservationWrapper, ObservationWrapperKind, WrapperKind};
+use crate::spaces::{Bound, Box, SpaceEnum};
+use crate::wrappers::utils::UnsupportedSpaceError;
+
+/// Weights of the red, green and blue channels in the luminance of a pixel.
+const LUMINANCE_WEIGHTS: [f32; 3] = [0.299, 0.587, 0.114];
+
+/// Converts `(H, W, 3)` RGB image observations to grayscale.
+///
+/// The luminance of each pixel is computed with the standard `0.299`/`0.587`/`0.114` weights and
+/// rounded to the dtype of the wrapped observations. If `keep_dim` is set, the observations have
+/// shape `(H, W, 1)`, otherwise `(H, W)`.
+pub struct GrayScaleObservation<ActType> {
+    env: std::boxed::Box<dyn Env<Tensor, ActType>>,
+    observation_space: SpaceEnum,
+    keep_dim: bool,
+}
+
+impl<ActType> GrayScaleObservation<ActType> {
+    /// Wraps `env`, whose observation space is a `Box` of shape `(H, W, 3)`, converting its
+    /// observations to grayscale.
+    ///
+    /// Returns an error if the observation space of `env` is not a `Box` of shape `(H, W, 3)`.
+    pub fn new(
+        env: std::boxed::Box<dyn Env<Tensor, ActType>>,
+        keep_dim: bool,
+    ) -> Result<Self, UnsupportedSpaceError> {
+        let error = UnsupportedSpaceError {
+            space: "observation",
+            expected: "a Box of shape (H, W, 3)",
+        };
+        let SpaceEnum::Box(observation_space) = env.observation_space() else {
+            return Err(error);
+        };
+        let &[height, width, 3] = observation_space.shape() else {
+            return Err(error);
+        };
+        let shape = if keep_dim {
+            vec![height, width, 1]
+        } else {
+            vec![height, width]
+        };
+        let observation_space = Box::new(
+            Bound::F64(0.0),
+            Bound::F64(255.0),
+            Some(shape),
+            observation_space.dtype(),
+            observation_space.low().device(),
+            None,
+        )
+        .expect("A shape is given");
+
+        Ok(Self {
+            env,
+            observation_space: observation_space.into(),
+            keep_dim,
+        })
+    }
+}
+
+impl<ActType> ObservationWrapper for GrayScaleObservation<ActType> {
+    type ObsType = Tensor;
+    type ActType = ActType;
+    type WrapperObsType = Tensor;
+
+    fn env(&self) -> &dyn Env<Tensor, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<Tensor, ActType> {
+        self.env.as_mut()
+    }
+
+    fn observation(&mut self, observation: Tensor) -> Tensor {
+        let gray_scale = || {
+            let weights = Tensor::new(&LUMINANCE_WEIGHTS, observation.device())?;
+            let luminance = observation
+                .to_dtype(DType::F32)?
+                .broadcast_mul(&weights)?
+                .sum(2)?
+                .round()?;
+            let luminance = if self.keep_dim {
+                luminance.unsqueeze(2)?
+            } else {
+                luminance
+            };
+            luminance.to_dtype(observation.dtype())
+        };
+        gray_scale().expect("Observation must have shape (H, W, 3)")
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+}
+
+impl<ActType> WrapperKind for GrayScaleObservation<ActType> {
+    type Kind = ObservationWrapperKind;
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::{DType, Device, Tensor};
+
+    use super::GrayScaleObservation;
+    use crate::common::InfoMap;
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Bound, Box, Discrete, SpaceEnum};
+
+    /// Observes a fixed `(1, 3, 3)` image of a red, a green and a white pixel.
+    struct ImageEnv {
+        space: SpaceEnum,
+    }
+
+    impl ImageEnv {
+        fn new() -> Self {
+            Self {
+                space: image_space().into(),
+            }
+        }
+
+        fn image() -> Tensor {
+            let pixels: [[u8; 3]; 3] = [[255, 0, 0], [0, 255, 0], [255, 255, 255]];
+            Tensor::new(&[pixels], &Device::Cpu).unwrap()
+        }
+    }
+
+    impl Env<Tensor, ()> for ImageEnv {
+        fn step(&mut self, _action: ()) -> StepResult<Tensor> {
+            (Self::image(), 0.0, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (Tensor, InfoMap) {
+            (Self::image(), InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    fn image_space() -> Box {
+        Box::new(
+            Bound::F64(0.0),
+            Bound::F64(255.0),
+            Some(vec![1, 3, 3]),
+            DType::U8,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap()
+    }
+
+    fn gray_space(shape: Vec<usize>) -> SpaceEnum {
+        let space = Box::new(
+            Bound::F64(0.0),
+            Bound::F64(255.0),
+            Some(shape),
+            DType::U8,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+        space.into()
+    }
+
+    #[test]
+    fn test_gray_scale_observation() {
+        let mut env =
+            GrayScaleObservation::new(std::boxed::Box::new(ImageEnv::new()), false).unwrap();
+        assert_eq!(Env::observation_space(&env), &gray_space(vec![1, 3]));
+
+        let (observation, _) = Env::reset(&mut env, None, None);
+        assert_eq!(observation.dtype(), DType::U8);
+        assert_eq!(observation.to_vec2::<u8>().unwrap(), [[76, 150, 255]]);
+        let StepResult { observation, .. } = Env::step(&mut env, ());
+        assert_eq!(observation.to_vec2::<u8>().unwrap(), [[76, 150, 255]]);
+    }
+
+    #[test]
+    fn test_gray_scale_observation_keep_dim() {
+        let mut env =
+            GrayScaleObservation::new(std::boxed::Box::new(ImageEnv::new()), true).unwrap();
+        assert_eq!(Env::observation_space(&env), &gray_space(vec![1, 3, 1]));
+
+        let (observation, _) = Env::reset(&mut env, None, None);
+        assert_eq!(observation.dims(), [1, 3, 1]);
+        assert_eq!(observation.to_vec3::<u8>().unwrap(), [[[76], [150], [255]]]);
+    }
+
+    #[test]
+    fn test_gray_scale_observation_rejects_non_rgb_spaces() {
+        for space in [gray_space(vec![1, 3]), Discrete::new(3, 0, None).into()] {
+            let env = GrayScaleObservation::new(std::boxed::Box::new(ImageEnv { space }), false);
+            assert_eq!(
+                env.err().unwrap().to_string(),
+                "The observation space of the wrapped environment must be a Box of shape (H, W, 3)"
+            );
+        }
+    }
+}
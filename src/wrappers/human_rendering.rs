@@ -0,0 +1,185 @@
+//! Wrapper that displays the "rgb_array" frames of an environment in a window.
+
+use candle_core::{DType, Tensor};
+
+use crate::common::InfoMap;
+use crate::core::{Env, RenderFrame, StepResult, Wrapper};
+
+/// Performs human rendering for an environment that only supports "rgb_array" or
+/// "rgb_array_list" rendering.
+///
+/// After each [`step`](Env::step) and [`reset`](Env::reset), the frame rendered by the wrapped
+/// environment is displayed in a window, opened on the first frame with the size of the frames and
+/// refreshed at most `render_fps` times per second. [`render`](Env::render) itself returns `None`,
+/// as in the "human" render mode.
+///
+/// The window requires the `human-rendering` feature. Without it, the frames are still rendered
+/// and checked, but not displayed, so that the wrapper can be used headless.
+pub struct HumanRendering<ObsType, ActType> {
+    env: Box<dyn Env<ObsType, ActType>>,
+    render_fps: Option<u32>,
+    frame_size: Option<(usize, usize)>,
+    #[cfg(feature = "human-rendering")]
+    window: Option<minifb::Window>,
+}
+
+impl<ObsType, ActType> HumanRendering<ObsType, ActType> {
+    /// Wraps `env`, which renders "rgb_array" frames, displaying them at `render_fps`, or as fast
+    /// as they are rendered if `None`.
+    pub fn new(env: Box<dyn Env<ObsType, ActType>>, render_fps: Option<u32>) -> Self {
+        Self {
+            env,
+            render_fps,
+            frame_size: None,
+            #[cfg(feature = "human-rendering")]
+            window: None,
+        }
+    }
+
+    /// The max number of frames displayed per second, or `None` if unlimited.
+    pub fn render_fps(&self) -> Option<u32> {
+        self.render_fps
+    }
+
+    /// The `(height, width)` of the frames displayed so far, or `None` before the first frame.
+    pub fn frame_size(&self) -> Option<(usize, usize)> {
+        self.frame_size
+    }
+
+    /// Renders a frame of the wrapped environment and displays it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the wrapped environment does not render `(height, width, 3)` RGB arrays.
+    fn render_frame(&mut self) {
+        let frame = match self.env.render() {
+            Some(RenderFrame::RgbArray(frame)) => frame,
+            Some(RenderFrame::List(mut frames)) => match frames.pop() {
+                Some(RenderFrame::RgbArray(frame)) => frame,
+                None => return,
+                Some(_) => panic!("Expected the wrapped environment to render RGB arrays"),
+            },
+            _ => panic!("Expected the wrapped environment to render RGB arrays"),
+        };
+        let &[height, width, 3] = frame.dims() else {
+            panic!(
+                "Expected an RGB array of shape (height, width, 3), got {:?}",
+                frame.dims()
+            );
+        };
+        self.frame_size = Some((height, width));
+        self.display(&frame, height, width);
+    }
+
+    #[cfg(feature = "human-rendering")]
+    fn display(&mut self, frame: &Tensor, height: usize, width: usize) {
+        let window = match &mut self.window {
+            Some(window) => window,
+            None => {
+                let mut window =
+                    minifb::Window::new("gymnust", width, height, minifb::WindowOptions::default())
+                        .expect("Failed to open a window");
+                if let Some(render_fps) = self.render_fps {
+                    window.set_target_fps(render_fps as usize);
+                }
+                self.window.insert(window)
+            }
+        };
+        window
+            .update_with_buffer(&pixels(frame), width, height)
+            .expect("The frame keeps the size of the window");
+    }
+
+    #[cfg(not(feature = "human-rendering"))]
+    fn display(&mut self, frame: &Tensor, _height: usize, _width: usize) {
+        // Converts the frame as if it were displayed.
+        pixels(frame);
+    }
+}
+
+/// Packs the pixels of a `(height, width, 3)` RGB array into `0RGB` values, row by row.
+fn pixels(frame: &Tensor) -> Vec<u32> {
+    let values = frame
+        .to_dtype(DType::U8)
+        .and_then(|frame| frame.flatten_all())
+        .and_then(|frame| frame.to_vec1::<u8>())
+        .expect("The frame is an RGB array");
+    values
+        .chunks_exact(3)
+        .map(|rgb| (rgb[0] as u32) << 16 | (rgb[1] as u32) << 8 | rgb[2] as u32)
+        .collect()
+}
+
+impl<ObsType, ActType> Wrapper for HumanRendering<ObsType, ActType> {
+    type ObsType = ObsType;
+    type ActType = ActType;
+
+    fn env(&self) -> &dyn Env<ObsType, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<ObsType, ActType> {
+        self.env.as_mut()
+    }
+
+    fn step(&mut self, action: ActType) -> StepResult<ObsType> {
+        let result = self.env.step(action);
+        self.render_frame();
+        result
+    }
+
+    fn reset(&mut self, seed: Option<u64>, options: Option<&InfoMap>) -> (ObsType, InfoMap) {
+        let result = self.env.reset(seed, options);
+        self.render_frame();
+        result
+    }
+
+    fn render(&mut self) -> Option<RenderFrame> {
+        None
+    }
+
+    fn close(&mut self) {
+        #[cfg(feature = "human-rendering")]
+        {
+            self.window = None;
+        }
+        self.env.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::{Device, Tensor};
+
+    use super::{pixels, HumanRendering};
+    use crate::core::Env;
+    use crate::envs::classic_control::CartPole;
+
+    #[test]
+    fn test_pixels() {
+        let frame = Tensor::new(&[[[255u8, 0, 0], [1, 2, 3]]], &Device::Cpu).unwrap();
+        assert_eq!(pixels(&frame), [0xff0000, 0x010203]);
+    }
+
+    #[cfg(not(feature = "human-rendering"))]
+    #[test]
+    fn test_human_rendering_headless() {
+        let mut env = HumanRendering::new(Box::new(CartPole::new(Some("rgb_array"))), Some(50));
+        assert_eq!(env.frame_size(), None);
+
+        env.reset(Some(0), None);
+        for _ in 0..3 {
+            env.step(1);
+        }
+        assert_eq!(env.frame_size(), Some((400, 600)));
+        assert!(env.render().is_none());
+        env.close();
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected the wrapped environment to render RGB arrays")]
+    fn test_human_rendering_requires_rgb_array() {
+        let mut env = HumanRendering::new(Box::new(CartPole::new(Some("ansi"))), None);
+        env.reset(Some(0), None);
+    }
+}
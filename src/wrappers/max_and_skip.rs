@@ -0,0 +1,187 @@
+//! Wrapper that repeats each action for several frames, keeping the brightest pixels.
+
+use candle_core::Tensor;
+
+use crate::core::{Env, StepResult, Wrapper};
+
+/// Repeats each action for `skip` frames, summing the rewards and returning the element-wise
+/// maximum of the last two observations, which removes the flickering of Atari games.
+///
+/// The frames stop early if the episode terminates or is truncated, in which case the maximum is
+/// taken over the last two observations received, or the last one if only one was. The
+/// termination and truncation signals and info are those of the last frame.
+pub struct MaxAndSkip<ActType> {
+    env: Box<dyn Env<Tensor, ActType>>,
+    skip: usize,
+}
+
+impl<ActType> MaxAndSkip<ActType> {
+    /// Wraps `env`, repeating each action for `skip` frames.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `skip` is 0.
+    pub fn new(env: Box<dyn Env<Tensor, ActType>>, skip: usize) -> Self {
+        assert!(skip > 0, "The number of frames to skip must be positive");
+        Self { env, skip }
+    }
+
+    /// The number of frames each action is repeated for.
+    pub fn skip(&self) -> usize {
+        self.skip
+    }
+}
+
+impl<ActType: Clone> Wrapper for MaxAndSkip<ActType> {
+    type ObsType = Tensor;
+    type ActType = ActType;
+
+    fn env(&self) -> &dyn Env<Tensor, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<Tensor, ActType> {
+        self.env.as_mut()
+    }
+
+    fn step(&mut self, action: ActType) -> StepResult<Tensor> {
+        let mut total_reward = 0.0;
+        let mut previous = None;
+        let mut frame = 0;
+        let mut result = loop {
+            let result = self.env.step(action.clone());
+            total_reward += result.reward;
+            frame += 1;
+            if frame == self.skip || result.terminated || result.truncated {
+                break result;
+            }
+            previous = Some(result.observation);
+        };
+
+        if let Some(previous) = previous {
+            result.observation = result
+                .observation
+                .maximum(&previous)
+                .expect("Observations must have the same shape");
+        }
+        result.reward = total_reward;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::{Device, Tensor};
+
+    use super::MaxAndSkip;
+    use crate::common::InfoMap;
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Discrete, SpaceEnum};
+
+    /// Observes the frames `[count, 10 - count]`, rewarding the frame count and terminating after
+    /// `length` frames.
+    struct FrameEnv {
+        count: u32,
+        length: u32,
+        space: SpaceEnum,
+    }
+
+    impl FrameEnv {
+        fn observation(&self) -> Tensor {
+            let count = self.count as f32;
+            Tensor::new(&[count, 10.0 - count], &Device::Cpu).unwrap()
+        }
+    }
+
+    impl Env<Tensor, ()> for FrameEnv {
+        fn step(&mut self, _action: ()) -> StepResult<Tensor> {
+            self.count += 1;
+            (
+                self.observation(),
+                self.count as f32,
+                self.count == self.length,
+                false,
+                InfoMap::new(),
+            )
+                .into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (Tensor, InfoMap) {
+            self.count = 0;
+            (self.observation(), InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    fn max_and_skip(length: u32, skip: usize) -> MaxAndSkip<()> {
+        let env = FrameEnv {
+            count: 0,
+            length,
+            space: Discrete::new(1, 0, None).into(),
+        };
+        MaxAndSkip::new(Box::new(env), skip)
+    }
+
+    #[test]
+    fn test_max_and_skip_accumulates_rewards() {
+        let mut env = max_and_skip(100, 4);
+        env.reset(None, None);
+
+        let StepResult {
+            observation,
+            reward,
+            terminated,
+            ..
+        } = env.step(());
+        // The rewards of the frames 1 to 4, and the maximum of the frames 3 and 4.
+        assert_eq!(reward, 10.0);
+        assert_eq!(observation.to_vec1::<f32>().unwrap(), [4.0, 7.0]);
+        assert!(!terminated);
+
+        let StepResult {
+            observation,
+            reward,
+            ..
+        } = env.step(());
+        assert_eq!(reward, 26.0);
+        assert_eq!(observation.to_vec1::<f32>().unwrap(), [8.0, 3.0]);
+    }
+
+    #[test]
+    fn test_max_and_skip_stops_on_termination() {
+        let mut env = max_and_skip(6, 4);
+        env.reset(None, None);
+        env.step(());
+
+        let StepResult {
+            observation,
+            reward,
+            terminated,
+            ..
+        } = env.step(());
+        // Only the frames 5 and 6 are taken before the termination.
+        assert!(terminated);
+        assert_eq!(reward, 11.0);
+        assert_eq!(observation.to_vec1::<f32>().unwrap(), [6.0, 5.0]);
+
+        // A termination on the first frame returns its observation as is.
+        let mut env = max_and_skip(1, 4);
+        env.reset(None, None);
+        let StepResult {
+            observation,
+            reward,
+            terminated,
+            ..
+        } = env.step(());
+        assert!(terminated);
+        assert_eq!(reward, 1.0);
+        assert_eq!(observation.to_vec1::<f32>().unwrap(), [1.0, 9.0]);
+    }
+}
@@ -0,0 +1,58 @@
+//! Wrappers that modify the behaviour of environments without changing their code.
+
+pub mod add_render_observation;
+pub mod autoreset;
+pub mod clip_action;
+pub mod clip_reward;
+pub mod dtype_observation;
+pub mod filter_observation;
+pub mod flatten_action;
+pub mod flatten_observation;
+pub mod frame_stack;
+pub mod gray_scale_observation;
+pub mod human_rendering;
+pub mod max_and_skip;
+pub mod normalize_observation;
+pub mod normalize_reward;
+pub mod order_enforcing;
+pub mod passive_env_checker;
+pub mod record_episode_statistics;
+pub mod record_video;
+pub mod render_collection;
+pub mod rescale_action;
+pub mod sticky_action;
+pub mod time_aware_observation;
+pub mod time_limit;
+pub mod transform_observation;
+pub mod transform_reward;
+pub mod utils;
+
+pub use crate::core::{
+    ActionWrapper, ActionWrapperKind, ObservationWrapper, ObservationWrapperKind, PlainWrapperKind,
+    RewardWrapper, Wrapper, WrapperKind,
+};
+pub use add_render_observation::AddRenderObservation;
+pub use autoreset::Autoreset;
+pub use clip_action::ClipAction;
+pub use clip_reward::ClipReward;
+pub use dtype_observation::DtypeObservation;
+pub use filter_observation::FilterObservation;
+pub use flatten_action::FlattenAction;
+pub use flatten_observation::FlattenObservation;
+pub use frame_stack::FrameStack;
+pub use gray_scale_observation::GrayScaleObservation;
+pub use human_rendering::HumanRendering;
+pub use max_and_skip::MaxAndSkip;
+pub use normalize_observation::NormalizeObservation;
+pub use normalize_reward::NormalizeReward;
+pub use order_enforcing::OrderEnforcing;
+pub use passive_env_checker::PassiveEnvChecker;
+pub use record_episode_statistics::RecordEpisodeStatistics;
+pub use record_video::RecordVideo;
+pub use render_collection::RenderCollection;
+pub use rescale_action::RescaleAction;
+pub use sticky_action::StickyAction;
+pub use time_aware_observation::TimeAwareObservation;
+pub use time_limit::TimeLimit;
+pub use transform_observation::TransformObservation;
+pub use transform_reward::TransformReward;
@@ -0,0 +1,343 @@
+//! Wrapper that normalizes observations with a running mean and variance.
+
+use candle_core::{DType, Tensor};
+
+use crate::core::{Env, ObservationWrapper, ObservationWrapperKind, WrapperKind};
+use crate::spaces::{Bound, SpaceEnum};
+use crate::wrappers::utils::{RunningMeanStd, UnsupportedSpaceError};
+
+/// Normalizes observations to be centered at the mean with unit variance.
+///
+/// The normalized observations are `F64` if those of the wrapped environment are, and `F32`
+/// otherwise, and lie in an unbounded `Box` of the shape of the wrapped observation space. The running mean and variance are tracked per dimension of the observation, with the
+/// statistics starting at a mean of zero and a variance of one the first time an observation is
+/// seen. The statistics are updated with every observation unless the update is disabled with
+/// [`set_update`](NormalizeObservation::set_update), e.g. to freeze them at evaluation time.
+pub struct NormalizeObservation<ActType> {
+    env: Box<dyn Env<Tensor, ActType>>,
+    observation_space: SpaceEnum,
+    dtype: DType,
+    obs_rms: Option<RunningMeanStd>,
+    epsilon: f64,
+    update_running_mean: bool,
+}
+
+impl<ActType> NormalizeObservation<ActType> {
+    /// Wraps `env`, whose observation space is a `Box`, adding `epsilon` to the variance when
+    /// normalizing to avoid dividing by zero.
+    ///
+    /// Returns an error if the observation space of `env` is not a `Box`.
+    pub fn new(
+        env: Box<dyn Env<Tensor, ActType>>,
+        epsilon: f64,
+    ) -> Result<Self, UnsupportedSpaceError> {
+        let SpaceEnum::Box(space) = env.observation_space() else {
+            return Err(UnsupportedSpaceError {
+                space: "observation",
+                expected: "a Box",
+            });
+        };
+        let dtype = match space.dtype() {
+            DType::F64 => DType::F64,
+            _ => DType::F32,
+        };
+        let observation_space = crate::spaces::Box::new(
+            Bound::F64(f64::NEG_INFINITY),
+            Bound::F64(f64::INFINITY),
+            Some(space.shape().to_vec()),
+            dtype,
+            space.low().device(),
+            None,
+        )
+        .expect("A shape is given");
+
+        Ok(Self {
+            env,
+            observation_space: observation_space.into(),
+            dtype,
+            obs_rms: None,
+            epsilon,
+            update_running_mean: true,
+        })
+    }
+
+    /// Sets whether the running mean and variance are updated with new observations.
+    pub fn set_update(&mut self, update: bool) {
+        self.update_running_mean = update;
+    }
+
+    /// The running mean of the observations, or `None` if no observation has been seen yet.
+    pub fn mean(&self) -> Option<&Tensor> {
+        self.obs_rms.as_ref().map(RunningMeanStd::mean)
+    }
+
+    /// The running variance of the observations, or `None` if no observation has been seen yet.
+    pub fn var(&self) -> Option<&Tensor> {
+        self.obs_rms.as_ref().map(RunningMeanStd::var)
+    }
+}
+
+impl<ActType> ObservationWrapper for NormalizeObservation<ActType> {
+    type ObsType = Tensor;
+    type ActType = ActType;
+    type WrapperObsType = Tensor;
+
+    fn env(&self) -> &dyn Env<Tensor, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<Tensor, ActType> {
+        self.env.as_mut()
+    }
+
+    /// Normalizes the observation using the running mean and variance of the observations.
+    fn observation(&mut self, observation: Tensor) -> Tensor {
+        let mut normalize = || {
+            let obs_rms = match &mut self.obs_rms {
+                Some(obs_rms) => obs_rms,
+                None => self.obs_rms.insert(RunningMeanStd::new(
+                    1e-4,
+                    observation.dims(),
+                    observation.device(),
+                )?),
+            };
+            if self.update_running_mean {
+                obs_rms.update(&observation.unsqueeze(0)?)?;
+            }
+
+            let std = (obs_rms.var() + self.epsilon)?.sqrt()?;
+            ((observation.to_dtype(DType::F64)? - obs_rms.mean())? / std)?.to_dtype(self.dtype)
+        };
+        normalize().expect("Observations must keep the same shape")
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+}
+
+impl<ActType> WrapperKind for NormalizeObservation<ActType> {
+    type Kind = ObservationWrapperKind;
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::{DType, Device, Tensor};
+
+    use super::NormalizeObservation;
+    use crate::common::InfoMap;
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Bound, Box, Discrete, Space, SpaceEnum};
+    use crate::wrappers::utils::UnsupportedSpaceError;
+
+    /// Observes normally distributed values with means `[5, -2]` and standard deviations `[3, 0.5]`.
+    struct GaussianEnv {
+        space: Box,
+        observation_space: SpaceEnum,
+    }
+
+    impl GaussianEnv {
+        fn new() -> Self {
+            let space = Box::new(
+                Bound::F64(f64::NEG_INFINITY),
+                Bound::F64(f64::INFINITY),
+                Some(vec![2]),
+                DType::F64,
+                &Device::Cpu,
+                Some(0),
+            )
+            .unwrap();
+            Self {
+                observation_space: space.clone().into(),
+                space,
+            }
+        }
+
+        fn observe(&mut self) -> Tensor {
+            let scale = Tensor::new(&[3.0f64, 0.5], &Device::Cpu).unwrap();
+            let shift = Tensor::new(&[5.0f64, -2.0], &Device::Cpu).unwrap();
+            ((self.space.sample(None) * scale).unwrap() + shift).unwrap()
+        }
+    }
+
+    impl Env<Tensor, ()> for GaussianEnv {
+        fn step(&mut self, _action: ()) -> StepResult<Tensor> {
+            (self.observe(), 0.0, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (Tensor, InfoMap) {
+            (self.observe(), InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.observation_space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.observation_space
+        }
+    }
+
+    /// Observes samples of its observation space.
+    struct SampleEnv {
+        space: Box,
+        observation_space: SpaceEnum,
+    }
+
+    impl Env<Tensor, ()> for SampleEnv {
+        fn step(&mut self, _action: ()) -> StepResult<Tensor> {
+            (self.space.sample(None), 0.0, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (Tensor, InfoMap) {
+            (self.space.sample(None), InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.observation_space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.observation_space
+        }
+    }
+
+    #[test]
+    fn test_normalize_observation_space() {
+        let space = Box::new(
+            Bound::F64(0.0),
+            Bound::F64(10.0),
+            Some(vec![3]),
+            DType::U8,
+            &Device::Cpu,
+            Some(0),
+        )
+        .unwrap();
+        let env = SampleEnv {
+            observation_space: space.clone().into(),
+            space,
+        };
+        let mut env = NormalizeObservation::new(std::boxed::Box::new(env), 1e-8).unwrap();
+        let expected = Box::new(
+            Bound::F64(f64::NEG_INFINITY),
+            Bound::F64(f64::INFINITY),
+            Some(vec![3]),
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+        assert_eq!(Env::observation_space(&env), &SpaceEnum::from(expected));
+
+        // The normalized observations of integer spaces are not truncated to integers.
+        let (observation, _) = Env::reset(&mut env, None, None);
+        assert!(Env::observation_space(&env).contains(&observation.into()));
+        let mut fractional = false;
+        for _ in 0..10 {
+            let StepResult { observation, .. } = Env::step(&mut env, ());
+            let values = observation.to_vec1::<f32>().unwrap();
+            fractional |= values.iter().any(|value| value.fract() != 0.0);
+            assert!(Env::observation_space(&env).contains(&observation.into()));
+        }
+        assert!(fractional);
+    }
+
+    #[test]
+    fn test_normalize_observation_rejects_discrete() {
+        let env = SampleEnv {
+            space: Box::bounded(0.0, 1.0, vec![1]),
+            observation_space: Discrete::new(3, 0, None).into(),
+        };
+        let result = NormalizeObservation::new(std::boxed::Box::new(env), 1e-8);
+        assert_eq!(
+            result.err(),
+            Some(UnsupportedSpaceError {
+                space: "observation",
+                expected: "a Box",
+            })
+        );
+    }
+
+    #[test]
+    fn test_normalize_observation_mean() {
+        let mut env =
+            NormalizeObservation::new(std::boxed::Box::new(GaussianEnv::new()), 1e-8).unwrap();
+        env.reset(None, None);
+        for _ in 0..2000 {
+            env.step(());
+        }
+
+        let observations: Vec<Tensor> = (0..2000).map(|_| env.step(()).observation).collect();
+        let observations = Tensor::stack(&observations, 0).unwrap();
+        let mean = observations.mean(0).unwrap().to_vec1::<f64>().unwrap();
+        let var = observations.var(0).unwrap().to_vec1::<f64>().unwrap();
+        for (mean, var) in mean.iter().zip(&var) {
+            assert!(mean.abs() < 0.1, "mean {mean} should be close to 0");
+            assert!(
+                (var - 1.0).abs() < 0.1,
+                "variance {var} should be close to 1"
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalize_observation_cold_start() {
+        let mut env =
+            NormalizeObservation::new(std::boxed::Box::new(GaussianEnv::new()), 1e-8).unwrap();
+        let (observation, _) = env.reset(None, None);
+
+        assert_eq!(observation.dims(), [2]);
+        let values = observation.to_vec1::<f64>().unwrap();
+        assert!(values.iter().all(|value| value.is_finite()));
+    }
+
+    #[test]
+    fn test_normalize_observation_frozen() {
+        let mut env =
+            NormalizeObservation::new(std::boxed::Box::new(GaussianEnv::new()), 1e-8).unwrap();
+        env.set_update(false);
+        let mut base = GaussianEnv::new();
+
+        // Without updates, the statistics stay at a mean of zero and a variance of one.
+        for _ in 0..10 {
+            let observation = env.step(()).observation.to_vec1::<f64>().unwrap();
+            let expected = base.step(()).observation.to_vec1::<f64>().unwrap();
+            for (observation, expected) in observation.iter().zip(&expected) {
+                assert!((observation - expected).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_normalize_observation_freeze_after_training() {
+        let mut env =
+            NormalizeObservation::new(std::boxed::Box::new(GaussianEnv::new()), 1e-8).unwrap();
+        let mut base = GaussianEnv::new();
+        assert!(env.mean().is_none() && env.var().is_none());
+        env.reset(None, None);
+        base.reset(None, None);
+        for _ in 0..500 {
+            env.step(());
+            base.step(());
+        }
+
+        env.set_update(false);
+        let mean = env.mean().unwrap().to_vec1::<f64>().unwrap();
+        let var = env.var().unwrap().to_vec1::<f64>().unwrap();
+        assert!(
+            (mean[0] - 5.0).abs() < 0.5 && (mean[1] + 2.0).abs() < 0.5,
+            "mean {mean:?} should be close to [5, -2]"
+        );
+        for _ in 0..10 {
+            let observation = env.step(()).observation.to_vec1::<f64>().unwrap();
+            let raw = base.step(()).observation.to_vec1::<f64>().unwrap();
+            // The frozen statistics still normalize the observations.
+            for i in 0..2 {
+                let expected = (raw[i] - mean[i]) / (var[i] + 1e-8).sqrt();
+                assert!((observation[i] - expected).abs() < 1e-6);
+            }
+        }
+        assert_eq!(env.mean().unwrap().to_vec1::<f64>().unwrap(), mean);
+        assert_eq!(env.var().unwrap().to_vec1::<f64>().unwrap(), var);
+    }
+}
@@ -0,0 +1,160 @@
+//! Wrapper that normalizes rewards with the running variance of the discounted return.
+
+use candle_core::{Device, Tensor};
+
+use crate::common::InfoMap;
+use crate::core::{Env, StepResult, Wrapper};
+use crate::wrappers::utils::RunningMeanStd;
+
+/// Normalizes immediate rewards such that their exponential moving average has an approximately
+/// fixed variance.
+///
+/// Each reward is divided by the standard deviation of a running estimate of the discounted
+/// return. The discounted return is accumulated across steps and cleared when an episode
+/// terminates or the environment is reset, while its variance is tracked over the whole lifetime
+/// of the wrapper. The statistics are updated with every step unless the update is disabled with
+/// [`set_update`](NormalizeReward::set_update), e.g. to freeze them at evaluation time.
+pub struct NormalizeReward<ObsType, ActType> {
+    env: Box<dyn Env<ObsType, ActType>>,
+    return_rms: RunningMeanStd,
+    discounted_reward: f64,
+    gamma: f64,
+    epsilon: f64,
+    update_running_mean: bool,
+}
+
+impl<ObsType, ActType> NormalizeReward<ObsType, ActType> {
+    /// Wraps `env`, discounting the return with `gamma` and adding `epsilon` to its variance when
+    /// normalizing to avoid dividing by zero.
+    pub fn new(env: Box<dyn Env<ObsType, ActType>>, gamma: f64, epsilon: f64) -> Self {
+        Self {
+            env,
+            return_rms: RunningMeanStd::new(1e-4, &[], &Device::Cpu)
+                .expect("Statistics of a scalar can always be created"),
+            discounted_reward: 0.0,
+            gamma,
+            epsilon,
+            update_running_mean: true,
+        }
+    }
+
+    /// Sets whether the running variance is updated with new rewards.
+    pub fn set_update(&mut self, update: bool) {
+        self.update_running_mean = update;
+    }
+
+    /// Scales `reward` by the standard deviation of the discounted return.
+    fn normalize(&mut self, reward: f32) -> candle_core::Result<f32> {
+        if self.update_running_mean {
+            let discounted_reward = Tensor::new(&[self.discounted_reward], &Device::Cpu)?;
+            self.return_rms.update(&discounted_reward)?;
+        }
+        let var = self.return_rms.var().to_scalar::<f64>()?;
+        Ok((reward as f64 / (var + self.epsilon).sqrt()) as f32)
+    }
+}
+
+impl<ObsType, ActType> Wrapper for NormalizeReward<ObsType, ActType> {
+    type ObsType = ObsType;
+    type ActType = ActType;
+
+    fn env(&self) -> &dyn Env<ObsType, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<ObsType, ActType> {
+        self.env.as_mut()
+    }
+
+    fn step(&mut self, action: ActType) -> StepResult<ObsType> {
+        let mut result = self.env.step(action);
+        self.discounted_reward = self.discounted_reward * self.gamma + result.reward as f64;
+        result.reward = self
+            .normalize(result.reward)
+            .expect("The return statistics must be a scalar");
+        if result.terminated {
+            self.discounted_reward = 0.0;
+        }
+        result
+    }
+
+    fn reset(&mut self, seed: Option<u64>, options: Option<&InfoMap>) -> (ObsType, InfoMap) {
+        self.discounted_reward = 0.0;
+        self.env.reset(seed, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NormalizeReward;
+    use crate::common::InfoMap;
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Discrete, SpaceEnum};
+
+    /// Gives rewards alternating between `100` and `-100`, never terminating.
+    struct AlternatingEnv {
+        sign: f32,
+        space: SpaceEnum,
+    }
+
+    impl Env<(), ()> for AlternatingEnv {
+        fn step(&mut self, _action: ()) -> StepResult<()> {
+            self.sign = -self.sign;
+            ((), 100.0 * self.sign, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> ((), InfoMap) {
+            self.sign = -1.0;
+            ((), InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    fn normalized_env() -> NormalizeReward<(), ()> {
+        NormalizeReward::new(
+            Box::new(AlternatingEnv {
+                sign: -1.0,
+                space: Discrete::new(1, 0, None).into(),
+            }),
+            0.99,
+            1e-8,
+        )
+    }
+
+    #[test]
+    fn test_normalize_reward_scales_down() {
+        let mut env = normalized_env();
+        env.reset(None, None);
+        for _ in 0..100 {
+            env.step(());
+        }
+
+        for _ in 0..10 {
+            let reward = env.step(()).reward;
+            assert!(
+                reward.abs() < 100.0 / 10.0,
+                "reward {reward} should be scaled down"
+            );
+            assert!(reward.abs() > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_normalize_reward_frozen() {
+        let mut env = normalized_env();
+        env.set_update(false);
+        env.reset(None, None);
+
+        // Without updates, the variance of the return stays at one.
+        for _ in 0..10 {
+            assert_eq!(env.step(()).reward.abs(), 100.0);
+        }
+    }
+}
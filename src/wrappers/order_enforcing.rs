@@ -0,0 +1,62 @@
+//! Wrapper that enforces calling `reset` before `step` or `render`.
+
+use crate::common::InfoMap;
+use crate::core::{Env, RenderFrame, StepResult, Wrapper};
+
+/// Panics if [`step`](Env::step) or [`render`](Env::render) is called before [`reset`](Env::reset).
+pub struct OrderEnforcing<ObsType, ActType> {
+    env: Box<dyn Env<ObsType, ActType>>,
+    has_reset: bool,
+    disable_render_order_enforcing: bool,
+}
+
+impl<ObsType, ActType> OrderEnforcing<ObsType, ActType> {
+    /// Wraps `env`. If `disable_render_order_enforcing` is `true`, `render` may be called before `reset`.
+    pub fn new(env: Box<dyn Env<ObsType, ActType>>, disable_render_order_enforcing: bool) -> Self {
+        Self {
+            env,
+            has_reset: false,
+            disable_render_order_enforcing,
+        }
+    }
+
+    /// Whether the environment has been reset.
+    pub fn has_reset(&self) -> bool {
+        self.has_reset
+    }
+}
+
+impl<ObsType, ActType> Wrapper for OrderEnforcing<ObsType, ActType> {
+    type ObsType = ObsType;
+    type ActType = ActType;
+
+    fn env(&self) -> &dyn Env<ObsType, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<ObsType, ActType> {
+        self.env.as_mut()
+    }
+
+    fn step(&mut self, action: ActType) -> StepResult<ObsType> {
+        assert!(
+            self.has_reset,
+            "Cannot call env.step() before calling env.reset()"
+        );
+        self.env.step(action)
+    }
+
+    fn reset(&mut self, seed: Option<u64>, options: Option<&InfoMap>) -> (ObsType, InfoMap) {
+        self.has_reset = true;
+        self.env.reset(seed, options)
+    }
+
+    fn render(&mut self) -> Option<RenderFrame> {
+        assert!(
+            self.disable_render_order_enforcing || self.has_reset,
+            "Cannot call `env.render()` before calling `env.reset()`, if this is an intended action, \
+             set `disable_render_order_enforcing=true` on the OrderEnforcing wrapper."
+        );
+        self.env.render()
+    }
+}
@@ -0,0 +1,188 @@
+//! Wrapper that checks the outputs of an environment without changing them.
+
+use std::fmt;
+
+use crate::common::InfoMap;
+use crate::core::{Env, StepResult, Wrapper};
+use crate::spaces::{Space, StructuredSample};
+
+/// A failure detected by [`PassiveEnvChecker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EnvCheckWarning {
+    /// The observation returned by `reset` is not an element of the observation space.
+    ResetObservationNotInSpace,
+    /// The observation returned by `step` is not an element of the observation space.
+    StepObservationNotInSpace,
+    /// The reward returned by `step` is NaN or infinite.
+    NonFiniteReward,
+}
+
+impl fmt::Display for EnvCheckWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Self::ResetObservationNotInSpace => {
+                "The obs returned by the `reset()` method is not within the observation space."
+            }
+            Self::StepObservationNotInSpace => {
+                "The obs returned by the `step()` method is not within the observation space."
+            }
+            Self::NonFiniteReward => "The reward returned by the `step()` method is not finite.",
+        };
+        f.write_str(message)
+    }
+}
+
+/// Checks that the observations and rewards of an environment are valid, without modifying them.
+///
+/// Each [`reset`](Env::reset) and [`step`](Env::step) checks that the observation is an element of
+/// the [`observation_space`](Env::observation_space) of the environment and that the reward is
+/// finite; `terminated` and `truncated` are booleans by construction. Instead of panicking, each
+/// kind of failure is recorded once in [`warnings`](PassiveEnvChecker::warnings) and logged as a
+/// warning through the [`log`] facade.
+pub struct PassiveEnvChecker<ObsType, ActType> {
+    env: Box<dyn Env<ObsType, ActType>>,
+    warnings: Vec<EnvCheckWarning>,
+}
+
+impl<ObsType, ActType> PassiveEnvChecker<ObsType, ActType>
+where
+    ObsType: Clone + Into<StructuredSample>,
+{
+    /// Wraps `env`, checking its outputs.
+    pub fn new(env: Box<dyn Env<ObsType, ActType>>) -> Self {
+        Self {
+            env,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// The warnings emitted so far, in the order in which they were first emitted.
+    pub fn warnings(&self) -> &[EnvCheckWarning] {
+        &self.warnings
+    }
+
+    /// Emits `warning` if `failed` and it has not been emitted before.
+    fn check(&mut self, failed: bool, warning: EnvCheckWarning) {
+        if failed && !self.warnings.contains(&warning) {
+            log::warn!("{warning}");
+            self.warnings.push(warning);
+        }
+    }
+
+    fn contains(&self, observation: &ObsType) -> bool {
+        self.env
+            .observation_space()
+            .contains(&observation.clone().into())
+    }
+}
+
+impl<ObsType, ActType> Wrapper for PassiveEnvChecker<ObsType, ActType>
+where
+    ObsType: Clone + Into<StructuredSample>,
+{
+    type ObsType = ObsType;
+    type ActType = ActType;
+
+    fn env(&self) -> &dyn Env<ObsType, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<ObsType, ActType> {
+        self.env.as_mut()
+    }
+
+    fn step(&mut self, action: ActType) -> StepResult<ObsType> {
+        let result = self.env.step(action);
+        let contained = self.contains(&result.observation);
+        self.check(!contained, EnvCheckWarning::StepObservationNotInSpace);
+        self.check(!result.reward.is_finite(), EnvCheckWarning::NonFiniteReward);
+        result
+    }
+
+    fn reset(&mut self, seed: Option<u64>, options: Option<&InfoMap>) -> (ObsType, InfoMap) {
+        let (observation, info) = self.env.reset(seed, options);
+        let contained = self.contains(&observation);
+        self.check(!contained, EnvCheckWarning::ResetObservationNotInSpace);
+        (observation, info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EnvCheckWarning, PassiveEnvChecker};
+    use crate::common::InfoMap;
+    use crate::core::{Env, StepResult};
+    use crate::envs::toy_text::FrozenLake;
+    use crate::spaces::{Discrete, SpaceEnum};
+
+    /// Observes values outside of `{0, 1}` and returns NaN rewards on odd steps.
+    struct MisbehavingEnv {
+        count: i64,
+        observation_space: SpaceEnum,
+        action_space: SpaceEnum,
+    }
+
+    impl Env<i64, ()> for MisbehavingEnv {
+        fn step(&mut self, _action: ()) -> StepResult<i64> {
+            self.count += 1;
+            let reward = if self.count % 2 == 1 { f32::NAN } else { 0.0 };
+            (self.count, reward, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (i64, InfoMap) {
+            self.count = 5;
+            (self.count, InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.action_space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.observation_space
+        }
+    }
+
+    #[test]
+    fn test_passive_env_checker_warns() {
+        let env = MisbehavingEnv {
+            count: 0,
+            observation_space: Discrete::new(2, 0, None).into(),
+            action_space: Discrete::new(1, 0, None).into(),
+        };
+        let mut env = PassiveEnvChecker::new(Box::new(env));
+
+        env.reset(None, None);
+        assert_eq!(
+            env.warnings(),
+            [EnvCheckWarning::ResetObservationNotInSpace]
+        );
+        for _ in 0..4 {
+            env.step(());
+        }
+
+        // Each kind of failure is reported once.
+        assert_eq!(
+            env.warnings(),
+            [
+                EnvCheckWarning::ResetObservationNotInSpace,
+                EnvCheckWarning::StepObservationNotInSpace,
+                EnvCheckWarning::NonFiniteReward,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_passive_env_checker_valid_env() {
+        let mut env = PassiveEnvChecker::new(Box::new(FrozenLake::default()));
+
+        env.reset(Some(0), None);
+        for action in [1, 2, 1, 0] {
+            let result = env.step(action);
+            if result.terminated {
+                env.reset(None, None);
+            }
+        }
+        assert!(env.warnings().is_empty());
+    }
+}
@@ -0,0 +1,279 @@
+//! Wrapper that records the cumulative reward, length and duration of episodes.
+
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use crate::common::{InfoMap, InfoValue};
+use crate::core::{Env, StepResult, Wrapper};
+
+/// Keeps track of the cumulative rewards and episode lengths.
+///
+/// At the end of an episode, the statistics of the episode are added to the info under the key
+/// `"episode"`, as a map with the keys:
+/// - `"r"`: The cumulative reward of the episode.
+/// - `"l"`: The length of the episode in steps.
+/// - `"t"`: The elapsed time since the beginning of the episode, in seconds.
+///
+/// If the info already has an `"episode"` key, e.g. when the wrapper is applied twice, a warning is
+/// logged and the existing entry is kept.
+///
+/// The statistics of the last `buffer_length` episodes are also kept in queues, e.g. for logging.
+pub struct RecordEpisodeStatistics<ObsType, ActType> {
+    env: Box<dyn Env<ObsType, ActType>>,
+    episode_start_time: Instant,
+    episode_returns: f64,
+    episode_lengths: usize,
+    buffer_length: usize,
+    time_queue: VecDeque<f64>,
+    return_queue: VecDeque<f64>,
+    length_queue: VecDeque<usize>,
+}
+
+impl<ObsType, ActType> RecordEpisodeStatistics<ObsType, ActType> {
+    /// Wraps `env`, keeping the statistics of the last `buffer_length` episodes.
+    pub fn new(env: Box<dyn Env<ObsType, ActType>>, buffer_length: usize) -> Self {
+        Self {
+            env,
+            episode_start_time: Instant::now(),
+            episode_returns: 0.0,
+            episode_lengths: 0,
+            buffer_length,
+            time_queue: VecDeque::with_capacity(buffer_length),
+            return_queue: VecDeque::with_capacity(buffer_length),
+            length_queue: VecDeque::with_capacity(buffer_length),
+        }
+    }
+
+    /// The elapsed times of the last episodes in seconds, from the oldest to the newest.
+    pub fn time_queue(&self) -> &VecDeque<f64> {
+        &self.time_queue
+    }
+
+    /// The cumulative rewards of the last episodes, from the oldest to the newest.
+    pub fn return_queue(&self) -> &VecDeque<f64> {
+        &self.return_queue
+    }
+
+    /// The lengths of the last episodes, from the oldest to the newest.
+    pub fn length_queue(&self) -> &VecDeque<usize> {
+        &self.length_queue
+    }
+}
+
+/// Appends `value` to `queue`, dropping the oldest values beyond `buffer_length`.
+fn push_bounded<T>(queue: &mut VecDeque<T>, value: T, buffer_length: usize) {
+    queue.push_back(value);
+    while queue.len() > buffer_length {
+        queue.pop_front();
+    }
+}
+
+impl<ObsType, ActType> Wrapper for RecordEpisodeStatistics<ObsType, ActType> {
+    type ObsType = ObsType;
+    type ActType = ActType;
+
+    fn env(&self) -> &dyn Env<ObsType, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<ObsType, ActType> {
+        self.env.as_mut()
+    }
+
+    fn step(&mut self, action: ActType) -> StepResult<ObsType> {
+        let mut result = self.env.step(action);
+        self.episode_returns += result.reward as f64;
+        self.episode_lengths += 1;
+
+        if result.terminated || result.truncated {
+            let episode_time_length = self.episode_start_time.elapsed().as_secs_f64();
+            if result.info.contains_key("episode") {
+                log::warn!(
+                    "The info already has episode statistics, keeping them instead of adding new ones"
+                );
+            } else {
+                result.info.insert(
+                    "episode".to_string(),
+                    InfoValue::Map(InfoMap::from([
+                        ("r".to_string(), InfoValue::Float(self.episode_returns)),
+                        ("l".to_string(), InfoValue::Int(self.episode_lengths as i64)),
+                        ("t".to_string(), InfoValue::Float(episode_time_length)),
+                    ])),
+                );
+            }
+
+            let buffer_length = self.buffer_length;
+            push_bounded(&mut self.time_queue, episode_time_length, buffer_length);
+            push_bounded(&mut self.return_queue, self.episode_returns, buffer_length);
+            push_bounded(&mut self.length_queue, self.episode_lengths, buffer_length);
+
+            self.episode_lengths = 0;
+            self.episode_returns = 0.0;
+            self.episode_start_time = Instant::now();
+        }
+        result
+    }
+
+    fn reset(&mut self, seed: Option<u64>, options: Option<&InfoMap>) -> (ObsType, InfoMap) {
+        let result = self.env.reset(seed, options);
+        self.episode_start_time = Instant::now();
+        self.episode_returns = 0.0;
+        self.episode_lengths = 0;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RecordEpisodeStatistics;
+    use crate::common::{InfoMap, InfoValue};
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Discrete, SpaceEnum};
+
+    /// Rewards the step count, terminating after `length` steps.
+    struct CountingEnv {
+        count: i64,
+        length: i64,
+        space: SpaceEnum,
+    }
+
+    impl Env<i64, ()> for CountingEnv {
+        fn step(&mut self, _action: ()) -> StepResult<i64> {
+            self.count += 1;
+            let terminated = self.count == self.length;
+            (
+                self.count,
+                self.count as f32,
+                terminated,
+                false,
+                InfoMap::new(),
+            )
+                .into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (i64, InfoMap) {
+            self.count = 0;
+            (self.count, InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    fn recorded_env(length: i64, buffer_length: usize) -> RecordEpisodeStatistics<i64, ()> {
+        RecordEpisodeStatistics::new(
+            Box::new(CountingEnv {
+                count: 0,
+                length,
+                space: Discrete::new(100, 0, None).into(),
+            }),
+            buffer_length,
+        )
+    }
+
+    #[test]
+    fn test_record_episode_statistics() {
+        let mut env = recorded_env(4, 100);
+        env.reset(None, None);
+
+        let mut rewards = 0.0;
+        let info = loop {
+            let result = env.step(());
+            rewards += result.reward as f64;
+            if result.terminated {
+                break result.info;
+            }
+            assert!(!result.info.contains_key("episode"));
+        };
+
+        let InfoValue::Map(episode) = &info["episode"] else {
+            panic!("The episode statistics must be a map");
+        };
+        assert!(matches!(episode["r"], InfoValue::Float(r) if r == rewards));
+        assert_eq!(rewards, 10.0);
+        assert!(matches!(episode["l"], InfoValue::Int(4)));
+        assert!(matches!(episode["t"], InfoValue::Float(t) if t >= 0.0));
+        assert_eq!(
+            env.return_queue().iter().copied().collect::<Vec<_>>(),
+            [10.0]
+        );
+        assert_eq!(env.length_queue().iter().copied().collect::<Vec<_>>(), [4]);
+        assert_eq!(env.time_queue().len(), 1);
+    }
+
+    #[test]
+    fn test_record_episode_statistics_reset() {
+        let mut env = recorded_env(3, 100);
+        env.reset(None, None);
+        env.step(());
+        env.step(());
+
+        // The partial episode is discarded, so only the rewards `1 + 2 + 3` are recorded.
+        env.reset(None, None);
+        let info = (0..3).map(|_| env.step(())).last().unwrap().info;
+        let InfoValue::Map(episode) = &info["episode"] else {
+            panic!("The episode statistics must be a map");
+        };
+        assert!(matches!(episode["r"], InfoValue::Float(r) if r == 6.0));
+        assert!(matches!(episode["l"], InfoValue::Int(3)));
+    }
+
+    #[test]
+    fn test_record_episode_statistics_buffer_length() {
+        let mut env = recorded_env(2, 2);
+        for _ in 0..3 {
+            env.reset(None, None);
+            env.step(());
+            env.step(());
+        }
+
+        assert_eq!(
+            env.return_queue().iter().copied().collect::<Vec<_>>(),
+            [3.0, 3.0]
+        );
+        assert_eq!(
+            env.length_queue().iter().copied().collect::<Vec<_>>(),
+            [2, 2]
+        );
+    }
+
+    /// Terminates after every step, with an `"episode"` entry in the info.
+    struct EpisodeInfoEnv {
+        space: SpaceEnum,
+    }
+
+    impl Env<i64, ()> for EpisodeInfoEnv {
+        fn step(&mut self, _action: ()) -> StepResult<i64> {
+            let info = InfoMap::from([("episode".to_string(), InfoValue::Int(7))]);
+            (0, 1.0, true, false, info).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (i64, InfoMap) {
+            (0, InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    #[test]
+    fn test_record_episode_statistics_keeps_existing_statistics() {
+        let space = Discrete::new(1, 0, None).into();
+        let mut env = RecordEpisodeStatistics::new(Box::new(EpisodeInfoEnv { space }), 100);
+        env.reset(None, None);
+        let info = env.step(()).info;
+
+        assert!(matches!(info["episode"], InfoValue::Int(7)));
+        assert_eq!(env.length_queue().iter().copied().collect::<Vec<_>>(), [1]);
+    }
+}
@@ -0,0 +1,334 @@
+//! Wrapper that records videos of the episodes of an environment.
+
+use std::path::{Path, PathBuf};
+
+use candle_core::Tensor;
+
+use crate::common::InfoMap;
+use crate::core::{Env, RenderFrame, StepResult, Wrapper};
+
+/// Records videos of the episodes selected by `episode_trigger`, from the "rgb_array" or
+/// "rgb_array_list" frames rendered by the environment.
+///
+/// Episodes are numbered from 0 by the [`reset`](Env::reset)s of the wrapper. The frames of a
+/// selected episode are collected after its reset and each of its steps, then encoded to
+/// `{video_folder}/rl-video-episode-{episode_id}.gif` when the next episode starts or the wrapper
+/// is [`close`](Env::close)d.
+///
+/// Encoding requires the `record-video` feature. Without it, the frames are still collected but no
+/// file is written.
+pub struct RecordVideo<ObsType, ActType> {
+    env: Box<dyn Env<ObsType, ActType>>,
+    video_folder: PathBuf,
+    episode_trigger: Box<dyn Fn(usize) -> bool>,
+    fps: u32,
+    episode_id: usize,
+    recording: bool,
+    recorded_frames: Vec<Tensor>,
+    video_path: Option<PathBuf>,
+}
+
+impl<ObsType, ActType> RecordVideo<ObsType, ActType> {
+    /// Wraps `env`, which renders "rgb_array" frames, recording the episodes for which
+    /// `episode_trigger` returns `true` to videos in `video_folder`, played at `fps` frames per
+    /// second.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fps` is 0.
+    pub fn new(
+        env: Box<dyn Env<ObsType, ActType>>,
+        video_folder: impl Into<PathBuf>,
+        episode_trigger: impl Fn(usize) -> bool + 'static,
+        fps: u32,
+    ) -> Self {
+        assert!(fps > 0, "The fps must be positive");
+        Self {
+            env,
+            video_folder: video_folder.into(),
+            episode_trigger: Box::new(episode_trigger),
+            fps,
+            episode_id: 0,
+            recording: false,
+            recorded_frames: Vec::new(),
+            video_path: None,
+        }
+    }
+
+    /// The path of the video being recorded, or of the last one if none is, or `None` if no
+    /// episode has been recorded yet.
+    pub fn video_path(&self) -> Option<&Path> {
+        self.video_path.as_deref()
+    }
+
+    /// The number of frames recorded in the current video.
+    pub fn frame_count(&self) -> usize {
+        self.recorded_frames.len()
+    }
+
+    /// Whether the current episode is being recorded.
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    /// Renders the frames of the wrapped environment and adds them to the current video.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the wrapped environment does not render `(height, width, 3)` RGB arrays of a
+    /// constant shape.
+    fn capture_frame(&mut self) {
+        let frames = match self.env.render() {
+            Some(RenderFrame::RgbArray(frame)) => vec![frame],
+            Some(RenderFrame::List(frames)) => frames
+                .into_iter()
+                .map(|frame| match frame {
+                    RenderFrame::RgbArray(frame) => frame,
+                    _ => panic!("Expected the wrapped environment to render RGB arrays"),
+                })
+                .collect(),
+            _ => panic!("Expected the wrapped environment to render RGB arrays"),
+        };
+        for frame in frames {
+            assert!(
+                matches!(frame.dims(), &[_, _, 3]),
+                "Expected an RGB array of shape (height, width, 3), got {:?}",
+                frame.dims()
+            );
+            if let Some(first) = self.recorded_frames.first() {
+                assert_eq!(
+                    first.dims(),
+                    frame.dims(),
+                    "The frames of a video must have the same shape"
+                );
+            }
+            self.recorded_frames.push(frame);
+        }
+    }
+
+    /// Starts recording the episode `episode_id`.
+    fn start_recording(&mut self, episode_id: usize) {
+        self.recording = true;
+        self.recorded_frames.clear();
+        self.video_path = Some(
+            self.video_folder
+                .join(format!("rl-video-episode-{episode_id}.gif")),
+        );
+    }
+
+    /// Stops recording, encoding the recorded frames to the video file.
+    fn stop_recording(&mut self) {
+        if let Some(path) = self.video_path.as_deref() {
+            if !self.recorded_frames.is_empty() {
+                save_video(path, &self.recorded_frames, self.fps);
+            }
+        }
+        self.recording = false;
+        self.recorded_frames.clear();
+    }
+}
+
+/// Encodes `frames` to a GIF file at `path`, creating its folder if needed.
+///
+/// # Panics
+///
+/// Panics if the file cannot be written.
+#[cfg(feature = "record-video")]
+fn save_video(path: &Path, frames: &[Tensor], fps: u32) {
+    use candle_core::DType;
+
+    let write = || -> Result<(), gif::EncodingError> {
+        if let Some(folder) = path.parent() {
+            std::fs::create_dir_all(folder)?;
+        }
+        let &[height, width, _] = frames[0].dims() else {
+            unreachable!("The frames are RGB arrays");
+        };
+        let (height, width) = (height as u16, width as u16);
+        let mut encoder = gif::Encoder::new(std::fs::File::create(path)?, width, height, &[])?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+        for frame in frames {
+            let pixels = frame
+                .to_dtype(DType::U8)
+                .and_then(|frame| frame.flatten_all())
+                .and_then(|frame| frame.to_vec1::<u8>())
+                .expect("The frame is an RGB array");
+            let mut frame = gif::Frame::from_rgb(width, height, &pixels);
+            // GIF delays are in hundredths of a second.
+            frame.delay = (100 / fps).max(1) as u16;
+            encoder.write_frame(&frame)?;
+        }
+        Ok(())
+    };
+    write().unwrap_or_else(|err| panic!("Failed to write the video {}: {err}", path.display()));
+}
+
+#[cfg(not(feature = "record-video"))]
+fn save_video(_path: &Path, _frames: &[Tensor], _fps: u32) {}
+
+impl<ObsType, ActType> Wrapper for RecordVideo<ObsType, ActType> {
+    type ObsType = ObsType;
+    type ActType = ActType;
+
+    fn env(&self) -> &dyn Env<ObsType, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<ObsType, ActType> {
+        self.env.as_mut()
+    }
+
+    fn step(&mut self, action: ActType) -> StepResult<ObsType> {
+        let result = self.env.step(action);
+        if self.recording {
+            self.capture_frame();
+        }
+        result
+    }
+
+    fn reset(&mut self, seed: Option<u64>, options: Option<&InfoMap>) -> (ObsType, InfoMap) {
+        let result = self.env.reset(seed, options);
+        if self.recording {
+            self.stop_recording();
+        }
+        let episode_id = self.episode_id;
+        self.episode_id += 1;
+        if (self.episode_trigger)(episode_id) {
+            self.start_recording(episode_id);
+            self.capture_frame();
+        }
+        result
+    }
+
+    fn close(&mut self) {
+        if self.recording {
+            self.stop_recording();
+        }
+        self.env.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::{Device, Tensor};
+
+    use super::RecordVideo;
+    use crate::common::InfoMap;
+    use crate::core::{Env, RenderFrame, StepResult};
+    use crate::spaces::{Discrete, SpaceEnum};
+
+    /// Renders the step count as a uniform 2x3 image, terminating after `length` steps.
+    struct FrameEnv {
+        count: u8,
+        length: u8,
+        space: SpaceEnum,
+    }
+
+    impl Env<u8, ()> for FrameEnv {
+        fn step(&mut self, _action: ()) -> StepResult<u8> {
+            self.count += 1;
+            (
+                self.count,
+                0.0,
+                self.count == self.length,
+                false,
+                InfoMap::new(),
+            )
+                .into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (u8, InfoMap) {
+            self.count = 0;
+            (self.count, InfoMap::new())
+        }
+
+        fn render(&mut self) -> Option<RenderFrame> {
+            let frame = Tensor::full(self.count, (2, 3, 3), &Device::Cpu).unwrap();
+            Some(RenderFrame::RgbArray(frame))
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    fn run_episode(env: &mut RecordVideo<u8, ()>) {
+        env.reset(None, None);
+        while !env.step(()).terminated {}
+    }
+
+    #[test]
+    fn test_record_video_episode_trigger() {
+        let folder = std::env::temp_dir().join("gymnust-record-video-trigger");
+        let env = FrameEnv {
+            count: 0,
+            length: 4,
+            space: Discrete::new(1, 0, None).into(),
+        };
+        let mut env = RecordVideo::new(Box::new(env), &folder, |episode| episode % 2 == 0, 30);
+        assert_eq!(env.video_path(), None);
+
+        let mut recorded = Vec::new();
+        for _ in 0..5 {
+            run_episode(&mut env);
+            recorded.push(env.is_recording());
+            if env.is_recording() {
+                // The frames of the reset and the 4 steps of the episode.
+                assert_eq!(env.frame_count(), 5);
+            } else {
+                assert_eq!(env.frame_count(), 0);
+            }
+        }
+        assert_eq!(recorded, [true, false, true, false, true]);
+        assert_eq!(
+            env.video_path(),
+            Some(folder.join("rl-video-episode-4.gif").as_path())
+        );
+
+        env.close();
+        assert!(!env.is_recording());
+        assert_eq!(env.frame_count(), 0);
+    }
+
+    #[test]
+    fn test_record_video_frames_reset_between_episodes() {
+        let folder = std::env::temp_dir().join("gymnust-record-video-frames");
+        let env = FrameEnv {
+            count: 0,
+            length: 3,
+            space: Discrete::new(1, 0, None).into(),
+        };
+        let mut env = RecordVideo::new(Box::new(env), &folder, |_| true, 30);
+
+        run_episode(&mut env);
+        assert_eq!(env.frame_count(), 4);
+        env.reset(None, None);
+        assert_eq!(env.frame_count(), 1);
+        assert_eq!(
+            env.video_path(),
+            Some(folder.join("rl-video-episode-1.gif").as_path())
+        );
+        env.close();
+    }
+
+    #[cfg(feature = "record-video")]
+    #[test]
+    fn test_record_video_writes_gif() {
+        let folder = std::env::temp_dir().join("gymnust-record-video-gif");
+        let env = FrameEnv {
+            count: 0,
+            length: 2,
+            space: Discrete::new(1, 0, None).into(),
+        };
+        let mut env = RecordVideo::new(Box::new(env), &folder, |episode| episode == 0, 30);
+
+        run_episode(&mut env);
+        env.close();
+        let video = std::fs::read(folder.join("rl-video-episode-0.gif")).unwrap();
+        assert!(video.starts_with(b"GIF89a"));
+    }
+}
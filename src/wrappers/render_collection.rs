@@ -0,0 +1,163 @@
+//! Wrapper that collects the rendered frames of an environment.
+
+use thiserror::Error;
+
+use crate::common::InfoMap;
+use crate::core::{Env, RenderFrame, StepResult, Wrapper};
+
+/// Error returned by [`RenderCollection::new`].
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RenderCollectionError {
+    #[error(
+        "The wrapped environment must render frames, e.g. in the \"rgb_array\" or \"ansi\" mode"
+    )]
+    NoFrames,
+}
+
+/// Collects the frames rendered by the environment after each [`step`](Env::step) and
+/// [`reset`](Env::reset), turning the "rgb_array" and "ansi" render modes into "rgb_array_list" and
+/// "ansi_list".
+///
+/// [`render`](Env::render) returns the collected frames as a [`RenderFrame::List`].
+pub struct RenderCollection<ObsType, ActType> {
+    env: Box<dyn Env<ObsType, ActType>>,
+    frame_list: Vec<RenderFrame>,
+    pop_frames: bool,
+    reset_clean: bool,
+}
+
+impl<ObsType, ActType> RenderCollection<ObsType, ActType> {
+    /// Wraps `env`, which must render frames.
+    ///
+    /// If `pop_frames` is `true`, the collected frames are cleared each time they are rendered.
+    /// If `reset_clean` is `true`, the collected frames are cleared when the environment is reset.
+    ///
+    /// The environment is reset once to check that it renders frames.
+    ///
+    /// Returns an error if `env` does not render frames, e.g. if it has no render mode or renders
+    /// in the "human" mode.
+    pub fn new(
+        mut env: Box<dyn Env<ObsType, ActType>>,
+        pop_frames: bool,
+        reset_clean: bool,
+    ) -> Result<Self, RenderCollectionError> {
+        env.reset(None, None);
+        if env.render().is_none() {
+            return Err(RenderCollectionError::NoFrames);
+        }
+        Ok(Self {
+            env,
+            frame_list: Vec::new(),
+            pop_frames,
+            reset_clean,
+        })
+    }
+
+    /// Renders a frame of the wrapped environment and adds it to the collected frames, if any.
+    fn collect_frame(&mut self) {
+        self.frame_list.extend(self.env.render());
+    }
+}
+
+impl<ObsType, ActType> Wrapper for RenderCollection<ObsType, ActType> {
+    type ObsType = ObsType;
+    type ActType = ActType;
+
+    fn env(&self) -> &dyn Env<ObsType, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<ObsType, ActType> {
+        self.env.as_mut()
+    }
+
+    fn step(&mut self, action: ActType) -> StepResult<ObsType> {
+        let result = self.env.step(action);
+        self.collect_frame();
+        result
+    }
+
+    fn reset(&mut self, seed: Option<u64>, options: Option<&InfoMap>) -> (ObsType, InfoMap) {
+        let result = self.env.reset(seed, options);
+        if self.reset_clean {
+            self.frame_list.clear();
+        }
+        self.collect_frame();
+        result
+    }
+
+    fn render(&mut self) -> Option<RenderFrame> {
+        let frames = if self.pop_frames {
+            std::mem::take(&mut self.frame_list)
+        } else {
+            self.frame_list.clone()
+        };
+        Some(RenderFrame::List(frames))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RenderCollection, RenderCollectionError};
+    use crate::core::{Env, RenderFrame};
+    use crate::envs::classic_control::CartPole;
+
+    fn frame_count(env: &mut RenderCollection<candle_core::Tensor, i64>) -> usize {
+        match env.render() {
+            Some(RenderFrame::List(frames)) => {
+                assert!(frames
+                    .iter()
+                    .all(|frame| matches!(frame, RenderFrame::Ansi(_))));
+                frames.len()
+            }
+            frame => panic!("Expected a list of frames, got {frame:?}"),
+        }
+    }
+
+    #[test]
+    fn test_render_collection_pop_frames() {
+        let mut env =
+            RenderCollection::new(Box::new(CartPole::new(Some("ansi"))), true, true).unwrap();
+        env.reset(Some(0), None);
+        for _ in 0..5 {
+            env.step(0);
+        }
+
+        assert_eq!(frame_count(&mut env), 6);
+        assert_eq!(frame_count(&mut env), 0);
+        env.step(1);
+        assert_eq!(frame_count(&mut env), 1);
+    }
+
+    #[test]
+    fn test_render_collection_keep_frames() {
+        let mut env =
+            RenderCollection::new(Box::new(CartPole::new(Some("ansi"))), false, true).unwrap();
+        env.reset(Some(0), None);
+        for _ in 0..3 {
+            env.step(0);
+        }
+
+        assert_eq!(frame_count(&mut env), 4);
+        assert_eq!(frame_count(&mut env), 4);
+        env.reset(None, None);
+        assert_eq!(frame_count(&mut env), 1);
+    }
+
+    #[test]
+    fn test_render_collection_no_reset_clean() {
+        let mut env =
+            RenderCollection::new(Box::new(CartPole::new(Some("ansi"))), false, false).unwrap();
+        env.reset(Some(0), None);
+        env.step(0);
+        env.reset(None, None);
+
+        assert_eq!(frame_count(&mut env), 3);
+    }
+
+    #[test]
+    fn test_render_collection_rejects_envs_without_frames() {
+        let result = RenderCollection::new(Box::new(CartPole::new(None)), true, true);
+        assert_eq!(result.err(), Some(RenderCollectionError::NoFrames));
+    }
+}
@@ -0,0 +1,302 @@
+//! Wrapper that affinely rescales continuous actions to the bounds of a `Box` action space.
+
+use candle_core::{DType, Tensor};
+use thiserror::Error;
+
+use crate::core::{ActionWrapper, ActionWrapperKind, Env, WrapperKind};
+use crate::spaces::{Bound, Bounded, Box, BoxError, SpaceEnum};
+use crate::wrappers::utils::UnsupportedSpaceError;
+
+/// Error returned by [`RescaleAction::new`].
+#[derive(Debug, Error)]
+pub enum RescaleActionError {
+    #[error(transparent)]
+    UnsupportedSpace(#[from] UnsupportedSpaceError),
+    #[error("The {0} action space must be bounded in every dimension")]
+    Unbounded(&'static str),
+    #[error("`min_action` must be less than `max_action` in every dimension")]
+    InvalidRange,
+    #[error(transparent)]
+    Box(#[from] BoxError),
+    #[error(transparent)]
+    Tensor(#[from] candle_core::Error),
+}
+
+/// Affinely (linearly) rescales a `Box` action space of the environment to within the range of
+/// `[min_action, max_action]`.
+///
+/// The agent acts in the rescaled space, e.g. `[-1, 1]`, and each action is mapped back to the
+/// bounds of the wrapped environment's action space before being forwarded, so that `min_action`
+/// maps to its `low` and `max_action` to its `high`.
+pub struct RescaleAction<ObsType> {
+    env: std::boxed::Box<dyn Env<ObsType, Tensor>>,
+    action_space: SpaceEnum,
+    env_low: Tensor,
+    env_high: Tensor,
+    min_action: Tensor,
+    max_action: Tensor,
+}
+
+impl<ObsType> RescaleAction<ObsType> {
+    /// Wraps `env`, whose action space is a `Box`, to take actions in `[min_action, max_action]`
+    /// instead.
+    ///
+    /// Returns an error if the action space of `env` is not a `Box`, if either action space is
+    /// unbounded in some dimension, or if `min_action` is not less than `max_action`.
+    pub fn new(
+        env: std::boxed::Box<dyn Env<ObsType, Tensor>>,
+        min_action: Bound,
+        max_action: Bound,
+    ) -> Result<Self, RescaleActionError> {
+        let SpaceEnum::Box(env_action_space) = env.action_space() else {
+            return Err(UnsupportedSpaceError {
+                space: "action",
+                expected: "a Box",
+            }
+            .into());
+        };
+        let env_action_space = env_action_space.clone();
+        let action_space = Box::new(
+            min_action,
+            max_action,
+            Some(env_action_space.shape().to_vec()),
+            env_action_space.dtype(),
+            env_action_space.low().device(),
+            None,
+        )?;
+        for (name, space) in [("wrapped", &env_action_space), ("rescaled", &action_space)] {
+            if !space.is_bounded(Bounded::Both) {
+                return Err(RescaleActionError::Unbounded(name));
+            }
+        }
+
+        let min_action = action_space.low().to_dtype(DType::F64)?;
+        let max_action = action_space.high().to_dtype(DType::F64)?;
+        let min_values = min_action.flatten_all()?.to_vec1::<f64>()?;
+        let max_values = max_action.flatten_all()?.to_vec1::<f64>()?;
+        if min_values
+            .iter()
+            .zip(&max_values)
+            .any(|(min, max)| min >= max)
+        {
+            return Err(RescaleActionError::InvalidRange);
+        }
+
+        Ok(Self {
+            env,
+            env_low: env_action_space.low().to_dtype(DType::F64)?,
+            env_high: env_action_space.high().to_dtype(DType::F64)?,
+            action_space: action_space.into(),
+            min_action,
+            max_action,
+        })
+    }
+}
+
+impl<ObsType> ActionWrapper for RescaleAction<ObsType> {
+    type ObsType = ObsType;
+    type ActType = Tensor;
+    type WrapperActType = Tensor;
+
+    fn env(&self) -> &dyn Env<ObsType, Tensor> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<ObsType, Tensor> {
+        self.env.as_mut()
+    }
+
+    /// Linearly maps `action` from `[min_action, max_action]` to the bounds of the wrapped
+    /// environment's action space, clipping the result to these bounds.
+    fn action(&mut self, action: Tensor) -> Tensor {
+        let rescale = || {
+            let dtype = action.dtype();
+            let action = action.to_dtype(DType::F64)?;
+            let fraction =
+                ((&action - &self.min_action)? / (&self.max_action - &self.min_action)?)?;
+            let action = (&self.env_low + ((&self.env_high - &self.env_low)? * fraction)?)?;
+            action.clamp(&self.env_low, &self.env_high)?.to_dtype(dtype)
+        };
+        rescale().expect("Action must have the shape of the action space")
+    }
+
+    fn action_space(&self) -> &SpaceEnum {
+        &self.action_space
+    }
+}
+
+impl<ObsType> WrapperKind for RescaleAction<ObsType> {
+    type Kind = ActionWrapperKind;
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::{DType, Device, Tensor};
+
+    use super::{RescaleAction, RescaleActionError};
+    use crate::common::InfoMap;
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Bound, Box, Discrete, SpaceEnum};
+    use crate::wrappers::utils::UnsupportedSpaceError;
+
+    /// Returns the action it received as the observation.
+    struct EchoEnv {
+        space: SpaceEnum,
+    }
+
+    impl Env<Tensor, Tensor> for EchoEnv {
+        fn step(&mut self, action: Tensor) -> StepResult<Tensor> {
+            (action, 0.0, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (Tensor, InfoMap) {
+            (
+                Tensor::zeros(2, DType::F32, &Device::Cpu).unwrap(),
+                InfoMap::new(),
+            )
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    fn env_action_space() -> Box {
+        let low = Tensor::new(&[0.0f32, -4.0], &Device::Cpu).unwrap();
+        let high = Tensor::new(&[10.0f32, 2.0], &Device::Cpu).unwrap();
+        Box::new(
+            Bound::Tensor(low),
+            Bound::Tensor(high),
+            None,
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap()
+    }
+
+    fn rescaled_env() -> RescaleAction<Tensor> {
+        RescaleAction::new(
+            std::boxed::Box::new(EchoEnv {
+                space: env_action_space().into(),
+            }),
+            Bound::F64(-1.0),
+            Bound::F64(1.0),
+        )
+        .unwrap()
+    }
+
+    fn step(env: &mut RescaleAction<Tensor>, action: [f32; 2]) -> Vec<f32> {
+        let action = Tensor::new(&action, &Device::Cpu).unwrap();
+        Env::step(env, action).observation.to_vec1::<f32>().unwrap()
+    }
+
+    #[test]
+    fn test_rescale_action_endpoints() {
+        let mut env = rescaled_env();
+        Env::reset(&mut env, None, None);
+
+        assert_eq!(step(&mut env, [-1.0, -1.0]), [0.0, -4.0]);
+        assert_eq!(step(&mut env, [1.0, 1.0]), [10.0, 2.0]);
+        assert_eq!(step(&mut env, [0.0, 0.0]), [5.0, -1.0]);
+        assert_eq!(step(&mut env, [2.0, -3.0]), [10.0, -4.0]);
+    }
+
+    #[test]
+    fn test_rescale_action_space() {
+        let env = rescaled_env();
+        let expected = Box::new(
+            Bound::F64(-1.0),
+            Bound::F64(1.0),
+            Some(vec![2]),
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(Env::action_space(&env), &SpaceEnum::from(expected));
+    }
+
+    #[test]
+    fn test_rescale_action_rejects_unbounded() {
+        let unbounded_space = Box::new(
+            Bound::F64(0.0),
+            Bound::F64(f64::INFINITY),
+            Some(vec![2]),
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+        let result = RescaleAction::new(
+            std::boxed::Box::new(EchoEnv {
+                space: unbounded_space.into(),
+            }),
+            Bound::F64(-1.0),
+            Bound::F64(1.0),
+        );
+        assert!(matches!(
+            result,
+            Err(RescaleActionError::Unbounded("wrapped"))
+        ));
+
+        let result = RescaleAction::new(
+            std::boxed::Box::new(EchoEnv {
+                space: env_action_space().into(),
+            }),
+            Bound::F64(f64::NEG_INFINITY),
+            Bound::F64(1.0),
+        );
+        assert!(matches!(
+            result,
+            Err(RescaleActionError::Unbounded("rescaled"))
+        ));
+    }
+
+    #[test]
+    fn test_rescale_action_rejects_invalid_range() {
+        let result = RescaleAction::new(
+            std::boxed::Box::new(EchoEnv {
+                space: env_action_space().into(),
+            }),
+            Bound::F64(1.0),
+            Bound::F64(-1.0),
+        );
+        assert!(matches!(result, Err(RescaleActionError::InvalidRange)));
+
+        // An empty range would divide by zero when rescaling.
+        let result = RescaleAction::new(
+            std::boxed::Box::new(EchoEnv {
+                space: env_action_space().into(),
+            }),
+            Bound::F64(1.0),
+            Bound::F64(1.0),
+        );
+        assert!(matches!(result, Err(RescaleActionError::InvalidRange)));
+    }
+
+    #[test]
+    fn test_rescale_action_rejects_discrete() {
+        let result = RescaleAction::new(
+            std::boxed::Box::new(EchoEnv {
+                space: Discrete::new(3, 0, None).into(),
+            }),
+            Bound::F64(-1.0),
+            Bound::F64(1.0),
+        );
+        assert!(matches!(
+            result,
+            Err(RescaleActionError::UnsupportedSpace(
+                UnsupportedSpaceError {
+                    space: "action",
+                    ..
+                }
+            ))
+        ));
+    }
+}
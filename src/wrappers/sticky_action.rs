@@ -0,0 +1,177 @@
+//! Wrapper that randomly repeats the previous action of the agent.
+
+use rand::Rng;
+
+use crate::common::InfoMap;
+use crate::core::{ActionWrapper, ActionWrapperKind, Env, WrapperKind};
+
+/// Repeats the previous action instead of the one chosen by the agent with probability
+/// `repeat_action_probability`, as in the evaluation protocol of Atari games.
+///
+/// The draws use the random number generator of the wrapped environment, so that seeding it with
+/// [`reset`](Env::reset) makes the repeated actions reproducible. The first action of each episode
+/// is never repeated.
+pub struct StickyAction<ObsType, ActType> {
+    env: Box<dyn Env<ObsType, ActType>>,
+    repeat_action_probability: f64,
+    last_action: Option<ActType>,
+}
+
+impl<ObsType, ActType> StickyAction<ObsType, ActType> {
+    /// Wraps `env`, repeating its previous action with probability `repeat_action_probability`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `repeat_action_probability` is not in `[0, 1)`, or if `env` has no random number
+    /// generator.
+    pub fn new(mut env: Box<dyn Env<ObsType, ActType>>, repeat_action_probability: f64) -> Self {
+        assert!(
+            (0.0..1.0).contains(&repeat_action_probability),
+            "The repeat action probability must be in [0, 1), got {repeat_action_probability}"
+        );
+        assert!(
+            env.rs_random().is_some(),
+            "The wrapped environment must have a random number generator"
+        );
+        Self {
+            env,
+            repeat_action_probability,
+            last_action: None,
+        }
+    }
+
+    /// The probability of repeating the previous action.
+    pub fn repeat_action_probability(&self) -> f64 {
+        self.repeat_action_probability
+    }
+}
+
+impl<ObsType, ActType: Clone> ActionWrapper for StickyAction<ObsType, ActType> {
+    type ObsType = ObsType;
+    type ActType = ActType;
+    type WrapperActType = ActType;
+
+    fn env(&self) -> &dyn Env<ObsType, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<ObsType, ActType> {
+        self.env.as_mut()
+    }
+
+    fn action(&mut self, action: ActType) -> ActType {
+        let draw = self
+            .env
+            .rs_random()
+            .expect("The wrapped environment has a random number generator")
+            .gen::<f64>();
+        let action = match self.last_action.take() {
+            Some(last_action) if draw < self.repeat_action_probability => last_action,
+            _ => action,
+        };
+        self.last_action = Some(action.clone());
+        action
+    }
+
+    fn reset(&mut self, seed: Option<u64>, options: Option<&InfoMap>) -> (ObsType, InfoMap) {
+        self.last_action = None;
+        self.env.reset(seed, options)
+    }
+}
+
+impl<ObsType, ActType: Clone> WrapperKind for StickyAction<ObsType, ActType> {
+    type Kind = ActionWrapperKind;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StickyAction;
+    use crate::common::InfoMap;
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Discrete, SpaceEnum};
+    use crate::utils::seeding::{Generator, LazyGenerator};
+
+    /// Returns the action it received as the observation.
+    struct EchoEnv {
+        space: SpaceEnum,
+        rs_random: LazyGenerator,
+    }
+
+    impl Env<i64, i64> for EchoEnv {
+        fn step(&mut self, action: i64) -> StepResult<i64> {
+            (action, 0.0, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(&mut self, seed: Option<u64>, _options: Option<&InfoMap>) -> (i64, InfoMap) {
+            if let Some(seed) = seed {
+                self.rs_random.reseed(seed);
+            }
+            (-1, InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn rs_random(&mut self) -> Option<&mut Generator> {
+            Some(self.rs_random.get())
+        }
+    }
+
+    fn sticky_env(repeat_action_probability: f64) -> StickyAction<i64, i64> {
+        let env = EchoEnv {
+            space: Discrete::new(2, 0, None).into(),
+            rs_random: LazyGenerator::default(),
+        };
+        StickyAction::new(Box::new(env), repeat_action_probability)
+    }
+
+    /// Always chooses the other action than the one last received by the wrapped environment,
+    /// returning the actions it received.
+    fn rollout(env: &mut StickyAction<i64, i64>, seed: u64, steps: usize) -> Vec<i64> {
+        let (mut last_action, _) = env.reset(Some(seed), None);
+        (0..steps)
+            .map(|_| {
+                last_action = env.step(if last_action == 0 { 1 } else { 0 }).observation;
+                last_action
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sticky_action_repeat_rate() {
+        let mut env = sticky_env(0.25);
+        let steps = 10_000;
+        let actions = rollout(&mut env, 0, steps);
+
+        // Since the agent always switches actions, an action is repeated exactly when it sticks.
+        assert_eq!(actions[0], 0);
+        let repeated = actions.windows(2).filter(|pair| pair[0] == pair[1]).count();
+        let rate = repeated as f64 / (steps - 1) as f64;
+        assert!((rate - 0.25).abs() < 0.02, "repeat rate {rate}");
+
+        assert_eq!(actions, rollout(&mut env, 0, steps));
+        assert_ne!(actions, rollout(&mut env, 1, steps));
+    }
+
+    #[test]
+    fn test_sticky_action_reset_forgets_last_action() {
+        let mut env = sticky_env(0.99);
+        env.reset(Some(0), None);
+        assert_eq!(env.step(1).observation, 1);
+
+        // The first action of an episode is never repeated.
+        env.reset(None, None);
+        assert_eq!(env.step(0).observation, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "The repeat action probability must be in [0, 1)")]
+    fn test_sticky_action_invalid_probability() {
+        sticky_env(1.0);
+    }
+}
@@ -0,0 +1,289 @@
+//! Wrapper that appends the elapsed time to the observations of an environment.
+
+use candle_core::Tensor;
+
+use crate::common::InfoMap;
+use crate::core::{Env, ObservationWrapper, ObservationWrapperKind, StepResult, WrapperKind};
+use crate::spaces::{Bound, Box, SpaceEnum};
+use crate::wrappers::utils::UnsupportedSpaceError;
+
+/// Appends the number of steps taken since the last reset to 1-D observations, so that
+/// time-limited tasks become Markovian.
+///
+/// If `max_episode_steps` is known, the appended value is normalized to `[0, 1]` by dividing by
+/// it, and stays at `1` for steps past the limit. Otherwise, the raw step count is appended. The
+/// observation space is extended by one dimension accordingly.
+pub struct TimeAwareObservation<ActType> {
+    env: std::boxed::Box<dyn Env<Tensor, ActType>>,
+    observation_space: SpaceEnum,
+    max_episode_steps: Option<usize>,
+    elapsed_steps: usize,
+}
+
+impl<ActType> TimeAwareObservation<ActType> {
+    /// Wraps `env`, whose observation space is a 1-D `Box`, appending the elapsed steps to its
+    /// observations, normalized by `max_episode_steps`.
+    ///
+    /// If `max_episode_steps` is `None`, that of the spec of `env` is used, if any.
+    ///
+    /// Returns an error if the observation space of `env` is not a 1-D `Box`.
+    pub fn new(
+        env: std::boxed::Box<dyn Env<Tensor, ActType>>,
+        max_episode_steps: Option<usize>,
+    ) -> Result<Self, UnsupportedSpaceError> {
+        let observation_space = match env.observation_space() {
+            SpaceEnum::Box(space) if space.shape().len() == 1 => space,
+            _ => {
+                return Err(UnsupportedSpaceError {
+                    space: "observation",
+                    expected: "a 1-D Box",
+                })
+            }
+        };
+        let max_episode_steps =
+            max_episode_steps.or_else(|| env.spec().and_then(|spec| spec.max_episode_steps));
+
+        let high = match max_episode_steps {
+            Some(_) => 1.0,
+            None => f64::INFINITY,
+        };
+        let extend = |bound: &Tensor, time: f64| {
+            let time = Tensor::new(&[time], bound.device())?.to_dtype(bound.dtype())?;
+            Tensor::cat(&[bound, &time], 0)
+        };
+        let observation_space = Box::new(
+            Bound::Tensor(
+                extend(observation_space.low(), 0.0).expect("Bounds of a 1-D space are 1-D"),
+            ),
+            Bound::Tensor(
+                extend(observation_space.high(), high).expect("Bounds of a 1-D space are 1-D"),
+            ),
+            None,
+            observation_space.dtype(),
+            observation_space.low().device(),
+            None,
+        )
+        .expect("The bounds are tensors");
+
+        Ok(Self {
+            env,
+            observation_space: observation_space.into(),
+            max_episode_steps,
+            elapsed_steps: 0,
+        })
+    }
+
+    /// The number of steps taken since the last reset.
+    pub fn elapsed_steps(&self) -> usize {
+        self.elapsed_steps
+    }
+}
+
+impl<ActType> ObservationWrapper for TimeAwareObservation<ActType> {
+    type ObsType = Tensor;
+    type ActType = ActType;
+    type WrapperObsType = Tensor;
+
+    fn env(&self) -> &dyn Env<Tensor, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<Tensor, ActType> {
+        self.env.as_mut()
+    }
+
+    /// Appends the (normalized) elapsed steps to `observation`.
+    fn observation(&mut self, observation: Tensor) -> Tensor {
+        let time = match self.max_episode_steps {
+            Some(max_episode_steps) => {
+                (self.elapsed_steps as f64 / max_episode_steps as f64).min(1.0)
+            }
+            None => self.elapsed_steps as f64,
+        };
+        let append = || {
+            let time = Tensor::new(&[time], observation.device())?.to_dtype(observation.dtype())?;
+            Tensor::cat(&[&observation, &time], 0)
+        };
+        append().expect("Observation must be 1-D")
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+
+    fn step(&mut self, action: ActType) -> StepResult<Tensor> {
+        self.elapsed_steps += 1;
+        let result = self.env.step(action);
+        StepResult {
+            observation: self.observation(result.observation),
+            ..result
+        }
+    }
+
+    fn reset(&mut self, seed: Option<u64>, options: Option<&InfoMap>) -> (Tensor, InfoMap) {
+        self.elapsed_steps = 0;
+        let (observation, info) = self.env.reset(seed, options);
+        (self.observation(observation), info)
+    }
+}
+
+impl<ActType> WrapperKind for TimeAwareObservation<ActType> {
+    type Kind = ObservationWrapperKind;
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::{DType, Device, Tensor};
+
+    use super::TimeAwareObservation;
+    use crate::common::InfoMap;
+    use crate::core::{Env, StepResult};
+    use crate::envs::registration::EnvSpec;
+    use crate::spaces::{Bound, Box, Discrete, Space, SpaceEnum};
+
+    /// Always observes `[0.5, -0.5]`.
+    struct ConstantEnv {
+        space: SpaceEnum,
+        spec: Option<EnvSpec>,
+    }
+
+    impl ConstantEnv {
+        fn observe() -> Tensor {
+            Tensor::new(&[0.5f32, -0.5], &Device::Cpu).unwrap()
+        }
+    }
+
+    impl Env<Tensor, ()> for ConstantEnv {
+        fn step(&mut self, _action: ()) -> StepResult<Tensor> {
+            (Self::observe(), 0.0, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (Tensor, InfoMap) {
+            (Self::observe(), InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn spec(&self) -> Option<&EnvSpec> {
+            self.spec.as_ref()
+        }
+    }
+
+    fn time_aware_env(
+        max_episode_steps: Option<usize>,
+        spec: Option<EnvSpec>,
+    ) -> TimeAwareObservation<()> {
+        let observation_space = Box::new(
+            Bound::F64(-1.0),
+            Bound::F64(1.0),
+            Some(vec![2]),
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+        TimeAwareObservation::new(
+            std::boxed::Box::new(ConstantEnv {
+                space: observation_space.into(),
+                spec,
+            }),
+            max_episode_steps,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_time_aware_observation_normalized() {
+        let mut env = time_aware_env(Some(4), None);
+        let low = Tensor::new(&[-1.0f32, -1.0, 0.0], &Device::Cpu).unwrap();
+        let high = Tensor::new(&[1.0f32, 1.0, 1.0], &Device::Cpu).unwrap();
+        let expected = Box::new(
+            Bound::Tensor(low),
+            Bound::Tensor(high),
+            None,
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+        assert_eq!(Env::observation_space(&env), &SpaceEnum::from(expected));
+
+        let (observation, _) = Env::reset(&mut env, None, None);
+        assert_eq!(observation.to_vec1::<f32>().unwrap(), [0.5, -0.5, 0.0]);
+        for time in [0.25, 0.5, 0.75] {
+            let StepResult { observation, .. } = Env::step(&mut env, ());
+            assert_eq!(observation.to_vec1::<f32>().unwrap(), [0.5, -0.5, time]);
+        }
+
+        let (observation, _) = Env::reset(&mut env, None, None);
+        assert_eq!(observation.to_vec1::<f32>().unwrap(), [0.5, -0.5, 0.0]);
+        assert_eq!(env.elapsed_steps(), 0);
+        let StepResult { observation, .. } = Env::step(&mut env, ());
+        assert_eq!(observation.to_vec1::<f32>().unwrap(), [0.5, -0.5, 0.25]);
+
+        // The normalized time stays at 1 past the limit.
+        for time in [0.5, 0.75, 1.0, 1.0] {
+            let StepResult { observation, .. } = Env::step(&mut env, ());
+            assert_eq!(observation.to_vec1::<f32>().unwrap(), [0.5, -0.5, time]);
+            assert!(Env::observation_space(&env).contains(&observation.into()));
+        }
+    }
+
+    #[test]
+    fn test_time_aware_observation_spec_max_episode_steps() {
+        let spec = EnvSpec::builder("test/TimeAware-v0", "tests:ConstantEnv")
+            .max_episode_steps(2)
+            .build()
+            .unwrap();
+        let mut env = time_aware_env(None, Some(spec.clone()));
+        Env::reset(&mut env, None, None);
+        let StepResult { observation, .. } = Env::step(&mut env, ());
+        assert_eq!(observation.to_vec1::<f32>().unwrap(), [0.5, -0.5, 0.5]);
+
+        // An explicit `max_episode_steps` takes precedence over the spec.
+        let mut env = time_aware_env(Some(4), Some(spec));
+        Env::reset(&mut env, None, None);
+        let StepResult { observation, .. } = Env::step(&mut env, ());
+        assert_eq!(observation.to_vec1::<f32>().unwrap(), [0.5, -0.5, 0.25]);
+    }
+
+    #[test]
+    fn test_time_aware_observation_rejects_non_vector_spaces() {
+        let image_space = Box::new(
+            Bound::F64(0.0),
+            Bound::F64(1.0),
+            Some(vec![2, 2]),
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+        for space in [image_space.into(), Discrete::new(3, 0, None).into()] {
+            let result = TimeAwareObservation::new(
+                std::boxed::Box::new(ConstantEnv { space, spec: None }),
+                None,
+            );
+            assert_eq!(
+                result.err().unwrap().to_string(),
+                "The observation space of the wrapped environment must be a 1-D Box"
+            );
+        }
+    }
+
+    #[test]
+    fn test_time_aware_observation_raw() {
+        let mut env = time_aware_env(None, None);
+        Env::reset(&mut env, None, None);
+
+        for time in [1.0, 2.0, 3.0] {
+            let StepResult { observation, .. } = Env::step(&mut env, ());
+            assert_eq!(observation.to_vec1::<f32>().unwrap(), [0.5, -0.5, time]);
+        }
+    }
+}
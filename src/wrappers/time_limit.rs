@@ -0,0 +1,157 @@
+//! Wrapper that truncates episodes after a maximum number of steps.
+
+use crate::common::InfoMap;
+use crate::core::{Env, StepResult, Wrapper};
+
+/// Limits the number of steps of an environment by truncating the episode once
+/// `max_episode_steps` is reached.
+///
+/// The `truncated` signal is set without touching `terminated`, while the observation, reward
+/// and info of the wrapped environment are forwarded unchanged.
+pub struct TimeLimit<ObsType, ActType> {
+    env: Box<dyn Env<ObsType, ActType>>,
+    max_episode_steps: usize,
+    elapsed_steps: usize,
+}
+
+impl<ObsType, ActType> TimeLimit<ObsType, ActType> {
+    /// Wraps `env`, truncating its episodes after `max_episode_steps` steps.
+    pub fn new(env: Box<dyn Env<ObsType, ActType>>, max_episode_steps: usize) -> Self {
+        Self {
+            env,
+            max_episode_steps,
+            elapsed_steps: 0,
+        }
+    }
+
+    /// The max number of steps of an episode.
+    pub fn max_episode_steps(&self) -> usize {
+        self.max_episode_steps
+    }
+
+    /// The number of steps taken since the last reset.
+    pub fn elapsed_steps(&self) -> usize {
+        self.elapsed_steps
+    }
+}
+
+impl<ObsType, ActType> Wrapper for TimeLimit<ObsType, ActType> {
+    type ObsType = ObsType;
+    type ActType = ActType;
+
+    fn env(&self) -> &dyn Env<ObsType, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<ObsType, ActType> {
+        self.env.as_mut()
+    }
+
+    fn step(&mut self, action: ActType) -> StepResult<ObsType> {
+        let mut result = self.env.step(action);
+        self.elapsed_steps += 1;
+
+        if self.elapsed_steps >= self.max_episode_steps {
+            result.truncated = true;
+        }
+
+        result
+    }
+
+    fn reset(&mut self, seed: Option<u64>, options: Option<&InfoMap>) -> (ObsType, InfoMap) {
+        self.elapsed_steps = 0;
+        self.env.reset(seed, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TimeLimit;
+    use crate::common::{InfoMap, InfoValue};
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Discrete, SpaceEnum};
+
+    /// Returns the number of steps taken since the last reset, never terminating.
+    struct StepCounter {
+        count: i64,
+        space: SpaceEnum,
+    }
+
+    impl Env<i64, ()> for StepCounter {
+        fn step(&mut self, _action: ()) -> StepResult<i64> {
+            self.count += 1;
+            let info = InfoMap::from([("count".to_string(), InfoValue::Int(self.count))]);
+            (self.count, 0.5, false, false, info).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (i64, InfoMap) {
+            self.count = 0;
+            (self.count, InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    #[test]
+    fn test_time_limit_truncates_at_limit() {
+        let mut env = TimeLimit::new(
+            Box::new(StepCounter {
+                count: 0,
+                space: Discrete::new(100, 0, None).into(),
+            }),
+            3,
+        );
+        env.reset(None, None);
+
+        for step in 1..3 {
+            let StepResult {
+                observation,
+                reward,
+                terminated,
+                truncated,
+                info,
+            } = env.step(());
+            assert_eq!(observation, step);
+            assert_eq!(reward, 0.5);
+            assert!(!terminated);
+            assert!(!truncated);
+            assert!(matches!(info["count"], InfoValue::Int(count) if count == step));
+        }
+
+        let StepResult {
+            observation,
+            terminated,
+            truncated,
+            ..
+        } = env.step(());
+        assert_eq!(observation, 3);
+        assert!(!terminated);
+        assert!(truncated);
+        assert_eq!(env.elapsed_steps(), 3);
+    }
+
+    #[test]
+    fn test_time_limit_resets_counter() {
+        let mut env = TimeLimit::new(
+            Box::new(StepCounter {
+                count: 0,
+                space: Discrete::new(100, 0, None).into(),
+            }),
+            2,
+        );
+        env.reset(None, None);
+        env.step(());
+        assert!(env.step(()).truncated);
+
+        env.reset(None, None);
+        assert_eq!(env.elapsed_steps(), 0);
+        assert!(!env.step(()).truncated);
+        assert!(env.step(()).truncated);
+    }
+}
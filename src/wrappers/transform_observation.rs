@@ -0,0 +1,129 @@
+//! Wrapper that applies a function to the observations of an environment.
+
+use crate::core::{Env, ObservationWrapper, ObservationWrapperKind, WrapperKind};
+use crate::spaces::SpaceEnum;
+
+/// Applies a function to the observations returned by [`reset`](Env::reset) and
+/// [`step`](Env::step).
+///
+/// This is the escape hatch for transforms that none of the built-in observation wrappers cover.
+/// As the function may change the kind of observation, the space of the transformed observations
+/// is given explicitly and reported by
+/// [`observation_space`](ObservationWrapper::observation_space).
+pub struct TransformObservation<ObsType, ActType, WrapperObsType> {
+    env: Box<dyn Env<ObsType, ActType>>,
+    func: Box<dyn Fn(ObsType) -> WrapperObsType>,
+    observation_space: SpaceEnum,
+}
+
+impl<ObsType, ActType, WrapperObsType> TransformObservation<ObsType, ActType, WrapperObsType> {
+    /// Wraps `env`, applying `func` to its observations, which then lie in `observation_space`.
+    pub fn new(
+        env: Box<dyn Env<ObsType, ActType>>,
+        func: impl Fn(ObsType) -> WrapperObsType + 'static,
+        observation_space: impl Into<SpaceEnum>,
+    ) -> Self {
+        Self {
+            env,
+            func: Box::new(func),
+            observation_space: observation_space.into(),
+        }
+    }
+}
+
+impl<ObsType, ActType, WrapperObsType> ObservationWrapper
+    for TransformObservation<ObsType, ActType, WrapperObsType>
+{
+    type ObsType = ObsType;
+    type ActType = ActType;
+    type WrapperObsType = WrapperObsType;
+
+    fn env(&self) -> &dyn Env<ObsType, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<ObsType, ActType> {
+        self.env.as_mut()
+    }
+
+    fn observation(&mut self, observation: ObsType) -> WrapperObsType {
+        (self.func)(observation)
+    }
+
+    fn observation_space(&self) -> &SpaceEnum {
+        &self.observation_space
+    }
+}
+
+impl<ObsType, ActType, WrapperObsType> WrapperKind
+    for TransformObservation<ObsType, ActType, WrapperObsType>
+{
+    type Kind = ObservationWrapperKind;
+}
+
+#[cfg(test)]
+mod tests {
+    use candle_core::{DType, Device, Tensor};
+
+    use super::TransformObservation;
+    use crate::common::InfoMap;
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Bound, Box, SpaceEnum};
+
+    /// Returns the action it received as the observation.
+    struct EchoEnv {
+        space: SpaceEnum,
+    }
+
+    impl Env<Tensor, Tensor> for EchoEnv {
+        fn step(&mut self, action: Tensor) -> StepResult<Tensor> {
+            (action, 0.0, false, false, InfoMap::new()).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (Tensor, InfoMap) {
+            (
+                Tensor::ones(2, DType::F32, &Device::Cpu).unwrap(),
+                InfoMap::new(),
+            )
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    #[test]
+    fn test_transform_observation() {
+        let observation_space = Box::new(
+            Bound::F64(-2.0),
+            Bound::F64(2.0),
+            Some(vec![2]),
+            DType::F32,
+            &Device::Cpu,
+            None,
+        )
+        .unwrap();
+        let mut env = TransformObservation::new(
+            std::boxed::Box::new(EchoEnv {
+                space: observation_space.clone().into(),
+            }),
+            |observation: Tensor| (observation * 2.0).unwrap(),
+            observation_space.clone(),
+        );
+
+        let (observation, _) = Env::reset(&mut env, None, None);
+        assert_eq!(observation.to_vec1::<f32>().unwrap(), [2.0, 2.0]);
+        let action = Tensor::new(&[0.5f32, -1.0], &Device::Cpu).unwrap();
+        let StepResult { observation, .. } = Env::step(&mut env, action);
+        assert_eq!(observation.to_vec1::<f32>().unwrap(), [1.0, -2.0]);
+
+        assert_eq!(
+            Env::observation_space(&env),
+            &SpaceEnum::from(observation_space)
+        );
+    }
+}
@@ -0,0 +1,104 @@
+//! Wrapper that applies a function to the rewards of an environment.
+
+use crate::core::{Env, RewardWrapper};
+
+/// Applies a function to the reward of each [`step`](Env::step), e.g. to log-scale or clip it.
+///
+/// The observation, termination and truncation signals and info are forwarded unchanged.
+pub struct TransformReward<ObsType, ActType> {
+    env: Box<dyn Env<ObsType, ActType>>,
+    func: Box<dyn Fn(f32) -> f32>,
+}
+
+impl<ObsType, ActType> TransformReward<ObsType, ActType> {
+    /// Wraps `env`, applying `func` to its rewards.
+    pub fn new(env: Box<dyn Env<ObsType, ActType>>, func: impl Fn(f32) -> f32 + 'static) -> Self {
+        Self {
+            env,
+            func: Box::new(func),
+        }
+    }
+}
+
+impl<ObsType, ActType> RewardWrapper for TransformReward<ObsType, ActType> {
+    type ObsType = ObsType;
+    type ActType = ActType;
+
+    fn env(&self) -> &dyn Env<ObsType, ActType> {
+        self.env.as_ref()
+    }
+
+    fn env_mut(&mut self) -> &mut dyn Env<ObsType, ActType> {
+        self.env.as_mut()
+    }
+
+    fn reward(&mut self, reward: f32) -> f32 {
+        (self.func)(reward)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TransformReward;
+    use crate::common::{InfoMap, InfoValue};
+    use crate::core::{Env, StepResult};
+    use crate::spaces::{Discrete, SpaceEnum};
+
+    /// Gives the action as the reward, observing the number of steps taken.
+    struct RewardEnv {
+        count: i64,
+        space: SpaceEnum,
+    }
+
+    impl Env<i64, f32> for RewardEnv {
+        fn step(&mut self, action: f32) -> StepResult<i64> {
+            self.count += 1;
+            let info = InfoMap::from([("count".to_string(), InfoValue::Int(self.count))]);
+            (self.count, action, self.count == 4, false, info).into()
+        }
+
+        fn reset(&mut self, _seed: Option<u64>, _options: Option<&InfoMap>) -> (i64, InfoMap) {
+            self.count = 0;
+            (self.count, InfoMap::new())
+        }
+
+        fn action_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+
+        fn observation_space(&self) -> &SpaceEnum {
+            &self.space
+        }
+    }
+
+    #[test]
+    fn test_transform_reward_clips() {
+        let mut env = TransformReward::new(
+            Box::new(RewardEnv {
+                count: 0,
+                space: Discrete::new(100, 0, None).into(),
+            }),
+            |reward| reward.clamp(-1.0, 1.0),
+        );
+        assert_eq!(env.reset(None, None).0, 0);
+
+        for (step, (action, expected)) in [(5.0, 1.0), (-3.5, -1.0), (0.25, 0.25), (-1.0, -1.0)]
+            .into_iter()
+            .enumerate()
+        {
+            let StepResult {
+                observation,
+                reward,
+                terminated,
+                truncated,
+                info,
+            } = env.step(action);
+            let count = step as i64 + 1;
+            assert_eq!(observation, count);
+            assert_eq!(reward, expected);
+            assert_eq!(terminated, count == 4);
+            assert!(!truncated);
+            assert!(matches!(info["count"], InfoValue::Int(c) if c == count));
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Utility functions for the wrappers.
+
+use candle_core::{DType, Device, Tensor};
+use thiserror::Error;
+
+/// Error returned by the constructors of wrappers when the space of the wrapped environment is not
+/// one they support, e.g. a `Discrete` action space for [`ClipAction`](super::ClipAction).
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("The {space} space of the wrapped environment must be {expected}")]
+pub struct UnsupportedSpaceError {
+    /// The unsupported space, `"observation"` or `"action"`.
+    pub space: &'static str,
+    /// The spaces supported by the wrapper, e.g. `"a Box"`.
+    pub expected: &'static str,
+}
+
+/// Tracks the mean, variance and count of values.
+///
+/// The statistics are computed in `F64` and combined across batches with the parallel algorithm of
+/// <https://en.wikipedia.org/wiki/Algorithms_for_calculating_variance#Parallel_algorithm>.
+#[derive(Debug, Clone)]
+pub struct RunningMeanStd {
+    mean: Tensor,
+    var: Tensor,
+    count: f64,
+}
+
+impl RunningMeanStd {
+    /// Tracks the running mean and variance of values of `shape`, starting from a mean of zero and
+    /// a variance of one weighted as `epsilon` values.
+    pub fn new(epsilon: f64, shape: &[usize], device: &Device) -> candle_core::Result<Self> {
+        Ok(Self {
+            mean: Tensor::zeros(shape, DType::F64, device)?,
+            var: Tensor::ones(shape, DType::F64, device)?,
+            count: epsilon,
+        })
+    }
+
+    /// The running mean of the values.
+    pub fn mean(&self) -> &Tensor {
+        &self.mean
+    }
+
+    /// The running variance of the values.
+    pub fn var(&self) -> &Tensor {
+        &self.var
+    }
+
+    /// The number of values seen so far, plus the initial `epsilon`.
+    pub fn count(&self) -> f64 {
+        self.count
+    }
+
+    /// Updates the mean, var and count from a batch of values stacked along the first dimension.
+    pub fn update(&mut self, x: &Tensor) -> candle_core::Result<()> {
+        let x = x.to_dtype(DType::F64)?;
+        let batch_count = x.dim(0)? as f64;
+        let batch_mean = x.mean(0)?;
+        let batch_var = x.broadcast_sub(&batch_mean)?.sqr()?.mean(0)?;
+        self.update_from_moments(&batch_mean, &batch_var, batch_count)
+    }
+
+    /// Updates from the moments of a batch of `batch_count` values.
+    fn update_from_moments(
+        &mut self,
+        batch_mean: &Tensor,
+        batch_var: &Tensor,
+        batch_count: f64,
+    ) -> candle_core::Result<()> {
+        let delta = (batch_mean - &self.mean)?;
+        let tot_count = self.count + batch_count;
+
+        let new_mean = (&self.mean + (&delta * (batch_count / tot_count))?)?;
+        let m_a = (&self.var * self.count)?;
+        let m_b = (batch_var * batch_count)?;
+        let m_2 = ((m_a + m_b)? + (delta.sqr()? * (self.count * batch_count / tot_count))?)?;
+
+        self.mean = new_mean;
+        self.var = (m_2 / tot_count)?;
+        self.count = tot_count;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_running_mean_std() {
+        let mut rms = RunningMeanStd::new(0.0, &[2], &Device::Cpu).unwrap();
+        let first = Tensor::new(&[[1.0f32, 10.0], [3.0, 10.0]], &Device::Cpu).unwrap();
+        let second = Tensor::new(&[[5.0f32, 10.0]], &Device::Cpu).unwrap();
+        rms.update(&first).unwrap();
+        rms.update(&second).unwrap();
+
+        // Same statistics as the whole batch `[[1, 10], [3, 10], [5, 10]]`.
+        assert_eq!(rms.count(), 3.0);
+        assert_eq!(rms.mean().to_vec1::<f64>().unwrap(), [3.0, 10.0]);
+        let var = rms.var().to_vec1::<f64>().unwrap();
+        assert!((var[0] - 8.0 / 3.0).abs() < 1e-12);
+        assert_eq!(var[1], 0.0);
+    }
+}